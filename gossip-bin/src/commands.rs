@@ -1,14 +1,16 @@
 use bech32::FromBase32;
+use gossip_lib::person_list_io;
 use gossip_lib::PersonRelay;
 use gossip_lib::GLOBALS;
 use gossip_lib::{Error, ErrorKind};
 use gossip_lib::{PersonList, PersonListMetadata};
 use nostr_types::{
-    Event, EventAddr, EventKind, Id, NostrBech32, NostrUrl, PreEvent, PrivateKey, PublicKey,
-    RelayUrl, Tag, UncheckedUrl, Unixtime,
+    EncryptedPrivateKey, Event, EventAddr, EventKind, Id, NostrBech32, NostrUrl, PreEvent,
+    PrivateKey, PublicKey, RelayUrl, Tag, UncheckedUrl, Unixtime,
 };
 use std::collections::HashSet;
 use std::env;
+use std::path::PathBuf;
 use tokio::runtime::Runtime;
 use zeroize::Zeroize;
 
@@ -29,7 +31,7 @@ impl Command {
     }
 }
 
-const COMMANDS: [Command; 28] = [
+const COMMANDS: [Command; 34] = [
     Command {
         cmd: "oneshot",
         usage_params: "{depends}",
@@ -80,6 +82,21 @@ const COMMANDS: [Command; 28] = [
         usage_params: "<pubkeyhex> <kind>",
         desc: "print IDs of all events from <pubkeyhex> of kind=<kind>",
     },
+    Command {
+        cmd: "export_ncryptsec",
+        usage_params: "[log_n]",
+        desc: "Export your private key as a NIP-49 ncryptsec (encrypted under your passphrase) with the given log_n, defaulting to the log_n setting",
+    },
+    Command {
+        cmd: "export_person_list",
+        usage_params: "<listnumber> <path>",
+        desc: "Export a person list to a file. The format depends on the extension: .json, .csv, or anything else for one npub per line",
+    },
+    Command {
+        cmd: "generate_mnemonic",
+        usage_params: "[--force]",
+        desc: "Generate a new NIP-06 seed phrase and make it your private key (--force replaces an existing key)",
+    },
     Command {
         cmd: "giftwrap_ids",
         usage_params: "",
@@ -95,6 +112,21 @@ const COMMANDS: [Command; 28] = [
         usage_params: "<event_json>",
         desc: "import and process a JSON event",
     },
+    Command {
+        cmd: "import_mnemonic",
+        usage_params: "[--force]",
+        desc: "Restore your private key from a NIP-06 seed phrase (you will be prompted for it; --force replaces an existing key)",
+    },
+    Command {
+        cmd: "import_ncryptsec",
+        usage_params: "<ncryptsec> [--force]",
+        desc: "Import a NIP-49 ncryptsec as your private key, keeping its log_n and key security (--force replaces an existing key)",
+    },
+    Command {
+        cmd: "import_person_list",
+        usage_params: "<path> [listnumber]",
+        desc: "Import a person list from a .json, .csv or npub-per-line file, into the given list or else into a new list",
+    },
     Command {
         cmd: "login",
         usage_params: "",
@@ -199,9 +231,15 @@ pub fn handle_command(mut args: env::Args, runtime: &Runtime) -> Result<bool, Er
         "delete_relay" => delete_relay(command, args)?,
         "events_of_kind" => events_of_kind(command, args)?,
         "events_of_pubkey_and_kind" => events_of_pubkey_and_kind(command, args)?,
+        "export_ncryptsec" => export_ncryptsec(command, args)?,
+        "export_person_list" => export_person_list(command, args)?,
+        "generate_mnemonic" => generate_mnemonic(command, args)?,
         "giftwrap_ids" => giftwrap_ids(command)?,
         "help" => help(command, args)?,
         "import_event" => import_event(command, args, runtime)?,
+        "import_mnemonic" => import_mnemonic(command, args)?,
+        "import_ncryptsec" => import_ncryptsec(command, args)?,
+        "import_person_list" => import_person_list(command, args)?,
         "login" => {
             login()?;
             return Ok(false);
@@ -770,6 +808,65 @@ pub fn ungiftwrap(cmd: Command, mut args: env::Args) -> Result<(), Error> {
     Ok(())
 }
 
+pub fn export_ncryptsec(_cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    let log_n = match args.next() {
+        Some(s) => s.parse::<u8>()?,
+        None => GLOBALS.storage.read_setting_log_n(),
+    };
+
+    if !GLOBALS.identity.has_private_key() {
+        return Err(ErrorKind::NoPrivateKey.into());
+    }
+
+    let mut password = rpassword::prompt_password("Password: ").unwrap();
+    let result = GLOBALS
+        .identity
+        .export_private_key_ncryptsec(&password, log_n);
+    password.zeroize();
+
+    println!("{}", result?.0);
+
+    Ok(())
+}
+
+pub fn export_person_list(cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    let number: u8 = match args.next() {
+        Some(number) => number.parse::<u8>()?,
+        None => return cmd.usage("Missing listnumber parameter".to_string()),
+    };
+
+    let path = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => return cmd.usage("Missing path parameter".to_string()),
+    };
+
+    let list = match PersonList::from_number(number) {
+        Some(list) => list,
+        None => {
+            println!("No list with number={}", number);
+            return Ok(());
+        }
+    };
+
+    person_list_io::export_person_list_to_file(list, &path)?;
+
+    Ok(())
+}
+
+pub fn generate_mnemonic(cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    check_key_replaceable(&cmd, args.next())?;
+
+    let mut mnemonic = gossip_lib::nip06::generate_mnemonic()?;
+    println!("Write these words down and keep them safe. Anyone with them has your key:");
+    println!();
+    println!("    {}", mnemonic);
+    println!();
+
+    let result = set_key_from_mnemonic(&mnemonic, "");
+    mnemonic.zeroize();
+    result
+}
+
 pub fn giftwrap_ids(_cmd: Command) -> Result<(), Error> {
     let ids = GLOBALS
         .storage
@@ -885,6 +982,111 @@ pub fn rename_person_list(cmd: Command, mut args: env::Args) -> Result<(), Error
     Ok(())
 }
 
+pub fn import_mnemonic(cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    check_key_replaceable(&cmd, args.next())?;
+
+    let mut mnemonic = rpassword::prompt_password("Seed phrase: ").unwrap();
+    let mut mnemonic_passphrase =
+        rpassword::prompt_password("Seed passphrase (empty if none): ").unwrap();
+    let result = set_key_from_mnemonic(&mnemonic, &mnemonic_passphrase);
+    mnemonic.zeroize();
+    mnemonic_passphrase.zeroize();
+    result
+}
+
+pub fn import_ncryptsec(cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    let ncryptsec = match args.next() {
+        Some(s) => s,
+        None => return cmd.usage("Missing ncryptsec parameter".to_string()),
+    };
+
+    check_key_replaceable(&cmd, args.next())?;
+
+    let mut password = rpassword::prompt_password("Password it is encrypted under: ").unwrap();
+    let result = GLOBALS
+        .identity
+        .import_encrypted_private_key(EncryptedPrivateKey(ncryptsec), &password);
+    password.zeroize();
+    result?;
+
+    println!(
+        "Imported key for {}",
+        GLOBALS.identity.public_key().unwrap().as_bech32_string()
+    );
+
+    Ok(())
+}
+
+pub fn import_person_list(cmd: Command, mut args: env::Args) -> Result<(), Error> {
+    let path = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => return cmd.usage("Missing path parameter".to_string()),
+    };
+
+    let into = match args.next() {
+        Some(number) => {
+            let number = number.parse::<u8>()?;
+            match PersonList::from_number(number) {
+                Some(list) => Some(list),
+                None => {
+                    println!("No list with number={}", number);
+                    return Ok(());
+                }
+            }
+        }
+        None => None,
+    };
+
+    let list = person_list_io::import_person_list_from_file(&path, into)?;
+    let len = GLOBALS.storage.get_people_in_list(list)?.len();
+    println!("List {} now has {} people", u8::from(list), len);
+
+    Ok(())
+}
+
+// Setting a private key replaces any existing one, which is then lost, so that
+// must be asked for with --force
+fn check_key_replaceable(cmd: &Command, flag: Option<String>) -> Result<(), Error> {
+    let force = match flag.as_deref() {
+        Some("--force") => true,
+        Some(other) => return cmd.usage(format!("Unexpected parameter {}", other)),
+        None => false,
+    };
+    if GLOBALS.identity.has_private_key() && !force {
+        return cmd.usage(
+            "You already have a private key, and it would be lost. Back it up, then use --force to replace it"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+// Callers must check_key_replaceable() first
+fn set_key_from_mnemonic(mnemonic: &str, mnemonic_passphrase: &str) -> Result<(), Error> {
+    let mut password = rpassword::prompt_password("Passphrase to encrypt the key under: ").unwrap();
+    let mut password2 = rpassword::prompt_password("Repeat passphrase: ").unwrap();
+    if password != password2 {
+        password.zeroize();
+        password2.zeroize();
+        return Err(ErrorKind::General("Passwords do not match".to_owned()).into());
+    }
+    password2.zeroize();
+
+    let result =
+        GLOBALS
+            .identity
+            .set_private_key_from_mnemonic(mnemonic, mnemonic_passphrase, &password);
+    password.zeroize();
+    result?;
+
+    println!(
+        "Your public key is {}",
+        GLOBALS.identity.public_key().unwrap().as_bech32_string()
+    );
+
+    Ok(())
+}
+
 pub fn login() -> Result<(), Error> {
     if GLOBALS.identity.has_private_key() {
         let mut password = rpassword::prompt_password("Password: ").unwrap();
@@ -13,14 +13,23 @@ use gossip_lib::comms::ToOverlordMessage;
 use gossip_lib::Error;
 use gossip_lib::GLOBALS;
 use std::sync::atomic::Ordering;
+use std::sync::OnceLock;
 use std::{env, thread};
+use time::UtcOffset;
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 pub const AVATAR_SIZE: u32 = 48; // points, not pixels
 pub const AVATAR_SIZE_F32: f32 = 48.0; // points, not pixels
 pub const AVATAR_SIZE_REPOST_F32: f32 = 27.0; // points, not pixels
 
+// The local time zone offset, or UTC if it can't be determined. It can only
+// be read reliably while we are single threaded, so it is read at startup.
+pub static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
 fn main() -> Result<(), Error> {
+    // Read the local offset before any threads are started
+    let _ = LOCAL_OFFSET.set(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC));
+
     // Setup logging
     if env::var("RUST_LOG").is_err() {
         env::set_var("RUST_LOG", "info");
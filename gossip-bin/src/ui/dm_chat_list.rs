@@ -128,7 +128,6 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
                         ctx,
                         Page::Feed(FeedKind::DmChat(channeldata.dm_channel.clone())),
                     );
-                    app.dm_draft_data.clear();
                     app.draft_needs_focus = true;
                 }
             }
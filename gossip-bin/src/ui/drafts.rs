@@ -0,0 +1,138 @@
+use super::feed::post;
+use super::{widgets, GossipUi, Page};
+use eframe::egui;
+use egui::{Context, Label, RichText, Ui};
+use gossip_lib::drafts::Draft;
+use gossip_lib::{DmChannel, FeedKind, GLOBALS};
+
+pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.heading("Drafts");
+    });
+    ui.add_space(10.0);
+
+    if !GLOBALS.identity.is_unlocked() {
+        ui.label("Direct message drafts are encrypted. Unlock your private key to see them.");
+        ui.add_space(10.0);
+    }
+
+    let drafts = gossip_lib::drafts::get_drafts();
+    if drafts.is_empty() {
+        ui.label("No drafts. Notes and messages are saved here while you write them.");
+        return;
+    }
+
+    let mut open: Option<Draft> = None;
+    let mut delete: Option<u64> = None;
+
+    app.vert_scroll_area().id_source("drafts").show(ui, |ui| {
+        let color = app.theme.accent_color();
+        for draft in drafts {
+            widgets::list_entry::make_frame(ui, Some(app.theme.main_content_bgcolor())).show(
+                ui,
+                |ui| {
+                    ui.set_min_width(ui.available_width());
+                    ui.vertical(|ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            if app.renaming_draft == Some(draft.id) {
+                                ui.add(text_edit_line!(app, app.renaming_draft_name));
+                                if ui.button("Save").clicked() {
+                                    let mut renamed = draft.clone();
+                                    renamed.name = app.renaming_draft_name.trim().to_owned();
+                                    if let Err(e) = gossip_lib::drafts::save_draft(&mut renamed) {
+                                        tracing::error!("{}", e);
+                                    }
+                                    // An open draft keeps its new name when it is next saved
+                                    for draft_data in [&mut app.draft_data, &mut app.dm_draft_data]
+                                    {
+                                        if let Some(saved) = &mut draft_data.saved_draft {
+                                            if saved.id == renamed.id {
+                                                saved.name = renamed.name.clone();
+                                            }
+                                        }
+                                    }
+                                    app.renaming_draft = None;
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    app.renaming_draft = None;
+                                }
+                            } else {
+                                let name = if draft.name.is_empty() {
+                                    "Untitled"
+                                } else {
+                                    draft.name.as_str()
+                                };
+                                ui.add(Label::new(RichText::new(name).heading().color(color)));
+                            }
+
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                ui.label(crate::date_ago::date_ago(draft.last_edit_time));
+                            });
+                        });
+
+                        let what = match &draft.dm_channel {
+                            Some(keys) => format!("Message to {}", DmChannel::new(keys).name()),
+                            None if draft.replying_to.is_some() => "Reply".to_owned(),
+                            None => "Note".to_owned(),
+                        };
+                        ui.label(RichText::new(what).weak());
+
+                        widgets::truncated_label(
+                            ui,
+                            draft.content.replace('\n', " "),
+                            ui.available_width() - 100.0,
+                        );
+
+                        ui.horizontal(|ui| {
+                            if ui.button("Open").clicked() {
+                                open = Some(draft.clone());
+                            }
+                            if ui.button("Rename").clicked() {
+                                app.renaming_draft = Some(draft.id);
+                                app.renaming_draft_name = draft.name.clone();
+                            }
+                            if ui.button("Delete").clicked() {
+                                delete = Some(draft.id);
+                            }
+                        });
+                    });
+                },
+            );
+        }
+    });
+
+    if let Some(id) = delete {
+        if let Err(e) = gossip_lib::drafts::delete_draft(id) {
+            tracing::error!("{}", e);
+        }
+        for draft_data in [&mut app.draft_data, &mut app.dm_draft_data] {
+            if draft_data.saved_draft.as_ref().map(|d| d.id) == Some(id) {
+                draft_data.saved_draft = None;
+            }
+        }
+    }
+
+    if let Some(draft) = open {
+        open_draft(app, ctx, draft);
+    }
+}
+
+fn open_draft(app: &mut GossipUi, ctx: &Context, draft: Draft) {
+    match draft.dm_channel.clone() {
+        Some(keys) => {
+            // Any message that was being written was saved when its chat
+            // was left
+            app.set_page(ctx, Page::Feed(FeedKind::DmChat(DmChannel::new(&keys))));
+            post::load_draft(&mut app.dm_draft_data, draft);
+        }
+        None => {
+            // Keep the note being written, if any
+            post::save_draft(&mut app.draft_data, None);
+
+            post::load_draft(&mut app.draft_data, draft);
+            app.show_post_area = true;
+        }
+    }
+    app.draft_needs_focus = true;
+}
@@ -216,6 +216,83 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
             let id = channel.unique_id();
             render_a_feed(app, ctx, frame, ui, feed, false, &id, load_more);
         }
+        FeedKind::Bookmarks(dtag) => {
+            let lists = gossip_lib::bookmarks::get_bookmark_lists();
+            let title = |dtag: &str| match lists.iter().find(|l| l.dtag == dtag) {
+                Some(list) if !list.is_main_list() => list.title.clone(),
+                _ => "Bookmarks".to_owned(),
+            };
+
+            ui.add_space(10.0);
+            ui.allocate_ui_with_layout(
+                Vec2::new(ui.available_width(), ui.spacing().interact_size.y),
+                egui::Layout::left_to_right(egui::Align::Center),
+                |ui| {
+                    add_left_space(ui);
+                    ui.heading(title(&dtag));
+                    recompute_btn(ui);
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_space(10.0);
+
+                        if !dtag.is_empty() && ui.button("Delete Set").clicked() {
+                            let _ = GLOBALS
+                                .to_overlord
+                                .send(ToOverlordMessage::DeleteBookmarkSet(dtag.clone()));
+                            app.set_page(ctx, Page::Feed(FeedKind::Bookmarks("".to_owned())));
+                        }
+
+                        ui.add_space(10.0);
+                        if ui.button("New Set").clicked()
+                            && !app.new_bookmark_set_title.trim().is_empty()
+                        {
+                            match gossip_lib::bookmarks::create_bookmark_set(
+                                app.new_bookmark_set_title.trim(),
+                            ) {
+                                Ok(new_dtag) => {
+                                    app.new_bookmark_set_title.clear();
+                                    app.set_page(ctx, Page::Feed(FeedKind::Bookmarks(new_dtag)));
+                                }
+                                Err(e) => GLOBALS.status_queue.write().write(format!("{}", e)),
+                            }
+                        }
+                        ui.add(
+                            text_edit_line!(app, app.new_bookmark_set_title)
+                                .hint_text("New set title")
+                                .desired_width(150.0),
+                        );
+
+                        ui.add_space(10.0);
+                        egui::ComboBox::from_id_source("bookmark_list_switcher")
+                            .selected_text(title(&dtag))
+                            .show_ui(ui, |ui| {
+                                for list in &lists {
+                                    if ui
+                                        .selectable_label(list.dtag == dtag, title(&list.dtag))
+                                        .clicked()
+                                    {
+                                        app.set_page(
+                                            ctx,
+                                            Page::Feed(FeedKind::Bookmarks(list.dtag.clone())),
+                                        );
+                                    }
+                                }
+                            });
+                    });
+                },
+            );
+            ui.add_space(6.0);
+
+            let feed = GLOBALS.feed.get_bookmarks_feed();
+            if feed.is_empty() {
+                ui.horizontal(|ui| {
+                    add_left_space(ui);
+                    ui.label("Nothing bookmarked here yet. Use the note menu to bookmark a note.");
+                });
+            }
+            let id = format!("bookmarks {}", dtag);
+            render_a_feed(app, ctx, frame, ui, feed, false, &id, load_more);
+        }
     }
 
     // Handle any changes due to changes in which notes are visible
@@ -223,7 +300,7 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
 }
 
 #[allow(clippy::too_many_arguments)]
-fn render_a_feed(
+pub(in crate::ui) fn render_a_feed(
     app: &mut GossipUi,
     ctx: &Context,
     frame: &mut eframe::Frame,
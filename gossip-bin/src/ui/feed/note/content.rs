@@ -6,6 +6,8 @@ use eframe::{
 };
 use egui::{Button, Color32, Pos2, RichText, Stroke, Ui};
 use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::file_metadata::FileMetadata;
+use gossip_lib::link_preview::LinkPreview;
 use gossip_lib::FeedKind;
 use gossip_lib::GLOBALS;
 use nostr_types::{ContentSegment, EventAddr, Id, IdHex, NostrBech32, PublicKey, Span, Url};
@@ -208,19 +210,79 @@ pub(super) fn render_hyperlink(
     // by controlling the image URL, and since only you see the URL it must have been you
     let privacy_issue = note.direct_message;
 
+    // What the event says about the file, if anything
+    let metadata = note.file_metadata.iter().find(|m| m.url == link);
+
     if let (Ok(url), Some(nurl)) = (url::Url::try_from(link), app.try_check_url(link)) {
-        if is_image_url(&url) {
-            show_image_toggle(app, ui, nurl, privacy_issue);
-        } else if is_video_url(&url) {
-            show_video_toggle(app, ui, nurl, privacy_issue);
+        let (is_image, is_video) = match metadata.filter(|m| m.mime.is_some()) {
+            Some(m) => (m.is_image(), m.is_video()),
+            None => (is_image_url(&url), is_video_url(&url)),
+        };
+        if is_image {
+            show_image_toggle(app, ui, nurl, privacy_issue, metadata);
+        } else if is_video {
+            show_video_toggle(app, ui, nurl, privacy_issue, metadata);
         } else {
             crate::ui::widgets::break_anywhere_hyperlink_to(ui, link, link);
+            if !privacy_issue {
+                if let Some(preview) = GLOBALS.link_previews.get(&nurl) {
+                    render_link_preview(app, ui, &preview);
+                }
+            }
         }
     } else {
         crate::ui::widgets::break_anywhere_hyperlink_to(ui, link, link);
     }
 }
 
+/// A card showing the title, description and image of a linked page
+fn render_link_preview(app: &mut GossipUi, ui: &mut Ui, preview: &LinkPreview) {
+    // Images are only loaded when media is shown inline
+    let image = if read_setting!(show_media) {
+        preview
+            .image
+            .as_ref()
+            .and_then(|i| app.try_get_media(ui.ctx(), i.clone(), None))
+    } else {
+        None
+    };
+
+    ui.end_row();
+    egui::Frame::group(ui.style())
+        .outer_margin(egui::Margin {
+            top: 6.0,
+            left: 0.0,
+            right: 0.0,
+            bottom: 6.0,
+        })
+        .show(ui, |ui| {
+            ui.set_max_width(ui.available_width().min(500.0));
+            ui.horizontal(|ui| {
+                if let Some(image) = image {
+                    ui.add(
+                        Image::new(&image)
+                            .max_size(Vec2::new(80.0, 80.0))
+                            .maintain_aspect_ratio(true),
+                    );
+                    ui.add_space(8.0);
+                }
+                ui.vertical(|ui| {
+                    ui.style_mut().spacing.item_spacing.y = 2.0;
+                    if let Some(site_name) = &preview.site_name {
+                        ui.label(RichText::new(site_name).small().weak());
+                    }
+                    if let Some(title) = &preview.title {
+                        ui.hyperlink_to(RichText::new(title).strong(), &preview.url);
+                    }
+                    if let Some(description) = &preview.description {
+                        ui.label(RichText::new(description).small());
+                    }
+                });
+            });
+        });
+    ui.end_row();
+}
+
 pub(super) fn render_plain(
     app: &mut GossipUi,
     ui: &mut Ui,
@@ -366,7 +428,13 @@ fn is_video_url(url: &url::Url) -> bool {
         || lower.ends_with(".webm")
 }
 
-fn show_image_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: bool) {
+fn show_image_toggle(
+    app: &mut GossipUi,
+    ui: &mut Ui,
+    url: Url,
+    privacy_issue: bool,
+    metadata: Option<&FileMetadata>,
+) {
     let row_height = ui.cursor().height();
     let url_string = url.to_string();
     let mut show_link = true;
@@ -378,7 +446,7 @@ fn show_image_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
         || (!read_setting!(show_media) && app.media_show_list.contains(&url));
 
     if show_image {
-        if let Some(response) = try_render_image(app, ui, url.clone()) {
+        if let Some(response) = try_render_image(app, ui, url.clone(), metadata) {
             show_link = false;
 
             // full-width toggle
@@ -398,7 +466,8 @@ fn show_image_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
             ui.link("[ PRIVACY RISK Image ]").on_hover_text(format!("The sender might be trying to associate your nostr pubkey with your IP address. URL={}", url_string))
         } else {
             // show url on hover
-            ui.link("[ Image ]").on_hover_text(url_string.clone())
+            ui.link(media_link_text("Image", metadata))
+                .on_hover_text(url_string.clone())
         };
 
         if response.clicked() {
@@ -438,9 +507,30 @@ fn show_image_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
 
 /// Try to fetch and render a piece of media
 ///  - return: true if successfully rendered, false otherwise
-fn try_render_image(app: &mut GossipUi, ui: &mut Ui, url: Url) -> Option<Response> {
+fn try_render_image(
+    app: &mut GossipUi,
+    ui: &mut Ui,
+    url: Url,
+    metadata: Option<&FileMetadata>,
+) -> Option<Response> {
     let mut response_return = None;
-    if let Some(media) = app.try_get_media(ui.ctx(), url.clone()) {
+    let alt = metadata.and_then(|m| m.alt.clone());
+    let media = app.try_get_media(ui.ctx(), url.clone(), metadata);
+    if media.is_none() {
+        // While it loads, hold its place if we know its size
+        if let Some((w, h)) = metadata.and_then(|m| m.dim) {
+            if !app.has_media_loading_failed(url.as_str()) {
+                return Some(render_media_placeholder(
+                    app,
+                    ui,
+                    &url,
+                    Vec2::new(w as f32, h as f32),
+                    metadata.and_then(|m| m.blurhash.as_deref()),
+                ));
+            }
+        }
+    }
+    if let Some(media) = media {
         let size = media_scale(
             app.media_full_width_list.contains(&url),
             ui,
@@ -473,6 +563,10 @@ fn try_render_image(app: &mut GossipUi, ui: &mut Ui, url: Url) -> Option<Respons
                 if response.hovered() {
                     ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
                 }
+                let response = match alt {
+                    Some(alt) => response.on_hover_text(alt),
+                    None => response,
+                };
                 add_media_menu(app, ui, url, &response);
                 response_return = Some(response);
             });
@@ -480,7 +574,13 @@ fn try_render_image(app: &mut GossipUi, ui: &mut Ui, url: Url) -> Option<Respons
     response_return
 }
 
-fn show_video_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: bool) {
+fn show_video_toggle(
+    app: &mut GossipUi,
+    ui: &mut Ui,
+    url: Url,
+    privacy_issue: bool,
+    metadata: Option<&FileMetadata>,
+) {
     let row_height = ui.cursor().height();
     let url_string = url.to_string();
     let mut show_link = true;
@@ -492,7 +592,7 @@ fn show_video_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
         || (!read_setting!(show_media) && app.media_show_list.contains(&url));
 
     if show_video {
-        if let Some(response) = try_render_video(app, ui, url.clone()) {
+        if let Some(response) = try_render_video(app, ui, url.clone(), metadata) {
             show_link = false;
 
             // full-width toggle
@@ -515,7 +615,8 @@ fn show_video_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
             ))
         } else {
             // show url on hover
-            ui.link("[ Video ]").on_hover_text(url_string.clone())
+            ui.link(media_link_text("Video", metadata))
+                .on_hover_text(url_string.clone())
         };
 
         if response.clicked() {
@@ -554,10 +655,15 @@ fn show_video_toggle(app: &mut GossipUi, ui: &mut Ui, url: Url, privacy_issue: b
 }
 
 #[cfg(feature = "video-ffmpeg")]
-fn try_render_video(app: &mut GossipUi, ui: &mut Ui, url: Url) -> Option<Response> {
+fn try_render_video(
+    app: &mut GossipUi,
+    ui: &mut Ui,
+    url: Url,
+    metadata: Option<&FileMetadata>,
+) -> Option<Response> {
     let mut response_return = None;
     let show_full_width = app.media_full_width_list.contains(&url);
-    if let Some(player_ref) = app.try_get_player(ui.ctx(), url.clone()) {
+    if let Some(player_ref) = app.try_get_player(ui.ctx(), url.clone(), metadata) {
         if let Ok(mut player) = player_ref.try_borrow_mut() {
             let size = media_scale(
                 show_full_width,
@@ -590,10 +696,66 @@ fn try_render_video(app: &mut GossipUi, ui: &mut Ui, url: Url) -> Option<Respons
 }
 
 #[cfg(not(feature = "video-ffmpeg"))]
-fn try_render_video(_app: &mut GossipUi, _ui: &mut Ui, _url: Url) -> Option<Response> {
+fn try_render_video(
+    _app: &mut GossipUi,
+    _ui: &mut Ui,
+    _url: Url,
+    _metadata: Option<&FileMetadata>,
+) -> Option<Response> {
     None
 }
 
+fn media_link_text(what: &str, metadata: Option<&FileMetadata>) -> String {
+    match metadata.and_then(|m| m.alt.as_ref()) {
+        Some(alt) => format!("[ {}: {} ]", what, alt),
+        None => format!("[ {} ]", what),
+    }
+}
+
+/// Take up the space media will occupy once it loads, showing its blurhash if
+/// there is one
+fn render_media_placeholder(
+    app: &mut GossipUi,
+    ui: &mut Ui,
+    url: &Url,
+    media_size: Vec2,
+    blurhash: Option<&str>,
+) -> Response {
+    let size = media_scale(app.media_full_width_list.contains(url), ui, media_size);
+
+    // insert a newline if the current line has text
+    if ui.cursor().min.x > ui.max_rect().min.x {
+        ui.end_row();
+    }
+
+    let texture = blurhash.and_then(|b| app.try_get_blurhash(ui.ctx(), b));
+    let mut response_return = None;
+    egui::Frame::none()
+        .outer_margin(egui::Margin {
+            top: 10.0,
+            left: 0.0,
+            right: 0.0,
+            bottom: 10.0,
+        })
+        .show(ui, |ui| {
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            match texture {
+                Some(texture) => {
+                    Image::new(&texture).paint_at(ui, rect);
+                }
+                None => {
+                    ui.painter().rect_filled(
+                        rect,
+                        ui.style().noninteractive().rounding,
+                        ui.visuals().faint_bg_color,
+                    );
+                }
+            }
+            response_return = Some(response.on_hover_text("Loading..."));
+        });
+    response_return.unwrap()
+}
+
 fn media_scale(show_full_width: bool, ui: &Ui, media_size: Vec2) -> Vec2 {
     let aspect = media_size.x / media_size.y;
     let ui_max = if show_full_width {
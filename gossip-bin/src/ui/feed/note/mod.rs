@@ -14,13 +14,16 @@ use eframe::egui::{self, Margin};
 use egui::{
     Align, Context, Frame, Label, Layout, RichText, Sense, Separator, Stroke, TextStyle, Ui,
 };
+use gossip_lib::bookmarks::Bookmark;
 use gossip_lib::comms::ToOverlordMessage;
 use gossip_lib::DmChannel;
 use gossip_lib::FeedKind;
+use gossip_lib::Mute;
+use gossip_lib::Relay;
 use gossip_lib::{ZapState, GLOBALS};
 use nostr_types::{
-    Event, EventAddr, EventDelegation, EventKind, EventPointer, EventReference, IdHex, NostrUrl,
-    UncheckedUrl,
+    Event, EventAddr, EventDelegation, EventKind, EventPointer, EventReference, Id, IdHex,
+    NostrUrl, UncheckedUrl,
 };
 
 pub struct NoteRenderData {
@@ -204,6 +207,19 @@ pub(super) fn render_note(
     }
 }
 
+// Relay hints for the note, most recently seen first. This reads storage, so
+// it is only called when a hint is needed, not every frame.
+fn relay_hints(id: Id) -> Vec<UncheckedUrl> {
+    match Relay::event_relay_hints(id) {
+        Ok(vec) => vec
+            .iter()
+            .map(|url| url.to_unchecked_url())
+            .take(3)
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
 // FIXME, create some way to limit the arguments here.
 fn render_note_inner(
     app: &mut GossipUi,
@@ -276,15 +292,6 @@ fn render_note_inner(
         let content_margin_left = AVATAR_SIZE_F32 + inner_margin.left;
         let footer_margin_left = content_margin_left;
 
-        let relays = match GLOBALS.storage.get_event_seen_on_relay(note.event.id) {
-            Ok(vec) => vec
-                .iter()
-                .map(|(url, _)| url.to_unchecked_url())
-                .take(3)
-                .collect(),
-            Err(_) => vec![],
-        };
-
         ui.vertical(|ui| {
             // First row
 
@@ -379,6 +386,45 @@ fn render_note_inner(
                         _ => {}
                     }
 
+                    if let Some(impersonated) = &note.impersonates {
+                        let color = app.theme.warning_marker_text_color();
+                        ui.add(Label::new(
+                            RichText::new("LOOKALIKE NAME")
+                                .color(color)
+                                .text_style(TextStyle::Small),
+                        ))
+                        .on_hover_text(format!(
+                            "This name looks like {}, who you follow, but it is a different person",
+                            gossip_lib::names::best_name_from_pubkey_lookup(impersonated)
+                        ));
+                    }
+
+                    if let Some((when, changes)) = &note.profile_change {
+                        let color = app.theme.notice_marker_text_color();
+                        let mut hover = String::new();
+                        for change in changes {
+                            hover.push_str(&format!(
+                                "{}: {} → {}\n",
+                                change.field,
+                                change.old.as_deref().unwrap_or("(none)"),
+                                change.new.as_deref().unwrap_or("(none)")
+                            ));
+                        }
+                        if let Ok(stamp) = time::OffsetDateTime::from_unix_timestamp(when.0) {
+                            if let Ok(formatted) =
+                                stamp.format(&time::format_description::well_known::Rfc2822)
+                            {
+                                hover.push_str(&format!("changed {}", formatted));
+                            }
+                        }
+                        ui.add(Label::new(
+                            RichText::new("PROFILE CHANGED")
+                                .color(color)
+                                .text_style(TextStyle::Small),
+                        ))
+                        .on_hover_text(hover.trim_end());
+                    }
+
                     if !note.deletions.is_empty() {
                         let color = app.theme.warning_marker_text_color();
                         ui.label(
@@ -388,6 +434,15 @@ fn render_note_inner(
                         );
                     }
 
+                    if note.pinned && matches!(app.page, Page::Feed(FeedKind::Person(_))) {
+                        let color = app.theme.notice_marker_text_color();
+                        ui.label(
+                            RichText::new("PINNED")
+                                .color(color)
+                                .text_style(TextStyle::Small),
+                        );
+                    }
+
                     if note.repost.is_some() {
                         let color = app.theme.notice_marker_text_color();
                         ui.label(
@@ -458,7 +513,7 @@ fn render_note_inner(
                             if ui.button("Copy naddr").clicked() {
                                 let event_addr = EventAddr {
                                     d: param,
-                                    relays: relays.clone(),
+                                    relays: relay_hints(note.event.id),
                                     kind: note.event.kind,
                                     author: note.event.pubkey,
                                 };
@@ -470,7 +525,7 @@ fn render_note_inner(
                             if ui.button("Copy nevent").clicked() {
                                 let event_pointer = EventPointer {
                                     id: note.event.id,
-                                    relays: relays.clone(),
+                                    relays: relay_hints(note.event.id),
                                     author: None,
                                     kind: None,
                                 };
@@ -483,7 +538,7 @@ fn render_note_inner(
                             if ui.button("Copy web link").clicked() {
                                 let event_pointer = EventPointer {
                                     id: note.event.id,
-                                    relays: relays.clone(),
+                                    relays: relay_hints(note.event.id),
                                     author: None,
                                     kind: None,
                                 };
@@ -511,10 +566,97 @@ fn render_note_inner(
                             });
                             *keep_open = false;
                         }
+                        if !note.event.kind.is_direct_message_related()
+                            && GLOBALS.identity.public_key().is_some()
+                        {
+                            let bookmark = Bookmark::of_event(&note.event);
+                            let in_lists = gossip_lib::bookmarks::lists_containing(&bookmark);
+                            let mut changes: Vec<(String, bool, bool)> = Vec::new();
+                            if in_lists.iter().any(|d| d.is_empty()) {
+                                if ui.button("Remove Bookmark").clicked() {
+                                    changes.push(("".to_owned(), false, true));
+                                }
+                            } else {
+                                if ui.button("Bookmark").clicked() {
+                                    changes.push(("".to_owned(), true, true));
+                                }
+                                if ui.button("Bookmark Privately").clicked() {
+                                    changes.push(("".to_owned(), true, false));
+                                }
+                            }
+                            for list in gossip_lib::bookmarks::get_bookmark_lists() {
+                                if list.is_main_list() {
+                                    continue;
+                                }
+                                let bookmarked = in_lists.contains(&list.dtag);
+                                let label = if bookmarked {
+                                    format!("Remove from {}", list.title)
+                                } else {
+                                    format!("Add to {}", list.title)
+                                };
+                                if ui.button(label).clicked() {
+                                    changes.push((list.dtag.clone(), !bookmarked, true));
+                                }
+                            }
+                            for (dtag, bookmarked, public) in changes {
+                                if let Err(e) = gossip_lib::bookmarks::set_bookmark(
+                                    &dtag,
+                                    bookmark.clone(),
+                                    bookmarked,
+                                    public,
+                                ) {
+                                    GLOBALS.status_queue.write().write(format!("{}", e));
+                                }
+                                *keep_open = false;
+                            }
+                        }
                         if ui.button("Dismiss").clicked() {
                             GLOBALS.dismissed.blocking_write().push(note.event.id);
                             *keep_open = false;
                         }
+                        if !note.event.kind.is_direct_message_related() {
+                            let thread = Mute::thread_of(&note.event);
+                            let thread_muted =
+                                GLOBALS.people.get_mutes().iter().any(|(m, _)| *m == thread);
+                            let label = if thread_muted {
+                                "Unmute Thread"
+                            } else {
+                                "Mute Thread"
+                            };
+                            if ui.button(label).clicked() {
+                                if let Err(e) = GLOBALS.people.set_mute(thread, !thread_muted, true)
+                                {
+                                    GLOBALS.status_queue.write().write(format!("{}", e));
+                                }
+                                *keep_open = false;
+                            }
+                            for hashtag in note.event.hashtags() {
+                                let mute = Mute::Hashtag(hashtag.to_lowercase());
+                                if ui.button(format!("Mute #{}", hashtag)).clicked() {
+                                    if let Err(e) = GLOBALS.people.set_mute(mute, true, true) {
+                                        GLOBALS.status_queue.write().write(format!("{}", e));
+                                    }
+                                    *keep_open = false;
+                                }
+                            }
+                        }
+                        if note.deletions.is_empty()
+                            && !note.event.kind.is_direct_message_related()
+                            && Some(note.event.pubkey) == GLOBALS.identity.public_key()
+                        {
+                            let label = if note.pinned {
+                                "Unpin from Profile"
+                            } else {
+                                "Pin to Profile"
+                            };
+                            if ui.button(label).clicked() {
+                                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::SetPinned(
+                                    note.event.id,
+                                    !note.pinned,
+                                ));
+                                *keep_open = false;
+                            }
+                        }
                         if note.deletions.is_empty() {
                             if ui.button("Delete").clicked() {
                                 let _ = GLOBALS
@@ -775,7 +917,7 @@ fn render_note_inner(
                                                     };
                                                     let event_addr = EventAddr {
                                                         d: param,
-                                                        relays: relays.clone(),
+                                                        relays: relay_hints(note.event.id),
                                                         kind: note.event.kind,
                                                         author: note.event.pubkey,
                                                     };
@@ -783,9 +925,9 @@ fn render_note_inner(
                                                 } else {
                                                     let event_pointer = EventPointer {
                                                         id: note.event.id,
-                                                        relays: relays.clone(),
-                                                        author: None,
-                                                        kind: None,
+                                                        relays: relay_hints(note.event.id),
+                                                        author: Some(note.event.pubkey),
+                                                        kind: Some(note.event.kind),
                                                     };
                                                     event_pointer.into()
                                                 };
@@ -1,10 +1,12 @@
+use gossip_lib::file_metadata::FileMetadata;
+use gossip_lib::metadata_history::{self, MetadataChange};
 use gossip_lib::GLOBALS;
 use gossip_lib::{Person, PersonList};
 use std::collections::HashMap;
 
 use nostr_types::{
     ContentSegment, Event, EventDelegation, EventKind, Id, MilliSatoshi, NostrBech32, PublicKey,
-    ShatteredContent,
+    ShatteredContent, Unixtime,
 };
 
 #[derive(PartialEq)]
@@ -36,9 +38,22 @@ pub(super) struct NoteData {
     /// Lists the author is on
     pub lists: HashMap<PersonList, bool>,
 
+    /// Who the author's name looks like, if they are pretending to be
+    /// somebody we follow
+    pub impersonates: Option<PublicKey>,
+
+    /// When the author recently changed their profile, and what changed
+    pub profile_change: Option<(Unixtime, Vec<MetadataChange>)>,
+
+    /// Is this note muted by a muted word, hashtag or thread?
+    pub muted_content: bool,
+
     /// Deletion reasons if any
     pub deletions: Vec<String>,
 
+    /// Has the author pinned this note to their profile?
+    pub pinned: bool,
+
     /// Do we consider this note as being a repost of another?
     pub repost: Option<RepostType>,
 
@@ -60,6 +75,9 @@ pub(super) struct NoteData {
     /// The content shattered into renderable elements
     pub shattered_content: ShatteredContent,
 
+    /// What the event says about the files it links to
+    pub file_metadata: Vec<FileMetadata>,
+
     /// error content (gossip-created notations)
     pub error_content: Option<String>,
 
@@ -161,6 +179,19 @@ impl NoteData {
             EventKind::LiveChatMessage => (event.content.clone(), None),
             EventKind::CommunityPost => (event.content.clone(), None),
             EventKind::DraftLongFormContent => (event.content.clone(), None),
+            EventKind::FileMetadata => match FileMetadata::from_nip94_event(&event) {
+                // Show the caption, then the file
+                Some(metadata) => (
+                    format!("{}\n{}", event.content.trim(), metadata.url)
+                        .trim()
+                        .to_owned(),
+                    None,
+                ),
+                None => (
+                    "".to_owned(),
+                    Some("FILE METADATA WITHOUT A URL".to_owned()),
+                ),
+            },
             k => {
                 let kind_number: u32 = k.into();
                 let mut dc = format!("UNSUPPORTED EVENT KIND {}", kind_number);
@@ -206,6 +237,8 @@ impl NoteData {
                 Some(RepostType::Kind6Embedded)
             } else if event.kind == EventKind::GenericRepost {
                 Some(RepostType::GenericRepost)
+            } else if event.kind == EventKind::FileMetadata {
+                None
             } else if has_tag_reference || has_nostr_event_reference || content_trim.is_empty() {
                 if content_trim.is_empty() {
                     // handle NIP-18 conform kind:6 with 'e' tag but no content
@@ -243,12 +276,27 @@ impl NoteData {
             _ => HashMap::new(),
         };
 
+        let impersonates = GLOBALS.impersonation.impersonates(&author);
+
+        let profile_change =
+            metadata_history::recent_profile_change(&author_pubkey).unwrap_or_default();
+
+        let muted_content = !direct_message && GLOBALS.people.is_event_muted(&event);
+
+        let pinned = !direct_message && gossip_lib::pins::is_pinned(&event);
+
+        let file_metadata = FileMetadata::from_event(&event);
+
         NoteData {
             event,
             delegation,
             author,
             lists,
+            impersonates,
+            profile_change,
+            muted_content,
             deletions,
+            pinned,
             repost,
             embedded_event,
             mentions,
@@ -256,6 +304,7 @@ impl NoteData {
             zaptotal,
             self_already_reacted,
             shattered_content,
+            file_metadata,
             error_content,
             direct_message,
             secure,
@@ -280,6 +329,6 @@ impl NoteData {
     }
 
     pub(super) fn muted(&self) -> bool {
-        self.lists.contains_key(&PersonList::Muted)
+        self.lists.contains_key(&PersonList::Muted) || self.muted_content
     }
 }
@@ -1,6 +1,8 @@
 use super::FeedNoteParams;
 use crate::ui::widgets::InformationPopup;
-use crate::ui::{widgets, you, FeedKind, GossipUi, HighlightType, Page, Theme};
+use crate::ui::{
+    scheduled, widgets, you, DraftData, FeedKind, GossipUi, HighlightType, Page, Theme,
+};
 use eframe::egui;
 use eframe::epaint::text::LayoutJob;
 use egui::containers::CollapsingHeader;
@@ -9,12 +11,21 @@ use egui_winit::egui::text::CCursor;
 use egui_winit::egui::text_edit::{CCursorRange, TextEditOutput};
 use egui_winit::egui::{vec2, AboveOrBelow, Id};
 use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::drafts::Draft;
+use gossip_lib::file_metadata::FileMetadata;
 use gossip_lib::DmChannel;
 use gossip_lib::Relay;
 use gossip_lib::GLOBALS;
 use memoize::memoize;
-use nostr_types::{ContentSegment, NostrBech32, NostrUrl, ShatteredContent, Tag};
+use nostr_types::{
+    ContentSegment, NostrBech32, NostrUrl, PublicKey, ShatteredContent, Tag, Unixtime,
+};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// While typing, drafts are saved at most this often
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(2);
 
 #[memoize]
 pub fn textarea_highlighter(theme: Theme, text: String, interests: Vec<String>) -> LayoutJob {
@@ -153,7 +164,7 @@ pub(in crate::ui) fn posting_area(
 
 fn dm_posting_area(
     app: &mut GossipUi,
-    _ctx: &Context,
+    ctx: &Context,
     _frame: &mut eframe::Frame,
     ui: &mut Ui,
     dm_channel: &DmChannel,
@@ -230,6 +241,7 @@ fn dm_posting_area(
 
     ui.horizontal(|ui| {
         if ui.button("Clear").clicked() {
+            discard_saved_draft(&mut app.dm_draft_data);
             app.reset_draft();
         }
 
@@ -268,16 +280,10 @@ fn dm_posting_area(
     });
 
     if send_now {
-        let mut tags: Vec<Tag> = Vec::new();
-        if app.dm_draft_data.include_content_warning {
-            tags.push(Tag::new_content_warning(&app.dm_draft_data.content_warning));
-        }
+        let mut tags = draft_tags(&app.dm_draft_data, &app.dm_draft_data.draft);
         if let Some(delegatee_tag) = GLOBALS.delegation.get_delegatee_tag() {
             tags.push(delegatee_tag);
         }
-        if app.dm_draft_data.include_subject {
-            tags.push(Tag::new_subject(app.dm_draft_data.subject.clone()));
-        }
 
         let _ = GLOBALS.to_overlord.send(ToOverlordMessage::Post {
             content: app.dm_draft_data.draft.clone(),
@@ -286,7 +292,10 @@ fn dm_posting_area(
             dm_channel: Some(dm_channel.to_owned()),
         });
 
+        discard_saved_draft(&mut app.dm_draft_data);
         app.reset_draft();
+    } else {
+        autosave_draft(ctx, &mut app.dm_draft_data, Some(dm_channel));
     }
 
     // List tags that will be applied
@@ -323,6 +332,20 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
     let screen_rect = ctx.input(|i| i.screen_rect);
     let window_height = screen_rect.max.y - screen_rect.min.y;
 
+    // Files dropped onto the window are uploaded and attached
+    if app.draft_data.repost.is_none() {
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|f| f.path.clone())
+                .collect()
+        });
+        for path in dropped {
+            start_upload(app, path);
+        }
+    }
+
     app.vert_scroll_area()
         .max_height(window_height * 0.7)
         .show(ui, |ui| {
@@ -388,6 +411,20 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
                     ui.add_space(10.0);
                 }
 
+                if app.draft_data.schedule {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Post at ({}): ", scheduled::zone_name()));
+                        ui.add(
+                            text_edit_line!(app, app.draft_data.schedule_time)
+                                .hint_text("YYYY-MM-DD HH:MM")
+                                .desired_width(150.0),
+                        );
+                        ui.checkbox(&mut app.draft_data.schedule_sign_now, "Sign now")
+                            .on_hover_text("Sign it now, dated when it is due. Otherwise it is signed when it is posted. Either way it waits for your key to be unlocked.");
+                    });
+                    ui.add_space(10.0);
+                }
+
                 // if we are tagging, we will consume arrow presses and enter key
                 let enter_key;
                 (app.draft_data.tagging_search_selected, enter_key) =
@@ -488,9 +525,14 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
             ui.add_space(8.0);
         });
 
+    attach_file_area(ui, app);
+    uploads_area(ui, app);
+
     ui.horizontal(|ui| {
         let send_label = if app.draft_data.repost.is_some() {
             "Repost note"
+        } else if app.draft_data.schedule {
+            "Schedule note"
         } else {
             "Send note"
         };
@@ -499,8 +541,8 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
             // show advanced action menu
             if app.draft_data.repost.is_none() {
                 widgets::MoreMenu::bubble(ui, app)
-                    .with_max_size(vec2(180.0, 80.0))
-                    .with_min_size(vec2(180.0, 80.0))
+                    .with_max_size(vec2(180.0, 140.0))
+                    .with_min_size(vec2(180.0, 140.0))
                     .place_above(!read_setting!(posting_area_at_top))
                     .show(ui, |ui, is_open| {
                         ui.vertical_centered_justified(|ui| {
@@ -532,6 +574,31 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
 
                             ui.add_space(10.0);
 
+                            if ui.button("Attach File").clicked() {
+                                app.draft_data.attach_path = Some("".to_owned());
+                                *is_open = false;
+                            }
+
+                            ui.add_space(10.0);
+
+                            if app.draft_data.schedule {
+                                if ui.button("Post Immediately").clicked() {
+                                    app.draft_data.schedule = false;
+                                    *is_open = false;
+                                }
+                            } else if ui.button("Schedule").clicked() {
+                                app.draft_data.schedule = true;
+                                if app.draft_data.schedule_time.is_empty() {
+                                    // Default to an hour from now
+                                    let in_an_hour = Unixtime(Unixtime::now().unwrap().0 + 60 * 60);
+                                    app.draft_data.schedule_time =
+                                        scheduled::format_time(in_an_hour);
+                                }
+                                *is_open = false;
+                            }
+
+                            ui.add_space(10.0);
+
                             ui.set_enabled(!app.draft_data.replacements.is_empty());
                             if ui.button("Show raw preview").clicked() {
                                 let raw = do_replacements(
@@ -550,6 +617,8 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
             ui.horizontal(|ui| {
                 ui.visuals_mut().hyperlink_color = ui.visuals().text_color();
                 if ui.link("Cancel").clicked() {
+                    // Keep what was written in the drafts
+                    save_draft(&mut app.draft_data, None);
                     app.reset_draft();
                 }
             });
@@ -598,45 +667,75 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
         }
     });
 
+    // A scheduled post needs a time in the future
+    let mut schedule_at: Option<Unixtime> = None;
+    if send_now && app.draft_data.schedule {
+        match scheduled::parse_time(&app.draft_data.schedule_time) {
+            Some(send_at) if send_at > Unixtime::now().unwrap() => schedule_at = Some(send_at),
+            Some(_) => {
+                GLOBALS
+                    .status_queue
+                    .write()
+                    .write("The scheduled time has already passed.".to_owned());
+                send_now = false;
+            }
+            None => {
+                GLOBALS
+                    .status_queue
+                    .write()
+                    .write("Enter the time to post at as YYYY-MM-DD HH:MM".to_owned());
+                send_now = false;
+            }
+        }
+    }
+
     if send_now {
         let replaced = do_replacements(&app.draft_data.draft, &app.draft_data.replacements);
 
-        let mut tags: Vec<Tag> = Vec::new();
-        if app.draft_data.include_content_warning {
-            tags.push(Tag::new_content_warning(&app.draft_data.content_warning));
-        }
+        let mut tags = draft_tags(&app.draft_data, &replaced);
         if let Some(delegatee_tag) = GLOBALS.delegation.get_delegatee_tag() {
             tags.push(delegatee_tag);
         }
-        if app.draft_data.include_subject {
-            tags.push(Tag::new_subject(app.draft_data.subject.clone()));
-        }
-        match app.draft_data.replying_to {
-            Some(replying_to_id) => {
-                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::Post {
-                    content: replaced,
-                    tags,
-                    in_reply_to: Some(replying_to_id),
-                    dm_channel: None,
-                });
-            }
-            None => {
-                if let Some(event_id) = app.draft_data.repost {
-                    let _ = GLOBALS
-                        .to_overlord
-                        .send(ToOverlordMessage::Repost(event_id));
-                } else {
+        if let Some(send_at) = schedule_at {
+            let _ = GLOBALS.to_overlord.send(ToOverlordMessage::SchedulePost {
+                content: replaced,
+                tags,
+                in_reply_to: app.draft_data.replying_to,
+                dm_channel: None,
+                send_at,
+                sign_now: app.draft_data.schedule_sign_now,
+            });
+        } else {
+            match app.draft_data.replying_to {
+                Some(replying_to_id) => {
                     let _ = GLOBALS.to_overlord.send(ToOverlordMessage::Post {
                         content: replaced,
                         tags,
-                        in_reply_to: None,
+                        in_reply_to: Some(replying_to_id),
                         dm_channel: None,
                     });
                 }
+                None => {
+                    if let Some(event_id) = app.draft_data.repost {
+                        let _ = GLOBALS
+                            .to_overlord
+                            .send(ToOverlordMessage::Repost(event_id));
+                    } else {
+                        let _ = GLOBALS.to_overlord.send(ToOverlordMessage::Post {
+                            content: replaced,
+                            tags,
+                            in_reply_to: None,
+                            dm_channel: None,
+                        });
+                    }
+                }
             }
         }
 
+        discard_saved_draft(&mut app.draft_data);
         app.reset_draft();
+    } else {
+        autosave_draft(ctx, &mut app.draft_data, None);
     }
 
     // List tags that will be applied
@@ -664,6 +763,93 @@ fn real_posting_area(app: &mut GossipUi, ctx: &Context, frame: &mut eframe::Fram
     }
 }
 
+fn start_upload(app: &mut GossipUi, path: PathBuf) {
+    let file_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or("file".to_owned());
+    let upload_id = gossip_lib::upload::new_upload(file_name);
+    app.draft_data.uploads.push(upload_id);
+    let _ = GLOBALS
+        .to_overlord
+        .send(ToOverlordMessage::UploadFile(upload_id, path));
+}
+
+fn attach_file_area(ui: &mut Ui, app: &mut GossipUi) {
+    let mut path = match app.draft_data.attach_path.take() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let mut keep = true;
+    ui.horizontal(|ui| {
+        ui.label("File to attach:");
+        let response = ui.add(text_edit_line!(app, path).desired_width(300.0));
+        let entered = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+        if (ui.button("Upload").clicked() || entered) && !path.trim().is_empty() {
+            start_upload(app, PathBuf::from(path.trim()));
+            keep = false;
+        }
+        if ui.link("Cancel").clicked() {
+            keep = false;
+        }
+    });
+    ui.label(RichText::new("You can also drop files onto the window.").weak());
+
+    if keep {
+        app.draft_data.attach_path = Some(path);
+    }
+}
+
+fn uploads_area(ui: &mut Ui, app: &mut GossipUi) {
+    let mut finished: Vec<u64> = Vec::new();
+
+    for upload_id in app.draft_data.uploads.iter() {
+        let upload = match GLOBALS.uploads.get(upload_id) {
+            Some(upload) => upload.clone(),
+            None => {
+                // It failed, which the overlord reported to the status queue
+                finished.push(*upload_id);
+                continue;
+            }
+        };
+
+        if upload.result.is_some() {
+            finished.push(*upload_id);
+            if let Some(file) = gossip_lib::upload::take_finished_upload(*upload_id) {
+                if !app.draft_data.draft.is_empty() && !app.draft_data.draft.ends_with('\n') {
+                    app.draft_data.draft.push('\n');
+                }
+                app.draft_data.draft.push_str(&file.url);
+                app.draft_data.attachments.push(file);
+            }
+        } else {
+            let progress = if upload.total > 0 {
+                upload.sent as f32 / upload.total as f32
+            } else {
+                0.0
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("Uploading {}", upload.file_name));
+                ui.add(
+                    egui::ProgressBar::new(progress)
+                        .desired_width(200.0)
+                        .show_percentage(),
+                );
+            });
+        }
+    }
+
+    if !finished.is_empty() {
+        app.draft_data.uploads.retain(|id| !finished.contains(id));
+        ui.ctx().request_repaint();
+    } else if !app.draft_data.uploads.is_empty() {
+        // Keep the progress moving
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_millis(200));
+    }
+}
+
 fn calc_tagging_search(app: &mut GossipUi) {
     // show tagging slector tooltip
     if let Some(search) = &app.draft_data.tagging_search_substring {
@@ -875,3 +1061,123 @@ fn do_replacements(draft: &str, replacements: &HashMap<String, ContentSegment>)
     }
     output
 }
+
+// The tags a draft is posted with, other than those derived from its content
+fn draft_tags(draft_data: &DraftData, content: &str) -> Vec<Tag> {
+    let mut tags: Vec<Tag> = Vec::new();
+    if draft_data.include_content_warning {
+        tags.push(Tag::new_content_warning(&draft_data.content_warning));
+    }
+    if draft_data.include_subject {
+        tags.push(Tag::new_subject(draft_data.subject.clone()));
+    }
+    // Describe attachments that are still in the note
+    for file in &draft_data.attachments {
+        if content.contains(&file.url) {
+            tags.push(file.imeta_tag());
+        }
+    }
+    // Restored attachment metadata only applies while the file is still in
+    // the note
+    for tag in &draft_data.restored_tags {
+        if let Some(metadata) = FileMetadata::from_imeta_tag(tag) {
+            if !content.contains(&metadata.url) {
+                continue;
+            }
+        }
+        tags.push(tag.clone());
+    }
+    tags
+}
+
+// Whether the draft differs from what was last saved. This compares the
+// text as typed, so replacements are only done when actually saving.
+fn draft_changed(draft_data: &DraftData, dm_channel: &Option<Vec<PublicKey>>) -> bool {
+    if draft_data.repost.is_some() {
+        return false;
+    }
+    match &draft_data.saved_draft {
+        Some(saved) => {
+            draft_data.saved_text != draft_data.draft
+                || saved.replying_to != draft_data.replying_to
+                || &saved.dm_channel != dm_channel
+                || saved.tags != draft_tags(draft_data, &draft_data.draft)
+        }
+        // Don't save empty drafts
+        None => !draft_data.draft.trim().is_empty(),
+    }
+}
+
+/// Save the draft now if it changed since it was last saved
+pub(in crate::ui) fn save_draft(draft_data: &mut DraftData, dm_channel: Option<&DmChannel>) {
+    let dm_channel = dm_channel.map(|dmc| dmc.keys().to_vec());
+    if !draft_changed(draft_data, &dm_channel) {
+        return;
+    }
+
+    // Direct message drafts are encrypted, which needs the private key
+    if dm_channel.is_some() && !GLOBALS.identity.is_unlocked() {
+        return;
+    }
+
+    draft_data.next_autosave = Instant::now() + AUTOSAVE_INTERVAL;
+
+    let content = do_replacements(&draft_data.draft, &draft_data.replacements);
+    let mut draft = draft_data.saved_draft.clone().unwrap_or_default();
+    draft.tags = draft_tags(draft_data, &content);
+    draft.content = content;
+    draft.replying_to = draft_data.replying_to;
+    draft.dm_channel = dm_channel;
+    match gossip_lib::drafts::save_draft(&mut draft) {
+        Ok(()) => {
+            draft_data.saved_text = draft_data.draft.clone();
+            draft_data.saved_draft = Some(draft);
+        }
+        Err(e) => tracing::error!("{}", e),
+    }
+}
+
+// Save the draft while it is being written, at most once every
+// AUTOSAVE_INTERVAL
+fn autosave_draft(ctx: &Context, draft_data: &mut DraftData, dm_channel: Option<&DmChannel>) {
+    let now = Instant::now();
+    if now < draft_data.next_autosave {
+        let keys = dm_channel.map(|dmc| dmc.keys().to_vec());
+        if draft_changed(draft_data, &keys) {
+            ctx.request_repaint_after(draft_data.next_autosave - now);
+        }
+        return;
+    }
+    save_draft(draft_data, dm_channel);
+}
+
+/// Replace the draft with a saved one
+pub(in crate::ui) fn load_draft(draft_data: &mut DraftData, draft: Draft) {
+    draft_data.clear();
+    draft_data.draft = draft.content.clone();
+    draft_data.replying_to = draft.replying_to;
+    for tag in &draft.tags {
+        match tag.tagname() {
+            "content-warning" => {
+                draft_data.include_content_warning = true;
+                draft_data.content_warning = tag.value().to_owned();
+            }
+            "subject" => {
+                draft_data.include_subject = true;
+                draft_data.subject = tag.value().to_owned();
+            }
+            _ => draft_data.restored_tags.push(tag.clone()),
+        }
+    }
+    draft_data.saved_text = draft.content.clone();
+    draft_data.saved_draft = Some(draft);
+}
+
+// The draft was posted or thrown away, so it is no longer kept
+fn discard_saved_draft(draft_data: &mut DraftData) {
+    if let Some(draft) = draft_data.saved_draft.take() {
+        if let Err(e) = gossip_lib::drafts::delete_draft(draft.id) {
+            tracing::error!("{}", e);
+        }
+    }
+}
@@ -35,10 +35,12 @@ macro_rules! write_setting {
 
 mod components;
 mod dm_chat_list;
+mod drafts;
 mod feed;
 mod help;
 mod people;
 mod relays;
+mod scheduled;
 mod search;
 mod settings;
 mod theme;
@@ -60,12 +62,16 @@ use egui_video::{AudioDevice, Player};
 use egui_winit::egui::Rect;
 use egui_winit::egui::Response;
 use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::drafts::Draft;
+use gossip_lib::file_metadata::FileMetadata;
 use gossip_lib::nip46::Approval;
+use gossip_lib::upload::UploadedFile;
 use gossip_lib::{
-    About, DmChannel, DmChannelData, Error, FeedKind, Person, PersonList, ZapState, GLOBALS,
+    About, DmChannel, DmChannelData, Error, FeedKind, MediaCacheStats, Person, PersonList,
+    ZapState, GLOBALS,
 };
 use nostr_types::ContentSegment;
-use nostr_types::{Id, Metadata, MilliSatoshi, Profile, PublicKey, UncheckedUrl, Url};
+use nostr_types::{Id, Metadata, MilliSatoshi, Profile, PublicKey, Tag, UncheckedUrl, Url};
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 #[cfg(feature = "video-ffmpeg")]
@@ -73,7 +79,7 @@ use std::rc::Rc;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
 use usvg::TreeParsing;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use self::feed::Notes;
 use self::widgets::NavItem;
@@ -124,10 +130,14 @@ pub fn run() -> Result<(), Error> {
 #[derive(Debug, Clone, PartialEq)]
 enum Page {
     DmChatList,
+    Drafts,
     Feed(FeedKind),
     PeopleLists,
     PeopleList(PersonList),
+    PeopleSuggestions,
+    PeopleNip05Domain,
     Person(PublicKey),
+    YourAccounts,
     YourKeys,
     YourMetadata,
     YourDelegation,
@@ -136,6 +146,7 @@ enum Page {
     RelaysCoverage,
     RelaysMine,
     RelaysKnownNetwork,
+    ScheduledPosts,
     Search,
     Settings,
     HelpHelp,
@@ -160,6 +171,8 @@ impl Page {
     pub fn to_readable(&self) -> (&'static str /* Category */, String /* Name */) {
         match self {
             Page::DmChatList => (SubMenu::Feeds.as_str(), "Private chats".into()),
+            Page::Drafts => (SubMenu::Feeds.as_str(), "Drafts".into()),
+            Page::ScheduledPosts => (SubMenu::Feeds.as_str(), "Scheduled Posts".into()),
             Page::Feed(feedkind) => ("Feed", feedkind.to_string()),
             Page::PeopleLists => ("Lists", "Lists".into()),
             Page::PeopleList(list) => {
@@ -170,10 +183,13 @@ impl Page {
                     .unwrap_or_default();
                 ("Lists", metadata.title)
             }
+            Page::PeopleSuggestions => ("People", "Follow Suggestions".into()),
+            Page::PeopleNip05Domain => ("People", "NIP-05 Directory".into()),
             Page::Person(pk) => {
                 let name = gossip_lib::names::best_name_from_pubkey_lookup(pk);
                 ("Profile", name)
             }
+            Page::YourAccounts => (SubMenu::Account.as_str(), "Accounts".into()),
             Page::YourKeys => (SubMenu::Account.as_str(), "Keys".into()),
             Page::YourMetadata => (SubMenu::Account.as_str(), "Profile".into()),
             Page::YourDelegation => (SubMenu::Account.as_str(), "Delegation".into()),
@@ -213,13 +229,16 @@ impl Page {
         }
 
         match self {
-            Page::DmChatList => cat_name(self),
+            Page::DmChatList | Page::Drafts | Page::ScheduledPosts => cat_name(self),
             Page::Feed(_) => name_cat(self),
             Page::PeopleLists | Page::PeopleList(_) => cat_name(self),
+            Page::PeopleSuggestions | Page::PeopleNip05Domain => name(self),
             Page::Person(_) => name_cat(self),
-            Page::YourKeys | Page::YourMetadata | Page::YourDelegation | Page::YourNostrConnect => {
-                cat_name(self)
-            }
+            Page::YourAccounts
+            | Page::YourKeys
+            | Page::YourMetadata
+            | Page::YourDelegation
+            | Page::YourNostrConnect => cat_name(self),
             Page::Wizard(_) => name_cat(self),
             _ => name(self),
         }
@@ -300,10 +319,33 @@ pub struct DraftData {
     pub include_content_warning: bool,
     pub content_warning: String,
 
+    // The path of a file to attach, while it is being entered
+    pub attach_path: Option<String>,
+
+    // Uploads in progress (see GLOBALS.uploads)
+    pub uploads: Vec<u64>,
+
+    // Files that were uploaded, to describe with imeta tags
+    pub attachments: Vec<UploadedFile>,
+
     // Data for normal draft
     pub repost: Option<Id>,
     pub replying_to: Option<Id>,
 
+    // The saved draft (see gossip_lib::drafts) as last saved, the text it was
+    // saved from, and when it may next be saved while typing
+    pub saved_draft: Option<Draft>,
+    pub saved_text: String,
+    pub next_autosave: Instant,
+
+    // Tags restored from a saved draft that the editor does not show
+    pub restored_tags: Vec<Tag>,
+
+    // Scheduling the post for later, at the time entered
+    pub schedule: bool,
+    pub schedule_time: String,
+    pub schedule_sign_now: bool,
+
     // If the user is typing a @tag, this is what they typed
     pub tagging_search_substring: Option<String>,
     pub tagging_search_selected: Option<usize>,
@@ -324,11 +366,23 @@ impl Default for DraftData {
             subject: "".to_owned(),
             include_content_warning: false,
             content_warning: "".to_owned(),
+            attach_path: None,
+            uploads: Vec::new(),
+            attachments: Vec::new(),
 
             // The following are ignored for DMs
             repost: None,
             replying_to: None,
 
+            saved_draft: None,
+            saved_text: "".to_owned(),
+            next_autosave: Instant::now(),
+            restored_tags: Vec::new(),
+
+            schedule: false,
+            schedule_time: "".to_owned(),
+            schedule_sign_now: true,
+
             tagging_search_substring: None,
             tagging_search_selected: None,
             tagging_search_searched: None,
@@ -349,8 +403,19 @@ impl DraftData {
         self.subject = "".to_owned();
         self.include_content_warning = false;
         self.content_warning = "".to_owned();
+        self.attach_path = None;
+        for upload_id in self.uploads.drain(..) {
+            gossip_lib::upload::abandon_upload(upload_id);
+        }
+        self.attachments.clear();
         self.repost = None;
         self.replying_to = None;
+        self.saved_draft = None;
+        self.saved_text = "".to_owned();
+        self.restored_tags.clear();
+        self.schedule = false;
+        self.schedule_time = "".to_owned();
+        self.schedule_sign_now = true;
         self.tagging_search_substring = None;
         self.tagging_search_selected = None;
         self.tagging_search_searched = None;
@@ -393,6 +458,15 @@ struct GossipUi {
     // people::ListUi
     people_list: people::ListUi,
 
+    // people::SuggestionsUi
+    people_suggestions: people::SuggestionsUi,
+
+    // people::Nip05DomainUi
+    people_nip05_domain: people::Nip05DomainUi,
+
+    // people::PersonUi
+    person_page: people::PersonUi,
+
     // Post rendering
     render_raw: Option<Id>,
     render_qr: Option<Id>,
@@ -408,6 +482,12 @@ struct GossipUi {
     history: Vec<Page>,
     mainfeed_include_nonroot: bool,
     inbox_include_indirect: bool,
+    new_bookmark_set_title: String,
+    renaming_draft: Option<u64>,
+    renaming_draft_name: String,
+    editing_scheduled_post: Option<u64>,
+    editing_scheduled_post_content: String,
+    editing_scheduled_post_time: String,
     submenu_ids: HashMap<SubMenu, egui::Id>,
     settings_tab: SettingsTab,
 
@@ -420,6 +500,10 @@ struct GossipUi {
     theme: Theme,
     avatars: HashMap<PublicKey, TextureHandle>,
     images: HashMap<Url, TextureHandle>,
+    /// blurhash placeholders, None if the blurhash was invalid
+    blurhashes: HashMap<String, Option<TextureHandle>>,
+    /// media cache statistics, loaded when the storage settings are shown
+    media_cache_stats: Option<MediaCacheStats>,
     /// used when settings.show_media=false to explicitly show
     media_show_list: HashSet<Url>,
     /// used when settings.show_media=true to explicitly hide
@@ -448,9 +532,14 @@ struct GossipUi {
     password2: String,
     password3: String,
     delete_confirm: bool,
+    migrate_confirm: bool,
     new_metadata_fieldname: String,
     import_priv: String,
     import_pub: String,
+    generated_mnemonic: Zeroizing<String>,
+    import_mnemonic: Zeroizing<String>,
+    mnemonic_passphrase: String,
+    export_log_n: u8,
     search: String,
     entering_search_page: bool,
     editing_petname: bool,
@@ -462,9 +551,16 @@ struct GossipUi {
     new_list_favorite: bool,
     renaming_list: Option<PersonList>,
     editing_list_error: Option<String>,
-    //nostr_connect_string: String,
+    nostr_connect_string: String,
+    account_to_remove: Option<PublicKey>,
+
+    /// The other accounts of this profile, and the active account they were read under.
+    /// Set to None to reread them.
+    accounts_cache: Option<(Option<PublicKey>, Vec<PublicKey>)>,
     nostr_connect_relay1: String,
     nostr_connect_relay2: String,
+    nostr_connect_new_kind: String,
+    nostr_connect_new_counterparty: String,
 
     // Collapsed threads
     collapsed: Vec<Id>,
@@ -498,6 +594,7 @@ impl Drop for GossipUi {
         self.password.zeroize();
         self.password2.zeroize();
         self.password3.zeroize();
+        self.mnemonic_passphrase.zeroize();
     }
 }
 
@@ -650,6 +747,9 @@ impl GossipUi {
             notes: Notes::new(),
             relays: relays::RelayUi::new(),
             people_list: people::ListUi::new(),
+            people_suggestions: people::SuggestionsUi::new(),
+            people_nip05_domain: people::Nip05DomainUi::new(),
+            person_page: people::PersonUi::new(),
             render_raw: None,
             render_qr: None,
             approved: HashSet::new(),
@@ -666,6 +766,12 @@ impl GossipUi {
                 .egui_ctx
                 .data_mut(|d| d.get_persisted(egui::Id::new("inbox_include_indirect")))
                 .unwrap_or(false),
+            new_bookmark_set_title: String::new(),
+            renaming_draft: None,
+            renaming_draft_name: String::new(),
+            editing_scheduled_post: None,
+            editing_scheduled_post_content: String::new(),
+            editing_scheduled_post_time: String::new(),
             submenu_ids,
             settings_tab: SettingsTab::Id,
             about: About::new(),
@@ -676,6 +782,8 @@ impl GossipUi {
             theme,
             avatars: HashMap::new(),
             images: HashMap::new(),
+            blurhashes: HashMap::new(),
+            media_cache_stats: None,
             media_show_list: HashSet::new(),
             media_hide_list: HashSet::new(),
             media_full_width_list: HashSet::new(),
@@ -693,8 +801,13 @@ impl GossipUi {
             password2: "".to_owned(),
             password3: "".to_owned(),
             delete_confirm: false,
+            migrate_confirm: false,
             new_metadata_fieldname: String::new(),
             import_priv: "".to_owned(),
+            generated_mnemonic: Zeroizing::new("".to_owned()),
+            import_mnemonic: Zeroizing::new("".to_owned()),
+            mnemonic_passphrase: "".to_owned(),
+            export_log_n: GLOBALS.storage.read_setting_log_n(),
             import_pub: "".to_owned(),
             search: "".to_owned(),
             entering_search_page: false,
@@ -707,9 +820,13 @@ impl GossipUi {
             new_list_favorite: false,
             renaming_list: None,
             editing_list_error: None,
-            //nostr_connect_string: "".to_owned(),
+            nostr_connect_string: "".to_owned(),
+            account_to_remove: None,
+            accounts_cache: None,
             nostr_connect_relay1: "".to_owned(),
             nostr_connect_relay2: "".to_owned(),
+            nostr_connect_new_kind: "".to_owned(),
+            nostr_connect_new_counterparty: "".to_owned(),
             collapsed: vec![],
             opened: HashSet::new(),
             visible_note_ids: vec![],
@@ -755,6 +872,10 @@ impl GossipUi {
             self.password3 = "".to_owned();
             self.import_priv.zeroize();
             self.import_priv = "".to_owned();
+            self.generated_mnemonic.zeroize();
+            self.import_mnemonic.zeroize();
+            self.mnemonic_passphrase.zeroize();
+            self.mnemonic_passphrase = "".to_owned();
         }
     }
 
@@ -768,6 +889,16 @@ impl GossipUi {
     }
 
     fn set_page_inner(&mut self, ctx: &Context, page: Page) {
+        // Keep the direct message being written, and start the next chat
+        // with an empty one
+        if self.page != page {
+            if let Page::Feed(FeedKind::DmChat(channel)) = &self.page {
+                let channel = channel.clone();
+                feed::post::save_draft(&mut self.dm_draft_data, Some(&channel));
+                self.dm_draft_data.clear();
+            }
+        }
+
         // Setting the page often requires some associated actions:
         match &page {
             Page::Feed(FeedKind::DmChat(channel)) => {
@@ -796,19 +927,39 @@ impl GossipUi {
                 GLOBALS.feed.set_feed_to_person(pubkey.to_owned());
                 self.close_all_menus_except_feeds(ctx);
             }
+            Page::Feed(FeedKind::Bookmarks(dtag)) => {
+                GLOBALS.feed.set_feed_to_bookmarks(dtag.to_owned());
+                self.close_all_menus_except_feeds(ctx);
+            }
             Page::PeopleLists => {
                 people::enter_page(self);
                 self.close_all_menus_except_feeds(ctx);
             }
+            Page::PeopleSuggestions => {
+                people::enter_suggestions_page(self);
+                self.close_all_menus_except_feeds(ctx);
+            }
+            Page::PeopleNip05Domain => {
+                self.close_all_menus_except_feeds(ctx);
+            }
             Page::Person(pubkey) => {
+                people::enter_person_page(self);
                 self.close_all_menus_except_feeds(ctx);
                 // Fetch metadata for that person at the page switch
                 // (this bypasses checking if it was done recently)
                 let _ = GLOBALS
                     .to_overlord
                     .send(ToOverlordMessage::UpdateMetadata(*pubkey));
+                // and the notes they pinned
+                let _ = GLOBALS
+                    .to_overlord
+                    .send(ToOverlordMessage::FetchPinList(*pubkey));
             }
-            Page::YourKeys | Page::YourMetadata | Page::YourDelegation | Page::YourNostrConnect => {
+            Page::YourAccounts
+            | Page::YourKeys
+            | Page::YourMetadata
+            | Page::YourDelegation
+            | Page::YourNostrConnect => {
                 self.open_menu(ctx, SubMenu::Account);
             }
             Page::RelaysActivityMonitor
@@ -965,6 +1116,16 @@ impl GossipUi {
                             Page::Feed(FeedKind::Inbox(self.inbox_include_indirect)),
                         );
                     }
+                    if self
+                        .add_selected_label(
+                            ui,
+                            matches!(self.page, Page::Feed(FeedKind::Bookmarks(_))),
+                            "Bookmarks",
+                        )
+                        .clicked()
+                    {
+                        self.set_page(ctx, Page::Feed(FeedKind::Bookmarks("".to_owned())));
+                    }
                 }
 
                 // Private chats
@@ -977,6 +1138,28 @@ impl GossipUi {
                     }
                 }
 
+                // Drafts
+                if GLOBALS.identity.public_key().is_some()
+                    && self
+                        .add_selected_label(ui, self.page == Page::Drafts, "Drafts")
+                        .clicked()
+                {
+                    self.set_page(ctx, Page::Drafts);
+                }
+
+                // Scheduled posts
+                if GLOBALS.identity.public_key().is_some()
+                    && self
+                        .add_selected_label(
+                            ui,
+                            self.page == Page::ScheduledPosts,
+                            "Scheduled Posts",
+                        )
+                        .clicked()
+                {
+                    self.set_page(ctx, Page::ScheduledPosts);
+                }
+
                 // Search
                 if self
                     .add_selected_label(ui, self.page == Page::Search, "Search")
@@ -995,6 +1178,26 @@ impl GossipUi {
                     {
                         self.set_page(ctx, Page::PeopleLists);
                     }
+                    if self
+                        .add_selected_label(
+                            ui,
+                            self.page == Page::PeopleSuggestions,
+                            "Follow Suggestions",
+                        )
+                        .clicked()
+                    {
+                        self.set_page(ctx, Page::PeopleSuggestions);
+                    }
+                    if self
+                        .add_selected_label(
+                            ui,
+                            self.page == Page::PeopleNip05Domain,
+                            "NIP-05 Directory",
+                        )
+                        .clicked()
+                    {
+                        self.set_page(ctx, Page::PeopleNip05Domain);
+                    }
                 }
 
                 // ---- Relays SubMenu ----
@@ -1031,6 +1234,8 @@ impl GossipUi {
                     let (mut cstate, header_response) =
                         self.get_openable_menu(ui, ctx, SubMenu::Account);
                     cstate.show_body_indented(&header_response, ui, |ui| {
+                        you::accounts::switcher(self, ui);
+                        self.add_menu_item_page(ui, Page::YourAccounts, None, true);
                         self.add_menu_item_page(ui, Page::YourMetadata, None, true);
                         self.add_menu_item_page(ui, Page::YourKeys, None, true);
                         self.add_menu_item_page(ui, Page::YourDelegation, None, true);
@@ -1151,6 +1356,11 @@ impl GossipUi {
 }
 
 impl eframe::App for GossipUi {
+    fn on_close_event(&mut self) -> bool {
+        self.save_drafts();
+        true
+    }
+
     fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
         // Run only on first frame
         if self.initializing {
@@ -1174,6 +1384,7 @@ impl eframe::App for GossipUi {
         }
 
         if GLOBALS.shutting_down.load(Ordering::Relaxed) {
+            self.save_drafts();
             frame.close();
             return;
         }
@@ -1368,11 +1579,15 @@ impl eframe::App for GossipUi {
                 self.begin_ui(ui);
                 match self.page {
                     Page::DmChatList => dm_chat_list::update(self, ctx, frame, ui),
+                    Page::Drafts => drafts::update(self, ctx, frame, ui),
                     Page::Feed(_) => feed::update(self, ctx, frame, ui),
-                    Page::PeopleLists | Page::PeopleList(_) | Page::Person(_) => {
-                        people::update(self, ctx, frame, ui)
-                    }
-                    Page::YourKeys
+                    Page::PeopleLists
+                    | Page::PeopleList(_)
+                    | Page::PeopleSuggestions
+                    | Page::PeopleNip05Domain
+                    | Page::Person(_) => people::update(self, ctx, frame, ui),
+                    Page::YourAccounts
+                    | Page::YourKeys
                     | Page::YourMetadata
                     | Page::YourDelegation
                     | Page::YourNostrConnect => you::update(self, ctx, frame, ui),
@@ -1380,6 +1595,7 @@ impl eframe::App for GossipUi {
                     | Page::RelaysCoverage
                     | Page::RelaysMine
                     | Page::RelaysKnownNetwork => relays::update(self, ctx, frame, ui),
+                    Page::ScheduledPosts => scheduled::update(self, ctx, frame, ui),
                     Page::Search => search::update(self, ctx, frame, ui),
                     Page::Settings => settings::update(self, ctx, frame, ui),
                     Page::HelpHelp | Page::HelpStats | Page::HelpAbout | Page::HelpTheme => {
@@ -1398,17 +1614,7 @@ impl GossipUi {
     }
 
     pub fn person_name(person: &Person) -> String {
-        if let Some(petname) = &person.petname {
-            petname.clone()
-        } else if let Some(display_name) = person.display_name() {
-            display_name.to_string()
-        } else if let Some(name) = person.name() {
-            name.to_string()
-        } else if let Some(nip05) = person.nip05() {
-            nip05.to_string()
-        } else {
-            gossip_lib::names::pubkey_short(&person.pubkey)
-        }
+        gossip_lib::names::display_names(person).0
     }
 
     pub fn richtext_from_person_nip05(person: &Person) -> RichText {
@@ -1446,12 +1652,11 @@ impl GossipUi {
                 false
             };
 
+            let (name, secondary_name) = gossip_lib::names::display_names(person);
+
             let tag_name_menu = {
                 let text = if !profile_page {
-                    match &person.petname {
-                        Some(pn) => pn.to_owned(),
-                        None => person.best_name(),
-                    }
+                    name
                 } else {
                     "ACTIONS".to_string()
                 };
@@ -1523,6 +1728,12 @@ impl GossipUi {
                 }
             });
 
+            if !profile_page {
+                if let Some(secondary_name) = secondary_name {
+                    ui.label(RichText::new(secondary_name).weak());
+                }
+            }
+
             if person.petname.is_some() {
                 ui.label(RichText::new("†").color(app.theme.accent_complementary_color()))
                     .on_hover_text("trusted petname");
@@ -1601,7 +1812,13 @@ impl GossipUi {
         GLOBALS.media.has_failed(&unchecked_url)
     }
 
-    pub fn try_get_media(&mut self, ctx: &Context, url: Url) -> Option<TextureHandle> {
+    /// `metadata` is what the note being rendered says about the file, if anything
+    pub fn try_get_media(
+        &mut self,
+        ctx: &Context,
+        url: Url,
+        metadata: Option<&FileMetadata>,
+    ) -> Option<TextureHandle> {
         // Do not keep retrying if failed
         if GLOBALS.media.has_failed(&url.to_unchecked_url()) {
             return None;
@@ -1612,7 +1829,7 @@ impl GossipUi {
             return Some(th.to_owned());
         }
 
-        if let Some(rgba_image) = GLOBALS.media.get_image(&url) {
+        if let Some(rgba_image) = GLOBALS.media.get_image(&url, metadata) {
             let current_size = [rgba_image.width() as usize, rgba_image.height() as usize];
             let pixels = rgba_image.as_flat_samples();
             let color_image = ColorImage::from_rgba_unmultiplied(current_size, pixels.as_slice());
@@ -1628,11 +1845,34 @@ impl GossipUi {
         }
     }
 
+    pub fn try_get_blurhash(&mut self, ctx: &Context, blurhash: &str) -> Option<TextureHandle> {
+        if let Some(th) = self.blurhashes.get(blurhash) {
+            return th.to_owned();
+        }
+
+        // Blurhashes are smooth, so a small image stretches well
+        let texture_handle =
+            gossip_lib::file_metadata::decode_blurhash(blurhash, 32, 32).map(|rgba_image| {
+                let size = [rgba_image.width() as usize, rgba_image.height() as usize];
+                let pixels = rgba_image.as_flat_samples();
+                let color_image = ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
+                ctx.load_texture(
+                    format!("blurhash:{}", blurhash),
+                    color_image,
+                    TextureOptions::LINEAR,
+                )
+            });
+        self.blurhashes
+            .insert(blurhash.to_owned(), texture_handle.clone());
+        texture_handle
+    }
+
     #[cfg(feature = "video-ffmpeg")]
     pub fn try_get_player(
         &mut self,
         ctx: &Context,
         url: Url,
+        metadata: Option<&FileMetadata>,
     ) -> Option<Rc<RefCell<egui_video::Player>>> {
         // Do not keep retrying if failed
         if GLOBALS.media.has_failed(&url.to_unchecked_url()) {
@@ -1644,7 +1884,7 @@ impl GossipUi {
             return Some(player.to_owned());
         }
 
-        if let Some(bytes) = GLOBALS.media.get_data(&url) {
+        if let Some(bytes) = GLOBALS.media.get_data(&url, metadata) {
             if let Ok(player) = Player::new_from_bytes(ctx, &bytes) {
                 if let Some(audio) = &mut self.audio_device {
                     if let Ok(player) = player.with_audio(audio) {
@@ -1952,6 +2192,15 @@ impl GossipUi {
         }
     }
 
+    // Save what is being written so it is not lost on exit
+    fn save_drafts(&mut self) {
+        feed::post::save_draft(&mut self.draft_data, None);
+        if let Page::Feed(FeedKind::DmChat(channel)) = &self.page {
+            let channel = channel.clone();
+            feed::post::save_draft(&mut self.dm_draft_data, Some(&channel));
+        }
+    }
+
     fn reset_draft(&mut self) {
         if let Page::Feed(FeedKind::DmChat(_)) = &self.page {
             self.dm_draft_data.clear();
@@ -2265,7 +2514,19 @@ fn approval_dialog_inner(_app: &mut GossipUi, ui: &mut Ui) {
     // NIP-46 approvals
     for (pubkey, parsed_command) in GLOBALS.nip46_approval_requests.read().iter() {
         ui.horizontal(|ui| {
-            let text = format!("Allow {}", parsed_command.method);
+            let client = match GLOBALS.storage.read_nip46server(*pubkey) {
+                Ok(Some(server)) => match server.metadata {
+                    Some(md) => md.name,
+                    None => gossip_lib::names::pubkey_short(pubkey),
+                },
+                _ => gossip_lib::names::pubkey_short(pubkey),
+            };
+            let text = format!(
+                "Allow {} to {} {}",
+                client,
+                parsed_command.method,
+                gossip_lib::nip46::command_detail(parsed_command)
+            );
             ui.label(text);
             if ui.button("Approve Once").clicked() {
                 let _ = GLOBALS
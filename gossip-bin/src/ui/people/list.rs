@@ -9,9 +9,20 @@ use egui_winit::egui::text::LayoutJob;
 use egui_winit::egui::text_edit::TextEditOutput;
 use egui_winit::egui::vec2;
 use gossip_lib::comms::ToOverlordMessage;
-use gossip_lib::{FeedKind, Person, PersonList, PersonListMetadata, GLOBALS};
+use gossip_lib::person_list_io;
+use gossip_lib::{
+    FeedKind, Mute, Person, PersonList, PersonListConflicts, PersonListMetadata, GLOBALS,
+};
 use nostr_types::{Profile, PublicKey, Unixtime};
 
+/// Moving a person list to or from a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(in crate::ui) enum ListTransfer {
+    Export(PersonList),
+    /// Import into an existing list, or None for a new list
+    Import(Option<PersonList>),
+}
+
 pub(in crate::ui) struct ListUi {
     // cache
     cache_last_list: Option<PersonList>,
@@ -28,6 +39,15 @@ pub(in crate::ui) struct ListUi {
 
     entering_follow_someone_on_list: bool,
     clear_list_needs_confirm: bool,
+    push_conflicts: Option<PersonListConflicts>,
+
+    // import/export
+    pub(in crate::ui) transfer: Option<ListTransfer>,
+    transfer_path: String,
+    transfer_error: Option<String>,
+
+    // muted words and hashtags (mute list only)
+    new_mute: String,
 }
 
 impl ListUi {
@@ -48,6 +68,13 @@ impl ListUi {
 
             entering_follow_someone_on_list: false,
             clear_list_needs_confirm: false,
+            push_conflicts: None,
+
+            transfer: None,
+            transfer_path: String::new(),
+            transfer_error: None,
+
+            new_mute: String::new(),
         }
     }
 }
@@ -80,6 +107,10 @@ pub(super) fn update(
     let mut enabled = false;
     if app.people_list.clear_list_needs_confirm {
         render_clear_list_confirm_popup(ui, app, list);
+    } else if app.people_list.push_conflicts.is_some() {
+        render_push_conflicts_popup(ui, app, list);
+    } else if let Some(transfer) = app.people_list.transfer {
+        render_transfer_popup(ui, app, transfer);
     } else if app.people_list.entering_follow_someone_on_list {
         render_add_contact_popup(ui, app, list, &metadata);
     } else if let Some(list) = app.deleting_list {
@@ -149,7 +180,7 @@ pub(super) fn update(
             if ui
                 .button("↓ Merge ↓")
                 .on_hover_text(
-                    "This imports data from the latest event, merging it into what is already here",
+                    "This imports data from the latest event, keeping additions and removals made both here and elsewhere since the last sync",
                 )
                 .clicked()
             {
@@ -167,9 +198,16 @@ pub(super) fn update(
                     .on_hover_text("This publishes the list to your relays")
                     .clicked()
                 {
-                    let _ = GLOBALS
-                        .to_overlord
-                        .send(ToOverlordMessage::PushPersonList(list));
+                    match GLOBALS.people.person_list_conflicts(list) {
+                        Ok(conflicts) if !conflicts.is_empty() => {
+                            app.people_list.push_conflicts = Some(conflicts);
+                        }
+                        _ => {
+                            let _ = GLOBALS
+                                .to_overlord
+                                .send(ToOverlordMessage::PushPersonList(list));
+                        }
+                    }
                 }
             } else {
                 ui.horizontal(|ui| {
@@ -192,6 +230,10 @@ pub(super) fn update(
     ui.add_space(10.0);
 
     app.vert_scroll_area().show(ui, |ui| {
+        if list == PersonList::Muted {
+            render_mutes(ui, app);
+        }
+
         // not nice but needed because of 'app' borrow in closure
         let people = app.people_list.cache_people.clone();
         for (person, public) in people.iter() {
@@ -292,6 +334,60 @@ pub(super) fn update(
     });
 }
 
+fn render_mutes(ui: &mut Ui, app: &mut GossipUi) {
+    ui.heading("Muted Words, Hashtags and Threads");
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        ui.label("Word or #hashtag:");
+        ui.add(text_edit_line!(app, app.people_list.new_mute).desired_width(200.0));
+        if ui.button("Mute").clicked() {
+            let entry = app.people_list.new_mute.trim();
+            if !entry.is_empty() {
+                let mute = match entry.strip_prefix('#') {
+                    Some(hashtag) => Mute::Hashtag(hashtag.to_lowercase()),
+                    None => Mute::Word(entry.to_lowercase()),
+                };
+                if let Err(e) = GLOBALS.people.set_mute(mute, true, true) {
+                    GLOBALS.status_queue.write().write(format!("{}", e));
+                }
+            }
+            app.people_list.new_mute.clear();
+        }
+    });
+
+    ui.add_space(5.0);
+
+    for (mute, public) in GLOBALS.people.get_mutes() {
+        let mut private = !public;
+        widgets::list_entry::make_frame(ui, Some(app.theme.main_content_bgcolor())).show(
+            ui,
+            |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(mute.describe());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Remove").clicked() {
+                            let _ = GLOBALS.people.set_mute(mute.clone(), false, public);
+                        }
+                        ui.add_space(20.0);
+                        ui.label("Private");
+                        if ui
+                            .add(widgets::Switch::onoff(&app.theme, &mut private))
+                            .clicked()
+                        {
+                            let _ = GLOBALS.people.set_mute(mute.clone(), true, !private);
+                        }
+                    });
+                });
+            },
+        );
+    }
+
+    ui.add_space(10.0);
+    ui.heading("Muted People");
+    ui.add_space(5.0);
+}
+
 pub(in crate::ui) fn layout_list_title(
     ui: &mut Ui,
     app: &mut GossipUi,
@@ -694,6 +790,14 @@ pub(super) fn render_more_list_actions(
                     *is_open = false;
                 }
             }
+            if ui.button("Export to File").clicked() {
+                app.people_list.transfer = Some(ListTransfer::Export(list));
+                *is_open = false;
+            }
+            if ui.button("Import from File").clicked() {
+                app.people_list.transfer = Some(ListTransfer::Import(Some(list)));
+                *is_open = false;
+            }
             if matches!(list, PersonList::Custom(_)) {
                 if ui.button("Rename").clicked() {
                     app.deleting_list = None;
@@ -819,6 +923,139 @@ fn render_clear_list_confirm_popup(ui: &mut Ui, app: &mut GossipUi, list: Person
     }
 }
 
+pub(super) fn render_transfer_popup(ui: &mut Ui, app: &mut GossipUi, transfer: ListTransfer) {
+    const DLG_SIZE: Vec2 = vec2(450.0, 150.0);
+    let popup = widgets::modal_popup(ui, DLG_SIZE, DLG_SIZE, true, |ui| {
+        ui.vertical(|ui| {
+            match transfer {
+                ListTransfer::Export(_) => ui.heading("Export list to a file"),
+                ListTransfer::Import(Some(_)) => ui.heading("Import people into this list"),
+                ListTransfer::Import(None) => ui.heading("Import a new list"),
+            };
+            ui.add_space(5.0);
+            ui.label("The format is chosen by the extension: .json, .csv, or anything else for one npub per line.");
+            ui.add_space(5.0);
+            if let Some(err) = &app.people_list.transfer_error {
+                ui.label(RichText::new(err).color(ui.visuals().error_fg_color));
+                ui.add_space(3.0);
+            }
+            ui.add(
+                text_edit_line!(app, app.people_list.transfer_path)
+                    .hint_text("/path/to/list.json")
+                    .desired_width(f32::INFINITY),
+            );
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                app.theme.accent_button_2_style(ui.style_mut());
+                if ui.button("Cancel").clicked() {
+                    app.people_list.transfer = None;
+                    app.people_list.transfer_error = None;
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::default()), |ui| {
+                    app.theme.accent_button_1_style(ui.style_mut());
+                    let label = match transfer {
+                        ListTransfer::Export(_) => "Export",
+                        ListTransfer::Import(_) => "Import",
+                    };
+                    if ui.button(label).clicked() {
+                        let path = std::path::PathBuf::from(app.people_list.transfer_path.trim());
+                        let result = match transfer {
+                            ListTransfer::Export(list) => {
+                                person_list_io::export_person_list_to_file(list, &path)
+                            }
+                            ListTransfer::Import(into) => {
+                                person_list_io::import_person_list_from_file(&path, into)
+                                    .map(|_| ())
+                            }
+                        };
+                        match result {
+                            Ok(()) => {
+                                app.people_list.transfer = None;
+                                app.people_list.transfer_error = None;
+                                mark_refresh(app);
+                            }
+                            Err(e) => app.people_list.transfer_error = Some(e.to_string()),
+                        }
+                    }
+                });
+            });
+        });
+    });
+
+    if popup.inner.clicked() {
+        app.people_list.transfer = None;
+        app.people_list.transfer_error = None;
+    }
+}
+
+fn render_push_conflicts_popup(ui: &mut Ui, app: &mut GossipUi, list: PersonList) {
+    let conflicts = match &app.people_list.push_conflicts {
+        Some(c) => c.clone(),
+        None => return,
+    };
+
+    const DLG_SIZE: Vec2 = vec2(450.0, 300.0);
+    let popup = widgets::modal_popup(ui, DLG_SIZE, DLG_SIZE, true, |ui| {
+        ui.vertical(|ui| {
+            ui.heading("This list was changed elsewhere");
+            ui.add_space(5.0);
+            ui.label("Publishing now would undo these changes made since the last sync:");
+            ui.add_space(10.0);
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for pubkey in &conflicts.added_remotely {
+                        ui.label(format!(
+                            "Added elsewhere: {}",
+                            gossip_lib::names::best_name_from_pubkey_lookup(pubkey)
+                        ));
+                    }
+                    for pubkey in &conflicts.removed_remotely {
+                        ui.label(format!(
+                            "Removed elsewhere: {}",
+                            gossip_lib::names::best_name_from_pubkey_lookup(pubkey)
+                        ));
+                    }
+                });
+            ui.add_space(10.0);
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                ui.horizontal(|ui| {
+                    app.theme.accent_button_2_style(ui.style_mut());
+                    if ui.button("Cancel").clicked() {
+                        app.people_list.push_conflicts = None;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::default()), |ui| {
+                        app.theme.accent_button_1_style(ui.style_mut());
+                        if ui.button("Merge, then Publish").clicked() {
+                            let _ = GLOBALS
+                                .to_overlord
+                                .send(ToOverlordMessage::UpdatePersonList {
+                                    person_list: list,
+                                    merge: true,
+                                });
+                            let _ = GLOBALS
+                                .to_overlord
+                                .send(ToOverlordMessage::PushPersonList(list));
+                            app.people_list.push_conflicts = None;
+                            mark_refresh(app);
+                        }
+                        if ui.button("Publish Anyway").clicked() {
+                            let _ = GLOBALS
+                                .to_overlord
+                                .send(ToOverlordMessage::PushPersonList(list));
+                            app.people_list.push_conflicts = None;
+                        }
+                    });
+                });
+            });
+        });
+    });
+
+    if popup.inner.clicked() {
+        app.people_list.push_conflicts = None;
+    }
+}
+
 fn mark_refresh(app: &mut GossipUi) {
     app.people_list.cache_next_refresh = Instant::now();
 }
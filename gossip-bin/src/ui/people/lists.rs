@@ -16,6 +16,8 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
         super::list::render_create_list_dialog(ui, app);
     } else if let Some(list) = app.renaming_list {
         super::list::render_rename_list_dialog(ui, app, list);
+    } else if let Some(transfer) = app.people_list.transfer {
+        super::list::render_transfer_popup(ui, app, transfer);
     } else {
         // only enable rest of ui when popups are not open
         enabled = true;
@@ -28,6 +30,10 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
                 app.creating_list = true;
                 app.list_name_field_needs_focus = true;
             }
+            btn_h_space!(ui);
+            if ui.button("Import a list").clicked() {
+                app.people_list.transfer = Some(super::list::ListTransfer::Import(None));
+            }
         });
     });
 
@@ -4,11 +4,18 @@ use egui::{Context, Ui};
 
 mod list;
 mod lists;
+mod nip05_domain;
 mod person;
+mod suggestions;
 
 pub(in crate::ui) use list::layout_list_title;
 pub(in crate::ui) use list::ListUi;
 pub(in crate::ui) use lists::sort_lists;
+pub(in crate::ui) use nip05_domain::Nip05DomainUi;
+pub(in crate::ui) use person::{enter_page as enter_person_page, PersonUi};
+pub(in crate::ui) use suggestions::{
+    enter_page as enter_suggestions_page, render_suggestions, SuggestionsUi,
+};
 
 pub(super) fn enter_page(app: &mut GossipUi) {
     if app.page == Page::PeopleLists {
@@ -25,6 +32,10 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
         lists::update(app, ctx, _frame, ui);
     } else if let Page::PeopleList(plist) = app.page {
         list::update(app, ctx, _frame, ui, plist);
+    } else if app.page == Page::PeopleSuggestions {
+        suggestions::update(app, ctx, _frame, ui);
+    } else if app.page == Page::PeopleNip05Domain {
+        nip05_domain::update(app, ctx, _frame, ui);
     } else if matches!(app.page, Page::Person(_)) {
         person::update(app, ctx, _frame, ui);
     }
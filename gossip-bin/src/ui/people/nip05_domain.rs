@@ -0,0 +1,135 @@
+use super::{GossipUi, Page};
+use crate::ui::widgets;
+use eframe::egui;
+use egui::{Context, RichText, Ui};
+use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::{PersonList, GLOBALS};
+use std::sync::atomic::Ordering;
+
+pub(in crate::ui) struct Nip05DomainUi {
+    // the domain being typed in
+    domain: String,
+
+    // the list that everybody gets followed into
+    target_list: PersonList,
+}
+
+impl Nip05DomainUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            domain: String::new(),
+            target_list: PersonList::Followed,
+        }
+    }
+}
+
+pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
+    widgets::page_header(ui, Page::PeopleNip05Domain.name(), |_ui| {});
+
+    ui.label("List everybody with a NIP-05 identifier at a domain, and follow them all at once.");
+    ui.add_space(10.0);
+
+    let mut browse = false;
+    ui.horizontal(|ui| {
+        ui.label("Domain:");
+        let response = ui.add(
+            text_edit_line!(app, app.people_nip05_domain.domain)
+                .hint_text("example.com")
+                .desired_width(300.0),
+        );
+        if ui.button("Browse").clicked() {
+            browse = true;
+        }
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            browse = true;
+        }
+    });
+    if browse && !app.people_nip05_domain.domain.trim().is_empty() {
+        let _ = GLOBALS
+            .to_overlord
+            .send(ToOverlordMessage::BrowseNip05Domain(
+                app.people_nip05_domain.domain.trim().to_owned(),
+            ));
+    }
+    ui.add_space(10.0);
+
+    if GLOBALS.nip05_domain_fetching.load(Ordering::Relaxed) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Fetching...");
+        });
+        return;
+    }
+
+    let listing = match GLOBALS.nip05_domain.read().clone() {
+        Some(listing) => listing,
+        None => return,
+    };
+
+    let all_lists = GLOBALS
+        .storage
+        .get_all_person_list_metadata()
+        .unwrap_or_default();
+    let target = app.people_nip05_domain.target_list;
+    let target_metadata = all_lists
+        .iter()
+        .find(|(list, _)| *list == target)
+        .map(|(_, metadata)| metadata.clone())
+        .unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        ui.heading(format!("{} ({})", listing.domain, listing.names.len()));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            app.theme.accent_button_1_style(ui.style_mut());
+            if ui.button("Follow all").clicked() {
+                let _ = GLOBALS
+                    .to_overlord
+                    .send(ToOverlordMessage::FollowNip05Domain(
+                        listing.domain.clone(),
+                        target,
+                        target == PersonList::Followed || !target_metadata.private,
+                    ));
+            }
+            egui::ComboBox::from_id_source("nip05_domain_target_list")
+                .selected_text(&target_metadata.title)
+                .show_ui(ui, |ui| {
+                    for (list, metadata) in &all_lists {
+                        if *list == PersonList::Muted {
+                            continue;
+                        }
+                        ui.selectable_value(
+                            &mut app.people_nip05_domain.target_list,
+                            *list,
+                            &metadata.title,
+                        );
+                    }
+                });
+            ui.label("into");
+        });
+    });
+    ui.add_space(10.0);
+
+    app.vert_scroll_area()
+        .id_source("people_nip05_domain_scroll")
+        .show(ui, |ui| {
+            for (name, pubkey) in &listing.names {
+                widgets::list_entry::make_frame(ui, Some(app.theme.main_content_bgcolor())).show(
+                    ui,
+                    |ui| {
+                        ui.set_min_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(format!("{}@{}", name, listing.domain)).strong(),
+                            );
+                            ui.add_space(10.0);
+                            let best = gossip_lib::names::best_name_from_pubkey_lookup(pubkey);
+                            if ui.link(best).clicked() {
+                                app.set_page(ctx, Page::Person(*pubkey));
+                            }
+                        });
+                    },
+                );
+            }
+            ui.add_space(crate::AVATAR_SIZE_F32 + 40.0);
+        });
+}
@@ -10,13 +10,18 @@ use egui_winit::egui::InnerResponse;
 use egui_winit::egui::Response;
 use egui_winit::egui::Widget;
 use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::metadata_history::{self, MetadataVersion};
+use gossip_lib::nip05::Nip05HistoryEntry;
+use gossip_lib::person_stats::{self, PersonStats};
 use gossip_lib::DmChannel;
 use gossip_lib::FeedKind;
 use gossip_lib::Person;
 use gossip_lib::PersonList;
 use gossip_lib::GLOBALS;
-use nostr_types::{PublicKey, RelayUrl};
+use nostr_types::{EventKind, Id, PublicKey, RelayUrl};
 use serde_json::Value;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
 const ITEM_V_SPACE: f32 = 2.0;
 const AVATAR_COL_WIDTH: f32 = AVATAR_SIZE_F32 * 3.0;
@@ -24,6 +29,118 @@ const AVATAR_COL_SPACE: f32 = 20.0;
 const AVATAR_COL_WIDTH_SPACE: f32 = AVATAR_COL_WIDTH + AVATAR_COL_SPACE * 2.0;
 const MIN_ITEM_WIDTH: f32 = 200.0;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(in crate::ui) enum PersonTab {
+    Profile,
+    Following,
+    Followers,
+    Notes,
+    Replies,
+    Media,
+    Zaps,
+}
+
+pub(in crate::ui) struct PersonUi {
+    tab: PersonTab,
+
+    // cache
+    cache_pubkey: Option<PublicKey>,
+    cache_tab: PersonTab,
+    cache_next_refresh: Instant,
+    stats: Option<PersonStats>,
+    stats_receiver: Option<(PublicKey, Receiver<Option<PersonStats>>)>,
+    note_ids: Vec<Id>,
+    nip05_history: Vec<Nip05HistoryEntry>,
+    metadata_history: Vec<MetadataVersion>,
+}
+
+impl PersonUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            tab: PersonTab::Profile,
+            cache_pubkey: None,
+            cache_tab: PersonTab::Profile,
+            cache_next_refresh: Instant::now(),
+            stats: None,
+            stats_receiver: None,
+            note_ids: Vec::new(),
+            nip05_history: Vec::new(),
+            metadata_history: Vec::new(),
+        }
+    }
+}
+
+pub(in crate::ui) fn enter_page(app: &mut GossipUi) {
+    app.editing_petname = false;
+    app.person_page.tab = PersonTab::Profile;
+    app.person_page.cache_next_refresh = Instant::now();
+}
+
+fn refresh_cache(app: &mut GossipUi, pubkey: PublicKey) {
+    let cache = &mut app.person_page;
+
+    // Take up stats computed in the background
+    if let Some((stats_pubkey, receiver)) = &cache.stats_receiver {
+        match receiver.try_recv() {
+            Ok(stats) => {
+                if *stats_pubkey == pubkey {
+                    cache.stats = stats;
+                }
+                cache.stats_receiver = None;
+            }
+            Err(TryRecvError::Disconnected) => cache.stats_receiver = None,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+
+    if cache.cache_pubkey == Some(pubkey)
+        && cache.cache_tab == cache.tab
+        && Instant::now() < cache.cache_next_refresh
+    {
+        return;
+    }
+
+    // Stats go through all of their events, so compute them off the UI thread
+    if cache.cache_pubkey != Some(pubkey) {
+        cache.stats = None;
+    }
+    if cache.stats_receiver.as_ref().map(|(pk, _)| *pk) != Some(pubkey) {
+        let (sender, receiver) = mpsc::channel();
+        std::mem::drop(tokio::task::spawn_blocking(move || {
+            let stats = match PersonStats::compute(pubkey) {
+                Ok(stats) => Some(stats),
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    None
+                }
+            };
+            let _ = sender.send(stats);
+        }));
+        cache.stats_receiver = Some((pubkey, receiver));
+    }
+
+    cache.note_ids = match cache.tab {
+        PersonTab::Notes => person_stats::person_notes(pubkey, false),
+        PersonTab::Replies => person_stats::person_notes(pubkey, true),
+        PersonTab::Media => person_stats::person_media_notes(pubkey),
+        PersonTab::Zaps => person_stats::person_zaps(pubkey),
+        _ => Ok(Vec::new()),
+    }
+    .unwrap_or_default();
+    cache.nip05_history = GLOBALS
+        .storage
+        .read_nip05_history(pubkey)
+        .unwrap_or_default();
+    cache.metadata_history = GLOBALS
+        .storage
+        .read_metadata_history(pubkey)
+        .unwrap_or_default();
+
+    cache.cache_pubkey = Some(pubkey);
+    cache.cache_tab = cache.tab;
+    cache.cache_next_refresh = Instant::now() + Duration::from_secs(10);
+}
+
 pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
     let (pubkey, person) = match &app.page {
         Page::Person(pubkey) => {
@@ -50,12 +167,90 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
         );
     });
 
+    ui.add_space(10.0);
+    ui.horizontal_wrapped(|ui| {
+        ui.add_space(10.0);
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Profile, "Profile");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Following, "Following");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Followers, "Followers");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Notes, "Notes");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Replies, "Replies");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Media, "Media");
+        ui.label("|");
+        ui.selectable_value(&mut app.person_page.tab, PersonTab::Zaps, "Zaps");
+    });
+    ui.add_space(10.0);
+
+    refresh_cache(app, pubkey);
+
+    match app.person_page.tab {
+        PersonTab::Profile => {
+            app.vert_scroll_area()
+                .id_source("person page")
+                .max_width(f32::INFINITY)
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    content(app, ctx, ui, pubkey, person);
+                });
+        }
+        PersonTab::Following | PersonTab::Followers => {
+            let people = match &app.person_page.stats {
+                Some(stats) if app.person_page.tab == PersonTab::Following => {
+                    stats.following.clone()
+                }
+                Some(stats) => stats.followers.clone(),
+                None => Vec::new(),
+            };
+            render_people(app, ctx, ui, people);
+        }
+        PersonTab::Notes | PersonTab::Replies | PersonTab::Media | PersonTab::Zaps => {
+            let feed = app.person_page.note_ids.clone();
+            if feed.is_empty() {
+                ui.label("Nothing found locally.");
+            }
+            crate::ui::feed::render_a_feed(
+                app,
+                ctx,
+                _frame,
+                ui,
+                feed,
+                false,
+                "person page notes",
+                false,
+            );
+            app.handle_visible_note_changes();
+        }
+    }
+}
+
+fn render_people(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, people: Vec<PublicKey>) {
+    if people.is_empty() {
+        ui.label("Nobody that we know of.");
+        return;
+    }
+
+    let my_follows = GLOBALS.wot.my_follows();
     app.vert_scroll_area()
-        .id_source("person page")
+        .id_source("person page people")
         .max_width(f32::INFINITY)
         .auto_shrink([false, false])
         .show(ui, |ui| {
-            content(app, ctx, ui, pubkey, person);
+            for pubkey in people {
+                ui.horizontal(|ui| {
+                    let name = gossip_lib::names::best_name_from_pubkey_lookup(&pubkey);
+                    if ui.link(name).clicked() {
+                        app.set_page(ctx, Page::Person(pubkey));
+                    }
+                    if my_follows.contains(&pubkey) {
+                        ui.label(RichText::new("followed").weak());
+                    }
+                });
+            }
         });
 }
 
@@ -124,29 +319,27 @@ fn content(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, pubkey: PublicKey, pe
                                     TextEdit::singleline(&mut app.petname).text_color(edit_color),
                                 );
                                 if ui.link("Save").clicked() {
-                                    let mut person = person.clone();
-                                    if app.petname.trim().is_empty() {
-                                        person.petname = None;
+                                    let petname = app.petname.trim();
+                                    let petname = if petname.is_empty() {
+                                        None
                                     } else {
-                                        person.petname = Some(app.petname.clone());
-                                    }
-                                    if let Err(e) = GLOBALS.storage.write_person(&person, None) {
+                                        Some(petname.to_owned())
+                                    };
+                                    if let Err(e) = GLOBALS.people.set_petname(&pubkey, petname) {
                                         GLOBALS.status_queue.write().write(format!("{}", e));
                                     }
                                     app.editing_petname = false;
-                                    app.notes.cache_invalidate_person(&person.pubkey);
+                                    app.notes.cache_invalidate_person(&pubkey);
                                 }
                                 if ui.link("Cancel").clicked() {
                                     app.editing_petname = false;
                                 }
                                 if ui.link("Remove").clicked() {
-                                    let mut person = person.clone();
-                                    person.petname = None;
-                                    if let Err(e) = GLOBALS.storage.write_person(&person, None) {
+                                    if let Err(e) = GLOBALS.people.set_petname(&pubkey, None) {
                                         GLOBALS.status_queue.write().write(format!("{}", e));
                                     }
                                     app.editing_petname = false;
-                                    app.notes.cache_invalidate_person(&person.pubkey);
+                                    app.notes.cache_invalidate_person(&pubkey);
                                 }
                             } else {
                                 if let Some(petname) = person.petname.clone() {
@@ -157,13 +350,10 @@ fn content(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, pubkey: PublicKey, pe
                                         app.petname = petname.to_owned();
                                     }
                                     if ui.link("Remove").clicked() {
-                                        let mut person = person.clone();
-                                        person.petname = None;
-                                        if let Err(e) = GLOBALS.storage.write_person(&person, None)
-                                        {
+                                        if let Err(e) = GLOBALS.people.set_petname(&pubkey, None) {
                                             GLOBALS.status_queue.write().write(format!("{}", e));
                                         }
-                                        app.notes.cache_invalidate_person(&person.pubkey);
+                                        app.notes.cache_invalidate_person(&pubkey);
                                     }
                                 } else {
                                     if ui
@@ -179,6 +369,32 @@ fn content(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, pubkey: PublicKey, pe
                         });
                     });
                 });
+
+                // How the people we follow regard them
+                let score = GLOBALS.wot.score(pubkey);
+                make_frame().show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        item_label(ui, "Web of Trust");
+                        ui.add_space(ITEM_V_SPACE);
+                        let response = ui.label(format!(
+                            "Followed by {} people you follow, muted by {}",
+                            score.followed_by, score.muted_by
+                        ));
+                        if score.followed_by > 0 {
+                            response.on_hover_ui(|ui| {
+                                let followers = GLOBALS.wot.followed_by(pubkey).unwrap_or_default();
+                                for follower in followers.iter().take(20) {
+                                    ui.label(gossip_lib::names::best_name_from_pubkey_lookup(
+                                        follower,
+                                    ));
+                                }
+                                if followers.len() > 20 {
+                                    ui.label(format!("and {} more", followers.len() - 20));
+                                }
+                            });
+                        }
+                    });
+                });
             }
 
             if let Some(about) = person.about() {
@@ -187,6 +403,119 @@ fn content(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, pubkey: PublicKey, pe
                 }
             }
 
+            if let Some(stats) = app.person_page.stats.clone() {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(10.0);
+                    ui.heading("Activity");
+                });
+                ui.separator();
+                ui.add_space(10.0);
+
+                make_frame().show(ui, |ui| {
+                    ui.label(format!(
+                        "Following {}, followed by {} that we know of",
+                        stats.following.len(),
+                        stats.followers.len()
+                    ));
+                    if !is_self {
+                        ui.label(format!(
+                            "{} mutual follows{}",
+                            stats.mutual_follows.len(),
+                            if stats.follows_you {
+                                ", follows you"
+                            } else {
+                                ""
+                            }
+                        ));
+                    }
+                    ui.label(format!(
+                        "{} notes and {} replies stored, {:.1} posts per day recently",
+                        stats.notes, stats.replies, stats.posts_per_day
+                    ));
+                    if !stats.top_hashtags.is_empty() {
+                        let hashtags: Vec<String> = stats
+                            .top_hashtags
+                            .iter()
+                            .map(|(tag, count)| format!("#{} ({})", tag, count))
+                            .collect();
+                        ui.label(format!("Top hashtags: {}", hashtags.join(", ")));
+                    }
+                    if stats.zaps_received > 0 {
+                        ui.label(format!(
+                            "Received {} zaps totalling {} sats",
+                            stats.zaps_received,
+                            stats.zapped_total.0 / 1000
+                        ));
+                    }
+                });
+            }
+
+            let nip05_history = app.person_page.nip05_history.clone();
+            if nip05_history.len() > 1 {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(10.0);
+                    ui.heading("NIP-05 History");
+                });
+                ui.separator();
+                ui.add_space(10.0);
+
+                make_frame().show(ui, |ui| {
+                    for entry in nip05_history.iter().rev() {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(format_when(entry.when as i64)).weak());
+                            ui.label(entry.nip05.as_deref().unwrap_or("(none)"));
+                            if entry.nip05.is_some() {
+                                if entry.valid {
+                                    ui.label("valid");
+                                } else {
+                                    ui.label(
+                                        RichText::new("not valid")
+                                            .color(app.theme.warning_marker_text_color()),
+                                    );
+                                }
+                            }
+                        });
+                    }
+                });
+            }
+
+            let metadata_history = app.person_page.metadata_history.clone();
+            if metadata_history.len() > 1 {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(10.0);
+                    ui.heading("Profile History");
+                });
+                ui.separator();
+                ui.add_space(10.0);
+
+                make_frame().show(ui, |ui| {
+                    for (i, version) in metadata_history.iter().enumerate().rev() {
+                        ui.label(RichText::new(format_when(version.created_at)).weak());
+                        if i == 0 {
+                            ui.label("Oldest version kept");
+                            continue;
+                        }
+                        let changes =
+                            metadata_history::diff_metadata(&metadata_history[i - 1], version);
+                        if changes.is_empty() {
+                            ui.label("Other details changed");
+                        }
+                        for change in changes {
+                            ui.label(format!(
+                                "{}: {} → {}",
+                                change.field,
+                                change.old.as_deref().unwrap_or("(none)"),
+                                change.new.as_deref().unwrap_or("(none)")
+                            ));
+                        }
+                        ui.add_space(ITEM_V_SPACE * 3.0);
+                    }
+                });
+            }
+
             ui.add_space(10.0);
             ui.horizontal(|ui| {
                 ui.add_space(10.0);
@@ -257,6 +586,54 @@ fn content(app: &mut GossipUi, ctx: &Context, ui: &mut Ui, pubkey: PublicKey, pe
                 }
             });
 
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.add_space(10.0);
+                ui.heading("Their lists");
+            });
+            ui.separator();
+            ui.add_space(10.0);
+
+            make_frame().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Follows");
+                    if ui
+                        .button("Copy to a new list")
+                        .on_hover_text(
+                            "Copy the people this person follows into a new list of your own",
+                        )
+                        .clicked()
+                    {
+                        let _ = GLOBALS
+                            .to_overlord
+                            .send(ToOverlordMessage::CopyPersonList { pubkey, dtag: None });
+                    }
+                });
+
+                let follow_sets = GLOBALS
+                    .storage
+                    .find_events(&[EventKind::FollowSets], &[pubkey], None, |_| true, true)
+                    .unwrap_or_default();
+                for event in follow_sets {
+                    let dtag = match event.parameter() {
+                        Some(dtag) => dtag,
+                        None => continue,
+                    };
+                    ui.horizontal(|ui| {
+                        match event.title() {
+                            Some(title) => ui.label(title),
+                            None => ui.label(&dtag),
+                        };
+                        if ui.button("Copy to a new list").clicked() {
+                            let _ = GLOBALS.to_overlord.send(ToOverlordMessage::CopyPersonList {
+                                pubkey,
+                                dtag: Some(dtag.clone()),
+                            });
+                        }
+                    });
+                }
+            });
+
             ui.add_space(10.0);
             ui.horizontal(|ui| {
                 ui.add_space(10.0);
@@ -638,3 +1015,14 @@ fn profile_item_frame(
 
     InnerResponse { inner, response }
 }
+
+fn format_when(secs: i64) -> String {
+    if let Ok(stamp) = time::OffsetDateTime::from_unix_timestamp(secs) {
+        if let Ok(formatted) = stamp.format(time::macros::format_description!(
+            "[year]-[month repr:short]-[day] [hour]:[minute]"
+        )) {
+            return formatted;
+        }
+    }
+    "time unknown".to_owned()
+}
@@ -0,0 +1,154 @@
+use super::{GossipUi, Page};
+use crate::ui::widgets;
+use eframe::egui;
+use egui::{Context, RichText, Ui};
+use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::{PersonList, GLOBALS};
+use std::sync::atomic::Ordering;
+
+pub(in crate::ui) struct SuggestionsUi {
+    // the list that one-click follows go into
+    target_list: PersonList,
+
+    // whether we asked the overlord for suggestions yet
+    requested: bool,
+}
+
+impl SuggestionsUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            target_list: PersonList::Followed,
+            requested: false,
+        }
+    }
+}
+
+pub(in crate::ui) fn enter_page(app: &mut GossipUi) {
+    if GLOBALS.follow_suggestions.read().is_empty() {
+        request(app);
+    }
+}
+
+pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
+    widgets::page_header(ui, Page::PeopleSuggestions.name(), |ui| {
+        app.theme.accent_button_1_style(ui.style_mut());
+        if ui.button("Refresh").clicked() {
+            request(app);
+        }
+    });
+
+    app.vert_scroll_area()
+        .id_source("people_suggestions_scroll")
+        .show(ui, |ui| {
+            render_suggestions(app, ctx, ui, None);
+            ui.add_space(crate::AVATAR_SIZE_F32 + 40.0);
+        });
+}
+
+fn request(app: &mut GossipUi) {
+    if !GLOBALS.follow_suggestions_computing.load(Ordering::Relaxed) {
+        let _ = GLOBALS
+            .to_overlord
+            .send(ToOverlordMessage::ComputeFollowSuggestions);
+    }
+    app.people_suggestions.requested = true;
+}
+
+/// Render follow suggestions with a list picker and one-click follow buttons.
+/// If `limit` is given, only that many are shown (and names are not links).
+pub(in crate::ui) fn render_suggestions(
+    app: &mut GossipUi,
+    ctx: &Context,
+    ui: &mut Ui,
+    limit: Option<usize>,
+) {
+    if !app.people_suggestions.requested {
+        request(app);
+    }
+
+    let all_lists = GLOBALS
+        .storage
+        .get_all_person_list_metadata()
+        .unwrap_or_default();
+    let target = app.people_suggestions.target_list;
+    let target_metadata = all_lists
+        .iter()
+        .find(|(list, _)| *list == target)
+        .map(|(_, metadata)| metadata.clone())
+        .unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        ui.label("Follow into:");
+        egui::ComboBox::from_id_source("follow_suggestions_target_list")
+            .selected_text(&target_metadata.title)
+            .show_ui(ui, |ui| {
+                for (list, metadata) in &all_lists {
+                    if *list == PersonList::Muted {
+                        continue;
+                    }
+                    ui.selectable_value(
+                        &mut app.people_suggestions.target_list,
+                        *list,
+                        &metadata.title,
+                    );
+                }
+            });
+    });
+    ui.add_space(10.0);
+
+    if GLOBALS.follow_suggestions_computing.load(Ordering::Relaxed) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Finding people you might want to follow...");
+        });
+        return;
+    }
+
+    let suggestions = GLOBALS.follow_suggestions.read().clone();
+    if suggestions.is_empty() {
+        ui.label("No suggestions yet. They come from the people you follow, so follow a few people first and check back once their lists have loaded.");
+        return;
+    }
+
+    let on_page = limit.is_none();
+    let limit = limit.unwrap_or(suggestions.len());
+    for suggestion in suggestions.iter().take(limit) {
+        widgets::list_entry::make_frame(ui, Some(app.theme.main_content_bgcolor())).show(
+            ui,
+            |ui| {
+                ui.set_min_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        let name =
+                            gossip_lib::names::best_name_from_pubkey_lookup(&suggestion.pubkey);
+                        let name = RichText::new(name).heading();
+                        if on_page {
+                            if ui.link(name).clicked() {
+                                app.set_page(ctx, Page::Person(suggestion.pubkey));
+                            }
+                        } else {
+                            ui.label(name);
+                        }
+                        for reason in &suggestion.reasons {
+                            ui.label(RichText::new(reason.to_string()).weak());
+                        }
+                    });
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        app.theme.accent_button_1_style(ui.style_mut());
+                        if ui.button("Follow").clicked() {
+                            let _ = GLOBALS.to_overlord.send(ToOverlordMessage::FollowPubkey(
+                                suggestion.pubkey,
+                                target,
+                                target == PersonList::Followed || !target_metadata.private,
+                            ));
+                            GLOBALS
+                                .follow_suggestions
+                                .write()
+                                .retain(|s| s.pubkey != suggestion.pubkey);
+                        }
+                    });
+                });
+            },
+        );
+    }
+}
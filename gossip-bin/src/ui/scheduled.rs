@@ -0,0 +1,186 @@
+use super::{widgets, GossipUi};
+use eframe::egui;
+use egui::{Context, Label, RichText, Ui};
+use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::{DmChannel, GLOBALS};
+use nostr_types::Unixtime;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+// How times of scheduled posts are entered and shown, in local time
+const TIME_FORMAT: &[FormatItem<'static>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]");
+
+// The local offset can't always be determined, in which case times are in UTC
+fn local_offset() -> UtcOffset {
+    crate::LOCAL_OFFSET.get().copied().unwrap_or(UtcOffset::UTC)
+}
+
+/// Parse a time entered as YYYY-MM-DD HH:MM in local time
+pub(in crate::ui) fn parse_time(text: &str) -> Option<Unixtime> {
+    let datetime = PrimitiveDateTime::parse(text.trim(), TIME_FORMAT).ok()?;
+    Some(Unixtime(
+        datetime.assume_offset(local_offset()).unix_timestamp(),
+    ))
+}
+
+/// Format a time as YYYY-MM-DD HH:MM in local time
+pub(in crate::ui) fn format_time(unixtime: Unixtime) -> String {
+    OffsetDateTime::from_unix_timestamp(unixtime.0)
+        .ok()
+        .and_then(|datetime| datetime.to_offset(local_offset()).format(TIME_FORMAT).ok())
+        .unwrap_or_default()
+}
+
+/// What to call the zone times are entered in
+pub(in crate::ui) fn zone_name() -> &'static str {
+    if local_offset() == UtcOffset::UTC {
+        "UTC"
+    } else {
+        "local time"
+    }
+}
+
+pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.heading("Scheduled Posts");
+    });
+    ui.add_space(10.0);
+
+    let posts = gossip_lib::scheduled::get_scheduled_posts();
+    if posts.is_empty() {
+        ui.label(
+            "Nothing is scheduled. Choose Schedule in the post editor's menu to post a note later.",
+        );
+        return;
+    }
+
+    let mut cancel: Option<u64> = None;
+
+    app.vert_scroll_area()
+        .id_source("scheduled_posts")
+        .show(ui, |ui| {
+            let color = app.theme.accent_color();
+            let warning_color = app.theme.warning_marker_text_color();
+            for post in posts {
+                widgets::list_entry::make_frame(ui, Some(app.theme.main_content_bgcolor())).show(
+                    ui,
+                    |ui| {
+                        ui.set_min_width(ui.available_width());
+                        ui.vertical(|ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.add(Label::new(
+                                    RichText::new(format_time(post.send_at))
+                                        .heading()
+                                        .color(color),
+                                ));
+                                if post.sending {
+                                    ui.label(RichText::new("SENDING").weak());
+                                } else if post.missed {
+                                    ui.label(RichText::new("MISSED").color(warning_color))
+                                        .on_hover_text(
+                                            "This was not posted when it was due. Post it now, or edit it to reschedule it.",
+                                        );
+                                }
+
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::TOP),
+                                    |ui| {
+                                        if post.event.is_some() {
+                                            ui.label(RichText::new("signed").weak())
+                                                .on_hover_text(
+                                                    "Signed ahead of time, dated when it is due",
+                                                );
+                                        }
+                                    },
+                                );
+                            });
+
+                            let what = match &post.dm_channel {
+                                Some(keys) => {
+                                    format!("Message to {}", DmChannel::new(keys).name())
+                                }
+                                None if post.in_reply_to.is_some() => "Reply".to_owned(),
+                                None => "Note".to_owned(),
+                            };
+                            ui.label(RichText::new(what).weak());
+
+                            if post.sending {
+                                widgets::truncated_label(
+                                    ui,
+                                    post.content.replace('\n', " "),
+                                    ui.available_width() - 100.0,
+                                );
+                            } else if app.editing_scheduled_post == Some(post.id) {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("Post at ({}):", zone_name()));
+                                    ui.add(
+                                        text_edit_line!(app, app.editing_scheduled_post_time)
+                                            .hint_text("YYYY-MM-DD HH:MM")
+                                            .desired_width(150.0),
+                                    );
+                                });
+                                ui.add(
+                                    text_edit_multiline!(app, app.editing_scheduled_post_content)
+                                        .desired_width(f32::INFINITY),
+                                );
+                                ui.horizontal(|ui| {
+                                    if ui.button("Save").clicked() {
+                                        match parse_time(&app.editing_scheduled_post_time) {
+                                            Some(send_at) => {
+                                                let _ = GLOBALS.to_overlord.send(
+                                                    ToOverlordMessage::UpdateScheduledPost(
+                                                        post.id,
+                                                        app.editing_scheduled_post_content.clone(),
+                                                        send_at,
+                                                    ),
+                                                );
+                                                app.editing_scheduled_post = None;
+                                            }
+                                            None => GLOBALS.status_queue.write().write(
+                                                "Enter the time as YYYY-MM-DD HH:MM".to_owned(),
+                                            ),
+                                        }
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        app.editing_scheduled_post = None;
+                                    }
+                                });
+                            } else {
+                                widgets::truncated_label(
+                                    ui,
+                                    post.content.replace('\n', " "),
+                                    ui.available_width() - 100.0,
+                                );
+
+                                ui.horizontal(|ui| {
+                                    if ui.button("Edit").clicked() {
+                                        app.editing_scheduled_post = Some(post.id);
+                                        app.editing_scheduled_post_content = post.content.clone();
+                                        app.editing_scheduled_post_time =
+                                            format_time(post.send_at);
+                                    }
+                                    if ui.button("Post Now").clicked() {
+                                        let _ = GLOBALS
+                                            .to_overlord
+                                            .send(ToOverlordMessage::SendScheduledPost(post.id));
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        cancel = Some(post.id);
+                                    }
+                                });
+                            }
+                        });
+                    },
+                );
+            }
+        });
+
+    if let Some(id) = cancel {
+        if let Err(e) = gossip_lib::scheduled::cancel_scheduled_post(id) {
+            tracing::error!("{}", e);
+        }
+    }
+}
@@ -96,12 +96,39 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
     ui.checkbox(&mut app.unsaved_settings.approve_content_warning, "Approve all content-warning tagged media automatically")
         .on_hover_text("If off, you have to click to show content-warning tagged media. If on, all content-warning tagged media in your feed will be rendered.");
 
+    ui.checkbox(&mut app.unsaved_settings.link_previews, "Show link previews")
+        .on_hover_text("If on, web pages linked from posts are fetched to show their title, description and image. This reveals your IP address to those sites. Nothing is fetched if Fetch Media is disabled, and previews are never shown in direct messages.");
+    if app.unsaved_settings.link_previews {
+        ui.horizontal(|ui| {
+            ui.label("Only preview links to these domains (empty for all):");
+            ui.add(text_edit_line!(
+                app,
+                app.unsaved_settings.link_preview_allowed_domains
+            ))
+            .on_hover_text("Separate domains with spaces or commas. Subdomains are included.");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Never preview links to these domains:");
+            ui.add(text_edit_line!(
+                app,
+                app.unsaved_settings.link_preview_denied_domains
+            ))
+            .on_hover_text("Separate domains with spaces or commas. Subdomains are included.");
+        });
+    }
+
     ui.checkbox(
         &mut app.unsaved_settings.hide_mutes_entirely,
         "Hide muted events entirely, including replies to them",
     )
         .on_hover_text("If on, muted events wont be in the feed at all. If off, they will be in the feed, but the content will be replaced with the word MUTED. You will see replies to them, and you can peek at the content by viewing the note in raw form.");
 
+    ui.checkbox(
+        &mut app.unsaved_settings.inbox_hide_untrusted,
+        "Hide inbox events from people outside your web of trust",
+    )
+        .on_hover_text("If on, inbox events from people you don't follow are only shown if more of the people you follow follow them than mute them.");
+
     ui.checkbox(
         &mut app.unsaved_settings.show_deleted_events,
         "Render delete events, but labeled as deleted",
@@ -25,6 +25,26 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
         );
     });
 
+    ui.horizontal(|ui| {
+        ui.label("Maximum size of downloaded files")
+            .on_hover_text("The least recently used files (and their thumbnails) are deleted to stay within this size");
+        ui.add(
+            Slider::new(&mut app.unsaved_settings.media_cache_max_mb, 100..=20000)
+                .logarithmic(true)
+                .text("MB"),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Profile versions to keep per person")
+            .on_hover_text(
+                "Older versions of people's profiles are kept so you can see what changed",
+            );
+        ui.add(
+            Slider::new(&mut app.unsaved_settings.max_metadata_versions, 1..=50).text("versions"),
+        );
+    });
+
     // Only let them prune after they have saved
     let stored_settings = UnsavedSettings::load();
     if stored_settings == app.unsaved_settings {
@@ -34,10 +54,62 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
         }
 
         ui.add_space(20.0);
-        if ui.button("Delete Old Downloaded Files").on_hover_text("This will delete cache files with modification times older than the period specified above (unfortunately access times are often unavailable and/or unreliable). Note that this will eventually delete everybody's avatar, even if those are in heavy use.").clicked() {
+        if ui.button("Delete Old Downloaded Files").on_hover_text("This will delete cached files that have not been used for longer than the period specified above.").clicked() {
             let _ = GLOBALS.to_overlord.send(ToOverlordMessage::PruneCache);
+            app.media_cache_stats = None;
         }
     }
 
     ui.add_space(20.0);
+    ui.heading("Downloaded Files");
+    ui.add_space(10.0);
+
+    if app.media_cache_stats.is_none() {
+        match GLOBALS.fetcher.cache_stats() {
+            Ok(stats) => app.media_cache_stats = Some(stats),
+            Err(e) => {
+                ui.label(format!("Could not read the cache: {}", e));
+            }
+        }
+    }
+
+    if let Some(stats) = &app.media_cache_stats {
+        ui.label(format!(
+            "{} URLs, stored as {} files taking {}",
+            stats.urls,
+            stats.files,
+            format_bytes(stats.bytes)
+        ));
+        ui.label(format!(
+            "{} saved by storing files served at several URLs once",
+            format_bytes(stats.url_bytes.saturating_sub(stats.bytes))
+        ));
+        ui.label(format!(
+            "{} decoded thumbnails taking {}",
+            stats.thumbnails,
+            format_bytes(stats.thumbnail_bytes)
+        ));
+        if stats.legacy_files > 0 {
+            ui.label(format!(
+                "{} files from an older version of the cache taking {}",
+                stats.legacy_files,
+                format_bytes(stats.legacy_bytes)
+            ));
+        }
+    }
+
+    if ui.button("Refresh").clicked() {
+        app.media_cache_stats = None;
+    }
+
+    ui.add_space(20.0);
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= 1024 * MB {
+        format!("{:.1} GB", bytes as f64 / (1024 * MB) as f64)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    }
 }
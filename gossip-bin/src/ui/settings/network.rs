@@ -246,6 +246,17 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
             Slider::new(&mut app.unsaved_settings.media_becomes_stale_hours, 2..=40).text("hours"),
         );
     });
+    ui.horizontal(|ui| {
+        ui.label("How long before a link preview becomes stale and needs rechecking?");
+        ui.add(
+            Slider::new(
+                &mut app.unsaved_settings.link_preview_becomes_stale_hours,
+                2..=720,
+            )
+            .logarithmic(true)
+            .text("hours"),
+        );
+    });
 
     ui.add_space(20.0);
 }
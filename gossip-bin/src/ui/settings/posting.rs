@@ -2,6 +2,7 @@ use crate::ui::GossipUi;
 use eframe::egui;
 use egui::widgets::Slider;
 use egui::{Context, Ui};
+use gossip_lib::upload::UploadProtocol;
 
 pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
     ui.heading("Posting Settings");
@@ -19,6 +20,12 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
     )
     .on_hover_text("Takes effect immediately.");
 
+    ui.checkbox(
+        &mut app.unsaved_settings.send_missed_scheduled_posts,
+        "Post scheduled notes that came due while gossip was not running",
+    )
+    .on_hover_text("Otherwise they are flagged for you to post or cancel.");
+
     ui.checkbox(
         &mut app.unsaved_settings.set_user_agent,
         &format!(
@@ -28,5 +35,36 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
     )
     .on_hover_text("Takes effect on next relay connection.");
 
+    ui.add_space(20.0);
+    ui.heading("Media Uploads");
+    ui.add_space(10.0);
+
+    ui.horizontal(|ui| {
+        ui.label("Upload server: ")
+            .on_hover_text("The base URL of your media server, e.g. https://nostr.build");
+        ui.add(text_edit_line!(app, app.unsaved_settings.upload_server).desired_width(300.0));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Upload protocol: ");
+        egui::ComboBox::from_id_source("UploadProtocol")
+            .selected_text(&app.unsaved_settings.upload_protocol)
+            .show_ui(ui, |ui| {
+                for protocol in UploadProtocol::ALL {
+                    ui.selectable_value(
+                        &mut app.unsaved_settings.upload_protocol,
+                        protocol.name().to_owned(),
+                        protocol.name(),
+                    );
+                }
+            });
+    });
+
+    ui.checkbox(
+        &mut app.unsaved_settings.upload_strip_metadata,
+        "Strip EXIF and other metadata from images before uploading",
+    )
+    .on_hover_text("Photo metadata can reveal where and when a photo was taken.");
+
     ui.add_space(20.0);
 }
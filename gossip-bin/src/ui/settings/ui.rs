@@ -15,6 +15,10 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
         &mut app.unsaved_settings.posting_area_at_top,
         "Show posting area at the top instead of the bottom",
     );
+    ui.checkbox(
+        &mut app.unsaved_settings.petnames_first,
+        "Show your petnames for people first, and the names they claim second",
+    );
 
     ui.add_space(20.0);
     ui.horizontal(|ui| {
@@ -127,6 +127,23 @@ pub(in crate::ui) fn show_contact_search(
                                             nip05 = nip05.strikethrough()
                                         }
                                         super::truncated_label(ui, nip05, super::TAGG_WIDTH - 33.0);
+
+                                        if let Some(impersonated) =
+                                            GLOBALS.impersonation.impersonates(&person)
+                                        {
+                                            super::truncated_label(
+                                                ui,
+                                                RichText::new(format!(
+                                                    "⚠ looks like {}",
+                                                    gossip_lib::names::best_name_from_pubkey_lookup(
+                                                        &impersonated
+                                                    )
+                                                ))
+                                                .small()
+                                                .color(app.theme.warning_marker_text_color()),
+                                                super::TAGG_WIDTH - 33.0,
+                                            );
+                                        }
                                     });
                                 })
                             };
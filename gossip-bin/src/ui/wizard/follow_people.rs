@@ -148,6 +148,14 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
     ui.label("  • Profile (nprofile1..)");
     ui.label("  • DNS ID (user@domain)");
 
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.heading("Suggestions:");
+    ui.add_space(10.0);
+    crate::ui::people::render_suggestions(app, ctx, ui, Some(10));
+
     if app.wizard_state.has_private_key {
         ui.add_space(20.0);
         let mut label = RichText::new("  >  Publish and Finish");
@@ -17,7 +17,7 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
     ui.horizontal_wrapped(|ui| {
         ui.label("Enter your private key");
         let response = text_edit_line!(app, app.import_priv)
-            .hint_text("nsec1, hex, or ncryptsec1")
+            .hint_text("nsec1, hex, ncryptsec1, or a seed phrase")
             .desired_width(f32::INFINITY)
             .password(true)
             .with_paste()
@@ -36,6 +36,15 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
     }
 
     let ncryptsec = app.import_priv.starts_with("ncryptsec1");
+    let mnemonic = gossip_lib::nip06::looks_like_mnemonic(&app.import_priv);
+
+    if mnemonic {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label("Seed passphrase (leave empty if you did not set one)");
+            ui.add(text_edit_line!(app, app.mnemonic_passphrase).password(true));
+        });
+    }
 
     ui.add_space(10.0);
     ui.horizontal(|ui| {
@@ -102,16 +111,26 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
             .button(RichText::new("  >  Import").color(app.theme.accent_color()))
             .clicked()
         {
-            let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportPriv {
-                privkey: app.import_priv.clone(),
-                password: app.password.clone(),
-            });
+            if mnemonic {
+                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportMnemonic {
+                    mnemonic: app.import_priv.clone(),
+                    mnemonic_passphrase: app.mnemonic_passphrase.clone(),
+                    password: app.password.clone(),
+                });
+            } else {
+                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportPriv {
+                    privkey: app.import_priv.clone(),
+                    password: app.password.clone(),
+                });
+            }
             app.import_priv.zeroize();
             app.import_priv = "".to_owned();
             app.password.zeroize();
             app.password = "".to_owned();
             app.password2.zeroize();
             app.password2 = "".to_owned();
+            app.mnemonic_passphrase.zeroize();
+            app.mnemonic_passphrase = "".to_owned();
         }
     }
 
@@ -4,7 +4,7 @@ use eframe::egui;
 use egui::{Context, RichText, Ui};
 use gossip_lib::comms::ToOverlordMessage;
 use gossip_lib::GLOBALS;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
     // If already generated, advance
@@ -89,6 +89,44 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
             app.password2.zeroize();
             app.password2 = "".to_owned();
         }
+
+        ui.add_space(10.0);
+        if app.generated_mnemonic.is_empty() {
+            if ui
+                .button("  >  Generate from a Seed Phrase instead (NIP-06)")
+                .on_hover_text("A seed phrase lets you restore your key from a list of words")
+                .clicked()
+            {
+                match gossip_lib::nip06::generate_mnemonic() {
+                    Ok(words) => app.generated_mnemonic = Zeroizing::new(words),
+                    Err(e) => app.wizard_state.error = Some(format!("{}", e)),
+                }
+            }
+        } else {
+            ui.label("Write these words down and keep them safe. Anyone with them has your key.");
+            ui.add_space(5.0);
+            ui.label(RichText::new(app.generated_mnemonic.as_str()).monospace());
+            ui.add_space(5.0);
+            if ui
+                .button(
+                    RichText::new("  >  I have written them down, use this key")
+                        .color(app.theme.accent_color()),
+                )
+                .clicked()
+            {
+                app.wizard_state.generating = true;
+                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportMnemonic {
+                    mnemonic: (*app.generated_mnemonic).clone(),
+                    mnemonic_passphrase: "".to_owned(),
+                    password: app.password.clone(),
+                });
+                app.generated_mnemonic.zeroize();
+                app.password.zeroize();
+                app.password = "".to_owned();
+                app.password2.zeroize();
+                app.password2 = "".to_owned();
+            }
+        }
     }
 
     if app.wizard_state.generating {
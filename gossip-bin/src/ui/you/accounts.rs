@@ -0,0 +1,122 @@
+use super::{GossipUi, Page};
+use eframe::egui;
+use egui::{Context, RichText, Ui};
+use gossip_lib::comms::ToOverlordMessage;
+use gossip_lib::names;
+use gossip_lib::GLOBALS;
+use nostr_types::PublicKey;
+
+pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
+    ui.add_space(10.0);
+    ui.heading("Accounts");
+    ui.add_space(10.0);
+    ui.label("Each account has its own keys, person lists and relay lists. Events are shared between the accounts of this profile.");
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.heading("Active Account");
+    match GLOBALS.identity.public_key() {
+        Some(pubkey) => {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(names::best_name_from_pubkey_lookup(&pubkey)).strong());
+                ui.label(pubkey.as_bech32_string());
+            });
+        }
+        None => {
+            ui.label("This account has no key yet. Set one up on the Keys page.");
+        }
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.heading("Other Accounts");
+    let accounts = cached_accounts(app);
+    if accounts.is_empty() {
+        ui.label("There are no other accounts in this profile.");
+    }
+    for pubkey in &accounts {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(names::best_name_from_pubkey_lookup(pubkey)).strong());
+            ui.label(pubkey.as_bech32_string());
+            if ui.button("Switch").clicked() {
+                let _ = GLOBALS
+                    .to_overlord
+                    .send(ToOverlordMessage::SwitchAccount(*pubkey));
+            }
+            if app.account_to_remove == Some(*pubkey) {
+                if ui.button("REMOVE (Yes I'm Sure)").clicked() {
+                    if let Err(e) = GLOBALS.storage.delete_account(*pubkey, None) {
+                        GLOBALS.status_queue.write().write(format!("{}", e));
+                    }
+                    app.account_to_remove = None;
+                    app.accounts_cache = None;
+                }
+            } else if ui
+                .button("Remove")
+                .on_hover_text("Removes the account and its key from this profile")
+                .clicked()
+            {
+                app.account_to_remove = Some(*pubkey);
+            }
+        });
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    if ui.button("Add Another Account").clicked() {
+        let _ = GLOBALS.to_overlord.send(ToOverlordMessage::AddAccount);
+        app.set_page(ctx, Page::YourKeys);
+    }
+}
+
+// The other accounts of this profile. These only change when the active account
+// changes (switching or adding an account) or when one is removed here.
+fn cached_accounts(app: &mut GossipUi) -> Vec<PublicKey> {
+    let active = GLOBALS.identity.public_key();
+    match &app.accounts_cache {
+        Some((pubkey, accounts)) if *pubkey == active => accounts.clone(),
+        _ => {
+            let accounts: Vec<PublicKey> = GLOBALS
+                .storage
+                .read_all_accounts()
+                .unwrap_or_default()
+                .iter()
+                .map(|account| account.pubkey)
+                .collect();
+            app.accounts_cache = Some((active, accounts.clone()));
+            accounts
+        }
+    }
+}
+
+/// A compact switcher for the side panel
+pub(in crate::ui) fn switcher(app: &mut GossipUi, ui: &mut Ui) {
+    let accounts = cached_accounts(app);
+    if accounts.is_empty() {
+        return;
+    }
+
+    let current = match GLOBALS.identity.public_key() {
+        Some(pubkey) => names::best_name_from_pubkey_lookup(&pubkey),
+        None => "(new account)".to_owned(),
+    };
+
+    egui::ComboBox::from_id_source("account_switcher")
+        .selected_text(current)
+        .show_ui(ui, |ui| {
+            for pubkey in &accounts {
+                let name = names::best_name_from_pubkey_lookup(pubkey);
+                if ui.selectable_label(false, name).clicked() {
+                    let _ = GLOBALS
+                        .to_overlord
+                        .send(ToOverlordMessage::SwitchAccount(*pubkey));
+                }
+            }
+        });
+}
@@ -6,8 +6,9 @@ use egui::{Color32, Context, Frame, Stroke, Ui};
 use gossip_lib::comms::ToOverlordMessage;
 use gossip_lib::{Globals, GLOBALS};
 use nostr_types::{KeySecurity, PublicKeyHex};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
+pub(super) mod accounts;
 mod delegation;
 mod metadata;
 mod nostr_connect;
@@ -55,6 +56,12 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
                     ui.separator();
                     ui.add_space(10.0);
 
+                    offer_migrate_key(app, ui);
+
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+
                     offer_delete(app, ui);
                 } else if GLOBALS.identity.has_private_key() {
                     Frame::none()
@@ -114,6 +121,8 @@ pub(super) fn update(app: &mut GossipUi, ctx: &Context, _frame: &mut eframe::Fra
                     offer_delete_or_import_pub_key(app, ui);
                 }
             });
+    } else if app.page == Page::YourAccounts {
+        accounts::update(app, ctx, _frame, ui);
     } else if app.page == Page::YourMetadata {
         metadata::update(app, ctx, _frame, ui);
     } else if app.page == Page::YourDelegation {
@@ -266,6 +275,41 @@ fn offer_change_password(app: &mut GossipUi, ui: &mut Ui) {
 fn offer_export_priv_key(app: &mut GossipUi, ui: &mut Ui) {
     let key_security = GLOBALS.identity.key_security().unwrap();
 
+    ui.heading("Encrypted Export (NIP-49)");
+    ui.label("This exports an ncryptsec under your current passphrase. It does not downgrade your key security.");
+
+    ui.horizontal(|ui| {
+        ui.add_space(10.0);
+        ui.label("Enter Passphrase To Export: ");
+        ui.add(text_edit_line!(app, app.password3).password(true));
+    });
+    ui.horizontal(|ui| {
+        ui.add_space(10.0);
+        ui.label("Encryption rounds (log2): ");
+        ui.add(egui::Slider::new(&mut app.export_log_n, 16..=22));
+    });
+
+    if ui.button("Export Private Key as ncryptsec").clicked() {
+        match GLOBALS
+            .identity
+            .export_private_key_ncryptsec(&app.password3, app.export_log_n)
+        {
+            Ok(epk) => {
+                println!("Exported private key (ncryptsec): {}", epk.0);
+                GLOBALS.status_queue.write().write(
+                    "Exported key has been printed to the console standard output.".to_owned(),
+                );
+            }
+            Err(e) => GLOBALS.status_queue.write().write(format!("{}", e)),
+        }
+        app.password3.zeroize();
+        app.password3 = "".to_owned();
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
     ui.heading("Raw Export");
     if key_security == KeySecurity::Medium {
         ui.label("WARNING: This will downgrade your key security to WEAK");
@@ -373,6 +417,55 @@ fn offer_import_priv_key(app: &mut GossipUi, ui: &mut Ui) {
         app.password.zeroize();
         app.password = "".to_owned();
     }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.heading("Import from a Seed Phrase (NIP-06)");
+
+    ui.horizontal(|ui| {
+        ui.label("Enter seed phrase");
+        ui.add(
+            text_edit_line!(app, *app.import_mnemonic)
+                .hint_text("12 or 24 words")
+                .desired_width(f32::INFINITY)
+                .password(true),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Seed passphrase (optional)");
+        ui.add(text_edit_line!(app, app.mnemonic_passphrase).password(true));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Enter a passphrase to keep it encrypted under");
+        ui.add(text_edit_line!(app, app.password).password(true));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Repeat passphrase to be sure");
+        ui.add(text_edit_line!(app, app.password2).password(true));
+    });
+    if ui.button("import").clicked() {
+        if app.password != app.password2 {
+            GLOBALS
+                .status_queue
+                .write()
+                .write("Passwords do not match".to_owned());
+        } else {
+            let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportMnemonic {
+                mnemonic: (*app.import_mnemonic).clone(),
+                mnemonic_passphrase: app.mnemonic_passphrase.clone(),
+                password: app.password.clone(),
+            });
+        }
+        app.import_mnemonic.zeroize();
+        app.mnemonic_passphrase.zeroize();
+        app.mnemonic_passphrase = "".to_owned();
+        app.password.zeroize();
+        app.password = "".to_owned();
+        app.password2.zeroize();
+        app.password2 = "".to_owned();
+    }
 }
 
 fn offer_delete_or_import_pub_key(app: &mut GossipUi, ui: &mut Ui) {
@@ -422,6 +515,55 @@ fn offer_delete_or_import_pub_key(app: &mut GossipUi, ui: &mut Ui) {
     }
 }
 
+fn offer_migrate_key(app: &mut GossipUi, ui: &mut Ui) {
+    ui.heading("Migrate to a New Key");
+    ui.label("If your private key has been compromised, gossip can move you to a new key. A notice pointing to the new key is posted from the old key, then your profile, lists and relay list are republished under the new key. The old key is kept as another account.");
+
+    let migrations = GLOBALS.storage.read_key_migrations().unwrap_or_default();
+    for (old, new, when) in migrations.iter() {
+        ui.label(format!(
+            "{}: {} -> {}",
+            crate::date_ago::date_ago(*when),
+            gossip_lib::names::pubkey_short(old),
+            gossip_lib::names::pubkey_short(new)
+        ));
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Enter a passphrase to keep the new key encrypted under");
+        ui.add(text_edit_line!(app, app.password).password(true));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Repeat passphrase to be sure");
+        ui.add(text_edit_line!(app, app.password2).password(true));
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        if app.migrate_confirm {
+            ui.label("Please confirm that you really mean to do this: ");
+            if ui.button("MIGRATE (Yes I'm Sure)").clicked() {
+                if app.password != app.password2 {
+                    GLOBALS
+                        .status_queue
+                        .write()
+                        .write("Passwords do not match".to_owned());
+                } else {
+                    let _ = GLOBALS
+                        .to_overlord
+                        .send(ToOverlordMessage::MigrateKey(app.password.clone()));
+                }
+                app.password.zeroize();
+                app.password = "".to_owned();
+                app.password2.zeroize();
+                app.password2 = "".to_owned();
+                app.migrate_confirm = false;
+            }
+        } else if ui.button("Migrate to a New Key").clicked() {
+            app.migrate_confirm = true;
+        }
+    });
+}
+
 pub(super) fn offer_delete(app: &mut GossipUi, ui: &mut Ui) {
     ui.heading("DELETE This Identity");
 
@@ -467,4 +609,54 @@ fn offer_generate(app: &mut GossipUi, ui: &mut Ui) {
         app.password2.zeroize();
         app.password2 = "".to_owned();
     }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.heading("Generate a Keypair from a Seed Phrase (NIP-06)");
+
+    if app.generated_mnemonic.is_empty() {
+        if ui.button("Generate Seed Phrase").clicked() {
+            match gossip_lib::nip06::generate_mnemonic() {
+                Ok(words) => app.generated_mnemonic = Zeroizing::new(words),
+                Err(e) => GLOBALS.status_queue.write().write(format!("{}", e)),
+            }
+        }
+    } else {
+        ui.label("Write these words down and keep them safe. Anyone with them has your key.");
+        ui.add_space(5.0);
+        ui.label(egui::RichText::new(app.generated_mnemonic.as_str()).monospace());
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            ui.label("Enter a passphrase to keep it encrypted under");
+            ui.add(text_edit_line!(app, app.password).password(true));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Repeat passphrase to be sure");
+            ui.add(text_edit_line!(app, app.password2).password(true));
+        });
+        if ui
+            .button("I have written them down, use this key")
+            .clicked()
+        {
+            if app.password != app.password2 {
+                GLOBALS
+                    .status_queue
+                    .write()
+                    .write("Passwords do not match".to_owned());
+            } else {
+                let _ = GLOBALS.to_overlord.send(ToOverlordMessage::ImportMnemonic {
+                    mnemonic: (*app.generated_mnemonic).clone(),
+                    mnemonic_passphrase: "".to_owned(),
+                    password: app.password.clone(),
+                });
+                app.generated_mnemonic.zeroize();
+            }
+            app.password.zeroize();
+            app.password = "".to_owned();
+            app.password2.zeroize();
+            app.password2 = "".to_owned();
+        }
+    }
 }
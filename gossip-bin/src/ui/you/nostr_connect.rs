@@ -1,10 +1,11 @@
 use super::GossipUi;
 use crate::ui::widgets::CopyButton;
 use eframe::egui;
-use egui::{Context, Ui};
+use egui::{Context, RichText, Ui};
 use gossip_lib::comms::ToOverlordMessage;
-use gossip_lib::{Nip46UnconnectedServer, GLOBALS};
-use nostr_types::RelayUrl;
+use gossip_lib::nip46::{Approval, Nip46Permission, Nip46RateLimit};
+use gossip_lib::{Nip46Server, Nip46UnconnectedServer, GLOBALS};
+use nostr_types::{PublicKey, RelayUrl};
 
 pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Frame, ui: &mut Ui) {
     ui.add_space(10.0);
@@ -57,15 +58,211 @@ pub(super) fn update(app: &mut GossipUi, _ctx: &Context, _frame: &mut eframe::Fr
             ui.add_space(10.0);
         }
         for server in &servers {
-            let peer = server.peer_pubkey.as_bech32_string();
-            ui.label(format!("Peer={}", peer));
+            ui.push_id(server.peer_pubkey.as_hex_string(), |ui| {
+                show_connected_server(app, ui, server);
+            });
+        }
+    }
+
+    ui.separator();
+}
+
+fn show_connected_server(app: &mut GossipUi, ui: &mut Ui, server: &Nip46Server) {
+    let peer = server.peer_pubkey.as_bech32_string();
+    let title = match &server.metadata {
+        Some(md) => format!("{} ({})", md.name, peer),
+        None => format!("Peer={}", peer),
+    };
+
+    let mut modified = server.clone();
+
+    egui::CollapsingHeader::new(title)
+        .default_open(false)
+        .show(ui, |ui| {
             if ui.button("Disconnect").clicked() {
                 let _ = GLOBALS.storage.delete_nip46server(server.peer_pubkey, None);
             }
-        }
+
+            ui.add_space(10.0);
+            ui.label(RichText::new("Defaults").strong());
+            ui.horizontal(|ui| {
+                ui.label("Sign events:");
+                approval_selector(ui, "sign", &mut modified.sign_approval);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Encrypt:");
+                approval_selector(ui, "encrypt", &mut modified.encrypt_approval);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Decrypt:");
+                approval_selector(ui, "decrypt", &mut modified.decrypt_approval);
+            });
+
+            ui.add_space(10.0);
+            ui.label(RichText::new("Signing rules by event kind").strong());
+            for (kind, permission) in &server.sign_kind_rules {
+                ui.horizontal(|ui| {
+                    ui.label(format!("kind {}: {}", kind, permission.as_str()));
+                    if ui.button("Remove").clicked() {
+                        modified.set_sign_kind_rule(*kind, None);
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Kind:");
+                ui.add(text_edit_line!(app, app.nostr_connect_new_kind).desired_width(60.0));
+                if let Ok(kind) = app.nostr_connect_new_kind.trim().parse::<u32>() {
+                    for permission in PERMISSIONS {
+                        if ui.button(permission.as_str()).clicked() {
+                            modified.set_sign_kind_rule(kind, Some(permission));
+                            app.nostr_connect_new_kind.clear();
+                        }
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+            ui.label(RichText::new("Encryption rules by counterparty").strong());
+            for (pubkey, permission) in &server.counterparty_rules {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{}: {}",
+                        gossip_lib::names::best_name_from_pubkey_lookup(pubkey),
+                        permission.as_str()
+                    ));
+                    if ui.button("Remove").clicked() {
+                        modified.set_counterparty_rule(*pubkey, None);
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("npub or hex:");
+                ui.add(text_edit_line!(app, app.nostr_connect_new_counterparty));
+                let input = app.nostr_connect_new_counterparty.trim();
+                let pubkey = PublicKey::try_from_bech32_string(input, true)
+                    .or_else(|_| PublicKey::try_from_hex_string(input, true));
+                if let Ok(pubkey) = pubkey {
+                    for permission in PERMISSIONS {
+                        if ui.button(permission.as_str()).clicked() {
+                            modified.set_counterparty_rule(pubkey, Some(permission));
+                            app.nostr_connect_new_counterparty.clear();
+                        }
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+            ui.label(RichText::new("Rate limit").strong());
+            ui.horizontal(|ui| {
+                let mut limited = modified.rate_limit.is_some();
+                if ui.checkbox(&mut limited, "Limit requests").changed() {
+                    modified.rate_limit = if limited {
+                        Some(Nip46RateLimit::default())
+                    } else {
+                        None
+                    };
+                }
+                if let Some(limit) = &mut modified.rate_limit {
+                    ui.add(egui::DragValue::new(&mut limit.max_requests).clamp_range(1..=10000));
+                    ui.label("requests per");
+                    ui.add(egui::DragValue::new(&mut limit.period_secs).clamp_range(1..=86400));
+                    ui.label("seconds");
+                }
+            });
+
+            ui.add_space(10.0);
+            egui::CollapsingHeader::new("Audit log")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let log = GLOBALS
+                        .storage
+                        .read_nip46_audit_log(server.peer_pubkey)
+                        .unwrap_or_default();
+                    if log.is_empty() {
+                        ui.label("No requests yet");
+                    }
+                    for entry in log.iter().rev() {
+                        let when = crate::date_ago::date_ago(entry.when);
+                        ui.label(format!(
+                            "{}  {} {}  {}",
+                            when,
+                            entry.method,
+                            entry.detail,
+                            entry.decision.as_str()
+                        ));
+                    }
+                });
+        });
+
+    // Apply only what was changed here to the stored server, so that approvals
+    // used up by requests in the meantime are not undone
+    let rules_changed = modified.sign_kind_rules != server.sign_kind_rules;
+    let counterparties_changed = modified.counterparty_rules != server.counterparty_rules;
+    let rate_limit_changed = modified.rate_limit != server.rate_limit;
+    let sign_changed = !modified.sign_approval.same_kind(&server.sign_approval);
+    let encrypt_changed = !modified
+        .encrypt_approval
+        .same_kind(&server.encrypt_approval);
+    let decrypt_changed = !modified
+        .decrypt_approval
+        .same_kind(&server.decrypt_approval);
+    if rules_changed
+        || counterparties_changed
+        || rate_limit_changed
+        || sign_changed
+        || encrypt_changed
+        || decrypt_changed
+    {
+        let _ = GLOBALS.storage.modify_nip46server(
+            server.peer_pubkey,
+            |stored| {
+                if rules_changed {
+                    stored.sign_kind_rules = modified.sign_kind_rules.clone();
+                }
+                if counterparties_changed {
+                    stored.counterparty_rules = modified.counterparty_rules.clone();
+                }
+                if rate_limit_changed {
+                    stored.rate_limit = modified.rate_limit;
+                }
+                if sign_changed {
+                    stored.sign_approval = modified.sign_approval;
+                }
+                if encrypt_changed {
+                    stored.encrypt_approval = modified.encrypt_approval;
+                }
+                if decrypt_changed {
+                    stored.decrypt_approval = modified.decrypt_approval;
+                }
+            },
+            None,
+        );
     }
+}
 
-    ui.separator();
+const PERMISSIONS: [Nip46Permission; 3] = [
+    Nip46Permission::Allow,
+    Nip46Permission::Deny,
+    Nip46Permission::Ask,
+];
+
+fn approval_selector(ui: &mut Ui, id: &str, approval: &mut Approval) {
+    let text = match approval {
+        Approval::None => "Ask",
+        Approval::Once => "Once",
+        Approval::Until(_) => "For a while",
+        Approval::Always => "Allow",
+    };
+    egui::ComboBox::from_id_source(id)
+        .selected_text(text)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(text == "Ask", "Ask").clicked() {
+                *approval = Approval::None;
+            }
+            if ui.selectable_label(text == "Allow", "Allow").clicked() {
+                *approval = Approval::Always;
+            }
+        });
 }
 
 fn setup_unconnected_service(app: &mut GossipUi, ui: &mut Ui) {
@@ -73,31 +270,21 @@ fn setup_unconnected_service(app: &mut GossipUi, ui: &mut Ui) {
     ui.add_space(10.0);
     ui.heading("Setup a Service");
 
-    /*
-        NIP-46 doesn't explain well enough how this can work. Servers can't send methods
-        to clients, yet it wants us to send a connect method to the client. Until that is
-        resolved, this method of setup is not available.
-
-        ui.add_space(10.0);
-        ui.label("OPTION 1: Paste a secret string from the client:");
-        ui.add(text_edit_line!(app, app.nostr_connect_string));
-        if !app.nostr_connect_string.is_empty() {
-        if ui.button("CONNECT").clicked() {
-        match Nip46Server::new_from_client(app.nostr_connect_string.clone()) {
-        Ok(server) => {
-        // GINA - save server
-        // GINA - server needs to send 'connect' to the client
-    },
-        Err(e) => {
-        GLOBALS.status_queue.write().write(format!("{}", e));
-    }
-    }
+    ui.add_space(10.0);
+    ui.label("OPTION 1: Paste a nostrconnect:// string from the client:");
+    ui.add(text_edit_line!(app, app.nostr_connect_string));
+    if !app.nostr_connect_string.is_empty() && ui.button("Connect").clicked() {
+        let _ = GLOBALS
+            .to_overlord
+            .send(ToOverlordMessage::Nip46ServerConnectFromClient(
+                app.nostr_connect_string.trim().to_owned(),
+            ));
         app.nostr_connect_string = "".to_owned();
     }
-    }
-         */
 
     ui.add_space(10.0);
+    ui.label("OPTION 2: Create a service and give the client a bunker:// string.");
+
     ui.label("Enter 1 or 2 relays to do nostr-connect over:");
 
     ui.horizontal(|ui| {
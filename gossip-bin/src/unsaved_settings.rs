@@ -66,9 +66,13 @@ pub struct UnsavedSettings {
 
     // Event Content Settings
     pub hide_mutes_entirely: bool,
+    pub inbox_hide_untrusted: bool,
     pub reactions: bool,
     pub enable_zap_receipts: bool,
     pub show_media: bool,
+    pub link_previews: bool,
+    pub link_preview_allowed_domains: String,
+    pub link_preview_denied_domains: String,
     pub approve_content_warning: bool,
     pub show_deleted_events: bool,
     pub avoid_spam_on_unsafe_relays: bool,
@@ -76,7 +80,11 @@ pub struct UnsavedSettings {
     // Posting Settings
     pub pow: u8,
     pub set_client_tag: bool,
+    pub send_missed_scheduled_posts: bool,
     pub set_user_agent: bool,
+    pub upload_server: String,
+    pub upload_protocol: String,
+    pub upload_strip_metadata: bool,
     pub delegatee_tag: String,
 
     // UI settings
@@ -89,6 +97,7 @@ pub struct UnsavedSettings {
     pub override_dpi: Option<u32>,
     pub highlight_unread_events: bool,
     pub posting_area_at_top: bool,
+    pub petnames_first: bool,
     pub status_bar: bool,
     pub image_resize_algorithm: String,
     pub inertial_scrolling: bool,
@@ -101,6 +110,7 @@ pub struct UnsavedSettings {
     pub nip05_becomes_stale_if_invalid_minutes: u64,
     pub avatar_becomes_stale_hours: u64,
     pub media_becomes_stale_hours: u64,
+    pub link_preview_becomes_stale_hours: u64,
 
     // Websocket settings
     pub max_websocket_message_size_kb: usize,
@@ -123,6 +133,8 @@ pub struct UnsavedSettings {
     // Database settings
     pub prune_period_days: u64,
     pub cache_prune_period_days: u64,
+    pub media_cache_max_mb: u64,
+    pub max_metadata_versions: u8,
 }
 
 impl Default for UnsavedSettings {
@@ -152,15 +164,23 @@ impl Default for UnsavedSettings {
             direct_messages: default_setting!(direct_messages),
             future_allowance_secs: default_setting!(future_allowance_secs),
             hide_mutes_entirely: default_setting!(hide_mutes_entirely),
+            inbox_hide_untrusted: default_setting!(inbox_hide_untrusted),
             reactions: default_setting!(reactions),
             enable_zap_receipts: default_setting!(enable_zap_receipts),
             show_media: default_setting!(show_media),
+            link_previews: default_setting!(link_previews),
+            link_preview_allowed_domains: default_setting!(link_preview_allowed_domains),
+            link_preview_denied_domains: default_setting!(link_preview_denied_domains),
             approve_content_warning: default_setting!(approve_content_warning),
             show_deleted_events: default_setting!(show_deleted_events),
             avoid_spam_on_unsafe_relays: default_setting!(avoid_spam_on_unsafe_relays),
             pow: default_setting!(pow),
             set_client_tag: default_setting!(set_client_tag),
+            send_missed_scheduled_posts: default_setting!(send_missed_scheduled_posts),
             set_user_agent: default_setting!(set_user_agent),
+            upload_server: default_setting!(upload_server),
+            upload_protocol: default_setting!(upload_protocol),
+            upload_strip_metadata: default_setting!(upload_strip_metadata),
             delegatee_tag: default_setting!(delegatee_tag),
             max_fps: default_setting!(max_fps),
             recompute_feed_periodically: default_setting!(recompute_feed_periodically),
@@ -171,6 +191,7 @@ impl Default for UnsavedSettings {
             override_dpi: default_setting!(override_dpi),
             highlight_unread_events: default_setting!(highlight_unread_events),
             posting_area_at_top: default_setting!(posting_area_at_top),
+            petnames_first: default_setting!(petnames_first),
             status_bar: default_setting!(status_bar),
             image_resize_algorithm: default_setting!(image_resize_algorithm),
             inertial_scrolling: default_setting!(inertial_scrolling),
@@ -185,6 +206,7 @@ impl Default for UnsavedSettings {
             ),
             avatar_becomes_stale_hours: default_setting!(avatar_becomes_stale_hours),
             media_becomes_stale_hours: default_setting!(media_becomes_stale_hours),
+            link_preview_becomes_stale_hours: default_setting!(link_preview_becomes_stale_hours),
             max_websocket_message_size_kb: default_setting!(max_websocket_message_size_kb),
             max_websocket_frame_size_kb: default_setting!(max_websocket_frame_size_kb),
             websocket_accept_unmasked_frames: default_setting!(websocket_accept_unmasked_frames),
@@ -207,6 +229,8 @@ impl Default for UnsavedSettings {
             nip11_lines_to_output_on_error: default_setting!(nip11_lines_to_output_on_error),
             prune_period_days: default_setting!(prune_period_days),
             cache_prune_period_days: default_setting!(prune_period_days),
+            media_cache_max_mb: default_setting!(media_cache_max_mb),
+            max_metadata_versions: default_setting!(max_metadata_versions),
         }
     }
 }
@@ -236,15 +260,23 @@ impl UnsavedSettings {
             direct_messages: load_setting!(direct_messages),
             future_allowance_secs: load_setting!(future_allowance_secs),
             hide_mutes_entirely: load_setting!(hide_mutes_entirely),
+            inbox_hide_untrusted: load_setting!(inbox_hide_untrusted),
             reactions: load_setting!(reactions),
             enable_zap_receipts: load_setting!(enable_zap_receipts),
             show_media: load_setting!(show_media),
+            link_previews: load_setting!(link_previews),
+            link_preview_allowed_domains: load_setting!(link_preview_allowed_domains),
+            link_preview_denied_domains: load_setting!(link_preview_denied_domains),
             approve_content_warning: load_setting!(approve_content_warning),
             show_deleted_events: load_setting!(show_deleted_events),
             avoid_spam_on_unsafe_relays: load_setting!(avoid_spam_on_unsafe_relays),
             pow: load_setting!(pow),
             set_client_tag: load_setting!(set_client_tag),
+            send_missed_scheduled_posts: load_setting!(send_missed_scheduled_posts),
             set_user_agent: load_setting!(set_user_agent),
+            upload_server: load_setting!(upload_server),
+            upload_protocol: load_setting!(upload_protocol),
+            upload_strip_metadata: load_setting!(upload_strip_metadata),
             delegatee_tag: load_setting!(delegatee_tag),
             max_fps: load_setting!(max_fps),
             recompute_feed_periodically: load_setting!(recompute_feed_periodically),
@@ -255,6 +287,7 @@ impl UnsavedSettings {
             override_dpi: load_setting!(override_dpi),
             highlight_unread_events: load_setting!(highlight_unread_events),
             posting_area_at_top: load_setting!(posting_area_at_top),
+            petnames_first: load_setting!(petnames_first),
             status_bar: load_setting!(status_bar),
             image_resize_algorithm: load_setting!(image_resize_algorithm),
             inertial_scrolling: load_setting!(inertial_scrolling),
@@ -267,6 +300,7 @@ impl UnsavedSettings {
             ),
             avatar_becomes_stale_hours: load_setting!(avatar_becomes_stale_hours),
             media_becomes_stale_hours: load_setting!(media_becomes_stale_hours),
+            link_preview_becomes_stale_hours: load_setting!(link_preview_becomes_stale_hours),
             max_websocket_message_size_kb: load_setting!(max_websocket_message_size_kb),
             max_websocket_frame_size_kb: load_setting!(max_websocket_frame_size_kb),
             websocket_accept_unmasked_frames: load_setting!(websocket_accept_unmasked_frames),
@@ -289,6 +323,8 @@ impl UnsavedSettings {
             nip11_lines_to_output_on_error: load_setting!(nip11_lines_to_output_on_error),
             prune_period_days: load_setting!(prune_period_days),
             cache_prune_period_days: load_setting!(cache_prune_period_days),
+            media_cache_max_mb: load_setting!(media_cache_max_mb),
+            max_metadata_versions: load_setting!(max_metadata_versions),
         }
     }
 
@@ -316,15 +352,23 @@ impl UnsavedSettings {
         save_setting!(direct_messages, self, txn);
         save_setting!(future_allowance_secs, self, txn);
         save_setting!(hide_mutes_entirely, self, txn);
+        save_setting!(inbox_hide_untrusted, self, txn);
         save_setting!(reactions, self, txn);
         save_setting!(enable_zap_receipts, self, txn);
         save_setting!(show_media, self, txn);
+        save_setting!(link_previews, self, txn);
+        save_setting!(link_preview_allowed_domains, self, txn);
+        save_setting!(link_preview_denied_domains, self, txn);
         save_setting!(approve_content_warning, self, txn);
         save_setting!(show_deleted_events, self, txn);
         save_setting!(avoid_spam_on_unsafe_relays, self, txn);
         save_setting!(pow, self, txn);
         save_setting!(set_client_tag, self, txn);
+        save_setting!(send_missed_scheduled_posts, self, txn);
         save_setting!(set_user_agent, self, txn);
+        save_setting!(upload_server, self, txn);
+        save_setting!(upload_protocol, self, txn);
+        save_setting!(upload_strip_metadata, self, txn);
         save_setting!(delegatee_tag, self, txn);
         save_setting!(max_fps, self, txn);
         save_setting!(recompute_feed_periodically, self, txn);
@@ -335,6 +379,7 @@ impl UnsavedSettings {
         save_setting!(override_dpi, self, txn);
         save_setting!(highlight_unread_events, self, txn);
         save_setting!(posting_area_at_top, self, txn);
+        save_setting!(petnames_first, self, txn);
         save_setting!(status_bar, self, txn);
         save_setting!(image_resize_algorithm, self, txn);
         save_setting!(inertial_scrolling, self, txn);
@@ -345,6 +390,7 @@ impl UnsavedSettings {
         save_setting!(nip05_becomes_stale_if_invalid_minutes, self, txn);
         save_setting!(avatar_becomes_stale_hours, self, txn);
         save_setting!(media_becomes_stale_hours, self, txn);
+        save_setting!(link_preview_becomes_stale_hours, self, txn);
         save_setting!(max_websocket_message_size_kb, self, txn);
         save_setting!(max_websocket_frame_size_kb, self, txn);
         save_setting!(websocket_accept_unmasked_frames, self, txn);
@@ -361,7 +407,11 @@ impl UnsavedSettings {
         save_setting!(nip11_lines_to_output_on_error, self, txn);
         save_setting!(prune_period_days, self, txn);
         save_setting!(cache_prune_period_days, self, txn);
+        save_setting!(media_cache_max_mb, self, txn);
+        save_setting!(max_metadata_versions, self, txn);
         txn.commit()?;
+        gossip_lib::names::reload_settings();
+        GLOBALS.link_previews.reload_settings();
         Ok(())
     }
 }
@@ -0,0 +1,375 @@
+use crate::comms::ToOverlordMessage;
+use crate::error::{Error, ErrorKind};
+use crate::globals::GLOBALS;
+use crate::relay::Relay;
+use nostr_types::{
+    ContentEncryptionAlgorithm, Event, EventKind, Id, PreEvent, PublicKey, RelayUrl, Tag, Unixtime,
+};
+use std::sync::atomic::Ordering;
+
+/// Bookmark type, aliased to the latest version
+pub type Bookmark = crate::storage::types::Bookmark1;
+
+/// BookmarkList type, aliased to the latest version
+pub type BookmarkList = crate::storage::types::BookmarkList1;
+
+/// The user's bookmark list followed by their bookmark sets (sorted by title).
+/// The bookmark list is always present, even if it is empty.
+/// (returns empty on error or without an identity)
+pub fn get_bookmark_lists() -> Vec<BookmarkList> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return vec![],
+    };
+    let mut lists = match GLOBALS.storage.read_bookmark_lists(my_pubkey) {
+        Ok(lists) => lists,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return vec![];
+        }
+    };
+    if !lists.iter().any(|l| l.is_main_list()) {
+        lists.push(BookmarkList {
+            last_edit_time: Unixtime(0),
+            ..Default::default()
+        });
+    }
+    lists.sort_by(|a, b| {
+        b.is_main_list()
+            .cmp(&a.is_main_list())
+            .then(a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    lists
+}
+
+/// Get the user's bookmark list (empty dtag) or one of their bookmark sets
+pub fn get_bookmark_list(dtag: &str) -> Option<BookmarkList> {
+    let my_pubkey = GLOBALS.identity.public_key()?;
+    match GLOBALS.storage.read_bookmark_list(my_pubkey, dtag) {
+        Ok(list) => list,
+        Err(e) => {
+            tracing::error!("{}", e);
+            None
+        }
+    }
+}
+
+/// The dtags of the lists that a bookmark is in
+pub fn lists_containing(bookmark: &Bookmark) -> Vec<String> {
+    get_bookmark_lists()
+        .iter()
+        .filter(|l| l.entries.iter().any(|(b, _)| b == bookmark))
+        .map(|l| l.dtag.clone())
+        .collect()
+}
+
+/// Add (or remove) a bookmark in the user's bookmark list (empty dtag) or one
+/// of their bookmark sets, then publish the list
+pub fn set_bookmark(
+    dtag: &str,
+    bookmark: Bookmark,
+    bookmarked: bool,
+    public: bool,
+) -> Result<(), Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+
+    let mut list = match GLOBALS.storage.read_bookmark_list(my_pubkey, dtag)? {
+        Some(list) => list,
+        None if dtag.is_empty() => BookmarkList::default(),
+        None => return Err(ErrorKind::ListNotFound.into()),
+    };
+
+    list.entries.retain(|(b, _)| *b != bookmark);
+    if bookmarked {
+        list.entries.push((bookmark, public));
+    }
+    list.last_edit_time = Unixtime::now().unwrap();
+    GLOBALS
+        .storage
+        .write_bookmark_list(my_pubkey, &list, None)?;
+
+    GLOBALS.feed.sync_recompute();
+
+    let _ = GLOBALS
+        .to_overlord
+        .send(ToOverlordMessage::PushBookmarkList(dtag.to_owned()));
+
+    Ok(())
+}
+
+/// Create a new, empty, bookmark set. Returns its dtag.
+pub fn create_bookmark_set(title: &str) -> Result<String, Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+
+    let dtag = format!("bm{}", Unixtime::now().unwrap().0);
+    let list = BookmarkList {
+        dtag: dtag.clone(),
+        title: title.to_owned(),
+        ..Default::default()
+    };
+    GLOBALS
+        .storage
+        .write_bookmark_list(my_pubkey, &list, None)?;
+
+    Ok(dtag)
+}
+
+/// The ids of the events in a bookmark list or set, most recently bookmarked
+/// first. Replaceable events are resolved to their latest local version, and
+/// bookmarks we don't have the event for are left out.
+pub fn bookmarked_ids(dtag: &str) -> Result<Vec<Id>, Error> {
+    let list = match get_bookmark_list(dtag) {
+        Some(list) => list,
+        None => return Ok(vec![]),
+    };
+
+    let mut ids: Vec<Id> = Vec::new();
+    for (bookmark, _) in list.entries.iter().rev() {
+        match bookmark {
+            Bookmark::Event(id) => {
+                if GLOBALS.storage.has_event(*id)? {
+                    ids.push(*id);
+                }
+            }
+            Bookmark::Addr { author, d, .. } => {
+                let ea = bookmark.event_addr().unwrap();
+                if let Some(event) = GLOBALS.storage.get_replaceable_event(ea.kind, *author, d)? {
+                    ids.push(event.id);
+                }
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Ask relays for the bookmarked events we don't have yet
+pub(crate) fn fetch_missing(dtag: &str) -> Result<(), Error> {
+    let list = match get_bookmark_list(dtag) {
+        Some(list) => list,
+        None => return Ok(()),
+    };
+
+    let relays: Vec<RelayUrl> = GLOBALS
+        .storage
+        .filter_relays(|r| r.has_usage_bits(Relay::READ) && r.rank != 0)?
+        .iter()
+        .map(|r| r.url.clone())
+        .collect();
+
+    for (bookmark, _) in &list.entries {
+        match bookmark {
+            Bookmark::Event(id) => {
+                if !GLOBALS.storage.has_event(*id)? {
+                    let _ = GLOBALS
+                        .to_overlord
+                        .send(ToOverlordMessage::FetchEvent(*id, relays.clone()));
+                }
+            }
+            Bookmark::Addr { author, d, .. } => {
+                let mut ea = bookmark.event_addr().unwrap();
+                if GLOBALS
+                    .storage
+                    .get_replaceable_event(ea.kind, *author, d)?
+                    .is_none()
+                {
+                    ea.relays = relays.iter().map(|r| r.to_unchecked_url()).collect();
+                    let _ = GLOBALS
+                        .to_overlord
+                        .send(ToOverlordMessage::FetchEventAddr(ea));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate the kind 10003 (empty dtag) or kind 30003 event for a bookmark list.
+/// Private entries are encrypted into the content.
+pub(crate) fn generate_bookmark_list_event(dtag: &str) -> Result<Event, Error> {
+    if !GLOBALS.identity.is_unlocked() {
+        return Err((ErrorKind::NoPrivateKey, file!(), line!()).into());
+    }
+
+    let my_pubkey = GLOBALS.identity.public_key().unwrap();
+
+    let list = match GLOBALS.storage.read_bookmark_list(my_pubkey, dtag)? {
+        Some(list) => list,
+        None if dtag.is_empty() => BookmarkList::default(),
+        None => return Err(ErrorKind::ListNotFound.into()),
+    };
+
+    let mut public_tags: Vec<Tag> = Vec::new();
+    let mut private_tags: Vec<Tag> = Vec::new();
+
+    let kind = if list.is_main_list() {
+        EventKind::BookmarkList
+    } else {
+        public_tags.push(Tag::new_identifier(list.dtag.clone()));
+        public_tags.push(Tag::new_title(list.title.clone()));
+        EventKind::BookmarkSets
+    };
+
+    for (bookmark, public) in &list.entries {
+        if *public {
+            public_tags.push(bookmark.to_tag());
+        } else {
+            private_tags.push(bookmark.to_tag());
+        }
+    }
+
+    let content = if private_tags.is_empty() {
+        "".to_owned()
+    } else {
+        let private_tags_string = serde_json::to_string(&private_tags)?;
+        GLOBALS.identity.encrypt(
+            &my_pubkey,
+            &private_tags_string,
+            ContentEncryptionAlgorithm::Nip04,
+        )?
+    };
+
+    let pre_event = PreEvent {
+        pubkey: my_pubkey,
+        created_at: Unixtime::now().unwrap(),
+        kind,
+        tags: public_tags,
+        content,
+    };
+
+    GLOBALS.identity.sign_event(pre_event)
+}
+
+/// Update the local copy of one of our bookmark lists from a newer event.
+/// If the local list was edited since it was last synchronized, entries from
+/// the event are added to it rather than replacing it.
+///
+/// While locked, private entries cannot be read. Ours are kept, and the list is
+/// not marked as synchronized with the event, so that it is processed again
+/// (see [process_stored_bookmark_lists]) once unlocked.
+pub(crate) fn process_bookmark_list_event(
+    event: &Event,
+    my_pubkey: PublicKey,
+) -> Result<(), Error> {
+    let dtag = match event.kind {
+        EventKind::BookmarkList => "".to_owned(),
+        EventKind::BookmarkSets => match event.parameter() {
+            Some(d) if !d.is_empty() => d,
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    let local = GLOBALS.storage.read_bookmark_list(my_pubkey, &dtag)?;
+    if let Some(ref local) = local {
+        if event.created_at <= local.event_created_at {
+            return Ok(());
+        }
+    }
+    let synchronized_at = local
+        .as_ref()
+        .map(|l| l.event_created_at)
+        .unwrap_or(Unixtime(0));
+    let locked = !event.content.is_empty() && !GLOBALS.identity.is_unlocked();
+
+    let mut entries: Vec<(Bookmark, bool)> = Vec::new();
+    let mut title = dtag.clone();
+
+    for tag in &event.tags {
+        if let Some(bookmark) = Bookmark::from_tag(tag) {
+            entries.push((bookmark, true));
+        }
+        if let Ok(t) = tag.parse_title() {
+            title = t.to_owned();
+        }
+    }
+
+    if !event.content.is_empty() {
+        if GLOBALS.identity.is_unlocked() {
+            let decrypted = if event.content.contains("?iv=") {
+                String::from_utf8_lossy(
+                    &GLOBALS.identity.decrypt_nip04(&my_pubkey, &event.content)?,
+                )
+                .into_owned()
+            } else {
+                GLOBALS.identity.decrypt_nip44(&my_pubkey, &event.content)?
+            };
+            let tags: Vec<Tag> = serde_json::from_str(&decrypted)?;
+            for tag in &tags {
+                if let Some(bookmark) = Bookmark::from_tag(tag) {
+                    entries.push((bookmark, false));
+                }
+                if let Ok(t) = tag.parse_title() {
+                    title = t.to_owned();
+                }
+            }
+        } else if let Some(ref local) = local {
+            // Keep the private entries we already had, since we can't read the new ones
+            entries.extend(local.entries.iter().filter(|(_, public)| !public).cloned());
+        }
+    }
+
+    let mut list = match local {
+        Some(local) if local.last_edit_time > local.event_created_at => {
+            // Merge in the entries we don't have yet
+            let mut merged = local;
+            for entry in entries {
+                if !merged.entries.iter().any(|(b, _)| *b == entry.0) {
+                    merged.entries.push(entry);
+                }
+            }
+            merged
+        }
+        _ => BookmarkList {
+            dtag,
+            entries,
+            // While locked it stays as of the last synchronization
+            last_edit_time: if locked {
+                synchronized_at
+            } else {
+                event.created_at
+            },
+            ..Default::default()
+        },
+    };
+    list.title = title;
+    list.event_created_at = if locked {
+        synchronized_at
+    } else {
+        event.created_at
+    };
+
+    GLOBALS
+        .storage
+        .write_bookmark_list(my_pubkey, &list, None)?;
+
+    GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Process our stored bookmark list events again, picking up any private entries
+/// that could not be read while locked
+pub(crate) fn process_stored_bookmark_lists() -> Result<(), Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Ok(()),
+    };
+    let events = GLOBALS.storage.find_events(
+        &[EventKind::BookmarkList, EventKind::BookmarkSets],
+        &[my_pubkey],
+        None,
+        |_| true,
+        false,
+    )?;
+    for event in &events {
+        process_bookmark_list_event(event, my_pubkey)?;
+    }
+    Ok(())
+}
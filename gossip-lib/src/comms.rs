@@ -7,6 +7,7 @@ use nostr_types::{
     UncheckedUrl, Unixtime,
 };
 use std::fmt;
+use std::path::PathBuf;
 
 /// This is a message sent to the Overlord. Tasks which take any amount of time,
 /// especially involving relays, are handled by the Overlord in this way. There is
@@ -15,6 +16,9 @@ use std::fmt;
 /// renderer.
 #[derive(Debug, Clone)]
 pub enum ToOverlordMessage {
+    /// Calls [add_account](crate::Overlord::add_account)
+    AddAccount,
+
     /// Calls [add_pubkey_relay](crate::Overlord::add_pubkey_relay)
     AddPubkeyRelay(PublicKey, RelayUrl),
 
@@ -33,21 +37,36 @@ pub enum ToOverlordMessage {
     /// Calls [auth_approved](crate::Overlord::auth_declined)
     AuthDeclined(RelayUrl),
 
+    /// Calls [browse_nip05_domain](crate::Overlord::browse_nip05_domain)
+    BrowseNip05Domain(String),
+
     /// Calls [change_passphrase](crate::Overlord::change_passphrase)
     ChangePassphrase { old: String, new: String },
 
     /// Calls [clear_person_list](crate::Overlord::clear_person_list)
     ClearPersonList(PersonList),
 
+    /// Calls [compute_follow_suggestions](crate::Overlord::compute_follow_suggestions)
+    ComputeFollowSuggestions,
+
     /// Calls [auth_approved](crate::Overlord::connect_approved)
     ConnectApproved(RelayUrl),
 
     /// Calls [auth_approved](crate::Overlord::connect_declined)
     ConnectDeclined(RelayUrl),
 
+    /// Calls [copy_person_list](crate::Overlord::copy_person_list)
+    CopyPersonList {
+        pubkey: PublicKey,
+        dtag: Option<String>,
+    },
+
     /// Calls [delegation_reset](crate::Overlord::delegation_reset)
     DelegationReset,
 
+    /// Calls [delete_bookmark_set](crate::Overlord::delete_bookmark_set)
+    DeleteBookmarkSet(String),
+
     /// Calls [delete_person_list](crate::Overlord::delete_person_list)
     DeletePersonList(PersonList),
 
@@ -69,12 +88,18 @@ pub enum ToOverlordMessage {
     /// Calls [fetch_event_addr](crate::Overlord::fetch_event_addr)
     FetchEventAddr(EventAddr),
 
+    /// Calls [fetch_pin_list](crate::Overlord::fetch_pin_list)
+    FetchPinList(PublicKey),
+
     /// Calls [follow_pubkey](crate::Overlord::follow_pubkey)
     FollowPubkey(PublicKey, PersonList, bool),
 
     /// Calls [follow_nip05](crate::Overlord::follow_nip05)
     FollowNip05(String, PersonList, bool),
 
+    /// Calls [follow_nip05_domain](crate::Overlord::follow_nip05_domain)
+    FollowNip05Domain(String, PersonList, bool),
+
     /// Calls [follow_nprofile](crate::Overlord::follow_nprofile)
     FollowNprofile(Profile, PersonList, bool),
 
@@ -84,9 +109,16 @@ pub enum ToOverlordMessage {
     /// Calls [hide_or_show_relay](crate::Overlord::hide_or_show_relay)
     HideOrShowRelay(RelayUrl, bool),
 
+    /// Calls [import_mnemonic](crate::Overlord::import_mnemonic)
+    ImportMnemonic {
+        mnemonic: String,
+        mnemonic_passphrase: String,
+        password: String,
+    },
+
     /// Calls [import_priv](crate::Overlord::import_priv)
     ImportPriv {
-        // nsec, hex, or ncryptsec
+        // nsec, hex, ncryptsec, or a mnemonic
         privkey: String,
         password: String,
     },
@@ -100,12 +132,18 @@ pub enum ToOverlordMessage {
     /// Calls [load_more_current_feed](crate::Overlord::load_more_current_feed)
     LoadMoreCurrentFeed,
 
+    /// Calls [migrate_key](crate::Overlord::migrate_key)
+    MigrateKey(String),
+
     /// internal (minions use this channel too)
     MinionJobComplete(RelayUrl, u64),
 
     /// internal (minions use this channel too)
     MinionJobUpdated(RelayUrl, u64, u64),
 
+    /// Calls [nip46_server_connect_from_client](crate::Overlord::nip46_server_connect_from_client)
+    Nip46ServerConnectFromClient(String),
+
     /// Calls [nip46_server_op_approval_response](crate::Overlord::nip46_server_op_approval_response)
     Nip46ServerOpApprovalResponse(PublicKey, ParsedCommand, Approval),
 
@@ -126,6 +164,9 @@ pub enum ToOverlordMessage {
     /// Calls [prune_database](crate::Overlord::prune_database)
     PruneDatabase,
 
+    /// Calls [push_bookmark_list](crate::Overlord::push_bookmark_list)
+    PushBookmarkList(String),
+
     /// Calls [push_person_list](crate::Overlord::push_person_list)
     PushPersonList(PersonList),
 
@@ -147,9 +188,25 @@ pub enum ToOverlordMessage {
     /// Calls [repost](crate::Overlord::repost)
     Repost(Id),
 
+    /// Calls [schedule_post](crate::Overlord::schedule_post)
+    SchedulePost {
+        content: String,
+        tags: Vec<Tag>,
+        in_reply_to: Option<Id>,
+        dm_channel: Option<DmChannel>,
+        send_at: Unixtime,
+        sign_now: bool,
+    },
+
     /// Calls [search](crate::Overlord::search)
     Search(String),
 
+    /// Calls [send_scheduled_post](crate::Overlord::send_scheduled_post)
+    SendScheduledPost(u64),
+
+    /// Calls [send_scheduled_posts](crate::Overlord::send_scheduled_posts)
+    SendScheduledPosts,
+
     /// Calls [set_active_person](crate::Overlord::set_active_person)
     SetActivePerson(PublicKey),
 
@@ -159,6 +216,9 @@ pub enum ToOverlordMessage {
     /// internal
     SetPersonFeed(PublicKey),
 
+    /// Calls [set_pinned](crate::Overlord::set_pinned)
+    SetPinned(Id, bool),
+
     /// internal
     SetThreadFeed {
         id: Id,
@@ -184,6 +244,9 @@ pub enum ToOverlordMessage {
     /// Calls [shutdown](crate::Overlord::shutdown)
     Shutdown,
 
+    /// Calls [switch_account](crate::Overlord::switch_account)
+    SwitchAccount(PublicKey),
+
     /// Calls [unlock_key](crate::Overlord::unlock_key)
     UnlockKey(String),
 
@@ -202,6 +265,12 @@ pub enum ToOverlordMessage {
     /// Calls [update_relay](crate::Overlord::update_relay)
     UpdateRelay(Relay, Relay),
 
+    /// Calls [update_scheduled_post](crate::Overlord::update_scheduled_post)
+    UpdateScheduledPost(u64, String, Unixtime),
+
+    /// Calls [upload_file](crate::Overlord::upload_file)
+    UploadFile(u64, PathBuf),
+
     /// Calls [visible_notes_changed](crate::Overlord::visible_notes_changed)
     VisibleNotesChanged(Vec<Id>),
 
@@ -277,6 +346,7 @@ pub enum RelayConnectionReason {
     Follow,
     NostrConnect,
     PostEvent,
+    PostBookmarks,
     PostContacts,
     PostLike,
     PostMetadata,
@@ -310,6 +380,7 @@ impl RelayConnectionReason {
             Advertising => "Advertising our relay list",
             PostLike => "Posting a reaction to an event",
             FetchContacts => "Fetching our contact list",
+            PostBookmarks => "Posting our bookmarks",
             PostContacts => "Posting our contact list",
             PostMuteList => "Posting our mute list",
             PostMetadata => "Posting our metadata",
@@ -335,6 +406,7 @@ impl RelayConnectionReason {
             Advertising => false,
             PostLike => false,
             FetchContacts => false,
+            PostBookmarks => false,
             PostContacts => false,
             PostMuteList => false,
             PostMetadata => false,
@@ -0,0 +1,117 @@
+use crate::error::{Error, ErrorKind};
+use crate::globals::GLOBALS;
+use nostr_types::{ContentEncryptionAlgorithm, PublicKey, Tag, Unixtime};
+
+/// Draft type, aliased to the latest version
+pub type Draft = crate::storage::types::Draft1;
+
+/// The current account's saved drafts, most recently edited first
+/// (returns empty on error or without an identity). Direct message drafts
+/// are only included while the identity is unlocked.
+pub fn get_drafts() -> Vec<Draft> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return vec![],
+    };
+    let mut drafts = match GLOBALS.storage.read_drafts(my_pubkey) {
+        Ok(drafts) => drafts,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return vec![];
+        }
+    };
+    let mut drafts: Vec<Draft> = drafts
+        .into_iter()
+        .filter_map(|draft| match decrypt_draft(draft) {
+            Ok(draft) => Some(draft),
+            Err(e) => {
+                tracing::debug!("{}", e);
+                None
+            }
+        })
+        .collect();
+    drafts.sort_by(|a, b| b.last_edit_time.cmp(&a.last_edit_time));
+    drafts
+}
+
+/// Get one of the current account's saved drafts
+pub fn get_draft(id: u64) -> Option<Draft> {
+    let my_pubkey = GLOBALS.identity.public_key()?;
+    match GLOBALS
+        .storage
+        .read_draft(my_pubkey, id)
+        .and_then(|draft| draft.map(decrypt_draft).transpose())
+    {
+        Ok(draft) => draft,
+        Err(e) => {
+            tracing::error!("{}", e);
+            None
+        }
+    }
+}
+
+/// Save a draft for the current account. A draft with an id of 0 is new, and
+/// is given an id. Direct message drafts are encrypted to ourselves, so they
+/// can only be saved while the identity is unlocked.
+pub fn save_draft(draft: &mut Draft) -> Result<(), Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+
+    if draft.id == 0 {
+        draft.id = crate::new_local_id();
+    }
+    draft.last_edit_time = Unixtime::now().unwrap();
+
+    if draft.dm_channel.is_some() {
+        let encrypted = encrypt_draft(my_pubkey, draft)?;
+        GLOBALS.storage.write_draft(my_pubkey, &encrypted, None)
+    } else {
+        GLOBALS.storage.write_draft(my_pubkey, draft, None)
+    }
+}
+
+/// Delete one of the current account's saved drafts
+pub fn delete_draft(id: u64) -> Result<(), Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+
+    GLOBALS.storage.delete_draft(my_pubkey, id, None)
+}
+
+// Direct message drafts are stored with their content and tags encrypted
+// together into the content field
+fn encrypt_draft(my_pubkey: PublicKey, draft: &Draft) -> Result<Draft, Error> {
+    if !GLOBALS.identity.is_unlocked() {
+        return Err(ErrorKind::NoPrivateKey.into());
+    }
+    let plaintext = serde_json::to_string(&(&draft.content, &draft.tags))?;
+    let mut encrypted = draft.clone();
+    encrypted.content =
+        GLOBALS
+            .identity
+            .encrypt(&my_pubkey, &plaintext, ContentEncryptionAlgorithm::Nip44v2)?;
+    encrypted.tags = vec![];
+    Ok(encrypted)
+}
+
+fn decrypt_draft(mut draft: Draft) -> Result<Draft, Error> {
+    if draft.dm_channel.is_none() {
+        return Ok(draft);
+    }
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+    if !GLOBALS.identity.is_unlocked() {
+        return Err(ErrorKind::NoPrivateKey.into());
+    }
+    let plaintext = GLOBALS.identity.decrypt_nip44(&my_pubkey, &draft.content)?;
+    let (content, tags): (String, Vec<Tag>) = serde_json::from_str(&plaintext)?;
+    draft.content = content;
+    draft.tags = tags;
+    Ok(draft)
+}
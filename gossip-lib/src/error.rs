@@ -6,6 +6,7 @@ use nostr_types::RelayUrl;
 #[derive(Debug)]
 pub enum ErrorKind {
     BadNostrConnectString,
+    Bip32(bip32::Error),
     BroadcastSend(String),
     BroadcastReceive(tokio::sync::broadcast::error::RecvError),
     CannotUpdateRelayUrl,
@@ -19,6 +20,7 @@ pub enum ErrorKind {
     JoinError(tokio::task::JoinError),
     Lmdb(heed::Error),
     MaxRelaysReached,
+    Mnemonic(bip39::Error),
     MpscSend(tokio::sync::mpsc::error::SendError<ToOverlordMessage>),
     Nip05KeyNotFound,
     Nip46CommandMissingId,
@@ -33,6 +35,7 @@ pub enum ErrorKind {
     NoRelay,
     NotAPersonListEvent,
     NoSlotsRemaining,
+    NoUploadServer,
     Image(image::error::ImageError),
     ImageFailure,
     Io(std::io::Error),
@@ -60,6 +63,7 @@ pub enum ErrorKind {
     TagNotIndexed(String),
     Timeout(tokio::time::error::Elapsed),
     UnknownCommand(String),
+    UploadFailed(String),
     UrlHasEmptyHostname,
     UrlHasNoHostname,
     UrlParse(url::ParseError),
@@ -89,6 +93,7 @@ impl std::fmt::Display for Error {
         }
         match &self.kind {
             BadNostrConnectString => write!(f, "Bad nostrconnect string"),
+            Bip32(e) => write!(f, "BIP-32 key derivation: {e}"),
             BroadcastSend(s) => write!(f, "Error broadcasting: {s}"),
             BroadcastReceive(e) => write!(f, "Error receiving broadcast: {e}"),
             CannotUpdateRelayUrl => {
@@ -107,6 +112,7 @@ impl std::fmt::Display for Error {
                 f,
                 "Maximum relay connections reached, will not connect to another"
             ),
+            Mnemonic(e) => write!(f, "Mnemonic: {e}"),
             MpscSend(e) => write!(f, "Error sending mpsc: {e}"),
             Nip05KeyNotFound => write!(f, "NIP-05 public key not found"),
             Nip46CommandMissingId => write!(f, "NIP-46 command missing ID"),
@@ -123,6 +129,7 @@ impl std::fmt::Display for Error {
             NoRelay => write!(f, "Could not determine a relay to use."),
             NotAPersonListEvent => write!(f, "Not a person list event"),
             NoSlotsRemaining => write!(f, "No custom list slots remaining."),
+            NoUploadServer => write!(f, "No upload server is configured."),
             Image(e) => write!(f, "Image: {e}"),
             ImageFailure => write!(f, "Image Failure"),
             Io(e) => write!(f, "I/O Error: {e}"),
@@ -150,6 +157,7 @@ impl std::fmt::Display for Error {
             TagNotIndexed(s) => write!(f, "Tag not indexed: {s}"),
             Timeout(e) => write!(f, "Timeout: {e}"),
             UnknownCommand(s) => write!(f, "Unknown command: {s}"),
+            UploadFailed(s) => write!(f, "Upload failed: {s}"),
             UrlHasEmptyHostname => write!(f, "URL has empty hostname"),
             UrlHasNoHostname => write!(f, "URL has no hostname"),
             UrlParse(e) => write!(f, "URL parse: {e}"),
@@ -211,6 +219,18 @@ impl From<&str> for ErrorKind {
     }
 }
 
+impl From<bip32::Error> for ErrorKind {
+    fn from(e: bip32::Error) -> ErrorKind {
+        ErrorKind::Bip32(e)
+    }
+}
+
+impl From<bip39::Error> for ErrorKind {
+    fn from(e: bip39::Error) -> ErrorKind {
+        ErrorKind::Mnemonic(e)
+    }
+}
+
 impl From<http::Error> for ErrorKind {
     fn from(e: http::Error) -> ErrorKind {
         ErrorKind::HttpError(e)
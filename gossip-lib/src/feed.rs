@@ -22,6 +22,7 @@ pub enum FeedKind {
     },
     Person(PublicKey),
     DmChat(DmChannel),
+    Bookmarks(String), // dtag, empty for the bookmark list
 }
 
 impl std::fmt::Display for FeedKind {
@@ -39,6 +40,10 @@ impl std::fmt::Display for FeedKind {
                 author: _,
             } => write!(f, "Thread {}", crate::names::hex_id_short(&(*id).into())),
             FeedKind::Person(pk) => write!(f, "{}", crate::names::best_name_from_pubkey_lookup(pk)),
+            FeedKind::Bookmarks(dtag) => match crate::bookmarks::get_bookmark_list(dtag) {
+                Some(list) if !list.is_main_list() => write!(f, "{}", list.title),
+                _ => write!(f, "Bookmarks"),
+            },
         }
     }
 }
@@ -50,7 +55,8 @@ impl FeedKind {
             Self::Inbox(_) => true,
             Self::Thread { .. } => false, // always full
             Self::Person(_) => true,
-            Self::DmChat(_) => false, // always full
+            Self::DmChat(_) => false,    // always full
+            Self::Bookmarks(_) => false, // always full
         }
     }
 }
@@ -67,6 +73,7 @@ pub struct Feed {
     inbox_feed: RwLock<Vec<Id>>,
     person_feed: RwLock<Vec<Id>>,
     dm_chat_feed: RwLock<Vec<Id>>,
+    bookmarks_feed: RwLock<Vec<Id>>,
 
     // When feeds start
     general_feed_start: RwLock<Unixtime>,
@@ -95,6 +102,7 @@ impl Feed {
             inbox_feed: RwLock::new(Vec::new()),
             person_feed: RwLock::new(Vec::new()),
             dm_chat_feed: RwLock::new(Vec::new()),
+            bookmarks_feed: RwLock::new(Vec::new()),
             general_feed_start: RwLock::new(Unixtime::now().unwrap()),
             person_feed_start: RwLock::new(Unixtime::now().unwrap()),
             inbox_feed_start: RwLock::new(Unixtime::now().unwrap()),
@@ -250,6 +258,22 @@ impl Feed {
             .send(ToOverlordMessage::SetDmChannel(channel));
     }
 
+    /// Change the feed to the user's bookmark list (empty dtag) or one of their bookmark sets
+    pub fn set_feed_to_bookmarks(&self, dtag: String) {
+        *self.current_feed_kind.write() = FeedKind::Bookmarks(dtag.clone());
+        *self.thread_parent.write() = None;
+
+        // Recompute as they switch
+        self.sync_recompute();
+
+        self.unlisten();
+
+        // Ask for the bookmarked events we don't have
+        if let Err(e) = crate::bookmarks::fetch_missing(&dtag) {
+            tracing::error!("{}", e);
+        }
+    }
+
     /// Get the kind of the current feed
     pub fn get_feed_kind(&self) -> FeedKind {
         self.current_feed_kind.read().to_owned()
@@ -279,6 +303,12 @@ impl Feed {
         self.dm_chat_feed.read().clone()
     }
 
+    /// Read the bookmarks feed
+    pub fn get_bookmarks_feed(&self) -> Vec<Id> {
+        self.sync_maybe_periodic_recompute();
+        self.bookmarks_feed.read().clone()
+    }
+
     /// Get the parent of the current thread feed.
     /// The children should be recursively found via `GLOBALS.storage.get_replies(id)`
     pub fn get_thread_parent(&self) -> Option<Id> {
@@ -346,6 +376,7 @@ impl Feed {
 
         // Filter further for the general feed
         let dismissed = GLOBALS.dismissed.read().await.clone();
+        let mutes = GLOBALS.people.get_mutes();
         let now = Unixtime::now().unwrap();
 
         let current_feed_kind = self.current_feed_kind.read().to_owned();
@@ -377,6 +408,7 @@ impl Feed {
                                     && e.kind != EventKind::EncryptedDirectMessage // no DMs
                                     && e.kind != EventKind::DmChat // no DMs
                                     && !dismissed.contains(&e.id) // not dismissed
+                                    && !mutes.iter().any(|(m, _)| m.matches(e)) // not muted
                                     && if !with_replies {
                                         e.replies_to().is_none() // is not a reply
                                     } else {
@@ -394,6 +426,13 @@ impl Feed {
             }
             FeedKind::Inbox(indirect) => {
                 if let Some(my_pubkey) = GLOBALS.identity.public_key() {
+                    let hide_untrusted = GLOBALS.storage.read_setting_inbox_hide_untrusted();
+                    let my_follows = if hide_untrusted {
+                        GLOBALS.wot.my_follows()
+                    } else {
+                        HashSet::new()
+                    };
+
                     // Unfortunately it is expensive to find all events referencing
                     // any of my events, and we don't have such an index.
                     //
@@ -431,6 +470,16 @@ impl Feed {
                                 {
                                     return true;
                                 }
+                                if mutes.iter().any(|(m, _)| m.matches(e)) {
+                                    return false;
+                                }
+                                if hide_untrusted
+                                    && e.pubkey != my_pubkey
+                                    && !my_follows.contains(&e.pubkey)
+                                    && !GLOBALS.wot.score(e.pubkey).is_trusted()
+                                {
+                                    return false;
+                                }
 
                                 // Include if it directly replies to one of my events
                                 match e.replies_to() {
@@ -513,7 +562,24 @@ impl Feed {
 
                 events.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
 
-                let events: Vec<Id> = events.iter().map(|e| e.id).collect();
+                // Pinned notes go first, however old they are
+                let mut pinned: Vec<Id> = Vec::new();
+                for id in crate::pins::get_pinned(person_pubkey)? {
+                    if GLOBALS.storage.has_event(id)? {
+                        pinned.push(id);
+                    }
+                }
+
+                let events: Vec<Id> = pinned
+                    .iter()
+                    .copied()
+                    .chain(
+                        events
+                            .iter()
+                            .map(|e| e.id)
+                            .filter(|id| !pinned.contains(id)),
+                    )
+                    .collect();
 
                 *self.person_feed.write() = events;
             }
@@ -521,6 +587,10 @@ impl Feed {
                 let ids = GLOBALS.storage.dm_events(&channel)?;
                 *self.dm_chat_feed.write() = ids;
             }
+            FeedKind::Bookmarks(dtag) => {
+                let ids = crate::bookmarks::bookmarked_ids(&dtag)?;
+                *self.bookmarks_feed.write() = ids;
+            }
         }
 
         self.recompute_lock.store(false, Ordering::Relaxed);
@@ -577,7 +647,7 @@ pub fn enabled_event_kinds() -> Vec<EventKind> {
                 || *k == EventKind::MuteList
             // || *k == EventKind::PinList
                 || *k == EventKind::RelayList
-            // || *k == EventKind::BookmarkList
+                || *k == EventKind::BookmarkList
             // || *k == EventKind::CommunityList
             // || *k == EventKind::PublicChatsList
             // || *k == EventKind::BlockedRelaysList
@@ -593,7 +663,7 @@ pub fn enabled_event_kinds() -> Vec<EventKind> {
                 || *k == EventKind::FollowSets
             // || *k == EventKind::GenericSets
             // || *k == EventKind::RelaySets
-            // || *k == EventKind::BookmarkSets
+                || *k == EventKind::BookmarkSets
             // || *k == EventKind::CurationSets
             // || *k == EventKind::ProfileBadges
             // || *k == EventKind::BadgeDefinition
@@ -1,19 +1,68 @@
 use crate::error::{Error, ErrorKind};
 use crate::globals::GLOBALS;
 use crate::profile::Profile;
+use crate::storage::types::MediaCacheEntry1;
 use crate::USER_AGENT;
-use futures::stream::{FuturesUnordered, StreamExt};
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use image::{ImageFormat, RgbaImage};
 use nostr_types::{Unixtime, Url};
 use reqwest::header::ETAG;
 use reqwest::Client;
 use reqwest::StatusCode;
 use sha2::Digest;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::RwLock;
 use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, Notify};
+
+// How often a progress stream reports, when nothing finishes sooner
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Subdirectories of the cache directory
+const CONTENT_DIR: &str = "content";
+const THUMBNAIL_DIR: &str = "thumbnails";
+
+// Last access times are only recorded this precisely, to limit database writes
+const TOUCH_GRANULARITY_SECS: i64 = 60 * 60;
+
+// How often the cache is checked against its size limit
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// A file in the media cache, aliased to the latest version
+pub type MediaCacheEntry = MediaCacheEntry1;
+
+/// Statistics about the media cache
+#[derive(Debug, Clone, Default)]
+pub struct MediaCacheStats {
+    /// URLs cached
+    pub urls: usize,
+
+    /// Distinct files cached (several URLs may serve the same file)
+    pub files: usize,
+
+    /// Size of the distinct files
+    pub bytes: u64,
+
+    /// Size the files would take if each URL was stored separately
+    pub url_bytes: u64,
+
+    /// Decoded thumbnails cached
+    pub thumbnails: usize,
+
+    /// Size of the decoded thumbnails
+    pub thumbnail_bytes: u64,
+
+    /// Files left from an older version of the cache, which are moved into the
+    /// cache when their URL is next requested
+    pub legacy_files: usize,
+
+    /// Size of the files left from an older version of the cache
+    pub legacy_bytes: u64,
+}
 
 #[derive(Copy, Clone, Debug)]
 enum FetchState {
@@ -24,6 +73,25 @@ enum FetchState {
     // If it succeeds, it is removed entirely.
 }
 
+/// Progress of a fetch, as reported by [Fetcher::fetch_media_with_progress]
+#[derive(Debug)]
+pub enum FetchProgress {
+    /// Waiting for its turn (host load limits, the penalty box, or the queue)
+    Queued,
+
+    /// Being downloaded
+    Downloading {
+        /// Bytes received so far
+        received: u64,
+
+        /// Total bytes, if the server said
+        total: Option<u64>,
+    },
+
+    /// Finished. This is always the last item.
+    Done(Result<Bytes, Error>),
+}
+
 /// System that fetches HTTP resources
 #[derive(Debug, Default)]
 pub struct Fetcher {
@@ -38,6 +106,19 @@ pub struct Fetcher {
 
     // Here is where we put hosts into a penalty box to time them out
     penalty_box: RwLock<HashMap<String, Unixtime>>,
+
+    // Bytes received and expected for each URL being downloaded
+    progress: RwLock<HashMap<Url, (u64, Option<u64>)>>,
+
+    // URLs that are not downloaded if they are larger than this many bytes
+    size_limits: RwLock<HashMap<Url, u64>>,
+
+    // Wakes async callers whenever any fetch finishes
+    finished: Notify,
+
+    // Held while changing cache files together with their index, so that
+    // eviction cannot remove a file between it being written and indexed
+    cache_lock: Mutex<()>,
 }
 
 impl Fetcher {
@@ -49,7 +130,10 @@ impl Fetcher {
 
     pub(crate) fn start() -> Result<(), Error> {
         // Setup the cache directory
-        *GLOBALS.fetcher.cache_dir.write().unwrap() = Profile::current()?.cache_dir;
+        let cache_dir = Profile::current()?.cache_dir;
+        fs::create_dir_all(cache_dir.join(CONTENT_DIR))?;
+        fs::create_dir_all(cache_dir.join(THUMBNAIL_DIR))?;
+        *GLOBALS.fetcher.cache_dir.write().unwrap() = cache_dir;
 
         // Create client
         let connect_timeout = std::time::Duration::new(
@@ -68,6 +152,22 @@ impl Fetcher {
                 .build()?,
         );
 
+        // Keep the cache within its size limit
+        tokio::task::spawn(async move {
+            loop {
+                match GLOBALS.fetcher.evict().await {
+                    Ok(0) => (),
+                    Ok(count) => tracing::info!("Evicted {count} files from the media cache"),
+                    Err(e) => tracing::error!("{}", e),
+                }
+
+                tokio::time::sleep(EVICTION_INTERVAL).await;
+                if GLOBALS.shutting_down.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+        });
+
         // Setup periodic queue management
         let fetcher_looptime_ms = GLOBALS.storage.read_setting_fetcher_looptime_ms();
         tokio::task::spawn(async move {
@@ -193,43 +293,35 @@ impl Fetcher {
         }
 
         // Check if a cached file exists and is fresh enough
-        let cache_file = self.cache_file(url);
         let mut stale = false;
-        match fs::metadata(cache_file.as_path()) {
-            Ok(md) => {
-                // We had a bug that put empty cache files in place (maybe we still have it).
-                // In any case, if the file is empty, don't honor it and wipe any etag
-                if md.len() == 0 {
-                    let etag_file = GLOBALS.fetcher.etag_file(url);
-                    let _ = fs::remove_file(etag_file);
-                } else {
-                    if let Ok(modified) = md.modified() {
-                        if let Ok(dur) = modified.elapsed() {
-                            if dur < max_age {
-                                match fs::read(cache_file.as_path()) {
-                                    Ok(contents) => {
-                                        tracing::debug!(
-                                            "FETCH {url}: Cache Hit age={}s",
-                                            dur.as_secs()
-                                        );
-                                        return Ok(Some(contents));
-                                    }
-                                    Err(e) => return Err(e.into()),
-                                }
-                            } else {
-                                stale = true;
-                            }
+        let entry = match GLOBALS.storage.read_media_cache_entry(url.as_str())? {
+            Some(entry) => Some(entry),
+            None => self.migrate_legacy_file(url)?,
+        };
+        if let Some(entry) = entry {
+            let now = Unixtime::now().unwrap().0;
+            let age = Duration::from_secs((now - entry.fetched).max(0) as u64);
+            // A max_age of zero means it never becomes stale
+            if max_age.is_zero() || age < max_age {
+                match fs::read(self.content_file(&entry.hash)) {
+                    Ok(contents) => {
+                        tracing::debug!("FETCH {url}: Cache Hit age={}s", age.as_secs());
+                        self.touch(entry, now);
+                        return Ok(Some(contents));
+                    }
+                    Err(e) => {
+                        if e.kind() != std::io::ErrorKind::NotFound {
+                            tracing::info!("FETCH {url}: Failed: {e}");
+                            return Err(e.into());
                         }
+                        // The file was evicted or deleted. Forget it and fetch again.
+                        GLOBALS
+                            .storage
+                            .delete_media_cache_entry(url.as_str(), None)?;
                     }
                 }
-                // fall through
-            }
-            Err(e) => {
-                // NotFound falls through
-                if e.kind() != std::io::ErrorKind::NotFound {
-                    tracing::info!("FETCH {url}: Failed: {e}");
-                    return Err(e.into());
-                }
+            } else {
+                stale = true;
             }
         }
 
@@ -247,7 +339,98 @@ impl Fetcher {
         Ok(None)
     }
 
+    /// Fetch the bytes of a file, from the cache if it is younger than `max_age`
+    /// (or at all, if `max_age` is zero).
+    ///
+    /// This shares the queue with [try_get](Self::try_get), so requests for the same
+    /// URL are coalesced, and the penalty box and host load limits are honored.
+    pub async fn fetch_media(&self, url: &Url, max_age: Duration) -> Result<Bytes, Error> {
+        let mut stream = Box::pin(self.fetch_media_with_progress(url, max_age));
+        while let Some(progress) = stream.next().await {
+            if let FetchProgress::Done(result) = progress {
+                return result;
+            }
+        }
+        Err(ErrorKind::General("Fetch ended without a result".to_owned()).into())
+    }
+
+    /// Like [fetch_media](Self::fetch_media), but fails instead of downloading
+    /// more than `max_len` bytes. This is for URLs that could be anything, where
+    /// only a small file is wanted.
+    pub async fn fetch_limited(
+        &self,
+        url: &Url,
+        max_age: Duration,
+        max_len: u64,
+    ) -> Result<Bytes, Error> {
+        self.size_limits
+            .write()
+            .unwrap()
+            .insert(url.to_owned(), max_len);
+        let result = self.fetch_media(url, max_age).await;
+        self.size_limits.write().unwrap().remove(url);
+        result
+    }
+
+    /// Like [fetch_media](Self::fetch_media), reporting progress as it goes. The
+    /// stream ends with a [FetchProgress::Done].
+    pub fn fetch_media_with_progress<'a>(
+        &'a self,
+        url: &Url,
+        max_age: Duration,
+    ) -> impl Stream<Item = FetchProgress> + 'a {
+        let url = url.to_owned();
+        futures::stream::unfold(Some((url, max_age)), move |state| async move {
+            let (url, mut max_age) = state?;
+            loop {
+                // Register before checking, so we cannot miss a notification
+                let finished = self.finished.notified();
+
+                match self.try_get(&url, max_age) {
+                    Ok(Some(bytes)) => {
+                        return Some((FetchProgress::Done(Ok(Bytes::from(bytes))), None));
+                    }
+                    Err(e) => return Some((FetchProgress::Done(Err(e)), None)),
+                    Ok(None) => {}
+                }
+
+                // Once it has been queued, whatever the fetch leaves in the cache is
+                // fresh enough. Otherwise a short max_age could have us fetch forever.
+                max_age = Duration::ZERO;
+
+                // The queue is not processed while offline
+                if GLOBALS.storage.read_setting_offline() {
+                    let e = ErrorKind::General("Offline".to_owned()).into();
+                    return Some((FetchProgress::Done(Err(e)), None));
+                }
+
+                if tokio::time::timeout(PROGRESS_INTERVAL, finished)
+                    .await
+                    .is_err()
+                {
+                    // Nothing finished, report where we are
+                    let progress = match self.progress.read().unwrap().get(&url) {
+                        Some((received, total)) => FetchProgress::Downloading {
+                            received: *received,
+                            total: *total,
+                        },
+                        None => FetchProgress::Queued,
+                    };
+                    return Some((progress, Some((url, max_age))));
+                }
+            }
+        })
+    }
+
     async fn fetch(&self, url: Url) {
+        self.fetch_inner(&url).await;
+        self.progress.write().unwrap().remove(&url);
+        self.finished.notify_waiters();
+    }
+
+    async fn fetch_inner(&self, url: &Url) {
+        let url = url.to_owned();
+
         // Do not fetch if offline
         if GLOBALS.storage.read_setting_offline() {
             tracing::debug!("FETCH {url}: Failed: offline mode");
@@ -255,19 +438,25 @@ impl Fetcher {
             return;
         }
 
-        let etag_file = GLOBALS.fetcher.etag_file(&url);
-        let cache_file = GLOBALS.fetcher.cache_file(&url);
+        let entry = match GLOBALS.storage.read_media_cache_entry(url.as_str()) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::error!("{}", e);
+                None
+            }
+        };
 
-        let etag: Option<Vec<u8>> = match tokio::fs::read(etag_file.as_path()).await {
-            Ok(contents) => {
-                // etag is only valid if the contents file is present
-                if matches!(tokio::fs::try_exists(cache_file.as_path()).await, Ok(true)) {
-                    Some(contents)
+        // etag is only valid if the contents file is present
+        let etag: Option<Vec<u8>> = match &entry {
+            Some(entry) => {
+                let content_file = self.content_file(&entry.hash);
+                if matches!(tokio::fs::try_exists(content_file).await, Ok(true)) {
+                    entry.etag.clone()
                 } else {
                     None
                 }
             }
-            Err(_) => None,
+            None => None,
         };
 
         let stale = matches!(
@@ -317,11 +506,7 @@ impl Fetcher {
                         } else {
                             tracing::info!("FETCH {url}: Failed (using stale cache): {message}");
                         }
-                        // FIXME: bumping the mtime might not be the best way to do this.
-                        let _ = filetime::set_file_mtime(
-                            cache_file.as_path(),
-                            filetime::FileTime::now(),
-                        );
+                        self.revalidated(entry.clone());
                         self.urls.write().unwrap().remove(&url);
                     } else {
                         if let Some(e) = err {
@@ -337,8 +522,7 @@ impl Fetcher {
                 }
                 FailOutcome::NotModified => {
                     tracing::debug!("FETCH {url}: Succeeded: {message}");
-                    let _ =
-                        filetime::set_file_mtime(cache_file.as_path(), filetime::FileTime::now());
+                    self.revalidated(entry.clone());
                     self.urls.write().unwrap().remove(&url);
                 }
                 FailOutcome::Requeue => {
@@ -450,15 +634,38 @@ impl Fetcher {
             .get(ETAG)
             .map(|e| e.as_bytes().to_owned());
 
-        // Convert to bytes
-        let maybe_bytes = response.bytes().await;
-        let bytes = match maybe_bytes {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                finish(FailOutcome::Fail, "response bytes", Some(e.into()), 0);
+        // Don't download more than the caller wanted
+        let max_len = self.size_limits.read().unwrap().get(&url).copied();
+        let total = response.content_length();
+        if let (Some(max_len), Some(total)) = (max_len, total) {
+            if total > max_len {
+                finish(FailOutcome::Fail, "too large", None, 0);
                 return;
             }
-        };
+        }
+
+        // Convert to bytes, tracking progress
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    bytes.extend_from_slice(&chunk);
+                    if max_len.is_some_and(|m| bytes.len() as u64 > m) {
+                        finish(FailOutcome::Fail, "too large", None, 0);
+                        return;
+                    }
+                    self.progress
+                        .write()
+                        .unwrap()
+                        .insert(url.clone(), (bytes.len() as u64, total));
+                }
+                Err(e) => {
+                    finish(FailOutcome::Fail, "response bytes", Some(e.into()), 0);
+                    return;
+                }
+            }
+        }
 
         // Do not accept zero-length files, and don't try again
         if bytes.is_empty() {
@@ -468,14 +675,21 @@ impl Fetcher {
 
         GLOBALS.bytes_read.fetch_add(bytes.len(), Ordering::Relaxed);
 
-        // Write to the file
-        if let Err(e) = tokio::fs::write(cache_file.as_path(), bytes).await {
-            finish(
-                FailOutcome::Fail,
-                "writing to cache file",
-                Some(e.into()),
-                0,
-            );
+        let now = Unixtime::now().unwrap().0;
+        let new_entry = MediaCacheEntry {
+            url: url.as_str().to_owned(),
+            hash: hex::encode(sha2::Sha256::digest(&bytes)),
+            size: bytes.len() as u64,
+            fetched: now,
+            last_access: now,
+            etag: maybe_etag,
+        };
+        let stored = {
+            let _cache_guard = self.cache_lock.lock().await;
+            self.store(&new_entry, &bytes).await
+        };
+        if let Err((message, e)) = stored {
+            finish(FailOutcome::Fail, message, Some(e), 0);
             return;
         }
 
@@ -484,26 +698,188 @@ impl Fetcher {
         // Remove from host load
         self.decrement_host_load(&host);
 
-        // If there was an etag, save it
-        if let Some(etag) = maybe_etag {
-            let _ = tokio::fs::write(etag_file, etag).await;
+        self.urls.write().unwrap().remove(&url);
+    }
+
+    // Write the content file, unless we already have this content, and index it.
+    // The caller holds the cache lock.
+    async fn store(
+        &self,
+        entry: &MediaCacheEntry,
+        bytes: &[u8],
+    ) -> Result<(), (&'static str, Error)> {
+        let content_file = self.content_file(&entry.hash);
+        if !matches!(tokio::fs::try_exists(&content_file).await, Ok(true)) {
+            if let Err(e) = tokio::fs::write(&content_file, bytes).await {
+                return Err(("writing to cache file", e.into()));
+            }
         }
+        if let Err(e) = GLOBALS.storage.write_media_cache_entry(entry, None) {
+            return Err(("indexing cache file", e));
+        }
+        Ok(())
+    }
 
-        self.urls.write().unwrap().remove(&url);
+    // Before the cache was content-addressed, files were stored at the top level
+    // named by the hash of their URL, with the etag alongside
+    fn legacy_file(&self, url: &Url) -> PathBuf {
+        let mut legacy_file = self.cache_dir.read().unwrap().clone();
+        legacy_file.push(hex::encode(sha2::Sha256::digest(url.as_str().as_bytes())));
+        legacy_file
     }
 
-    fn cache_file(&self, url: &Url) -> PathBuf {
-        // Hash the url into a SHA256 hex string
-        let hash = {
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(url.as_str().as_bytes());
-            let result = hasher.finalize();
-            hex::encode(result)
+    // Move a file left from before the cache was content-addressed into the
+    // cache, if there is one for this URL. This is skipped (for now) if the
+    // cache is busy.
+    fn migrate_legacy_file(&self, url: &Url) -> Result<Option<MediaCacheEntry>, Error> {
+        let legacy_file = self.legacy_file(url);
+        let metadata = match fs::metadata(&legacy_file) {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(None),
+        };
+        let _cache_guard = match self.cache_lock.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => return Ok(None),
         };
 
-        let mut cache_file = self.cache_dir.read().unwrap().clone();
-        cache_file.push(hash);
-        cache_file
+        let etag_file = legacy_file.with_extension("etag");
+        let bytes = fs::read(&legacy_file)?;
+        let etag = fs::read(&etag_file).ok();
+
+        // We had a bug that put empty cache files in place; those are dropped
+        let mut entry: Option<MediaCacheEntry> = None;
+        if !bytes.is_empty() {
+            let hash = hex::encode(sha2::Sha256::digest(&bytes));
+            let content_file = self.content_file(&hash);
+            if !content_file.exists() {
+                fs::rename(&legacy_file, &content_file)?;
+            }
+            let fetched = metadata
+                .modified()
+                .ok()
+                .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let new_entry = MediaCacheEntry {
+                url: url.as_str().to_owned(),
+                hash,
+                size: bytes.len() as u64,
+                fetched,
+                last_access: Unixtime::now().unwrap().0,
+                etag,
+            };
+            GLOBALS.storage.write_media_cache_entry(&new_entry, None)?;
+            entry = Some(new_entry);
+        }
+        let _ = fs::remove_file(&legacy_file);
+        let _ = fs::remove_file(&etag_file);
+
+        tracing::debug!("FETCH {url}: Moved from the old cache layout");
+        Ok(entry)
+    }
+
+    // Files left from before the cache was content-addressed, with their sizes
+    // and when they were last written
+    fn legacy_files(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, Error> {
+        let cache_path = self.cache_dir.read().unwrap().to_owned();
+        let mut output: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
+        for entry in fs::read_dir(cache_path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                output.push((entry.path(), metadata.len(), modified));
+            }
+        }
+        Ok(output)
+    }
+
+    // The hash of the file each thumbnail was made from, and its size
+    fn thumbnail_sizes(&self) -> Result<Vec<(String, u64)>, Error> {
+        let mut thumbnail_dir = self.cache_dir.read().unwrap().clone();
+        thumbnail_dir.push(THUMBNAIL_DIR);
+        let mut output: Vec<(String, u64)> = Vec::new();
+        for entry in fs::read_dir(thumbnail_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Thumbnails are named "{hash}_{variant}"
+            let hash = name.split('_').next().unwrap_or_default().to_owned();
+            output.push((hash, entry.metadata()?.len()));
+        }
+        Ok(output)
+    }
+
+    // Content files are named by the hash of their content, so the same file
+    // at several URLs is stored once
+    fn content_file(&self, hash: &str) -> PathBuf {
+        let mut content_file = self.cache_dir.read().unwrap().clone();
+        content_file.push(CONTENT_DIR);
+        content_file.push(hash);
+        content_file
+    }
+
+    fn thumbnail_file(&self, hash: &str, variant: &str) -> PathBuf {
+        let mut thumbnail_file = self.cache_dir.read().unwrap().clone();
+        thumbnail_file.push(THUMBNAIL_DIR);
+        thumbnail_file.push(format!("{hash}_{variant}"));
+        thumbnail_file
+    }
+
+    // Record that a cached file was used, not too often
+    fn touch(&self, mut entry: MediaCacheEntry, now: i64) {
+        if now - entry.last_access < TOUCH_GRANULARITY_SECS {
+            return;
+        }
+        entry.last_access = now;
+        if let Err(e) = GLOBALS.storage.write_media_cache_entry(&entry, None) {
+            tracing::error!("{}", e);
+        }
+    }
+
+    // Record that a cached file is still good, as if it were fetched again
+    fn revalidated(&self, entry: Option<MediaCacheEntry>) {
+        if let Some(mut entry) = entry {
+            let now = Unixtime::now().unwrap().0;
+            entry.fetched = now;
+            entry.last_access = now;
+            if let Err(e) = GLOBALS.storage.write_media_cache_entry(&entry, None) {
+                tracing::error!("{}", e);
+            }
+        }
+    }
+
+    /// A decoded thumbnail of the file at this URL, if one was saved with
+    /// [write_thumbnail](Self::write_thumbnail). `variant` distinguishes
+    /// different sizes and shapes.
+    pub(crate) fn read_thumbnail(&self, url: &Url, variant: &str) -> Option<RgbaImage> {
+        let entry = GLOBALS
+            .storage
+            .read_media_cache_entry(url.as_str())
+            .ok()??;
+        let bytes = fs::read(self.thumbnail_file(&entry.hash, variant)).ok()?;
+        image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+            .ok()
+            .map(|i| i.into_rgba8())
+    }
+
+    /// Save a decoded thumbnail of the (cached) file at this URL
+    pub(crate) fn write_thumbnail(
+        &self,
+        url: &Url,
+        variant: &str,
+        image: &RgbaImage,
+    ) -> Result<(), Error> {
+        let entry = match GLOBALS.storage.read_media_cache_entry(url.as_str())? {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        let mut output = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut output, ImageFormat::Png)?;
+        fs::write(
+            self.thumbnail_file(&entry.hash, variant),
+            output.into_inner(),
+        )?;
+        Ok(())
     }
 
     fn sinbin(&self, url: &Url, duration: Duration) {
@@ -534,10 +910,6 @@ impl Fetcher {
         u.host_str().map(|s| s.to_owned())
     }
 
-    fn etag_file(&self, url: &Url) -> PathBuf {
-        self.cache_file(url).with_extension("etag")
-    }
-
     fn fetch_host_load(&self, host: &str) -> usize {
         let hashmap = self.host_load.read().unwrap();
         if let Some(load) = hashmap.get(host) {
@@ -567,30 +939,132 @@ impl Fetcher {
         }
     }
 
+    /// Remove cached files that have not been used for `age`, including files
+    /// left over from older versions of the cache, and files nothing refers to.
+    /// Returns how many files were removed.
     pub(crate) async fn prune(&self, age: Duration) -> Result<usize, Error> {
+        let _cache_guard = self.cache_lock.lock().await;
         let mut count: usize = 0;
-        let cache_path = self.cache_dir.read().unwrap().to_owned();
-        let mut entries = tokio::fs::read_dir(cache_path.as_path()).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            if let Ok(metadata) = entry.metadata().await {
-                if metadata.is_dir() {
-                    continue;
-                }
 
-                // FIXME - many filesystems do not track access times. We may want
-                //         to track these in LMDB (along with etags)
-                let file_time = match metadata.accessed() {
-                    Ok(st) => st,
-                    Err(_) => match metadata.modified() {
-                        Ok(st) => st,
-                        Err(_) => metadata.created()?,
-                    },
-                };
-                let file_age = match SystemTime::now().duration_since(file_time) {
-                    Ok(dur) => dur,
-                    Err(_) => continue,
-                };
-                if file_age > age {
+        // Files at the top level were named by the hash of their URL, so they
+        // can only be moved into the cache when that URL is next fetched. Until
+        // then they are pruned by age as they always were.
+        for (path, _, modified) in self.legacy_files()? {
+            let file_age = match SystemTime::now().duration_since(modified) {
+                Ok(dur) => dur,
+                Err(_) => continue,
+            };
+            if file_age > age {
+                tokio::fs::remove_file(path.as_path()).await?;
+                count += 1;
+            }
+        }
+
+        // Forget what has not been used for too long
+        let now = Unixtime::now().unwrap().0;
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+        for entry in GLOBALS.storage.read_all_media_cache_entries()? {
+            if now - entry.last_access > age.as_secs() as i64 {
+                GLOBALS
+                    .storage
+                    .delete_media_cache_entry(&entry.url, Some(&mut txn))?;
+            }
+        }
+        txn.commit()?;
+
+        count += self.remove_unreferenced_files().await?;
+
+        Ok(count)
+    }
+
+    /// Evict the least recently used files until the cache, including its
+    /// thumbnails and any files left from an older version of the cache, fits
+    /// within the `media_cache_max_mb` setting. Returns how many files were removed.
+    pub(crate) async fn evict(&self) -> Result<usize, Error> {
+        let max_bytes = GLOBALS.storage.read_setting_media_cache_max_mb() * 1024 * 1024;
+        let _cache_guard = self.cache_lock.lock().await;
+
+        // Group by content, which may be shared by several URLs
+        let mut contents: HashMap<String, (u64, i64)> = HashMap::new();
+        let mut urls_by_hash: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in GLOBALS.storage.read_all_media_cache_entries()? {
+            let content = contents
+                .entry(entry.hash.clone())
+                .or_insert((entry.size, 0));
+            content.1 = content.1.max(entry.last_access);
+            urls_by_hash.entry(entry.hash).or_default().push(entry.url);
+        }
+
+        // Thumbnails go with the file they were made from
+        for (hash, size) in self.thumbnail_sizes()? {
+            if let Some(content) = contents.get_mut(&hash) {
+                content.0 += size;
+            }
+        }
+
+        let mut total: u64 = contents.values().map(|(size, _)| *size).sum();
+        let mut count: usize = 0;
+
+        // Files left from an older version of the cache go first, oldest first
+        let mut legacy = self.legacy_files()?;
+        total += legacy.iter().map(|(_, size, _)| *size).sum::<u64>();
+        legacy.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in legacy.iter() {
+            if total <= max_bytes {
+                break;
+            }
+            tokio::fs::remove_file(path.as_path()).await?;
+            total = total.saturating_sub(*size);
+            count += 1;
+        }
+
+        if total <= max_bytes {
+            return Ok(count);
+        }
+
+        let mut lru: Vec<(String, u64, i64)> = contents
+            .drain()
+            .map(|(hash, (size, last_access))| (hash, size, last_access))
+            .collect();
+        lru.sort_by_key(|(_, _, last_access)| *last_access);
+
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+        for (hash, size, _) in lru.iter() {
+            if total <= max_bytes {
+                break;
+            }
+            for url in urls_by_hash.get(hash).into_iter().flatten() {
+                GLOBALS
+                    .storage
+                    .delete_media_cache_entry(url, Some(&mut txn))?;
+            }
+            total = total.saturating_sub(*size);
+        }
+        txn.commit()?;
+
+        count += self.remove_unreferenced_files().await?;
+        Ok(count)
+    }
+
+    // Remove content files and thumbnails that no cache entry refers to. The
+    // caller holds the cache lock.
+    async fn remove_unreferenced_files(&self) -> Result<usize, Error> {
+        let referenced: HashSet<String> = GLOBALS
+            .storage
+            .read_all_media_cache_entries()?
+            .drain(..)
+            .map(|e| e.hash)
+            .collect();
+
+        let mut count: usize = 0;
+        let cache_path = self.cache_dir.read().unwrap().to_owned();
+        for dir in [CONTENT_DIR, THUMBNAIL_DIR] {
+            let mut entries = tokio::fs::read_dir(cache_path.join(dir)).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Thumbnails are named "{hash}_{variant}"
+                let hash = name.split('_').next().unwrap_or_default();
+                if !referenced.contains(hash) {
                     tokio::fs::remove_file(entry.path().as_path()).await?;
                     count += 1;
                 }
@@ -599,4 +1073,30 @@ impl Fetcher {
 
         Ok(count)
     }
+
+    /// Statistics about the media cache
+    pub fn cache_stats(&self) -> Result<MediaCacheStats, Error> {
+        let mut stats = MediaCacheStats::default();
+        let mut hashes: HashSet<String> = HashSet::new();
+        for entry in GLOBALS.storage.read_all_media_cache_entries()? {
+            stats.urls += 1;
+            stats.url_bytes += entry.size;
+            if hashes.insert(entry.hash) {
+                stats.files += 1;
+                stats.bytes += entry.size;
+            }
+        }
+
+        for (_, size) in self.thumbnail_sizes()? {
+            stats.thumbnails += 1;
+            stats.thumbnail_bytes += size;
+        }
+
+        for (_, size, _) in self.legacy_files()? {
+            stats.legacy_files += 1;
+            stats.legacy_bytes += size;
+        }
+
+        Ok(stats)
+    }
 }
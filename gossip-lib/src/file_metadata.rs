@@ -0,0 +1,277 @@
+use image::{Rgba, RgbaImage};
+use nostr_types::{Event, EventKind, Tag};
+
+/// What we know about a file referenced by an event, from a NIP-92 `imeta` tag
+/// or a NIP-94 file metadata event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileMetadata {
+    /// The URL of the file
+    pub url: String,
+
+    /// The MIME type
+    pub mime: Option<String>,
+
+    /// Width and height in pixels
+    pub dim: Option<(u32, u32)>,
+
+    /// A blurhash to show while the file loads
+    pub blurhash: Option<String>,
+
+    /// The SHA-256 of the file, hex encoded
+    pub sha256: Option<String>,
+
+    /// Size in bytes
+    pub size: Option<u64>,
+
+    /// A description for those who cannot see the file
+    pub alt: Option<String>,
+
+    /// Other URLs the same file can be fetched from
+    pub fallbacks: Vec<String>,
+}
+
+impl FileMetadata {
+    /// Is this an image (per its MIME type)?
+    pub fn is_image(&self) -> bool {
+        self.mime.as_ref().is_some_and(|m| m.starts_with("image/"))
+    }
+
+    /// Is this a video (per its MIME type)?
+    pub fn is_video(&self) -> bool {
+        self.mime.as_ref().is_some_and(|m| m.starts_with("video/"))
+    }
+
+    // Set a field from a key and a value, as found in tags of both kinds
+    fn set(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        match key {
+            "url" => self.url = value.to_owned(),
+            "m" => self.mime = Some(value.to_lowercase()),
+            "dim" => self.dim = parse_dim(value),
+            "blurhash" => self.blurhash = Some(value.to_owned()),
+            "x" => self.sha256 = Some(value.to_lowercase()),
+            "size" => self.size = value.parse().ok(),
+            "alt" => self.alt = Some(value.to_owned()),
+            "fallback" => self.fallbacks.push(value.to_owned()),
+            _ => (),
+        }
+    }
+
+    /// Parse a NIP-92 `imeta` tag, which holds "key value" pairs
+    pub fn from_imeta_tag(tag: &Tag) -> Option<FileMetadata> {
+        if tag.tagname() != "imeta" {
+            return None;
+        }
+        let mut metadata = FileMetadata::default();
+        let mut index = 1;
+        while let Ok(field) = tag.get_index(index) {
+            if let Some((key, value)) = field.split_once(' ') {
+                metadata.set(key, value);
+            }
+            index += 1;
+        }
+        if metadata.url.is_empty() {
+            None
+        } else {
+            Some(metadata)
+        }
+    }
+
+    /// Parse a NIP-94 file metadata event (kind 1063)
+    pub fn from_nip94_event(event: &Event) -> Option<FileMetadata> {
+        if event.kind != EventKind::FileMetadata {
+            return None;
+        }
+        let mut metadata = FileMetadata::default();
+        for tag in &event.tags {
+            metadata.set(tag.tagname(), tag.value());
+        }
+        if metadata.url.is_empty() {
+            None
+        } else {
+            Some(metadata)
+        }
+    }
+
+    /// All the file metadata in an event
+    pub fn from_event(event: &Event) -> Vec<FileMetadata> {
+        let mut output: Vec<FileMetadata> = event
+            .tags
+            .iter()
+            .filter_map(FileMetadata::from_imeta_tag)
+            .collect();
+        if let Some(metadata) = FileMetadata::from_nip94_event(event) {
+            output.push(metadata);
+        }
+        output
+    }
+}
+
+pub(crate) fn parse_dim(dim: &str) -> Option<(u32, u32)> {
+    let (w, h) = dim.split_once('x')?;
+    let (w, h): (u32, u32) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+    if w == 0 || h == 0 {
+        None
+    } else {
+        Some((w, h))
+    }
+}
+
+const BASE83: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn decode83(s: &[u8]) -> Option<u32> {
+    let mut value: u32 = 0;
+    for c in s {
+        let digit = BASE83.iter().position(|b| b == c)? as u32;
+        value = value * 83 + digit;
+    }
+    Some(value)
+}
+
+fn srgb_to_linear(value: u32) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.003_130_8 {
+        (v * 12.92 * 255.0 + 0.5) as u8
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u8
+    }
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Decode a blurhash into a small image of the given size, for stretching over
+/// the space a file will occupy once it loads
+pub fn decode_blurhash(blurhash: &str, width: u32, height: u32) -> Option<RgbaImage> {
+    let bytes = blurhash.as_bytes();
+    if bytes.len() < 6 || width == 0 || height == 0 {
+        return None;
+    }
+
+    let size_flag = decode83(&bytes[0..1])?;
+    let num_x = (size_flag % 9 + 1) as usize;
+    let num_y = (size_flag / 9 + 1) as usize;
+    if bytes.len() != 4 + 2 * num_x * num_y {
+        return None;
+    }
+
+    let quantised_max = decode83(&bytes[1..2])?;
+    let max_value = (quantised_max + 1) as f32 / 166.0;
+
+    let mut colors: Vec<[f32; 3]> = Vec::with_capacity(num_x * num_y);
+    let dc = decode83(&bytes[2..6])?;
+    colors.push([
+        srgb_to_linear(dc >> 16),
+        srgb_to_linear((dc >> 8) & 255),
+        srgb_to_linear(dc & 255),
+    ]);
+    for i in 1..num_x * num_y {
+        let ac = decode83(&bytes[4 + i * 2..6 + i * 2])?;
+        let component = |q: u32| sign_pow((q as f32 - 9.0) / 9.0, 2.0) * max_value;
+        colors.push([
+            component(ac / (19 * 19)),
+            component((ac / 19) % 19),
+            component(ac % 19),
+        ]);
+    }
+
+    let mut image = RgbaImage::new(width, height);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let mut rgb = [0.0_f32; 3];
+        for j in 0..num_y {
+            for i in 0..num_x {
+                let basis = (std::f32::consts::PI * x as f32 * i as f32 / width as f32).cos()
+                    * (std::f32::consts::PI * y as f32 * j as f32 / height as f32).cos();
+                let color = colors[i + j * num_x];
+                rgb[0] += color[0] * basis;
+                rgb[1] += color[1] * basis;
+                rgb[2] += color[2] * basis;
+            }
+        }
+        *pixel = Rgba([
+            linear_to_srgb(rgb[0]),
+            linear_to_srgb(rgb[1]),
+            linear_to_srgb(rgb[2]),
+            255,
+        ]);
+    }
+
+    Some(image)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_dim() {
+        assert_eq!(parse_dim("640x480"), Some((640, 480)));
+        assert_eq!(parse_dim(" 640 x 480 "), Some((640, 480)));
+        assert_eq!(parse_dim("0x480"), None);
+        assert_eq!(parse_dim("640"), None);
+        assert_eq!(parse_dim("wide x tall"), None);
+        assert_eq!(parse_dim("640x-1"), None);
+    }
+
+    #[test]
+    fn test_from_imeta_tag() {
+        let tag = Tag::new(&[
+            "imeta",
+            "url https://example.com/a.jpg",
+            "m Image/JPEG",
+            "dim 640x480",
+            "blurhash LEHV6nWB2yk8pyo0adR*.7kCMdnj",
+            "x ABCDEF",
+            "alt A cat on a mat",
+            "fallback https://mirror.example.com/a.jpg",
+            "bogus",
+        ]);
+        let metadata = FileMetadata::from_imeta_tag(&tag).unwrap();
+        assert_eq!(metadata.url, "https://example.com/a.jpg");
+        assert!(metadata.is_image());
+        assert_eq!(metadata.dim, Some((640, 480)));
+        assert_eq!(
+            metadata.blurhash.as_deref(),
+            Some("LEHV6nWB2yk8pyo0adR*.7kCMdnj")
+        );
+        assert_eq!(metadata.sha256.as_deref(), Some("abcdef"));
+        assert_eq!(metadata.alt.as_deref(), Some("A cat on a mat"));
+        assert_eq!(
+            metadata.fallbacks,
+            vec!["https://mirror.example.com/a.jpg".to_owned()]
+        );
+
+        // No URL, no metadata
+        let tag = Tag::new(&["imeta", "dim 640x480"]);
+        assert!(FileMetadata::from_imeta_tag(&tag).is_none());
+    }
+
+    #[test]
+    fn test_decode_blurhash() {
+        let image = decode_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdnj", 32, 24).unwrap();
+        assert_eq!((image.width(), image.height()), (32, 24));
+
+        // A single component is a flat color
+        let image = decode_blurhash("00TI:j", 4, 4).unwrap();
+        assert!(image.pixels().all(|p| *p == Rgba([255, 0, 0, 255])));
+
+        // Wrong length, bad characters, no size
+        assert!(decode_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdn", 32, 32).is_none());
+        assert!(decode_blurhash("00TI'j", 4, 4).is_none());
+        assert!(decode_blurhash("00TI:j", 0, 4).is_none());
+    }
+}
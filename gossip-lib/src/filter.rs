@@ -1,7 +1,7 @@
 use crate::globals::GLOBALS;
 use crate::people::Person;
 use crate::profile::Profile;
-use nostr_types::{Event, EventKind, Id, Rumor};
+use nostr_types::{Event, EventKind, Id, PublicKey, Rumor};
 use rhai::{Engine, Scope, AST};
 use std::fs;
 
@@ -64,6 +64,7 @@ pub fn filter_rumor(rumor: Rumor, author: Option<Person>, id: Id) -> EventFilter
             None => false,
         },
     );
+    push_trust(&mut scope, rumor.pubkey);
 
     filter(scope, id)
 }
@@ -87,10 +88,21 @@ pub fn filter_event(event: Event, author: Option<Person>) -> EventFilterAction {
             None => false,
         },
     );
+    push_trust(&mut scope, event.pubkey);
 
     filter(scope, event.id)
 }
 
+fn push_trust(scope: &mut Scope, pubkey: PublicKey) {
+    let score = GLOBALS.wot.score(pubkey);
+    scope.push("followed_by", score.followed_by as i64);
+    scope.push("muted_by", score.muted_by as i64);
+    scope.push(
+        "impersonator",
+        GLOBALS.impersonation.impersonates_pubkey(&pubkey).is_some(),
+    );
+}
+
 fn filter(mut scope: Scope, id: Id) -> EventFilterAction {
     let ast = match &GLOBALS.filter {
         Some(ast) => ast,
@@ -0,0 +1,220 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use crate::people::PersonList;
+use crate::wot::GraphEdge;
+use nostr_types::{EventKind, PublicKey, Unixtime};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// How far back we look in our feeds for replies, zaps and hashtag posts
+const ACTIVITY_WINDOW_SECS: i64 = 60 * 60 * 24 * 14;
+
+// How many of our own hashtags count as our interests
+const MAX_INTEREST_HASHTAGS: usize = 20;
+
+// How many events per hashtag we look at
+const MAX_EVENTS_PER_HASHTAG: usize = 500;
+
+/// Why somebody was suggested
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuggestionReason {
+    /// Followed by this many people we follow
+    FollowedBy(usize),
+
+    /// Replied to this many times by people we follow
+    RepliedTo(usize),
+
+    /// Zapped this many times in our feeds
+    Zapped(usize),
+
+    /// Posts with these hashtags of ours
+    SharedHashtags(Vec<String>),
+}
+
+impl fmt::Display for SuggestionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestionReason::FollowedBy(1) => write!(f, "followed by 1 person you follow"),
+            SuggestionReason::FollowedBy(n) => write!(f, "followed by {} people you follow", n),
+            SuggestionReason::RepliedTo(1) => write!(f, "replied to once in your feed"),
+            SuggestionReason::RepliedTo(n) => write!(f, "replied to {} times in your feed", n),
+            SuggestionReason::Zapped(1) => write!(f, "zapped once in your feed"),
+            SuggestionReason::Zapped(n) => write!(f, "zapped {} times in your feed", n),
+            SuggestionReason::SharedHashtags(tags) => {
+                let tags: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+                write!(f, "also posts about {}", tags.join(", "))
+            }
+        }
+    }
+}
+
+impl SuggestionReason {
+    fn weight(&self) -> u64 {
+        match self {
+            SuggestionReason::FollowedBy(n) => 3 * *n as u64,
+            SuggestionReason::RepliedTo(n) => *n as u64,
+            SuggestionReason::Zapped(n) => 2 * *n as u64,
+            SuggestionReason::SharedHashtags(tags) => 2 * tags.len() as u64,
+        }
+    }
+}
+
+/// A person we might want to follow
+#[derive(Debug, Clone)]
+pub struct FollowSuggestion {
+    /// Who
+    pub pubkey: PublicKey,
+
+    /// Higher is a stronger suggestion
+    pub score: u64,
+
+    /// Why, strongest reason first
+    pub reasons: Vec<SuggestionReason>,
+}
+
+#[derive(Default)]
+struct Tally {
+    followed_by: usize,
+    replied_to: usize,
+    zapped: usize,
+    hashtags: Vec<String>,
+}
+
+/// Rank people we don't follow (and haven't muted) using the follow graph and
+/// the events in local storage. Returns at most `limit` suggestions, best first.
+pub fn compute_follow_suggestions(limit: usize) -> Result<Vec<FollowSuggestion>, Error> {
+    let my_pubkey = GLOBALS.identity.public_key();
+    let my_follows = GLOBALS.wot.my_follows();
+    let muted: HashSet<PublicKey> = GLOBALS
+        .storage
+        .get_people_in_list(PersonList::Muted)?
+        .drain(..)
+        .map(|(pk, _)| pk)
+        .collect();
+
+    let excluded = |pk: &PublicKey| -> bool {
+        Some(*pk) == my_pubkey || my_follows.contains(pk) || muted.contains(pk)
+    };
+
+    let mut tallies: HashMap<PublicKey, Tally> = HashMap::new();
+
+    // Followed by people we follow
+    for follow in &my_follows {
+        for target in GLOBALS
+            .storage
+            .get_graph_edges_out(GraphEdge::Follows, *follow)?
+        {
+            if !excluded(&target) {
+                tallies.entry(target).or_default().followed_by += 1;
+            }
+        }
+    }
+
+    let since = Unixtime(Unixtime::now().unwrap().0 - ACTIVITY_WINDOW_SECS);
+
+    // Replied to by people we follow
+    let follows: Vec<PublicKey> = my_follows.iter().copied().collect();
+    if !follows.is_empty() {
+        let replies = GLOBALS.storage.find_events(
+            &[EventKind::TextNote],
+            &follows,
+            Some(since),
+            |e| e.replies_to().is_some(),
+            false,
+        )?;
+        for reply in &replies {
+            let mut counted: HashSet<PublicKey> = HashSet::new();
+            for (pubkey, _, _) in reply.people() {
+                if !excluded(&pubkey) && counted.insert(pubkey) {
+                    tallies.entry(pubkey).or_default().replied_to += 1;
+                }
+            }
+        }
+    }
+
+    // Zapped in our feeds
+    let zaps = GLOBALS
+        .storage
+        .find_events(&[EventKind::Zap], &[], Some(since), |_| true, false)?;
+    for zap in &zaps {
+        if let Ok(Some(zapdata)) = zap.zaps() {
+            if !excluded(&zapdata.pubkey) {
+                tallies.entry(zapdata.pubkey).or_default().zapped += 1;
+            }
+        }
+    }
+
+    // Sharing our interest hashtags
+    if let Some(my_pubkey) = my_pubkey {
+        let mut my_hashtags: HashMap<String, usize> = HashMap::new();
+        for event in GLOBALS.storage.find_events(
+            &[EventKind::TextNote, EventKind::LongFormContent],
+            &[my_pubkey],
+            None,
+            |_| true,
+            false,
+        )? {
+            for hashtag in event.hashtags() {
+                *my_hashtags.entry(hashtag.to_lowercase()).or_default() += 1;
+            }
+        }
+        let mut my_hashtags: Vec<(String, usize)> = my_hashtags.drain().collect();
+        my_hashtags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        my_hashtags.truncate(MAX_INTEREST_HASHTAGS);
+
+        for (hashtag, _) in &my_hashtags {
+            let mut authors: HashSet<PublicKey> = HashSet::new();
+            for id in GLOBALS
+                .storage
+                .get_event_ids_with_hashtag(hashtag)?
+                .iter()
+                .rev()
+                .take(MAX_EVENTS_PER_HASHTAG)
+            {
+                if let Some(event) = GLOBALS.storage.read_event(*id)? {
+                    if event.created_at >= since && !excluded(&event.pubkey) {
+                        authors.insert(event.pubkey);
+                    }
+                }
+            }
+            for author in authors {
+                tallies
+                    .entry(author)
+                    .or_default()
+                    .hashtags
+                    .push(hashtag.clone());
+            }
+        }
+    }
+
+    let mut suggestions: Vec<FollowSuggestion> = tallies
+        .drain()
+        .map(|(pubkey, tally)| {
+            let mut reasons: Vec<SuggestionReason> = Vec::new();
+            if tally.followed_by > 0 {
+                reasons.push(SuggestionReason::FollowedBy(tally.followed_by));
+            }
+            if tally.replied_to > 0 {
+                reasons.push(SuggestionReason::RepliedTo(tally.replied_to));
+            }
+            if tally.zapped > 0 {
+                reasons.push(SuggestionReason::Zapped(tally.zapped));
+            }
+            if !tally.hashtags.is_empty() {
+                reasons.push(SuggestionReason::SharedHashtags(tally.hashtags));
+            }
+            reasons.sort_by_key(|r| std::cmp::Reverse(r.weight()));
+            let score = reasons.iter().map(|r| r.weight()).sum();
+            FollowSuggestion {
+                pubkey,
+                score,
+                reasons,
+            }
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| b.score.cmp(&a.score).then(a.pubkey.cmp(&b.pubkey)));
+    suggestions.truncate(limit);
+
+    Ok(suggestions)
+}
@@ -2,14 +2,20 @@ use crate::comms::{RelayJob, ToMinionMessage, ToOverlordMessage};
 use crate::delegation::Delegation;
 use crate::feed::Feed;
 use crate::fetcher::Fetcher;
+use crate::follow_suggestions::FollowSuggestion;
 use crate::gossip_identity::GossipIdentity;
+use crate::impersonation::ImpersonationDetector;
+use crate::link_preview::LinkPreviews;
 use crate::media::Media;
+use crate::nip05::Nip05Domain;
 use crate::nip46::ParsedCommand;
 use crate::people::{People, Person};
 use crate::relay::Relay;
 use crate::relay_picker_hooks::Hooks;
 use crate::status::StatusQueue;
 use crate::storage::Storage;
+use crate::upload::Upload;
+use crate::wot::WebOfTrust;
 use dashmap::{DashMap, DashSet};
 use gossip_relay_picker::RelayPicker;
 use nostr_types::{Event, Id, PayRequestData, Profile, PublicKey, RelayUrl, UncheckedUrl};
@@ -30,6 +36,68 @@ pub enum ZapState {
     ReadyToPay(Id, String), // String is the Zap Invoice as a string, to be shown as a QR code
 }
 
+/// Loading of the active account's own lists (bookmarks, pins, ...) from its
+/// relays. Until they have loaded, and been merged into ours, publishing ours
+/// could overwrite newer versions.
+#[derive(Debug, Default)]
+pub struct OwnListsLoad {
+    /// The account whose lists are loading, or have loaded
+    pub pubkey: Option<PublicKey>,
+
+    /// Relays asked for the lists that have not yet sent them all
+    pub waiting_on: HashSet<RelayUrl>,
+
+    /// Whether every relay asked has sent them, or will not
+    pub loaded: bool,
+
+    /// Messages to send to the overlord once they have loaded
+    pub(crate) deferred: Vec<ToOverlordMessage>,
+}
+
+impl OwnListsLoad {
+    /// Whether the lists of this account have loaded
+    pub fn loaded_for(&self, pubkey: Option<PublicKey>) -> bool {
+        self.loaded && pubkey.is_some() && self.pubkey == pubkey
+    }
+
+    // The lists of `pubkey` are being asked for from these relays. If they
+    // are another account's than before, we start over.
+    pub(crate) fn start(&mut self, pubkey: Option<PublicKey>, relays: &[RelayUrl]) {
+        if self.pubkey != pubkey {
+            *self = OwnListsLoad {
+                pubkey,
+                ..Default::default()
+            };
+        }
+        if !self.loaded {
+            self.waiting_on.extend(relays.iter().cloned());
+            self.check_loaded();
+        }
+    }
+
+    // A relay has sent all the lists of `pubkey` that it has, or will not send
+    // any more (None if it doesn't matter whose they were, as when it
+    // disconnects)
+    pub(crate) fn relay_done(&mut self, url: &RelayUrl, pubkey: Option<PublicKey>) {
+        if pubkey.is_some() && pubkey != self.pubkey {
+            return;
+        }
+        if self.waiting_on.remove(url) {
+            self.check_loaded();
+        }
+    }
+
+    fn check_loaded(&mut self) {
+        if self.loaded || self.pubkey.is_none() || !self.waiting_on.is_empty() {
+            return;
+        }
+        self.loaded = true;
+        for message in self.deferred.drain(..) {
+            let _ = GLOBALS.to_overlord.send(message);
+        }
+    }
+}
+
 /// Global data shared between threads. Access via the static ref `GLOBALS`.
 pub struct Globals {
     /// This is a broadcast channel. All Minions should listen on it.
@@ -92,6 +160,9 @@ pub struct Globals {
     /// Media loading
     pub media: Media,
 
+    /// Link previews
+    pub link_previews: LinkPreviews,
+
     /// Search results
     pub events_being_searched_for: PRwLock<Vec<Id>>, // being searched for
     //pub event_addrs_being_searched_for: PRwLock<Vec<EventAddr>>, // being searched for
@@ -146,6 +217,30 @@ pub struct Globals {
 
     // nip46 approval requests
     pub nip46_approval_requests: PRwLock<Vec<(PublicKey, ParsedCommand)>>,
+
+    /// Web of trust
+    pub wot: WebOfTrust,
+
+    /// Lookalike name detection
+    pub impersonation: ImpersonationDetector,
+
+    /// Follow suggestions, best first
+    pub follow_suggestions: PRwLock<Vec<FollowSuggestion>>,
+    pub follow_suggestions_computing: AtomicBool,
+
+    /// The last NIP-05 domain listing that was browsed
+    pub nip05_domain: PRwLock<Option<Nip05Domain>>,
+    pub nip05_domain_fetching: AtomicBool,
+
+    /// Media uploads, in progress or finished but not yet taken up by the UI
+    pub uploads: DashMap<u64, Upload>,
+
+    /// Whether our own lists have loaded from our relays, and what is waiting
+    /// for them to
+    pub own_lists: PRwLock<OwnListsLoad>,
+
+    /// Notified whenever a contact list or follow set event arrives
+    pub person_list_event_notify: Notify,
 }
 
 lazy_static! {
@@ -187,6 +282,7 @@ lazy_static! {
             open_subscriptions: AtomicUsize::new(0),
             delegation: Delegation::default(),
             media: Media::new(),
+            link_previews: LinkPreviews::new(),
             events_being_searched_for: PRwLock::new(Vec::new()),
             //event_addrs_being_searched_for: PRwLock::new(Vec::new()),
             people_search_results: PRwLock::new(Vec::new()),
@@ -208,6 +304,15 @@ lazy_static! {
             connect_requests: PRwLock::new(Vec::new()),
             auth_requests: PRwLock::new(Vec::new()),
             nip46_approval_requests: PRwLock::new(Vec::new()),
+            wot: WebOfTrust::new(),
+            impersonation: ImpersonationDetector::new(),
+            follow_suggestions: PRwLock::new(Vec::new()),
+            follow_suggestions_computing: AtomicBool::new(false),
+            nip05_domain: PRwLock::new(None),
+            nip05_domain_fetching: AtomicBool::new(false),
+            own_lists: PRwLock::new(OwnListsLoad::default()),
+            uploads: DashMap::new(),
+            person_list_event_notify: Notify::new(),
         }
     };
 }
@@ -9,6 +9,9 @@ use parking_lot::RwLock;
 use std::sync::mpsc::Sender;
 use tokio::task;
 
+/// An identity held in this profile other than the active one
+pub type Account = crate::storage::types::Account1;
+
 pub struct GossipIdentity {
     pub inner: RwLock<Identity>,
 }
@@ -98,6 +101,19 @@ impl GossipIdentity {
         Ok(())
     }
 
+    pub(crate) fn switch_to(
+        &self,
+        public_key: PublicKey,
+        epk: Option<EncryptedPrivateKey>,
+    ) -> Result<(), Error> {
+        *self.inner.write() = match epk {
+            Some(epk) => Identity::from_locked_parts(public_key, epk),
+            None => Identity::Public(public_key),
+        };
+        self.on_keychange()?;
+        Ok(())
+    }
+
     pub(crate) fn clear_public_key(&self) -> Result<(), Error> {
         *self.inner.write() = Identity::None;
         self.on_keychange()?;
@@ -129,6 +145,33 @@ impl GossipIdentity {
         Ok(())
     }
 
+    /// Import a NIP-49 encrypted private key as-is (keeping its log_n and key
+    /// security) and unlock it
+    pub fn import_encrypted_private_key(
+        &self,
+        epk: EncryptedPrivateKey,
+        pass: &str,
+    ) -> Result<(), Error> {
+        // Decrypt once to learn the public key and to verify the passphrase
+        let public_key = epk.decrypt(pass)?.public_key();
+        *self.inner.write() = Identity::from_locked_parts(public_key, epk);
+        self.on_keychange()?;
+        self.unlock(pass)?;
+        Ok(())
+    }
+
+    /// Set the private key from a NIP-06 mnemonic, keeping it encrypted under `pass`
+    pub fn set_private_key_from_mnemonic(
+        &self,
+        mnemonic: &str,
+        mnemonic_passphrase: &str,
+        pass: &str,
+    ) -> Result<(), Error> {
+        let pk = crate::nip06::private_key_from_mnemonic(mnemonic, mnemonic_passphrase, 0)?;
+        self.set_private_key(pk, pass)?;
+        Ok(())
+    }
+
     pub fn unlock(&self, pass: &str) -> Result<(), Error> {
         self.inner.write().unlock(pass)?;
 
@@ -203,6 +246,21 @@ impl GossipIdentity {
             .export_private_key_in_bech32(pass, log_n)?)
     }
 
+    /// Export the private key as a NIP-49 ncryptsec, encrypted under the same
+    /// passphrase with the given log_n. Key security is carried over.
+    pub fn export_private_key_ncryptsec(
+        &self,
+        pass: &str,
+        log_n: u8,
+    ) -> Result<EncryptedPrivateKey, Error> {
+        let epk = match self.encrypted_private_key() {
+            Some(epk) => epk,
+            None => return Err(ErrorKind::NoPrivateKey.into()),
+        };
+        let pk = epk.decrypt(pass)?;
+        Ok(pk.export_encrypted(pass, log_n)?)
+    }
+
     pub fn export_private_key_hex(&self, pass: &str) -> Result<(String, bool), Error> {
         let log_n = GLOBALS.storage.read_setting_log_n();
         Ok(self.inner.write().export_private_key_in_hex(pass, log_n)?)
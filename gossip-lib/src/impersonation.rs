@@ -0,0 +1,280 @@
+use crate::globals::GLOBALS;
+use crate::people::Person;
+use nostr_types::PublicKey;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+// How long the name index of the people we follow is trusted. Their names
+// change with their metadata, so it is rebuilt periodically.
+const INDEX_TTL: Duration = Duration::from_secs(30);
+
+// Shorter skeletons match too much to be meaningful
+const MIN_SKELETON_LEN: usize = 3;
+
+/// Reduce a name to a skeleton, so that names which look alike to a person
+/// have the same skeleton. This is in the spirit of the Unicode confusables
+/// skeleton (UTS #39) but also ignores case, spacing and punctuation, and only
+/// knows the most commonly abused homoglyphs.
+pub fn skeleton(name: &str) -> String {
+    // Fold case first, so that the confusables only need to be known in lowercase
+    let lowered = name.nfkc().collect::<String>().to_lowercase();
+
+    let mut folded = String::new();
+    for c in lowered.nfkd() {
+        if is_combining_mark(c) || !c.is_alphanumeric() {
+            continue;
+        }
+        folded.push(confusable(c).unwrap_or(c));
+    }
+    folded.replace("rn", "m").replace("vv", "w")
+}
+
+// Map a lowercase character to the Latin lowercase letter it is confusable with
+fn confusable(c: char) -> Option<char> {
+    Some(match c {
+        // Digits and letters that look like an 'l' (including an uppercase 'I')
+        // or an 'o'
+        '0' => 'o',
+        '1' | 'i' | 'ı' | 'ι' | 'і' | 'ӏ' => 'l',
+
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'һ' | 'н' => 'h',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' => 't',
+        'ԝ' => 'w',
+        'х' => 'x',
+        'у' | 'ү' => 'y',
+
+        // Greek (including the lowercase forms of uppercase lookalikes)
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'η' => 'h',
+        'κ' => 'k',
+        'μ' => 'm',
+        'ν' => 'v',
+        'ο' | 'σ' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        'γ' => 'y',
+        'ζ' => 'z',
+
+        // Latin lookalikes
+        'ɡ' => 'g',
+        'ɑ' => 'a',
+
+        _ => return None,
+    })
+}
+
+/// The names somebody presents themselves with: their name, display name, and
+/// the local part of their NIP-05 identifier
+pub fn claimed_names(person: &Person) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    if let Some(name) = person.name() {
+        names.push(name.to_owned());
+    }
+    if let Some(display_name) = person.display_name() {
+        names.push(display_name.to_owned());
+    }
+    if let Some(nip05) = person.nip05() {
+        if let Some((local, _)) = nip05.split_once('@') {
+            if local != "_" {
+                names.push(local.to_owned());
+            }
+        }
+    }
+    names
+}
+
+struct NameIndex {
+    built: Instant,
+    follows: HashSet<PublicKey>,
+
+    // skeleton -> the followed people that have it
+    names: HashMap<String, Vec<PublicKey>>,
+}
+
+/// Detects people whose names look like the names of people we follow.
+pub struct ImpersonationDetector {
+    index: RwLock<Option<NameIndex>>,
+}
+
+impl ImpersonationDetector {
+    pub(crate) fn new() -> ImpersonationDetector {
+        ImpersonationDetector {
+            index: RwLock::new(None),
+        }
+    }
+
+    /// Forget the name index. Call this when our Followed list changes.
+    pub fn invalidate(&self) {
+        *self.index.write() = None;
+    }
+
+    fn refresh_index(&self) {
+        if let Some(index) = &*self.index.read() {
+            if index.built.elapsed() < INDEX_TTL {
+                return;
+            }
+        }
+
+        let follows = GLOBALS.wot.my_follows();
+        let mut people: Vec<(PublicKey, Vec<String>)> = Vec::new();
+        for pubkey in follows.iter().copied() {
+            let person = match GLOBALS.storage.read_person(&pubkey) {
+                Ok(Some(person)) => person,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    continue;
+                }
+            };
+            let mut claimed = claimed_names(&person);
+            if let Some(petname) = &person.petname {
+                claimed.push(petname.to_owned());
+            }
+            people.push((pubkey, claimed));
+        }
+        let names = index_names(people);
+
+        *self.index.write() = Some(NameIndex {
+            built: Instant::now(),
+            follows,
+            names,
+        });
+    }
+
+    /// If this person (who we don't follow) has a name confusable with the name
+    /// of somebody we do follow, returns who that is.
+    pub fn impersonates(&self, person: &Person) -> Option<PublicKey> {
+        if Some(person.pubkey) == GLOBALS.identity.public_key() {
+            return None;
+        }
+
+        self.refresh_index();
+
+        let guard = self.index.read();
+        let index = match &*guard {
+            Some(index) => index,
+            None => return None,
+        };
+        if index.follows.contains(&person.pubkey) {
+            return None;
+        }
+
+        lookalike(&index.names, person.pubkey, &claimed_names(person))
+    }
+
+    /// Like [impersonates](Self::impersonates), looking the person up by key
+    pub fn impersonates_pubkey(&self, pubkey: &PublicKey) -> Option<PublicKey> {
+        match GLOBALS.storage.read_person(pubkey) {
+            Ok(Some(person)) => self.impersonates(&person),
+            _ => None,
+        }
+    }
+}
+
+// Index people by the skeletons of their names
+fn index_names(people: Vec<(PublicKey, Vec<String>)>) -> HashMap<String, Vec<PublicKey>> {
+    let mut names: HashMap<String, Vec<PublicKey>> = HashMap::new();
+    for (pubkey, claimed) in people {
+        for name in claimed {
+            let skeleton = skeleton(&name);
+            if skeleton.chars().count() >= MIN_SKELETON_LEN {
+                let pubkeys = names.entry(skeleton).or_default();
+                if !pubkeys.contains(&pubkey) {
+                    pubkeys.push(pubkey);
+                }
+            }
+        }
+    }
+    names
+}
+
+// Find somebody other than `pubkey` in the index with a name that looks like one
+// of the `claimed` names
+fn lookalike(
+    names: &HashMap<String, Vec<PublicKey>>,
+    pubkey: PublicKey,
+    claimed: &[String],
+) -> Option<PublicKey> {
+    for name in claimed {
+        if let Some(pubkeys) = names.get(&skeleton(name)) {
+            if let Some(other) = pubkeys.iter().find(|pk| **pk != pubkey) {
+                return Some(*other);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nostr_types::PrivateKey;
+
+    #[test]
+    fn test_skeleton_case() {
+        // Case is folded before mapping confusables
+        assert_eq!(skeleton("Ian"), skeleton("ian"));
+        assert_eq!(skeleton("IAN"), skeleton("ian"));
+
+        // An uppercase 'I' still looks like an 'l'
+        assert_eq!(skeleton("Ian"), skeleton("lan"));
+    }
+
+    #[test]
+    fn test_skeleton_cyrillic() {
+        // Cyrillic 'а' and 'А' vs Latin 'a'
+        assert_eq!(skeleton("\u{0430}lice"), skeleton("alice"));
+        assert_eq!(skeleton("\u{0410}lice"), skeleton("Alice"));
+        assert_eq!(
+            skeleton("J\u{0430}ck D\u{043E}rsey"),
+            skeleton("jack dorsey")
+        );
+        assert_ne!(skeleton("alice"), skeleton("bob"));
+    }
+
+    #[test]
+    fn test_lookalike() {
+        let alice = PrivateKey::generate().public_key();
+        let also_alice = PrivateKey::generate().public_key();
+        let impostor = PrivateKey::generate().public_key();
+
+        let names = index_names(vec![
+            (alice, vec!["Alice".to_owned(), "alice".to_owned()]),
+            (also_alice, vec!["ALICE".to_owned()]),
+        ]);
+        assert_eq!(names.get(&skeleton("alice")).map(|v| v.len()), Some(2));
+
+        // A followed person does not flag themselves
+        let found = lookalike(&names, alice, &["Alice".to_owned()]);
+        assert_eq!(found, Some(also_alice));
+        let names = index_names(vec![(alice, vec!["Alice".to_owned()])]);
+        assert_eq!(lookalike(&names, alice, &["Alice".to_owned()]), None);
+
+        // Somebody else with a lookalike name is flagged
+        assert_eq!(
+            lookalike(&names, impostor, &["\u{0410}lice".to_owned()]),
+            Some(alice)
+        );
+        assert_eq!(lookalike(&names, impostor, &["Bob".to_owned()]), None);
+    }
+}
@@ -66,6 +66,9 @@
 mod about;
 pub use about::About;
 
+/// NIP-51 bookmark lists and bookmark sets
+pub mod bookmarks;
+
 /// Defines messages sent to the overlord
 pub mod comms;
 
@@ -78,6 +81,9 @@ pub use dm_channel::{DmChannel, DmChannelData};
 // direct quick-temporary communication with relays, without overlord/minion involvement
 pub mod direct;
 
+/// Drafts of notes and direct messages, saved per account
+pub mod drafts;
+
 mod error;
 pub use error::{Error, ErrorKind};
 
@@ -85,18 +91,33 @@ mod feed;
 pub use feed::{Feed, FeedKind};
 
 mod fetcher;
-pub use fetcher::Fetcher;
+pub use fetcher::{FetchProgress, Fetcher, MediaCacheEntry, MediaCacheStats};
+
+/// NIP-92 and NIP-94 file metadata
+pub mod file_metadata;
 
 mod filter;
 
+/// Suggestions of people to follow
+pub mod follow_suggestions;
+
 mod globals;
 pub use globals::{Globals, ZapState, GLOBALS};
 
 mod gossip_identity;
-pub use gossip_identity::GossipIdentity;
+pub use gossip_identity::{Account, GossipIdentity};
+
+/// Detecting lookalike names
+pub mod impersonation;
+
+/// OpenGraph link previews
+pub mod link_preview;
 
 mod media;
-pub use media::Media;
+pub use media::{ImageOptions, Media};
+
+/// Per-person history of metadata versions
+pub mod metadata_history;
 
 /// Rendering various names of users
 pub mod names;
@@ -104,6 +125,9 @@ pub mod names;
 /// nip05 handling
 pub mod nip05;
 
+/// NIP-06 keys from mnemonic seed phrases
+pub mod nip06;
+
 #[allow(dead_code)]
 pub mod nip46;
 pub use nip46::{Nip46Server, Nip46UnconnectedServer};
@@ -112,11 +136,22 @@ mod overlord;
 pub use overlord::Overlord;
 
 mod people;
-pub use people::{People, Person, PersonList, PersonListMetadata};
+pub use people::{
+    merge_person_lists, Mute, People, Person, PersonList, PersonListConflicts, PersonListMetadata,
+};
+
+/// Import and export of person lists
+pub mod person_list_io;
+
+/// Statistics about people, from stored events and the follow graph
+pub mod person_stats;
 
 mod person_relay;
 pub use person_relay::PersonRelay;
 
+/// Notes pinned to profiles (NIP-51 pin lists)
+pub mod pins;
+
 /// Processing incoming events
 pub mod process;
 
@@ -130,6 +165,9 @@ pub use relay::Relay;
 mod relay_picker_hooks;
 pub use relay_picker_hooks::Hooks;
 
+/// The outbox of posts scheduled to be posted later
+pub mod scheduled;
+
 mod status;
 pub use status::StatusQueue;
 
@@ -139,6 +177,12 @@ pub use storage::Storage;
 
 mod tags;
 
+/// Uploading media to NIP-96 and Blossom servers
+pub mod upload;
+
+mod wot;
+pub use wot::{GraphEdge, TrustScore, WebOfTrust};
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -146,6 +190,15 @@ extern crate lazy_static;
 /// when connecting to relays
 pub static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
+// An id for something we keep locally (drafts, scheduled posts): microseconds
+// since the epoch, so ids sort by creation
+pub(crate) fn new_local_id() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_else(|_| rand::random::<u64>())
+}
+
 use std::ops::DerefMut;
 
 /// Initialize gossip-lib
@@ -0,0 +1,315 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use dashmap::{DashMap, DashSet};
+use nostr_types::Url;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+// Only this much of a page is searched for its metadata, which is in the head
+const MAX_HTML_LEN: usize = 256 * 1024;
+
+// Links to anything larger are not downloaded, as they are unlikely to be pages
+const MAX_PAGE_LEN: u64 = 4 * 1024 * 1024;
+
+// Previews held before expired ones are dropped, and then the oldest
+const MAX_PREVIEWS: usize = 2000;
+
+// Longer descriptions are cut short
+const MAX_DESCRIPTION_LEN: usize = 300;
+
+/// A preview of a web page, from its OpenGraph or Twitter card metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkPreview {
+    /// The page's URL
+    pub url: String,
+
+    /// The title
+    pub title: Option<String>,
+
+    /// A short description
+    pub description: Option<String>,
+
+    /// The name of the site
+    pub site_name: Option<String>,
+
+    /// An image representing the page
+    pub image: Option<Url>,
+}
+
+impl LinkPreview {
+    /// Does the page have anything worth showing?
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.image.is_none()
+    }
+}
+
+lazy_static! {
+    static ref META_RE: Regex = Regex::new(r"(?is)<meta\s([^>]*)>").unwrap();
+    static ref ATTR_RE: Regex =
+        Regex::new(r#"(?is)([a-z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    static ref TITLE_RE: Regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+}
+
+/// Parse a preview from a page's HTML. OpenGraph tags are preferred over
+/// Twitter card tags, which are preferred over the plain title and description.
+pub fn parse_link_preview(url: &str, html: &str) -> LinkPreview {
+    let mut properties: HashMap<String, String> = HashMap::new();
+    for meta in META_RE.captures_iter(html) {
+        let mut key: Option<String> = None;
+        let mut content: Option<String> = None;
+        for attr in ATTR_RE.captures_iter(&meta[1]) {
+            let value = attr
+                .get(2)
+                .or(attr.get(3))
+                .map(|m| m.as_str())
+                .unwrap_or_default();
+            match attr[1].to_lowercase().as_str() {
+                "property" | "name" => key = Some(value.to_lowercase()),
+                "content" => content = Some(decode_entities(value.trim())),
+                _ => (),
+            }
+        }
+        if let (Some(key), Some(content)) = (key, content) {
+            if !content.is_empty() {
+                properties.entry(key).or_insert(content);
+            }
+        }
+    }
+
+    let get =
+        |keys: &[&str]| -> Option<String> { keys.iter().find_map(|k| properties.get(*k).cloned()) };
+
+    let title = get(&["og:title", "twitter:title"]).or_else(|| {
+        TITLE_RE
+            .captures(html)
+            .map(|c| decode_entities(c[1].trim()))
+            .filter(|t| !t.is_empty())
+    });
+
+    let description = get(&["og:description", "twitter:description", "description"]).map(|d| {
+        if d.chars().count() > MAX_DESCRIPTION_LEN {
+            let mut short: String = d.chars().take(MAX_DESCRIPTION_LEN).collect();
+            short.push('…');
+            short
+        } else {
+            d
+        }
+    });
+
+    // Image URLs may be relative to the page
+    let image = get(&[
+        "og:image",
+        "og:image:url",
+        "twitter:image",
+        "twitter:image:src",
+    ])
+    .and_then(|i| url::Url::parse(url).ok()?.join(&i).ok())
+    .and_then(|i| Url::try_from_str(i.as_str()).ok());
+
+    LinkPreview {
+        url: url.to_owned(),
+        title,
+        description,
+        site_name: get(&["og:site_name"]),
+        image,
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+// Domains from a setting, separated by spaces, commas or newlines
+fn domain_list(setting: &str) -> Vec<String> {
+    setting
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|d| !d.is_empty())
+        .map(|d| d.trim_start_matches('.').to_lowercase())
+        .collect()
+}
+
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+// The settings that are checked for every link shown, read once
+#[derive(Debug, Clone)]
+struct PreviewSettings {
+    enabled: bool,
+    allowed: Vec<String>,
+    denied: Vec<String>,
+    max_age: Duration,
+}
+
+impl PreviewSettings {
+    fn load() -> PreviewSettings {
+        PreviewSettings {
+            enabled: GLOBALS.storage.read_setting_link_previews()
+                && GLOBALS.storage.read_setting_load_media(),
+            allowed: domain_list(&GLOBALS.storage.read_setting_link_preview_allowed_domains()),
+            denied: domain_list(&GLOBALS.storage.read_setting_link_preview_denied_domains()),
+            max_age: Duration::from_secs(
+                60 * 60
+                    * GLOBALS
+                        .storage
+                        .read_setting_link_preview_becomes_stale_hours(),
+            ),
+        }
+    }
+
+    // Denied domains win over allowed ones, and if any domains are allowed,
+    // only they are
+    fn domain_allowed(&self, url: &Url) -> bool {
+        let host = match url::Url::parse(url.as_str())
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        {
+            Some(host) => host,
+            None => return false,
+        };
+
+        if self.denied.iter().any(|d| host_matches(&host, d)) {
+            return false;
+        }
+
+        self.allowed.is_empty() || self.allowed.iter().any(|d| host_matches(&host, d))
+    }
+}
+
+/// Whether the settings allow previews of links to this URL. Denied domains
+/// win over allowed ones, and if any domains are allowed, only they are.
+pub fn preview_allowed(url: &Url) -> bool {
+    let settings = GLOBALS.link_previews.settings();
+    settings.enabled && settings.domain_allowed(url)
+}
+
+/// System that fetches and holds link previews
+#[derive(Debug, Default)]
+pub struct LinkPreviews {
+    // None if the page has no preview, or could not be fetched, with when we
+    // found out
+    previews: DashMap<Url, (Instant, Option<LinkPreview>)>,
+    pending: DashSet<Url>,
+    settings: RwLock<Option<PreviewSettings>>,
+}
+
+impl LinkPreviews {
+    pub(crate) fn new() -> LinkPreviews {
+        LinkPreviews::default()
+    }
+
+    fn settings(&self) -> PreviewSettings {
+        if let Some(settings) = &*self.settings.read().unwrap() {
+            return settings.clone();
+        }
+        let settings = PreviewSettings::load();
+        *self.settings.write().unwrap() = Some(settings.clone());
+        settings
+    }
+
+    /// Forget the cached settings. Call this after saving settings.
+    pub fn reload_settings(&self) {
+        *self.settings.write().unwrap() = None;
+    }
+
+    /// Get the preview of a link, if the settings allow it
+    ///
+    /// This returns immediately, usually with None if never called on that Url before.
+    /// Call it again later to try to pick up the result. Pages are kept in the
+    /// media cache, so their previews survive a restart, and are fetched again
+    /// once they are older than the `link_preview_becomes_stale_hours` setting.
+    pub fn get(&self, url: &Url) -> Option<LinkPreview> {
+        let settings = self.settings();
+        if !settings.enabled || !settings.domain_allowed(url) {
+            return None;
+        }
+
+        if let Some(entry) = self.previews.get(url) {
+            let (fetched, preview) = &*entry;
+            if fetched.elapsed() < settings.max_age {
+                return preview.clone();
+            }
+        }
+
+        if self.pending.insert(url.clone()) {
+            let aurl = url.to_owned();
+            tokio::spawn(async move {
+                let preview = GLOBALS.link_previews.fetch(&aurl).await;
+                GLOBALS.link_previews.insert(aurl.clone(), preview);
+                GLOBALS.link_previews.pending.remove(&aurl);
+            });
+        }
+
+        None
+    }
+
+    fn insert(&self, url: Url, preview: Option<LinkPreview>) {
+        self.previews.insert(url, (Instant::now(), preview));
+        if self.previews.len() <= MAX_PREVIEWS {
+            return;
+        }
+
+        let max_age = self.settings().max_age;
+        self.previews
+            .retain(|_, (fetched, _)| fetched.elapsed() < max_age);
+        if self.previews.len() > MAX_PREVIEWS {
+            // Drop the older half
+            let mut times: Vec<Instant> = self.previews.iter().map(|e| e.value().0).collect();
+            times.sort();
+            let cutoff = times[times.len() / 2];
+            self.previews.retain(|_, (fetched, _)| *fetched >= cutoff);
+        }
+    }
+
+    /// Fetch and parse the preview of a link. This ignores the settings, except
+    /// that an image on a domain they do not allow is left out.
+    pub async fn fetch(&self, url: &Url) -> Option<LinkPreview> {
+        let html = match self.fetch_html(url).await {
+            Ok(Some(html)) => html,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::info!("No preview for {}: {}", url, e);
+                return None;
+            }
+        };
+        let mut preview = parse_link_preview(url.as_str(), &html);
+        let settings = self.settings();
+        preview.image = preview.image.filter(|i| settings.domain_allowed(i));
+        if preview.is_empty() {
+            None
+        } else {
+            Some(preview)
+        }
+    }
+
+    // The start of a page, or None if it is not HTML. Pages are fetched
+    // through the fetcher, which caches them.
+    async fn fetch_html(&self, url: &Url) -> Result<Option<String>, Error> {
+        let max_age = self.settings().max_age;
+        let bytes = GLOBALS
+            .fetcher
+            .fetch_limited(url, max_age, MAX_PAGE_LEN)
+            .await?;
+        let start = &bytes[..bytes.len().min(MAX_HTML_LEN)];
+        if !looks_like_html(start) {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(start).into_owned()))
+    }
+}
+
+// The fetcher does not keep the content type, so look for the tags that start
+// an HTML document instead
+fn looks_like_html(bytes: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_lowercase();
+    start.contains("<!doctype html") || start.contains("<html") || start.contains("<head")
+}
@@ -1,16 +1,45 @@
 use crate::error::{Error, ErrorKind};
+use crate::file_metadata::FileMetadata;
 use crate::globals::GLOBALS;
+use bytes::Bytes;
 use dashmap::{DashMap, DashSet};
 use image::imageops;
 use image::imageops::FilterType;
 use image::{DynamicImage, Rgba, RgbaImage};
 use nostr_types::{UncheckedUrl, Url};
+use sha2::Digest;
 use std::collections::HashSet;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use usvg::TreeParsing;
 
+/// How [Media::fetch_image] should shape an image
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageOptions {
+    /// Crop to a square
+    pub square: bool,
+
+    /// Resize to the requested size even if it is not too large
+    pub force_resize: bool,
+
+    /// Make it round (for avatars)
+    pub round: bool,
+}
+
+impl ImageOptions {
+    // Names the thumbnail made with these options at this size
+    pub(crate) fn thumbnail_variant(&self, size: u32) -> String {
+        format!(
+            "{}{}{}{}",
+            size,
+            if self.square { "s" } else { "" },
+            if self.force_resize { "f" } else { "" },
+            if self.round { "r" } else { "" }
+        )
+    }
+}
+
 /// System that processes media fetched from the internet
 pub struct Media {
     // We fetch (with Fetcher), process, and temporarily hold media
@@ -20,6 +49,7 @@ pub struct Media {
     image_temp: DashMap<Url, RgbaImage>,
     data_temp: DashMap<Url, Vec<u8>>,
     media_pending_processing: DashSet<Url>,
+    data_pending: DashSet<Url>,
     failed_media: RwLock<HashSet<UncheckedUrl>>,
 }
 
@@ -35,6 +65,7 @@ impl Media {
             image_temp: DashMap::new(),
             data_temp: DashMap::new(),
             media_pending_processing: DashSet::new(),
+            data_pending: DashSet::new(),
             failed_media: RwLock::new(HashSet::new()),
         }
     }
@@ -66,10 +97,12 @@ impl Media {
     /// Get an image by Url
     ///
     /// This returns immediately, usually with None if never called on that Url before.
-    /// Call it again later to try to pick up the result.
+    /// Call it again later to try to pick up the result. Async clients should use
+    /// [fetch_image](Self::fetch_image) instead.
     ///
-    /// FIXME: this API doesn't serve async clients well.
-    pub fn get_image(&self, url: &Url) -> Option<RgbaImage> {
+    /// `metadata` is what the note being rendered says about the file, if anything
+    /// (see [fetch_data](Self::fetch_data)). It is only used when the fetch starts.
+    pub fn get_image(&self, url: &Url, metadata: Option<&FileMetadata>) -> Option<RgbaImage> {
         // If we have it, hand it over (we won't need a copy anymore)
         if let Some(th) = self.image_temp.remove(url) {
             return Some(th.1);
@@ -80,50 +113,53 @@ impl Media {
             return None; // will recover after processing completes
         }
 
-        match self.get_data(url) {
-            Some(bytes) => {
-                // Finish this later (spawn)
-                let aurl = url.to_owned();
-                tokio::spawn(async move {
-                    let size = 800 * 3 // 3x feed size, 1x Media page size
-                        * GLOBALS
-                            .pixels_per_point_times_100
-                            .load(Ordering::Relaxed)
-                        / 100;
-
-                    match load_image_bytes(
-                        &bytes, false, // don't crop square
-                        size,  // default size,
-                        false, // don't force that size
-                        false, // don't round
-                    ) {
-                        Ok(color_image) => {
-                            GLOBALS.media.image_temp.insert(aurl, color_image);
-                        }
-                        Err(_) => {
-                            GLOBALS
-                                .media
-                                .failed_media
-                                .write()
-                                .await
-                                .insert(aurl.to_unchecked_url());
-                        }
-                    }
-                });
-                self.media_pending_processing.insert(url.clone());
-                None
-            }
-            None => None,
+        // If it failed before, or we may not fetch it, do not start
+        if self.has_failed(&url.to_unchecked_url()) || !GLOBALS.storage.read_setting_load_media() {
+            return None;
         }
+
+        // Finish this later (spawn)
+        self.media_pending_processing.insert(url.clone());
+        let aurl = url.to_owned();
+        let metadata = metadata.cloned();
+        tokio::spawn(async move {
+            let size = 800 * 3 // 3x feed size, 1x Media page size
+                * GLOBALS
+                    .pixels_per_point_times_100
+                    .load(Ordering::Relaxed)
+                / 100;
+
+            match GLOBALS
+                .media
+                .fetch_image(&aurl, metadata.as_ref(), size, ImageOptions::default())
+                .await
+            {
+                Ok(image) => {
+                    GLOBALS.media.image_temp.insert(aurl, image);
+                }
+                Err(e) => {
+                    tracing::info!("{}", e);
+                    GLOBALS.media.media_pending_processing.remove(&aurl);
+                    GLOBALS
+                        .media
+                        .failed_media
+                        .write()
+                        .await
+                        .insert(aurl.to_unchecked_url());
+                }
+            }
+        });
+        None
     }
 
     /// Get data by Url
     ///
     /// This returns immediately, usually with None if never called on that Url before.
-    /// Call it again later to try to pick up the result.
+    /// Call it again later to try to pick up the result. Async clients should use
+    /// [fetch_data](Self::fetch_data) instead.
     ///
-    /// FIXME: this API doesn't serve async clients well.
-    pub fn get_data(&self, url: &Url) -> Option<Vec<u8>> {
+    /// `metadata` is as for [get_image](Self::get_image).
+    pub fn get_data(&self, url: &Url, metadata: Option<&FileMetadata>) -> Option<Vec<u8>> {
         // If it failed before, error out now
         if self
             .failed_media
@@ -143,24 +179,115 @@ impl Media {
             return None; // can recover if the setting is switched
         }
 
-        match GLOBALS.fetcher.try_get(
-            url,
-            Duration::from_secs(60 * 60 * GLOBALS.storage.read_setting_media_becomes_stale_hours()),
-        ) {
-            Ok(None) => None,
-            Ok(Some(bytes)) => {
-                self.data_temp.insert(url.clone(), bytes);
-                None
+        // Fetch in the background, unless we already are
+        if self.data_pending.insert(url.clone()) {
+            let aurl = url.to_owned();
+            let metadata = metadata.cloned();
+            tokio::spawn(async move {
+                match GLOBALS.media.fetch_data(&aurl, metadata.as_ref()).await {
+                    Ok(bytes) => {
+                        GLOBALS.media.data_temp.insert(aurl.clone(), bytes.to_vec());
+                    }
+                    Err(e) => {
+                        tracing::error!("{}", e);
+                        // this cannot recover without new metadata
+                        GLOBALS
+                            .media
+                            .failed_media
+                            .write()
+                            .await
+                            .insert(aurl.to_unchecked_url());
+                    }
+                }
+                GLOBALS.media.data_pending.remove(&aurl);
+            });
+        }
+
+        None
+    }
+
+    /// Fetch the bytes of some media. If the note referencing it described the
+    /// file in `metadata`, the bytes are checked against its hash. Only then are its
+    /// fallback URLs tried in turn, as what they serve can be verified.
+    ///
+    /// Unlike [get_data](Self::get_data), this does not check the `load_media` setting.
+    pub async fn fetch_data(
+        &self,
+        url: &Url,
+        metadata: Option<&FileMetadata>,
+    ) -> Result<Bytes, Error> {
+        let max_age =
+            Duration::from_secs(60 * 60 * GLOBALS.storage.read_setting_media_becomes_stale_hours());
+        let expected = metadata.and_then(|m| m.sha256.clone());
+
+        let mut sources: Vec<Url> = vec![url.clone()];
+        if expected.is_some() {
+            if let Some(m) = metadata {
+                sources.extend(m.fallbacks.iter().filter_map(|f| Url::try_from_str(f).ok()));
+            }
+        }
+
+        let mut last_error: Option<Error> = None;
+        for source in sources.iter() {
+            if let Some(e) = last_error.take() {
+                tracing::info!("{}", e);
+                tracing::info!("Fetching {} from fallback {}", url, source);
             }
-            Err(e) => {
-                tracing::error!("{}", e);
-                // this cannot recover without new metadata
-                self.failed_media
-                    .blocking_write()
-                    .insert(url.to_unchecked_url());
-                None
+            match GLOBALS.fetcher.fetch_media(source, max_age).await {
+                Ok(bytes) => match &expected {
+                    Some(x) if hex::encode(sha2::Sha256::digest(&bytes)) != *x => {
+                        last_error = Some(
+                            ErrorKind::General(format!(
+                                "{} does not match the hash given for {}",
+                                source, url
+                            ))
+                            .into(),
+                        );
+                    }
+                    _ => return Ok(bytes),
+                },
+                Err(e) => last_error = Some(e),
             }
         }
+
+        Err(last_error
+            .unwrap_or_else(|| ErrorKind::General(format!("Could not fetch {}", url)).into()))
+    }
+
+    /// Fetch and decode an image, scaled to fit within `size` if it is too large
+    /// (or always, if `opts.force_resize`). `metadata` is as for
+    /// [fetch_data](Self::fetch_data).
+    ///
+    /// Unlike [get_image](Self::get_image), this does not check the `load_media` setting.
+    pub async fn fetch_image(
+        &self,
+        url: &Url,
+        metadata: Option<&FileMetadata>,
+        size: u32,
+        opts: ImageOptions,
+    ) -> Result<RgbaImage, Error> {
+        let bytes = self.fetch_data(url, metadata).await?;
+
+        // Images resized to a fixed size are small enough to keep decoded
+        let variant = opts.thumbnail_variant(size);
+        if opts.force_resize {
+            if let Some(image) = GLOBALS.fetcher.read_thumbnail(url, &variant) {
+                return Ok(image);
+            }
+        }
+
+        let image = tokio::task::spawn_blocking(move || {
+            load_image_bytes(&bytes, opts.square, size, opts.force_resize, opts.round)
+        })
+        .await??;
+
+        if opts.force_resize {
+            if let Err(e) = GLOBALS.fetcher.write_thumbnail(url, &variant, &image) {
+                tracing::warn!("{}", e);
+            }
+        }
+
+        Ok(image)
     }
 }
 
@@ -216,7 +343,7 @@ pub(crate) fn load_image_bytes(
     }
 }
 
-fn adjust_orientation(image_bytes: &[u8], image: DynamicImage) -> DynamicImage {
+pub(crate) fn adjust_orientation(image_bytes: &[u8], image: DynamicImage) -> DynamicImage {
     match get_orientation(image_bytes) {
         1 => image,
         2 => DynamicImage::ImageRgba8(imageops::flip_horizontal(&image)),
@@ -236,7 +363,7 @@ fn adjust_orientation(image_bytes: &[u8], image: DynamicImage) -> DynamicImage {
     }
 }
 
-fn get_orientation(image_bytes: &[u8]) -> u32 {
+pub(crate) fn get_orientation(image_bytes: &[u8]) -> u32 {
     let mut cursor = std::io::Cursor::new(image_bytes);
     let exifreader = exif::Reader::new();
     let exif = match exifreader.read_from_container(&mut cursor) {
@@ -0,0 +1,146 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use crate::people::PersonList;
+use crate::storage::types::MetadataVersion1;
+use nostr_types::{Metadata, PublicKey, Unixtime};
+use std::fmt;
+
+/// A version of somebody's metadata, aliased to the latest version
+pub type MetadataVersion = MetadataVersion1;
+
+// Profile changes younger than this are flagged on notes
+const RECENT_CHANGE_SECS: i64 = 60 * 60 * 24 * 7;
+
+/// The metadata fields whose changes we track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    Name,
+    DisplayName,
+    Picture,
+    Nip05,
+    Lud16,
+}
+
+impl MetadataField {
+    /// All of the tracked fields
+    pub const ALL: [MetadataField; 5] = [
+        MetadataField::Name,
+        MetadataField::DisplayName,
+        MetadataField::Picture,
+        MetadataField::Nip05,
+        MetadataField::Lud16,
+    ];
+
+    /// The value of this field in a version of the metadata, if set
+    pub fn value(&self, version: &MetadataVersion) -> Option<String> {
+        match *self {
+            MetadataField::Name => version.name.clone(),
+            MetadataField::DisplayName => version.display_name.clone(),
+            MetadataField::Picture => version.picture.clone(),
+            MetadataField::Nip05 => version.nip05.clone(),
+            MetadataField::Lud16 => version.lud16.clone(),
+        }
+    }
+
+    /// Whether this field identifies the person, so that changing it is worth
+    /// flagging on their notes
+    pub fn is_identity(&self) -> bool {
+        !matches!(*self, MetadataField::Picture)
+    }
+}
+
+impl fmt::Display for MetadataField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MetadataField::Name => write!(f, "name"),
+            MetadataField::DisplayName => write!(f, "display name"),
+            MetadataField::Picture => write!(f, "picture"),
+            MetadataField::Nip05 => write!(f, "nip05"),
+            MetadataField::Lud16 => write!(f, "lud16"),
+        }
+    }
+}
+
+/// A change to one tracked metadata field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    /// The field that changed
+    pub field: MetadataField,
+
+    /// What it was
+    pub old: Option<String>,
+
+    /// What it became
+    pub new: Option<String>,
+}
+
+/// The tracked fields that differ between two versions of metadata
+pub fn diff_metadata(old: &MetadataVersion, new: &MetadataVersion) -> Vec<MetadataChange> {
+    let mut changes: Vec<MetadataChange> = Vec::new();
+    for field in MetadataField::ALL {
+        let old = field.value(old);
+        let new = field.value(new);
+        if old != new {
+            changes.push(MetadataChange { field, old, new });
+        }
+    }
+    changes
+}
+
+/// Add a metadata version (fresh or not) to the person's history, if they are
+/// followed or we are viewing them
+pub(crate) async fn record_metadata_version(
+    pubkey: &PublicKey,
+    metadata: &Metadata,
+    created_at: Unixtime,
+) -> Result<(), Error> {
+    if GLOBALS.people.get_active_person_async().await != Some(*pubkey)
+        && !GLOBALS
+            .storage
+            .is_person_in_list(pubkey, PersonList::Followed)?
+    {
+        return Ok(());
+    }
+
+    // Skip versions where none of the tracked fields changed
+    let version = MetadataVersion::new(created_at.0, metadata);
+    if let Some(last) = GLOBALS.storage.read_metadata_history(*pubkey)?.last() {
+        if last.created_at <= version.created_at
+            && *last
+                == (MetadataVersion {
+                    created_at: last.created_at,
+                    ..version.clone()
+                })
+        {
+            return Ok(());
+        }
+    }
+
+    let max = GLOBALS.storage.read_setting_max_metadata_versions() as usize;
+    GLOBALS
+        .storage
+        .add_metadata_version(*pubkey, version, max.max(1), None)?;
+    Ok(())
+}
+
+/// The latest change to the identifying fields of somebody's profile (anything
+/// but their picture), if it was made within the last week
+pub fn recent_profile_change(
+    pubkey: &PublicKey,
+) -> Result<Option<(Unixtime, Vec<MetadataChange>)>, Error> {
+    let since = Unixtime::now().unwrap().0 - RECENT_CHANGE_SECS;
+    let history = GLOBALS.storage.read_metadata_history(*pubkey)?;
+    for pair in history.windows(2).rev() {
+        if pair[1].created_at < since {
+            break;
+        }
+        let changes: Vec<MetadataChange> = diff_metadata(&pair[0], &pair[1])
+            .drain(..)
+            .filter(|change| change.field.is_identity())
+            .collect();
+        if !changes.is_empty() {
+            return Ok(Some((Unixtime(pair[1].created_at), changes)));
+        }
+    }
+    Ok(None)
+}
@@ -1,5 +1,29 @@
 use crate::globals::GLOBALS;
+use crate::people::Person;
 use nostr_types::{IdHex, PublicKey};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// The petnames_first setting, which is read for every name shown: 0 if it has
+// not been read yet, 1 if false, 2 if true
+static PETNAMES_FIRST: AtomicU8 = AtomicU8::new(0);
+
+/// Whether our petnames are shown before the names people give themselves
+pub fn petnames_first() -> bool {
+    match PETNAMES_FIRST.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => {
+            let petnames_first = GLOBALS.storage.read_setting_petnames_first();
+            PETNAMES_FIRST.store(if petnames_first { 2 } else { 1 }, Ordering::Relaxed);
+            petnames_first
+        }
+    }
+}
+
+/// Forget the cached settings. Call this after saving settings.
+pub fn reload_settings() {
+    PETNAMES_FIRST.store(0, Ordering::Relaxed);
+}
 
 /// A short rendering of a `PublicKey`
 pub fn pubkey_short(pk: &PublicKey) -> String {
@@ -18,9 +42,40 @@ pub fn hex_id_short(idhex: &IdHex) -> String {
     idhex.as_str()[0..8].to_string()
 }
 
+/// The name a person gives themselves: their display name, or else their name
+pub fn claimed_name(person: &Person) -> Option<String> {
+    person
+        .display_name()
+        .filter(|n| !n.trim().is_empty())
+        .or(person.name().filter(|n| !n.trim().is_empty()))
+        .map(|n| n.to_owned())
+}
+
+/// The name to show for a person, and a secondary name to show beside it (if
+/// any). With the `petnames_first` setting our petname is primary and their
+/// claimed name secondary, otherwise it is the other way around.
+pub fn display_names(person: &Person) -> (String, Option<String>) {
+    let petname = person.petname.clone().filter(|n| !n.trim().is_empty());
+    let claimed = claimed_name(person);
+    let (first, second) = if petnames_first() {
+        (petname, claimed)
+    } else {
+        (claimed, petname)
+    };
+    match (first, second) {
+        (Some(first), second) => (first, second.filter(|s| *s != first)),
+        (None, Some(second)) => (second, None),
+        (None, None) => match person.nip05() {
+            Some(nip05) => (nip05.trim_start_matches("_@").to_owned(), None),
+            None => (pubkey_short(&person.pubkey), None),
+        },
+    }
+}
+
+/// The name to show for a person, looked up by their public key
 pub fn best_name_from_pubkey_lookup(pubkey: &PublicKey) -> String {
     match GLOBALS.storage.read_person(pubkey) {
-        Ok(Some(person)) => person.best_name(),
+        Ok(Some(person)) => display_names(&person).0,
         _ => pubkey_short(pubkey),
     }
 }
@@ -2,9 +2,27 @@ use crate::error::{Error, ErrorKind};
 use crate::globals::GLOBALS;
 use crate::people::{Person, PersonList};
 use crate::person_relay::PersonRelay;
+use crate::storage::types::Nip05HistoryEntry1;
+use heed::RwTxn;
 use nostr_types::{Metadata, Nip05, PublicKey, RelayUrl, Unixtime};
 use std::sync::atomic::Ordering;
 
+/// A NIP-05 history entry, aliased to the latest version
+pub type Nip05HistoryEntry = Nip05HistoryEntry1;
+
+// How many NIP-05 changes we remember per person
+const MAX_NIP05_HISTORY: usize = 20;
+
+/// Everybody listed in a domain's `/.well-known/nostr.json`
+#[derive(Debug, Clone, Default)]
+pub struct Nip05Domain {
+    /// The domain
+    pub domain: String,
+
+    /// The names and their public keys, sorted by name
+    pub names: Vec<(String, PublicKey)>,
+}
+
 // This updates the people map and the database with the result
 pub async fn validate_nip05(person: Person) -> Result<(), Error> {
     if !GLOBALS.storage.read_setting_check_nip05() {
@@ -17,7 +35,7 @@ pub async fn validate_nip05(person: Person) -> Result<(), Error> {
     if person.metadata.is_none() || matches!(person.metadata, Some(Metadata { nip05: None, .. })) {
         GLOBALS
             .people
-            .upsert_nip05_validity(&person.pubkey, None, false, now.0 as u64)
+            .upsert_nip05_validity(&person.pubkey, None, false, false, now.0 as u64)
             .await?;
         return Ok(());
     }
@@ -31,7 +49,7 @@ pub async fn validate_nip05(person: Person) -> Result<(), Error> {
         Err(_) => {
             GLOBALS
                 .people
-                .upsert_nip05_validity(&person.pubkey, Some(nip05), false, now.0 as u64)
+                .upsert_nip05_validity(&person.pubkey, Some(nip05), false, false, now.0 as u64)
                 .await?;
             return Ok(());
         }
@@ -46,48 +64,162 @@ pub async fn validate_nip05(person: Person) -> Result<(), Error> {
         }
     };
 
-    // Check if the response matches their public key
-    let mut valid = false;
-    match nip05file.names.get(&user) {
-        Some(pk) => {
-            if let Ok(pubkey) = PublicKey::try_from_hex_string(pk, true) {
-                if pubkey == person.pubkey {
-                    // Validated
-                    GLOBALS
-                        .people
-                        .upsert_nip05_validity(
-                            &person.pubkey,
-                            Some(nip05.clone()),
-                            true,
-                            now.0 as u64,
-                        )
-                        .await?;
-                    valid = true;
-                }
-            } else {
-                // Failed
-                GLOBALS
-                    .people
-                    .upsert_nip05_validity(&person.pubkey, Some(nip05.clone()), false, now.0 as u64)
-                    .await?;
+    // Check if the response matches their public key. Only a different public key
+    // under their name is a definitive mismatch; a missing name or an unreadable key
+    // may be a problem with the server.
+    let listed = nip05file
+        .names
+        .get(&user)
+        .and_then(|pk| PublicKey::try_from_hex_string(pk, true).ok());
+    let valid = listed == Some(person.pubkey);
+    let mismatch = listed.is_some() && !valid;
+    GLOBALS
+        .people
+        .upsert_nip05_validity(
+            &person.pubkey,
+            Some(nip05.clone()),
+            valid,
+            mismatch,
+            now.0 as u64,
+        )
+        .await?;
+
+    // UI cache invalidation (so notes of the person get rerendered)
+    GLOBALS.ui_people_to_invalidate.write().push(person.pubkey);
+
+    if valid {
+        update_relays(&nip05, &nip05file, &person.pubkey, None)?;
+    }
+
+    Ok(())
+}
+
+/// Record the result of a NIP-05 check in the person's history, if something
+/// changed. If they are followed, the user is alerted when their NIP-05 changed,
+/// or when it stopped validating because the domain now lists a different key
+/// (`mismatch`). Other failures, such as the name going missing, are recorded
+/// without an alert as they may be a problem with the server.
+pub(crate) fn record_nip05_check(
+    pubkey: &PublicKey,
+    nip05: &Option<String>,
+    valid: bool,
+    mismatch: bool,
+    when: u64,
+) -> Result<(), Error> {
+    let history = GLOBALS.storage.read_nip05_history(*pubkey)?;
+    let last = history.last();
+    if let Some(last) = last {
+        if last.nip05 == *nip05 && last.valid == valid {
+            return Ok(());
+        }
+    }
+
+    if let Some(last) = last {
+        if GLOBALS
+            .storage
+            .is_person_in_list(pubkey, PersonList::Followed)?
+        {
+            let name = crate::names::best_name_from_pubkey_lookup(pubkey);
+            if last.nip05 != *nip05 {
+                GLOBALS.status_queue.write().write(format!(
+                    "{} changed their NIP-05 from {} to {}",
+                    name,
+                    last.nip05.as_deref().unwrap_or("nothing"),
+                    nip05.as_deref().unwrap_or("nothing")
+                ));
+            } else if last.valid && mismatch {
+                GLOBALS.status_queue.write().write(format!(
+                    "The NIP-05 of {} ({}) now belongs to somebody else",
+                    name,
+                    nip05.as_deref().unwrap_or("")
+                ));
             }
         }
-        None => {
-            // Failed
-            GLOBALS
-                .people
-                .upsert_nip05_validity(&person.pubkey, Some(nip05.clone()), false, now.0 as u64)
-                .await?;
+    }
+
+    GLOBALS.storage.add_nip05_history(
+        *pubkey,
+        Nip05HistoryEntry {
+            when,
+            nip05: nip05.clone(),
+            valid,
+        },
+        MAX_NIP05_HISTORY,
+        None,
+    )?;
+
+    Ok(())
+}
+
+/// Fetch everybody listed by a domain. Relay hints for all of them are cached.
+pub async fn browse_nip05_domain(domain: &str) -> Result<Nip05Domain, Error> {
+    let domain = domain.trim().trim_start_matches('@').to_lowercase();
+    if domain.len() < 4 {
+        return Err((ErrorKind::InvalidDnsId, file!(), line!()).into());
+    }
+
+    let nip05file = fetch_nip05_file(&format!("https://{}/.well-known/nostr.json", domain)).await?;
+
+    let mut names: Vec<(String, PublicKey)> = Vec::new();
+    for (name, pk) in nip05file.names.iter() {
+        if let Ok(pubkey) = PublicKey::try_from_hex_string(pk, true) {
+            names.push((name.to_owned(), pubkey));
         }
     }
+    names.sort();
 
-    // UI cache invalidation (so notes of the person get rerendered)
-    GLOBALS.ui_people_to_invalidate.write().push(person.pubkey);
+    let mut txn = GLOBALS.storage.get_write_txn()?;
+    for (name, pubkey) in &names {
+        GLOBALS
+            .storage
+            .write_person_if_missing(pubkey, Some(&mut txn))?;
+        update_relays(
+            &format!("{}@{}", name, domain),
+            &nip05file,
+            pubkey,
+            Some(&mut txn),
+        )?;
+    }
+    txn.commit()?;
 
-    if valid {
-        update_relays(&nip05, nip05file, &person.pubkey).await?;
+    Ok(Nip05Domain { domain, names })
+}
+
+/// Follow everybody listed by a domain
+pub async fn follow_nip05_domain(
+    domain: &str,
+    list: PersonList,
+    public: bool,
+) -> Result<(), Error> {
+    let listing = browse_nip05_domain(domain).await?;
+
+    let my_pubkey = GLOBALS.identity.public_key();
+    let pubkeys: Vec<PublicKey> = listing
+        .names
+        .iter()
+        .map(|(_, pubkey)| *pubkey)
+        .filter(|pubkey| Some(*pubkey) != my_pubkey)
+        .collect();
+    GLOBALS.people.follow_all(&pubkeys, list, public)?;
+
+    // Discover their relays all at once
+    if !pubkeys.is_empty() {
+        let _ = GLOBALS
+            .to_overlord
+            .send(crate::comms::ToOverlordMessage::SubscribeDiscover(
+                pubkeys.clone(),
+                None,
+            ));
     }
 
+    GLOBALS.status_queue.write().write(format!(
+        "Followed {} people from {}",
+        pubkeys.len(),
+        listing.domain
+    ));
+
+    *GLOBALS.nip05_domain.write() = Some(listing);
+
     Ok(())
 }
 
@@ -115,11 +247,12 @@ pub async fn get_and_follow_nip05(
             &pubkey,
             Some(nip05.clone()),
             true,
+            false,
             Unixtime::now().unwrap().0 as u64,
         )
         .await?;
 
-    update_relays(&nip05, nip05file, &pubkey).await?;
+    update_relays(&nip05, &nip05file, &pubkey, None)?;
 
     // Follow
     GLOBALS.people.follow(&pubkey, true, list, public, true)?;
@@ -129,26 +262,46 @@ pub async fn get_and_follow_nip05(
     Ok(())
 }
 
-async fn update_relays(nip05: &str, nip05file: Nip05, pubkey: &PublicKey) -> Result<(), Error> {
+fn update_relays<'a>(
+    nip05: &str,
+    nip05file: &Nip05,
+    pubkey: &PublicKey,
+    rw_txn: Option<&mut RwTxn<'a>>,
+) -> Result<(), Error> {
     // Set their relays
     let relays = match nip05file.relays.get(&(*pubkey).into()) {
         Some(relays) => relays,
         None => return Ok(()),
     };
-    for relay in relays.iter() {
-        // Save relay
-        if let Ok(relay_url) = RelayUrl::try_from_unchecked_url(relay) {
-            GLOBALS.storage.write_relay_if_missing(&relay_url, None)?;
-
-            // Save person_relay
-            let mut pr = match GLOBALS.storage.read_person_relay(*pubkey, &relay_url)? {
-                Some(pr) => pr,
-                None => PersonRelay::new(*pubkey, relay_url.clone()),
-            };
-            pr.last_suggested_nip05 = Some(Unixtime::now().unwrap().0 as u64);
-            GLOBALS.storage.write_person_relay(&pr, None)?;
+
+    let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+        for relay in relays.iter() {
+            // Save relay
+            if let Ok(relay_url) = RelayUrl::try_from_unchecked_url(relay) {
+                GLOBALS
+                    .storage
+                    .write_relay_if_missing(&relay_url, Some(txn))?;
+
+                // Save person_relay
+                let mut pr = match GLOBALS.storage.read_person_relay(*pubkey, &relay_url)? {
+                    Some(pr) => pr,
+                    None => PersonRelay::new(*pubkey, relay_url.clone()),
+                };
+                pr.last_suggested_nip05 = Some(Unixtime::now().unwrap().0 as u64);
+                GLOBALS.storage.write_person_relay(&pr, Some(txn))?;
+            }
+        }
+        Ok(())
+    };
+
+    match rw_txn {
+        Some(txn) => f(txn)?,
+        None => {
+            let mut txn = GLOBALS.storage.get_write_txn()?;
+            f(&mut txn)?;
+            txn.commit()?;
         }
-    }
+    };
 
     tracing::info!("Setup {} relays for {}", relays.len(), nip05);
 
@@ -179,6 +332,14 @@ pub fn parse_nip05(nip05: &str) -> Result<(String, String), Error> {
 }
 
 async fn fetch_nip05(user: &str, domain: &str) -> Result<Nip05, Error> {
+    fetch_nip05_file(&format!(
+        "https://{}/.well-known/nostr.json?name={}",
+        domain, user
+    ))
+    .await
+}
+
+async fn fetch_nip05_file(url: &str) -> Result<Nip05, Error> {
     // FIXME add user-agent if configured
 
     let nip05_future = reqwest::Client::builder()
@@ -188,10 +349,7 @@ async fn fetch_nip05(user: &str, domain: &str) -> Result<Nip05, Error> {
         .brotli(true)
         .deflate(true)
         .build()?
-        .get(format!(
-            "https://{}/.well-known/nostr.json?name={}",
-            domain, user
-        ))
+        .get(url)
         .send();
     let response = nip05_future.await?;
     let bytes = response.bytes().await?;
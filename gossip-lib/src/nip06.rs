@@ -0,0 +1,89 @@
+use crate::error::Error;
+use bip32::{DerivationPath, XPrv};
+use bip39::Mnemonic;
+use nostr_types::PrivateKey;
+use zeroize::Zeroize;
+
+// 128 bits of entropy gives a 12 word mnemonic
+const MNEMONIC_ENTROPY_BYTES: usize = 16;
+
+/// Generate a new BIP-39 mnemonic (seed phrase)
+pub fn generate_mnemonic() -> Result<String, Error> {
+    let mut entropy: [u8; MNEMONIC_ENTROPY_BYTES] = rand::random();
+    let mnemonic = Mnemonic::from_entropy(&entropy)?;
+    entropy.zeroize();
+    Ok(mnemonic.to_string())
+}
+
+/// Is this plausibly a mnemonic (as opposed to an nsec, hex or ncryptsec key)?
+pub fn looks_like_mnemonic(input: &str) -> bool {
+    input.split_whitespace().count() >= 12
+}
+
+/// Derive the private key from a BIP-39 mnemonic and optional passphrase at the
+/// NIP-06 derivation path m/44'/1237'/<account>'/0/0
+pub fn private_key_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+    account: u32,
+) -> Result<PrivateKey, Error> {
+    let words: Vec<&str> = mnemonic.split_whitespace().collect();
+    let mnemonic = Mnemonic::parse(words.join(" "))?;
+    let mut seed = mnemonic.to_seed(passphrase);
+
+    let path: DerivationPath = format!("m/44'/1237'/{}'/0/0", account).parse()?;
+    let xprv = XPrv::derive_from_path(seed, &path);
+    seed.zeroize();
+    let xprv = xprv?;
+
+    let mut hex = hex::encode(xprv.to_bytes());
+    let private_key = PrivateKey::try_from_hex_string(&hex);
+    hex.zeroize();
+
+    Ok(private_key?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The test vectors from NIP-06
+    const VECTORS: [(&str, &str, &str); 2] = [
+        (
+            "leader monkey parrot ring guide accident before fence cannon height naive bean",
+            "7f7ff03d123792d6ac594bfa67bf6d0c0ab55b6b1fdb6249303fe861f1ccba9a",
+            "17162c921dc4d2518f9a101db33695df1afb56ab82f5ff3e5da6eec3ca5cd917",
+        ),
+        (
+            "what bleak badge arrange retreat wolf trade produce cricket blur garlic valid proud rude strong choose busy staff weather area salt hollow arm fade",
+            "c15d739894c81a2fcfd3a2df85a0d2c0dbc47a280d092799f144d73d7ae78add",
+            "d41b22899549e1f3d335a31002cfd382174006e166d3e658e3a5eecdb6463573",
+        ),
+    ];
+
+    #[test]
+    fn test_nip06_vectors() {
+        for (mnemonic, private_key_hex, public_key_hex) in VECTORS {
+            let private_key = private_key_from_mnemonic(mnemonic, "", 0).unwrap();
+            let expected = PrivateKey::try_from_hex_string(private_key_hex).unwrap();
+            assert_eq!(private_key.public_key(), expected.public_key());
+            assert_eq!(private_key.public_key().as_hex_string(), public_key_hex);
+        }
+    }
+
+    #[test]
+    fn test_nip06_extra_whitespace() {
+        let (mnemonic, _, public_key_hex) = VECTORS[0];
+        let spaced = format!("  {}\n", mnemonic.replace(' ', "   "));
+        let private_key = private_key_from_mnemonic(&spaced, "", 0).unwrap();
+        assert_eq!(private_key.public_key().as_hex_string(), public_key_hex);
+    }
+
+    #[test]
+    fn test_generated_mnemonic_roundtrip() {
+        let mnemonic = generate_mnemonic().unwrap();
+        assert_eq!(mnemonic.split_whitespace().count(), 12);
+        assert!(looks_like_mnemonic(&mnemonic));
+        assert!(private_key_from_mnemonic(&mnemonic, "", 0).is_ok());
+    }
+}
@@ -1,16 +1,24 @@
 use crate::comms::ToOverlordMessage;
 use crate::globals::GLOBALS;
 use crate::{Error, ErrorKind};
+use dashmap::DashMap;
 use nostr_types::{
     ContentEncryptionAlgorithm, Event, EventKind, PreEvent, PublicKey, RelayUrl, Tag, Unixtime,
 };
 use serde::{Deserialize, Serialize};
 use speedy::{Readable, Writable};
+use std::collections::VecDeque;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Readable, Writable, Serialize, Deserialize)]
 pub struct Nip46ClientMetadata {
     pub name: String,
-    pub url: RelayUrl,
+
+    // This is the client's website, not a relay
+    #[serde(default)]
+    pub url: String,
+
+    #[serde(default)]
     pub description: String,
 }
 
@@ -66,6 +74,11 @@ pub enum Approval {
 }
 
 impl Approval {
+    /// Whether both are the same kind of approval, ignoring any expiry time
+    pub fn same_kind(&self, other: &Approval) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
     fn is_approved(&mut self) -> bool {
         match self {
             Approval::None => false,
@@ -85,6 +98,103 @@ impl Approval {
     }
 }
 
+/// A standing rule for a class of NIP-46 requests (e.g. signing a particular event kind,
+/// or encrypting to a particular counterparty)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Readable, Writable, Serialize, Deserialize)]
+pub enum Nip46Permission {
+    /// Ask the user every time
+    Ask,
+
+    /// Allow without asking
+    Allow,
+
+    /// Deny without asking
+    Deny,
+}
+
+impl Nip46Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Nip46Permission::Ask => "Ask",
+            Nip46Permission::Allow => "Allow",
+            Nip46Permission::Deny => "Deny",
+        }
+    }
+}
+
+/// Limits how many requests a client may make within a period
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Readable, Writable, Serialize, Deserialize)]
+pub struct Nip46RateLimit {
+    pub max_requests: u32,
+    pub period_secs: u64,
+}
+
+impl Default for Nip46RateLimit {
+    fn default() -> Nip46RateLimit {
+        Nip46RateLimit {
+            max_requests: 60,
+            period_secs: 60,
+        }
+    }
+}
+
+/// What happened to a NIP-46 request
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Readable, Writable)]
+pub enum Nip46Decision {
+    /// Allowed by a rule or a standing approval
+    Allowed,
+
+    /// Allowed by the user when asked
+    AllowedByUser,
+
+    /// Denied by a rule
+    Denied,
+
+    /// Denied by the user when asked
+    DeniedByUser,
+
+    /// Denied because the client exceeded its rate limit
+    RateLimited,
+
+    /// The user was asked
+    Asked,
+}
+
+impl Nip46Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Nip46Decision::Allowed => "allowed",
+            Nip46Decision::AllowedByUser => "allowed by user",
+            Nip46Decision::Denied => "denied",
+            Nip46Decision::DeniedByUser => "denied by user",
+            Nip46Decision::RateLimited => "rate limited",
+            Nip46Decision::Asked => "asked",
+        }
+    }
+}
+
+/// An entry in the per-client NIP-46 audit log
+#[derive(Debug, Clone, Readable, Writable)]
+pub struct Nip46AuditEntry {
+    pub when: Unixtime,
+    pub method: String,
+    pub detail: String,
+    pub decision: Nip46Decision,
+}
+
+lazy_static! {
+    // When each client's recent requests were made, for rate limiting
+    static ref REQUEST_TIMES: DashMap<PublicKey, VecDeque<Unixtime>> = DashMap::new();
+}
+
+// The outcome of checking a request against the server's permissions
+enum Verdict {
+    Approved,
+    Denied(&'static str),
+    RateLimited,
+    NeedApproval,
+}
+
 #[derive(Debug, Clone, Readable, Writable)]
 pub struct Nip46Server {
     pub peer_pubkey: PublicKey,
@@ -93,78 +203,124 @@ pub struct Nip46Server {
     pub sign_approval: Approval,
     pub encrypt_approval: Approval,
     pub decrypt_approval: Approval,
-}
 
-impl Nip46Server {
-    /*
+    /// Per event-kind signing rules. These take precedence over `sign_approval`.
+    pub sign_kind_rules: Vec<(u32, Nip46Permission)>,
 
-    NIP-46 does not explain how this can work, because a server event doesn't have the
-    capability of sending a method to a client.  So we keep this commented out until
-    that gets resolved.
+    /// Per counterparty encryption and decryption rules. These take precedence over
+    /// `encrypt_approval` and `decrypt_approval`.
+    pub counterparty_rules: Vec<(PublicKey, Nip46Permission)>,
 
-    pub fn new_from_client(input: String) -> Result<Nip46Server, Error> {
-        // nostrconnect://<client-key-hex>?relay=wss://...&metadata={"name":"...", "url": "...", "description": "..."}
+    /// Optional rate limit on requests from this client
+    pub rate_limit: Option<Nip46RateLimit>,
+}
 
-        // "nostrconnect://"
+impl Nip46Server {
+    /// Create a server from a `nostrconnect://` URI pasted from a client application.
+    ///
+    /// The URI looks like
+    /// `nostrconnect://<client-key-hex>?relay=wss://...&metadata={"name":"...","url":"...","description":"..."}`
+    /// and may also carry `secret`, `perms`, `name`, `url` and `description` parameters.
+    ///
+    /// This returns the server along with the secret (if any), which must be echoed back
+    /// to the client in the connect acknowledgement.
+    pub fn new_from_client(input: &str) -> Result<(Nip46Server, Option<String>), Error> {
+        let input = input.trim();
         if !input.starts_with("nostrconnect://") {
             return Err(ErrorKind::BadNostrConnectString.into());
         }
-        let mut pos = 15;
 
-        // client-key-kex
-        if input.len() < pos + 64 {
-            return Err(ErrorKind::BadNostrConnectString.into());
-        }
-        let peer_pubkey = PublicKey::try_from_hex_string(&input[pos..pos + 64], true)?;
-        pos += 64;
+        let url = url::Url::parse(input)?;
 
-        // '?'
-        if input.len() < pos + 1 {
-            return Err(ErrorKind::BadNostrConnectString.into());
-        }
-        if &input[pos..pos + 1] != "?" {
-            return Err(ErrorKind::BadNostrConnectString.into());
-        }
-        pos += 1;
+        let peer_pubkey = match url.host_str() {
+            Some(host) => PublicKey::try_from_hex_string(host, true)?,
+            None => return Err(ErrorKind::BadNostrConnectString.into()),
+        };
 
         let mut relays: Vec<RelayUrl> = Vec::new();
         let mut metadata: Option<Nip46ClientMetadata> = None;
+        let mut secret: Option<String> = None;
+        let mut perms: Option<String> = None;
+        let mut name: Option<String> = None;
+        let mut client_url = String::new();
+        let mut description = String::new();
+
+        for (key, value) in url.query_pairs() {
+            match &*key {
+                "relay" => relays.push(RelayUrl::try_from_str(&value)?),
+                "metadata" => metadata = Some(serde_json::from_str(&value)?),
+                "secret" => secret = Some(value.into_owned()),
+                "perms" => perms = Some(value.into_owned()),
+                "name" => name = Some(value.into_owned()),
+                "url" => client_url = value.into_owned(),
+                "description" => description = value.into_owned(),
+                _ => tracing::debug!("Ignoring unknown nostrconnect parameter {}", key),
+            }
+        }
 
-        loop {
-            if &input[pos..pos + 6] == "relay=" {
-                pos += 6;
-                if let Some(amp) = input[pos..].find('&') {
-                    relays.push(RelayUrl::try_from_str(&input[pos..amp])?);
-                    pos += amp;
-                } else {
-                    relays.push(RelayUrl::try_from_str(&input[pos..])?);
-                    break;
-                }
-            } else if &input[pos..pos + 9] == "metadata=" {
-                pos += 9;
-                metadata = Some(serde_json::from_str(&input[pos..])?);
-                break;
-            } else {
-                // FIXME, we should tolerate unknown fields
-                return Err(ErrorKind::BadNostrConnectString.into());
+        if relays.is_empty() {
+            return Err(ErrorKind::BadNostrConnectString.into());
+        }
+        relays.sort();
+        relays.dedup();
+
+        if metadata.is_none() {
+            if let Some(name) = name {
+                metadata = Some(Nip46ClientMetadata {
+                    name,
+                    url: client_url,
+                    description,
+                });
             }
         }
 
-        let server = Nip46Server {
+        let mut server = Nip46Server {
             peer_pubkey,
             relays,
             metadata,
+            sign_approval: Approval::None,
+            encrypt_approval: Approval::None,
+            decrypt_approval: Approval::None,
+            sign_kind_rules: Vec::new(),
+            counterparty_rules: Vec::new(),
+            rate_limit: None,
         };
 
-        // Send the connect command to the client
-        unimplemented!();
+        if let Some(perms) = perms {
+            server.record_requested_perms(&perms);
+        }
 
-        Ok(server)
+        Ok((server, secret))
     }
 
-     */
-
+    /// Handle a command, checking it against the permissions of this server.
+    ///
+    /// If the user needs to be asked, this returns an `ErrorKind::Nip46NeedApproval` error
+    /// and no response is sent.  Once the user decides, call
+    /// [execute](Nip46Server::execute) or [decline](Nip46Server::decline).
     pub fn handle(&mut self, cmd: &ParsedCommand) -> Result<(), Error> {
+        match self.check(cmd) {
+            Verdict::Approved => {
+                self.audit(cmd, Nip46Decision::Allowed)?;
+                self.execute(cmd)
+            }
+            Verdict::Denied(reason) => {
+                self.audit(cmd, Nip46Decision::Denied)?;
+                self.respond_error(cmd, reason.to_owned())
+            }
+            Verdict::RateLimited => {
+                self.audit(cmd, Nip46Decision::RateLimited)?;
+                self.respond_error(cmd, "Rate limit exceeded".to_owned())
+            }
+            Verdict::NeedApproval => {
+                self.audit(cmd, Nip46Decision::Asked)?;
+                Err(ErrorKind::Nip46NeedApproval.into())
+            }
+        }
+    }
+
+    /// Execute a command without checking permissions, and send the response
+    pub fn execute(&self, cmd: &ParsedCommand) -> Result<(), Error> {
         let ParsedCommand {
             ref id,
             ref method,
@@ -174,49 +330,13 @@ impl Nip46Server {
         let result: Result<String, Error> = match method.as_str() {
             "connect" => Err("You are already connected".into()),
             "get_public_key" => self.get_public_key(),
-            "sign_event" => {
-                if self.sign_approval.is_approved() {
-                    self.sign_event(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
+            "sign_event" => self.sign_event(params),
             "get_relays" => self.get_relays(),
-            "nip04_encrypt" => {
-                if self.encrypt_approval.is_approved() {
-                    self.nip04_encrypt(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
-            "nip04_decrypt" => {
-                if self.decrypt_approval.is_approved() {
-                    self.nip04_decrypt(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
-            "nip44_get_key" => {
-                if self.encrypt_approval.is_approved() || self.decrypt_approval.is_approved() {
-                    self.nip44_get_key(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
-            "nip44_encrypt" => {
-                if self.encrypt_approval.is_approved() {
-                    self.nip44_encrypt(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
-            "nip44_decrypt" => {
-                if self.decrypt_approval.is_approved() {
-                    self.nip44_decrypt(params)
-                } else {
-                    Err(ErrorKind::Nip46NeedApproval.into())
-                }
-            }
+            "nip04_encrypt" => self.nip04_encrypt(params),
+            "nip04_decrypt" => self.nip04_decrypt(params),
+            "nip44_get_key" => self.nip44_get_key(params),
+            "nip44_encrypt" => self.nip44_encrypt(params),
+            "nip44_decrypt" => self.nip44_decrypt(params),
             "ping" => self.ping(),
             _ => Err("unrecognized command".into()),
         };
@@ -241,6 +361,160 @@ impl Nip46Server {
         Ok(())
     }
 
+    /// Decline a command that the user was asked about
+    pub fn decline(&self, cmd: &ParsedCommand) -> Result<(), Error> {
+        self.audit(cmd, Nip46Decision::DeniedByUser)?;
+        self.respond_error(cmd, "Declined by the user".to_owned())
+    }
+
+    /// Get the signing rule for an event kind, if there is one
+    pub fn sign_kind_rule(&self, kind: u32) -> Option<Nip46Permission> {
+        self.sign_kind_rules
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, p)| *p)
+    }
+
+    /// Set (or with `None`, remove) the signing rule for an event kind
+    pub fn set_sign_kind_rule(&mut self, kind: u32, permission: Option<Nip46Permission>) {
+        self.sign_kind_rules.retain(|(k, _)| *k != kind);
+        if let Some(p) = permission {
+            self.sign_kind_rules.push((kind, p));
+            self.sign_kind_rules.sort_by_key(|(k, _)| *k);
+        }
+    }
+
+    /// Get the encryption/decryption rule for a counterparty, if there is one
+    pub fn counterparty_rule(&self, pubkey: &PublicKey) -> Option<Nip46Permission> {
+        self.counterparty_rules
+            .iter()
+            .find(|(pk, _)| pk == pubkey)
+            .map(|(_, p)| *p)
+    }
+
+    /// Set (or with `None`, remove) the encryption/decryption rule for a counterparty
+    pub fn set_counterparty_rule(
+        &mut self,
+        pubkey: PublicKey,
+        permission: Option<Nip46Permission>,
+    ) {
+        self.counterparty_rules.retain(|(pk, _)| *pk != pubkey);
+        if let Some(p) = permission {
+            self.counterparty_rules.push((pubkey, p));
+        }
+    }
+
+    /// Record the permissions requested by a client in the `connect` command.
+    ///
+    /// These are comma separated `method[:param]` entries such as
+    /// `sign_event:1,sign_event:7,nip44_encrypt`.  Nothing is granted: each requested
+    /// event kind gets an Ask rule, so it is listed on the Nostr Connect page where the
+    /// user can allow it.  Signing any kind, encrypting and decrypting are left as Ask.
+    pub fn record_requested_perms(&mut self, perms: &str) {
+        for perm in perms.split(',') {
+            let perm = perm.trim();
+            let (method, param) = match perm.split_once(':') {
+                Some((m, p)) => (m, Some(p)),
+                None => (perm, None),
+            };
+            match method {
+                "sign_event" => match param.map(|p| p.parse::<u32>()) {
+                    Some(Ok(kind)) => {
+                        // Do not override a rule the user already set
+                        if self.sign_kind_rule(kind).is_none() {
+                            self.set_sign_kind_rule(kind, Some(Nip46Permission::Ask));
+                        }
+                    }
+                    Some(Err(_)) => {
+                        tracing::warn!("NIP-46: ignoring malformed requested perm {}", perm)
+                    }
+                    None => tracing::info!(
+                        "NIP-46: client requested signing of any kind, which will be asked"
+                    ),
+                },
+                "nip04_encrypt" | "nip44_encrypt" | "nip04_decrypt" | "nip44_decrypt" => {
+                    tracing::info!("NIP-46: client requested {}, which will be asked", perm)
+                }
+                "" => {}
+                _ => tracing::debug!("NIP-46: ignoring requested perm {}", perm),
+            }
+        }
+    }
+
+    fn check(&mut self, cmd: &ParsedCommand) -> Verdict {
+        if let Some(limit) = self.rate_limit {
+            if !within_rate_limit(self.peer_pubkey, limit) {
+                return Verdict::RateLimited;
+            }
+        }
+
+        match cmd.method.as_str() {
+            "sign_event" => {
+                let kind = match cmd.params.first().and_then(|p| requested_kind(p)) {
+                    Some(k) => k,
+                    None => return Verdict::Approved, // it will fail to parse when executed
+                };
+                match self.sign_kind_rule(kind) {
+                    Some(Nip46Permission::Allow) => Verdict::Approved,
+                    Some(Nip46Permission::Deny) => {
+                        Verdict::Denied("Signing this event kind is not permitted")
+                    }
+                    Some(Nip46Permission::Ask) => Verdict::NeedApproval,
+                    None => approval_verdict(self.sign_approval.is_approved()),
+                }
+            }
+            "nip04_encrypt" | "nip44_encrypt" => match self.counterparty_verdict(cmd) {
+                Some(v) => v,
+                None => approval_verdict(self.encrypt_approval.is_approved()),
+            },
+            "nip04_decrypt" | "nip44_decrypt" => match self.counterparty_verdict(cmd) {
+                Some(v) => v,
+                None => approval_verdict(self.decrypt_approval.is_approved()),
+            },
+            "nip44_get_key" => match self.counterparty_verdict(cmd) {
+                Some(v) => v,
+                None => approval_verdict(
+                    self.encrypt_approval.is_approved() || self.decrypt_approval.is_approved(),
+                ),
+            },
+            _ => Verdict::Approved,
+        }
+    }
+
+    fn counterparty_verdict(&self, cmd: &ParsedCommand) -> Option<Verdict> {
+        let pubkey = PublicKey::try_from_hex_string(cmd.params.first()?, true).ok()?;
+        match self.counterparty_rule(&pubkey)? {
+            Nip46Permission::Allow => Some(Verdict::Approved),
+            Nip46Permission::Deny => Some(Verdict::Denied(
+                "Encryption with this counterparty is not permitted",
+            )),
+            Nip46Permission::Ask => Some(Verdict::NeedApproval),
+        }
+    }
+
+    /// Record a decision about a command in this client's audit log
+    pub fn audit(&self, cmd: &ParsedCommand, decision: Nip46Decision) -> Result<(), Error> {
+        let entry = Nip46AuditEntry {
+            when: Unixtime::now().unwrap(),
+            method: cmd.method.clone(),
+            detail: command_detail(cmd),
+            decision,
+        };
+        GLOBALS
+            .storage
+            .add_nip46_audit_entry(self.peer_pubkey, entry, None)
+    }
+
+    fn respond_error(&self, cmd: &ParsedCommand, error: String) -> Result<(), Error> {
+        send_response(
+            cmd.id.clone(),
+            "".to_owned(),
+            error,
+            self.peer_pubkey,
+            self.relays.clone(),
+        )
+    }
+
     fn get_public_key(&self) -> Result<String, Error> {
         if let Some(pk) = GLOBALS.identity.public_key() {
             Ok(pk.as_hex_string())
@@ -372,6 +646,56 @@ fn default_now() -> Option<Unixtime> {
     Some(Unixtime::now().unwrap())
 }
 
+// Count a request against the client's rate limit, returning false if it is over the limit.
+// Requests that are refused for being over the limit are not counted.
+fn within_rate_limit(peer_pubkey: PublicKey, limit: Nip46RateLimit) -> bool {
+    let now = Unixtime::now().unwrap();
+    let since = now - Duration::from_secs(limit.period_secs);
+    let mut times = REQUEST_TIMES.entry(peer_pubkey).or_default();
+    while times.front().is_some_and(|when| *when < since) {
+        times.pop_front();
+    }
+    if times.len() >= limit.max_requests as usize {
+        return false;
+    }
+    times.push_back(now);
+    true
+}
+
+fn approval_verdict(approved: bool) -> Verdict {
+    if approved {
+        Verdict::Approved
+    } else {
+        Verdict::NeedApproval
+    }
+}
+
+/// The event kind that a `sign_event` parameter asks to be signed
+pub fn requested_kind(param: &str) -> Option<u32> {
+    let json: serde_json::Value = serde_json::from_str(param).ok()?;
+    json.get("kind")?.as_u64().map(|k| k as u32)
+}
+
+/// A short human readable detail about a command, for the audit log and approval dialog
+pub fn command_detail(cmd: &ParsedCommand) -> String {
+    match cmd.method.as_str() {
+        "sign_event" => match cmd.params.first().and_then(|p| requested_kind(p)) {
+            Some(kind) => format!("kind {}", kind),
+            None => "".to_owned(),
+        },
+        "nip04_encrypt" | "nip04_decrypt" | "nip44_get_key" | "nip44_encrypt" | "nip44_decrypt" => {
+            match cmd.params.first() {
+                Some(p) => match PublicKey::try_from_hex_string(p, true) {
+                    Ok(pk) => crate::names::best_name_from_pubkey_lookup(&pk),
+                    Err(_) => "".to_owned(),
+                },
+                None => "".to_owned(),
+            }
+        }
+        _ => "".to_owned(),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedCommand {
     pub id: String,
@@ -479,6 +803,24 @@ fn send_response(
     Ok(())
 }
 
+/// Acknowledge a client-initiated (`nostrconnect://`) connection.
+///
+/// The client is waiting for a response carrying its secret (or "ack" if it did
+/// not supply one).
+pub fn send_connect_ack(server: &Nip46Server, secret: Option<String>) -> Result<(), Error> {
+    let id = textnonce::TextNonce::sized_urlsafe(16)
+        .unwrap()
+        .into_string();
+
+    send_response(
+        id,
+        secret.unwrap_or("ack".to_owned()),
+        "".to_owned(),
+        server.peer_pubkey,
+        server.relays.clone(),
+    )
+}
+
 pub fn handle_command(event: &Event, seen_on: Option<RelayUrl>) -> Result<(), Error> {
     // If we have a server for that pubkey
     if let Some(mut server) = GLOBALS.storage.read_nip46server(event.pubkey)? {
@@ -503,7 +845,37 @@ pub fn handle_command(event: &Event, seen_on: Option<RelayUrl>) -> Result<(), Er
         };
 
         // Handle the command
-        if let Err(e) = server.handle(&parsed_command) {
+        let before = (
+            server.sign_approval,
+            server.encrypt_approval,
+            server.decrypt_approval,
+        );
+        let result = server.handle(&parsed_command);
+
+        // Handling may have used up a temporary approval. Save only that, so that
+        // changes made to the stored server in the meantime are kept.
+        let sign_used = !before.0.same_kind(&server.sign_approval);
+        let encrypt_used = !before.1.same_kind(&server.encrypt_approval);
+        let decrypt_used = !before.2.same_kind(&server.decrypt_approval);
+        if sign_used || encrypt_used || decrypt_used {
+            GLOBALS.storage.modify_nip46server(
+                event.pubkey,
+                |stored| {
+                    if sign_used {
+                        stored.sign_approval = server.sign_approval;
+                    }
+                    if encrypt_used {
+                        stored.encrypt_approval = server.encrypt_approval;
+                    }
+                    if decrypt_used {
+                        stored.decrypt_approval = server.decrypt_approval;
+                    }
+                },
+                None,
+            )?;
+        }
+
+        if let Err(e) = result {
             if matches!(e.kind, ErrorKind::Nip46NeedApproval) {
                 GLOBALS
                     .nip46_approval_requests
@@ -580,11 +952,11 @@ pub fn handle_command(event: &Event, seen_on: Option<RelayUrl>) -> Result<(), Er
         return Ok(()); // no need to pass back error
     }
 
-    if params.len() != 2 {
+    if params.len() != 2 && params.len() != 3 {
         send_response(
             id.clone(),
             "".to_owned(),
-            "connect requires two parameters".to_string(),
+            "connect requires two or three parameters".to_string(),
             event.pubkey,
             reply_relays,
         )?;
@@ -629,15 +1001,23 @@ pub fn handle_command(event: &Event, seen_on: Option<RelayUrl>) -> Result<(), Er
     }
 
     // Turn it into a full server
-    let server = Nip46Server {
+    let mut server = Nip46Server {
         peer_pubkey: event.pubkey,
         relays: reply_relays.clone(),
         metadata: None,
         sign_approval: Approval::None,
         encrypt_approval: Approval::None,
         decrypt_approval: Approval::None,
+        sign_kind_rules: Vec::new(),
+        counterparty_rules: Vec::new(),
+        rate_limit: None,
     };
 
+    // Honor the permissions the client requested
+    if let Some(perms) = params.get(2) {
+        server.record_requested_perms(perms);
+    }
+
     // Save the server, and delete the unconnected server
     let mut txn = GLOBALS.storage.get_write_txn()?;
     GLOBALS.storage.write_nip46server(&server, Some(&mut txn))?;
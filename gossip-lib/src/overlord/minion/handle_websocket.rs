@@ -114,6 +114,12 @@ impl Minion {
                         } else {
                             sub.set_eose();
                         }
+                        if handle == "config_feed" {
+                            GLOBALS
+                                .own_lists
+                                .write()
+                                .relay_done(&self.url, self.config_feed_pubkey);
+                        }
                         if handle == "general_feed" {
                             // Update last general EOSE
                             let now = Unixtime::now().unwrap().0 as u64;
@@ -292,6 +298,14 @@ impl Minion {
                     }
                 }
 
+                // Our lists won't come from a closed config feed
+                if handle == "config_feed" {
+                    GLOBALS
+                        .own_lists
+                        .write()
+                        .relay_done(&self.url, self.config_feed_pubkey);
+                }
+
                 // Remove the subscription
                 tracing::info!("{}: removed subscription {}", &self.url, handle);
                 let _ = self.subscription_map.remove(&handle);
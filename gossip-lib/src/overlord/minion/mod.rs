@@ -86,6 +86,7 @@ pub struct Minion {
     exiting: Option<MinionExitReason>,
     auth_state: AuthState,
     failed_subs: HashSet<String>,
+    config_feed_pubkey: Option<PublicKey>,
 }
 
 impl Minion {
@@ -116,6 +117,7 @@ impl Minion {
             exiting: None,
             auth_state: AuthState::None,
             failed_subs: HashSet::new(),
+            config_feed_pubkey: None,
         })
     }
 }
@@ -771,6 +773,9 @@ impl Minion {
                         EventKind::MuteList,
                         EventKind::FollowSets,
                         EventKind::RelayList,
+                        EventKind::BookmarkList,
+                        EventKind::BookmarkSets,
+                        EventKind::PinList,
                     ],
                     // these are all replaceable, no since required
                     ..Default::default()
@@ -792,6 +797,7 @@ impl Minion {
                 },
             ];
 
+            self.config_feed_pubkey = Some(pubkey);
             self.subscribe(filters, "config_feed", job_id).await?;
         }
 
@@ -1212,7 +1218,9 @@ impl Minion {
         let pkh: PublicKeyHex = ea.author.into();
         filter.authors = vec![pkh];
         filter.kinds = vec![ea.kind];
-        filter.set_tag_values('d', vec![ea.d]);
+        if ea.kind.is_parameterized_replaceable() {
+            filter.set_tag_values('d', vec![ea.d]);
+        }
 
         self.subscribe(vec![filter], &handle, job_id).await
     }
@@ -7,13 +7,15 @@ use crate::comms::{
 use crate::dm_channel::DmChannel;
 use crate::error::{Error, ErrorKind};
 use crate::feed::FeedKind;
-use crate::globals::{ZapState, GLOBALS};
-use crate::nip46::{Approval, ParsedCommand};
-use crate::people::{Person, PersonList};
+use crate::globals::{OwnListsLoad, ZapState, GLOBALS};
+use crate::nip46::{Approval, Nip46Decision, Nip46Permission, Nip46Server, ParsedCommand};
+use crate::people::{Mute, Person, PersonList};
 use crate::person_relay::PersonRelay;
 use crate::relay::Relay;
+use crate::scheduled::ScheduledPost;
 use crate::tags::{
-    add_addr_to_tags, add_event_to_tags, add_pubkey_to_tags, add_subject_to_tags_if_missing,
+    add_addr_to_tags, add_event_to_tags, add_pubkey_to_tags, add_quote_to_tags,
+    add_subject_to_tags_if_missing,
 };
 use gossip_relay_picker::{Direction, RelayAssignment};
 use heed::RwTxn;
@@ -25,6 +27,7 @@ use nostr_types::{
     PublicKey, RelayUrl, Tag, UncheckedUrl, Unixtime,
 };
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc;
 use std::time::Duration;
@@ -52,6 +55,10 @@ pub struct Overlord {
 
     // Map from minion task::Id to Url
     minions_task_url: HashMap<task::Id, RelayUrl>,
+
+    // How many due scheduled posts are held until the key is unlocked, so we
+    // only say so when there are more
+    held_scheduled_posts: usize,
 }
 
 impl Overlord {
@@ -99,6 +106,7 @@ impl Overlord {
             inbox,
             minions: task::JoinSet::new(),
             minions_task_url: HashMap::new(),
+            held_scheduled_posts: 0,
         }
     }
 
@@ -190,11 +198,17 @@ impl Overlord {
         // Start periodic tasks in people manager (after signer)
         crate::people::People::start();
 
+        // Start posting scheduled posts when they are due
+        crate::scheduled::start();
+
         // Initialize the relay picker
         GLOBALS.relay_picker.init().await?;
 
         // Do the startup procedures
         self.start_long_lived_subscriptions().await?;
+        if let Err(e) = self.handle_missed_scheduled_posts().await {
+            tracing::error!("{}", e);
+        }
 
         'mainloop: loop {
             if let Err(e) = self.loop_handler().await {
@@ -420,6 +434,9 @@ impl Overlord {
         // Set to not connected
         let relayjobs = GLOBALS.connected_relays.remove(&url).map(|(_, v)| v);
 
+        // It won't be sending our lists
+        GLOBALS.own_lists.write().relay_done(&url, None);
+
         let mut exclusion: u64;
         let mut completed: bool = false;
 
@@ -556,6 +573,9 @@ impl Overlord {
 
     async fn handle_message(&mut self, message: ToOverlordMessage) -> Result<(), Error> {
         match message {
+            ToOverlordMessage::AddAccount => {
+                self.add_account().await?;
+            }
             ToOverlordMessage::AddPubkeyRelay(pubkey, relayurl) => {
                 self.add_pubkey_relay(pubkey, relayurl).await?;
             }
@@ -574,21 +594,33 @@ impl Overlord {
             ToOverlordMessage::AuthDeclined(relay_url) => {
                 self.auth_declined(relay_url)?;
             }
+            ToOverlordMessage::BrowseNip05Domain(domain) => {
+                Self::browse_nip05_domain(domain).await?;
+            }
             ToOverlordMessage::ChangePassphrase { old, new } => {
                 Self::change_passphrase(old, new).await?;
             }
             ToOverlordMessage::ClearPersonList(list) => {
                 self.clear_person_list(list)?;
             }
+            ToOverlordMessage::ComputeFollowSuggestions => {
+                Self::compute_follow_suggestions().await?;
+            }
             ToOverlordMessage::ConnectApproved(relay_url) => {
                 self.connect_approved(relay_url).await?;
             }
             ToOverlordMessage::ConnectDeclined(relay_url) => {
                 self.connect_declined(relay_url).await?;
             }
+            ToOverlordMessage::CopyPersonList { pubkey, dtag } => {
+                self.copy_person_list(pubkey, dtag).await?;
+            }
             ToOverlordMessage::DelegationReset => {
                 Self::delegation_reset().await?;
             }
+            ToOverlordMessage::DeleteBookmarkSet(dtag) => {
+                self.delete_bookmark_set(dtag).await?;
+            }
             ToOverlordMessage::DeletePersonList(list) => {
                 self.delete_person_list(list).await?;
             }
@@ -610,12 +642,18 @@ impl Overlord {
             ToOverlordMessage::FetchEventAddr(ea) => {
                 self.fetch_event_addr(ea).await?;
             }
+            ToOverlordMessage::FetchPinList(pubkey) => {
+                self.fetch_pin_list(pubkey).await?;
+            }
             ToOverlordMessage::FollowPubkey(pubkey, list, public) => {
                 self.follow_pubkey(pubkey, list, public).await?;
             }
             ToOverlordMessage::FollowNip05(nip05, list, public) => {
                 Self::follow_nip05(nip05, list, public).await?;
             }
+            ToOverlordMessage::FollowNip05Domain(domain, list, public) => {
+                Self::follow_nip05_domain(domain, list, public).await?;
+            }
             ToOverlordMessage::FollowNprofile(nprofile, list, public) => {
                 self.follow_nprofile(nprofile, list, public).await?;
             }
@@ -625,6 +663,13 @@ impl Overlord {
             ToOverlordMessage::HideOrShowRelay(relay_url, hidden) => {
                 Self::hide_or_show_relay(relay_url, hidden)?;
             }
+            ToOverlordMessage::ImportMnemonic {
+                mnemonic,
+                mnemonic_passphrase,
+                password,
+            } => {
+                Self::import_mnemonic(mnemonic, mnemonic_passphrase, password).await?;
+            }
             ToOverlordMessage::ImportPriv { privkey, password } => {
                 Self::import_priv(privkey, password).await?;
             }
@@ -640,9 +685,13 @@ impl Overlord {
                     FeedKind::Inbox(_) => self.load_more_inbox_feed().await?,
                     FeedKind::Person(pubkey) => self.load_more_person_feed(pubkey).await?,
                     FeedKind::DmChat(_) => (), // DmChat is complete, not chunked
+                    FeedKind::Bookmarks(_) => (), // Bookmarks are complete, not chunked
                     FeedKind::Thread { .. } => (), // Thread is complete, not chunked
                 }
             }
+            ToOverlordMessage::MigrateKey(password) => {
+                self.migrate_key(password).await?;
+            }
             ToOverlordMessage::MinionJobComplete(url, job_id) => {
                 self.finish_job(url, Some(job_id), None)?;
             }
@@ -664,6 +713,9 @@ impl Overlord {
                     self.maybe_disconnect_relay(&url)?;
                 }
             }
+            ToOverlordMessage::Nip46ServerConnectFromClient(uri) => {
+                self.nip46_server_connect_from_client(uri).await?;
+            }
             ToOverlordMessage::Nip46ServerOpApprovalResponse(pubkey, parsed_command, approval) => {
                 self.nip46_server_op_approval_response(pubkey, parsed_command, approval)
                     .await?;
@@ -688,6 +740,9 @@ impl Overlord {
             ToOverlordMessage::PruneDatabase => {
                 Self::prune_database()?;
             }
+            ToOverlordMessage::PushBookmarkList(dtag) => {
+                self.push_bookmark_list(dtag).await?;
+            }
             ToOverlordMessage::PushPersonList(person_list) => {
                 self.push_person_list(person_list).await?;
             }
@@ -706,9 +761,26 @@ impl Overlord {
             ToOverlordMessage::Repost(id) => {
                 self.repost(id).await?;
             }
+            ToOverlordMessage::SchedulePost {
+                content,
+                tags,
+                in_reply_to,
+                dm_channel,
+                send_at,
+                sign_now,
+            } => {
+                self.schedule_post(content, tags, in_reply_to, dm_channel, send_at, sign_now)
+                    .await?;
+            }
             ToOverlordMessage::Search(text) => {
                 Self::search(text).await?;
             }
+            ToOverlordMessage::SendScheduledPost(id) => {
+                self.send_scheduled_post(id).await?;
+            }
+            ToOverlordMessage::SendScheduledPosts => {
+                self.send_scheduled_posts().await?;
+            }
             ToOverlordMessage::SetActivePerson(pubkey) => {
                 Self::set_active_person(pubkey).await?;
             }
@@ -718,6 +790,9 @@ impl Overlord {
             ToOverlordMessage::SetPersonFeed(pubkey) => {
                 self.set_person_feed(pubkey).await?;
             }
+            ToOverlordMessage::SetPinned(id, pinned) => {
+                self.set_pinned(id, pinned).await?;
+            }
             ToOverlordMessage::SetThreadFeed {
                 id,
                 referenced_by,
@@ -743,8 +818,19 @@ impl Overlord {
             ToOverlordMessage::Shutdown => {
                 Self::shutdown()?;
             }
+            ToOverlordMessage::SwitchAccount(pubkey) => {
+                self.switch_account(pubkey).await?;
+            }
             ToOverlordMessage::UnlockKey(password) => {
                 Self::unlock_key(password)?;
+                if GLOBALS.identity.is_unlocked() {
+                    // Read what could not be decrypted while locked
+                    crate::bookmarks::process_stored_bookmark_lists()?;
+                    // and post what was held while locked
+                    if let Err(e) = self.send_scheduled_posts().await {
+                        tracing::error!("{}", e);
+                    }
+                }
             }
             ToOverlordMessage::UpdateMetadata(pubkey) => {
                 self.update_metadata(pubkey).await?;
@@ -758,6 +844,12 @@ impl Overlord {
             ToOverlordMessage::UpdateRelay(old, new) => {
                 self.update_relay(old, new).await?;
             }
+            ToOverlordMessage::UpdateScheduledPost(id, content, send_at) => {
+                self.update_scheduled_post(id, content, send_at).await?;
+            }
+            ToOverlordMessage::UploadFile(upload_id, path) => {
+                Self::upload_file(upload_id, path)?;
+            }
             ToOverlordMessage::VisibleNotesChanged(visible) => {
                 self.visible_notes_changed(visible).await?;
             }
@@ -772,6 +864,35 @@ impl Overlord {
         Ok(())
     }
 
+    /// Stash the active account and start a fresh one with no keys and empty
+    /// person lists. The relay configuration is carried over as a starting point.
+    /// The caller should then generate or import keys for the new account.
+    pub async fn add_account(&mut self) -> Result<(), Error> {
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+        if GLOBALS
+            .storage
+            .stash_active_account(Some(&mut txn))?
+            .is_none()
+        {
+            // There is no active account, so the fresh one is already here
+            return Ok(());
+        }
+        GLOBALS.storage.reset_active_account(true, Some(&mut txn))?;
+        txn.commit()?;
+
+        GLOBALS.identity.delete_identity()?;
+        *GLOBALS.own_lists.write() = OwnListsLoad::default();
+        Self::delegation_reset().await?;
+        self.restart_for_account().await?;
+
+        GLOBALS
+            .status_queue
+            .write()
+            .write("Started a new account. Generate or import a key for it.".to_owned());
+
+        Ok(())
+    }
+
     /// Manually associate a relay with a person. This sets both read and write, and
     /// remembers that they were manual associations (not from a relay list) so they
     /// have less weight. This is so the user can make these associations manually if
@@ -994,6 +1115,33 @@ impl Overlord {
         Ok(())
     }
 
+    /// Fetch everybody listed in a domain's `/.well-known/nostr.json` into
+    /// `GLOBALS.nip05_domain`, caching their relay hints
+    pub async fn browse_nip05_domain(domain: String) -> Result<(), Error> {
+        GLOBALS.nip05_domain_fetching.store(true, Ordering::Relaxed);
+        *GLOBALS.nip05_domain.write() = None;
+        std::mem::drop(tokio::spawn(async move {
+            match crate::nip05::browse_nip05_domain(&domain).await {
+                Ok(listing) => {
+                    let pubkeys: Vec<PublicKey> =
+                        listing.names.iter().map(|(_, pubkey)| *pubkey).collect();
+                    GLOBALS.people.people_of_interest(&pubkeys);
+                    *GLOBALS.nip05_domain.write() = Some(listing);
+                }
+                Err(e) => {
+                    GLOBALS
+                        .status_queue
+                        .write()
+                        .write(format!("Could not browse {}: {}", domain, e));
+                }
+            }
+            GLOBALS
+                .nip05_domain_fetching
+                .store(false, Ordering::Relaxed);
+        }));
+        Ok(())
+    }
+
     /// Change the user's passphrase.
     pub async fn change_passphrase(mut old: String, mut new: String) -> Result<(), Error> {
         GLOBALS.identity.change_passphrase(&old, &new).await?;
@@ -1009,6 +1157,123 @@ impl Overlord {
         Ok(())
     }
 
+    /// Compute suggestions of people to follow. The results arrive in
+    /// `GLOBALS.follow_suggestions`.
+    pub async fn compute_follow_suggestions() -> Result<(), Error> {
+        // Only one computation at a time
+        if GLOBALS
+            .follow_suggestions_computing
+            .swap(true, Ordering::Relaxed)
+        {
+            return Ok(());
+        }
+
+        std::mem::drop(tokio::spawn(async move {
+            let result =
+                task::spawn_blocking(|| crate::follow_suggestions::compute_follow_suggestions(100))
+                    .await;
+
+            match result {
+                Ok(Ok(suggestions)) => {
+                    // Make sure we can show their names
+                    for suggestion in &suggestions {
+                        GLOBALS.people.person_of_interest(suggestion.pubkey);
+                    }
+                    *GLOBALS.follow_suggestions.write() = suggestions;
+                }
+                Ok(Err(e)) => tracing::error!("{}", e),
+                Err(e) => tracing::error!("{}", e),
+            }
+
+            GLOBALS
+                .follow_suggestions_computing
+                .store(false, Ordering::Relaxed);
+        }));
+        Ok(())
+    }
+
+    /// Copy somebody's kind-3 contact list (if `dtag` is None) or kind-30000 follow
+    /// set into a new local list. If we don't have their list event, it is fetched
+    /// from their relays first.
+    pub async fn copy_person_list(
+        &mut self,
+        pubkey: PublicKey,
+        dtag: Option<String>,
+    ) -> Result<(), Error> {
+        if let Some(list) =
+            crate::person_list_io::copy_person_list_from_user(pubkey, dtag.as_deref())?
+        {
+            Self::report_copied_person_list(list);
+            return Ok(());
+        }
+
+        // Fetch it from their relays
+        let ea = EventAddr {
+            d: dtag.clone().unwrap_or_default(),
+            relays: GLOBALS
+                .storage
+                .get_best_relays(pubkey, Direction::Write)?
+                .iter()
+                .map(|(url, _)| url.to_unchecked_url())
+                .collect(),
+            kind: if dtag.is_some() {
+                EventKind::FollowSets
+            } else {
+                EventKind::ContactList
+            },
+            author: pubkey,
+        };
+        if ea.relays.is_empty() {
+            GLOBALS
+                .status_queue
+                .write()
+                .write("We don't know where to find that list.".to_string());
+            return Ok(());
+        }
+        self.fetch_event_addr(ea).await?;
+
+        // Wait for it to arrive
+        std::mem::drop(tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(30);
+            loop {
+                // Register before checking so that an arrival in between isn't missed
+                let arrived = GLOBALS.person_list_event_notify.notified();
+                match crate::person_list_io::copy_person_list_from_user(pubkey, dtag.as_deref()) {
+                    Ok(Some(list)) => {
+                        Self::report_copied_person_list(list);
+                        return;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!("{}", e);
+                        return;
+                    }
+                }
+                if tokio::time::timeout_at(deadline, arrived).await.is_err() {
+                    break;
+                }
+            }
+            GLOBALS
+                .status_queue
+                .write()
+                .write("Could not fetch that list from their relays.".to_string());
+        }));
+
+        Ok(())
+    }
+
+    fn report_copied_person_list(list: PersonList) {
+        let title = match GLOBALS.storage.get_person_list_metadata(list) {
+            Ok(Some(metadata)) => metadata.title,
+            _ => "a new list".to_owned(),
+        };
+        GLOBALS
+            .status_queue
+            .write()
+            .write(format!("Copied into {}", title));
+        GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+    }
+
     /// User has approved connection to this relay. Save this result for later
     /// and inform the minion.
     pub async fn connect_approved(&mut self, relay_url: RelayUrl) -> Result<(), Error> {
@@ -1074,6 +1339,85 @@ impl Overlord {
         Ok(())
     }
 
+    /// Delete a bookmark set, locally and (if signed in) on relays
+    pub async fn delete_bookmark_set(&mut self, dtag: String) -> Result<(), Error> {
+        let public_key = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Ok(()),
+        };
+
+        GLOBALS
+            .storage
+            .delete_bookmark_list(public_key, &dtag, None)?;
+
+        // Find all local-storage events that define the set, and delete them locally
+        let bad_events = GLOBALS.storage.find_events(
+            &[EventKind::BookmarkSets],
+            &[public_key],
+            None,
+            |event| event.parameter().as_ref() == Some(&dtag),
+            false,
+        )?;
+        for bad_event in &bad_events {
+            GLOBALS.storage.delete_event(bad_event.id, None)?;
+        }
+
+        // Require sign in to delete further
+        if !GLOBALS.identity.is_unlocked() {
+            GLOBALS
+                .status_queue
+                .write()
+                .write("The bookmark set was only deleted locally because you are not signed in. It may reappear when it is next fetched.".to_string());
+            return Ok(());
+        }
+
+        let event = {
+            let ea = EventAddr {
+                d: dtag,
+                relays: vec![],
+                kind: EventKind::BookmarkSets,
+                author: public_key,
+            };
+            let mut tags: Vec<Tag> = vec![Tag::new_address(&ea, None)];
+            for bad_event in &bad_events {
+                tags.push(Tag::new_event(bad_event.id, None, None));
+            }
+
+            let pre_event = PreEvent {
+                pubkey: public_key,
+                created_at: Unixtime::now().unwrap(),
+                kind: EventKind::EventDeletion,
+                tags,
+                content: "Deleting bookmark set".to_owned(),
+            };
+
+            GLOBALS.identity.sign_event(pre_event)?
+        };
+
+        // Process this event locally
+        crate::process::process_new_event(&event, None, None, false, false).await?;
+
+        let relays: Vec<Relay> = GLOBALS
+            .storage
+            .filter_relays(|r| r.has_usage_bits(Relay::WRITE) && r.rank != 0)?;
+
+        for relay in relays {
+            self.engage_minion(
+                relay.url.clone(),
+                vec![RelayJob {
+                    reason: RelayConnectionReason::PostEvent,
+                    payload: ToMinionPayload {
+                        job_id: rand::random::<u64>(),
+                        detail: ToMinionPayloadDetail::PostEvent(Box::new(event.clone())),
+                    },
+                }],
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     /// Delete a person list
     pub async fn delete_person_list(&mut self, list: PersonList) -> Result<(), Error> {
         // Get the metadata first, we need it to delete events
@@ -1363,6 +1707,36 @@ impl Overlord {
         Ok(())
     }
 
+    /// Fetch the notes a person has pinned to their profile, and their pin list
+    pub async fn fetch_pin_list(&mut self, pubkey: PublicKey) -> Result<(), Error> {
+        let num_relays_per_person = GLOBALS.storage.read_setting_num_relays_per_person();
+        let relays: Vec<UncheckedUrl> = GLOBALS
+            .storage
+            .get_best_relays(pubkey, Direction::Write)?
+            .iter()
+            .take(num_relays_per_person as usize + 1)
+            .map(|(url, _score)| url.to_unchecked_url())
+            .collect();
+
+        // Fetch the pinned notes we know of but don't have
+        let relay_urls: Vec<RelayUrl> = relays
+            .iter()
+            .filter_map(|u| RelayUrl::try_from_unchecked_url(u).ok())
+            .collect();
+        for id in crate::pins::get_pinned(pubkey)? {
+            self.fetch_event(id, relay_urls.clone()).await?;
+        }
+
+        // Fetch their pin list, in case it changed
+        self.fetch_event_addr(EventAddr {
+            d: "".to_owned(),
+            relays,
+            kind: EventKind::PinList,
+            author: pubkey,
+        })
+        .await
+    }
+
     /// Follow a person by `PublicKey`
     pub async fn follow_pubkey(
         &mut self,
@@ -1385,6 +1759,20 @@ impl Overlord {
         Ok(())
     }
 
+    /// Follow everybody listed in a domain's `/.well-known/nostr.json`
+    pub async fn follow_nip05_domain(
+        domain: String,
+        list: PersonList,
+        public: bool,
+    ) -> Result<(), Error> {
+        std::mem::drop(tokio::spawn(async move {
+            if let Err(e) = crate::nip05::follow_nip05_domain(&domain, list, public).await {
+                tracing::error!("{}", e);
+            }
+        }));
+        Ok(())
+    }
+
     /// Follow a person by a `Profile` (nprofile1...)
     pub async fn follow_nprofile(
         &mut self,
@@ -1442,12 +1830,37 @@ impl Overlord {
         Ok(())
     }
 
+    /// Import a private key from a NIP-06 mnemonic (with an optional BIP-39 passphrase)
+    pub async fn import_mnemonic(
+        mut mnemonic: String,
+        mut mnemonic_passphrase: String,
+        mut password: String,
+    ) -> Result<(), Error> {
+        let result = GLOBALS.identity.set_private_key_from_mnemonic(
+            &mnemonic,
+            &mnemonic_passphrase,
+            &password,
+        );
+        mnemonic.zeroize();
+        mnemonic_passphrase.zeroize();
+        password.zeroize();
+        if let Err(e) = result {
+            GLOBALS
+                .status_queue
+                .write()
+                .write(format!("Mnemonic not accepted: {}", e));
+        }
+        Ok(())
+    }
+
     /// Import a private key
     pub async fn import_priv(mut privkey: String, mut password: String) -> Result<(), Error> {
         if privkey.starts_with("ncryptsec") {
-            let epk = EncryptedPrivateKey(privkey);
-            GLOBALS.identity.set_encrypted_private_key(epk)?;
-            if let Err(e) = GLOBALS.identity.unlock(&password) {
+            let epk = EncryptedPrivateKey(privkey.trim().to_owned());
+            if let Err(e) = GLOBALS
+                .identity
+                .import_encrypted_private_key(epk, &password)
+            {
                 password.zeroize();
                 GLOBALS
                     .status_queue
@@ -1456,6 +1869,18 @@ impl Overlord {
             } else {
                 password.zeroize();
             }
+        } else if crate::nip06::looks_like_mnemonic(&privkey) {
+            let result = GLOBALS
+                .identity
+                .set_private_key_from_mnemonic(&privkey, "", &password);
+            privkey.zeroize();
+            password.zeroize();
+            if let Err(e) = result {
+                GLOBALS
+                    .status_queue
+                    .write()
+                    .write(format!("Mnemonic not accepted: {}", e));
+            }
         } else {
             let maybe_pk1 = PrivateKey::try_from_bech32_string(privkey.trim());
             let maybe_pk2 = PrivateKey::try_from_hex_string(privkey.trim());
@@ -1682,44 +2107,177 @@ impl Overlord {
         Ok(())
     }
 
-    /// Process approved nip46 server operation
-    pub async fn nip46_server_op_approval_response(
-        &mut self,
-        pubkey: PublicKey,
-        parsed_command: ParsedCommand,
-        approval: Approval,
-    ) -> Result<(), Error> {
-        // Clear the request
-        GLOBALS
-            .nip46_approval_requests
-            .write()
-            .retain(|(pk, pc)| *pk != pubkey || *pc != parsed_command);
+    /// Migrate to a brand new key, for when the current key is compromised.
+    ///
+    /// This posts a migration notice signed by the old key which points to the new key,
+    /// then switches to a newly generated key (encrypted under `password`) and republishes
+    /// our metadata, person lists (including contacts and mutes) and relay list under it.
+    /// The old key is kept as another account, and the old->new mapping is recorded.
+    pub async fn migrate_key(&mut self, mut password: String) -> Result<(), Error> {
+        let old_pubkey = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Err((ErrorKind::NoPublicKey, file!(), line!()).into()),
+        };
+        if !GLOBALS.identity.is_unlocked() {
+            password.zeroize();
+            return Err((ErrorKind::NoPrivateKey, file!(), line!()).into());
+        }
 
-        // Handle the request
-        if let Some(mut server) = GLOBALS.storage.read_nip46server(pubkey)? {
-            // Temporarily set the approval (we don't save this)
-            // NOTE: for now we set the server approval setting in memory but don't save it back.
-            //       So the approval only applies to this one time. FIXME: we should use the options
-            //       to approve always (saved) and Until a set time.
-            match parsed_command.method.as_str() {
-                "sign_event" => server.sign_approval = approval,
-                "nip04_encrypt" | "nip44_encrypt" => server.encrypt_approval = approval,
-                "nip04_decrypt" | "nip44_decrypt" => server.decrypt_approval = approval,
-                "nip44_get_key" => {
-                    server.encrypt_approval = approval;
-                    server.decrypt_approval = approval;
-                }
-                _ => {}
-            }
+        let new_private_key = PrivateKey::generate();
+        let new_pubkey = new_private_key.public_key();
 
-            server.handle(&parsed_command)?;
-        }
+        // Prepare the announcement from the old key. It is not signed until the
+        // new key has been saved, so we never point people at a key we could lose.
+        let content = format!(
+            "I have migrated to a new key. Please follow me at nostr:{}",
+            new_pubkey.as_bech32_string()
+        );
+        let tags = vec![Tag::new_pubkey(new_pubkey, None, None)];
+        let pre_event = Self::build_post(content, tags, None, None).await?;
 
-        Ok(())
-    }
+        // Keep the old key around as another account
+        GLOBALS.storage.stash_active_account(None)?;
 
-    /// Trigger the relay picker to find relays for people not fully covered
-    pub async fn refresh_scores_and_pick_relays(&mut self) -> Result<(), Error> {
+        // Switch to the new key (our lists and relays carry over), which saves it
+        // encrypted. The old identity is held, still unlocked, to sign the announcement.
+        let old_identity = std::mem::take(&mut *GLOBALS.identity.inner.write());
+        let result = GLOBALS.identity.set_private_key(new_private_key, &password);
+        password.zeroize();
+        if let Err(e) = result {
+            *GLOBALS.identity.inner.write() = old_identity;
+            return Err(e);
+        }
+
+        GLOBALS.storage.add_key_migration(
+            old_pubkey,
+            new_pubkey,
+            Unixtime::now().unwrap(),
+            None,
+        )?;
+        *GLOBALS.own_lists.write() = OwnListsLoad::default();
+
+        // Announce the new key
+        let event = old_identity.sign_event(pre_event)?;
+        self.publish_post(event).await?;
+
+        // Republish everything under the new key
+        if let Some(metadata) = GLOBALS
+            .storage
+            .read_person(&old_pubkey)?
+            .and_then(|p| p.metadata)
+        {
+            self.push_metadata(metadata).await?;
+        }
+        for (list, _) in GLOBALS.storage.get_all_person_list_metadata()? {
+            self.push_person_list(list).await?;
+        }
+        self.advertise_relay_list().await?;
+
+        // Resubscribe under the new key. We don't drop the minions first because
+        // they are still posting the events above.
+        self.start_long_lived_subscriptions().await?;
+        GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+
+        GLOBALS.status_queue.write().write(format!(
+            "Migrated to new key {}",
+            new_pubkey.as_bech32_string()
+        ));
+
+        Ok(())
+    }
+
+    /// Pair with a NIP-46 client that offered a `nostrconnect://` URI
+    pub async fn nip46_server_connect_from_client(&mut self, uri: String) -> Result<(), Error> {
+        let (server, secret) = Nip46Server::new_from_client(&uri)?;
+
+        // Save the server before acknowledging, so we are ready for the client's requests
+        GLOBALS.storage.write_nip46server(&server, None)?;
+
+        // Listen on the client's relays
+        self.subscribe_nip46(server.relays.clone()).await?;
+
+        // Acknowledge the connection to the client
+        crate::nip46::send_connect_ack(&server, secret)?;
+
+        let name = match &server.metadata {
+            Some(md) => md.name.clone(),
+            None => crate::names::pubkey_short(&server.peer_pubkey),
+        };
+        GLOBALS
+            .status_queue
+            .write()
+            .write(format!("Nostr Connect paired with {}", name));
+
+        Ok(())
+    }
+
+    /// Process approved nip46 server operation
+    pub async fn nip46_server_op_approval_response(
+        &mut self,
+        pubkey: PublicKey,
+        parsed_command: ParsedCommand,
+        approval: Approval,
+    ) -> Result<(), Error> {
+        // Clear the request
+        GLOBALS
+            .nip46_approval_requests
+            .write()
+            .retain(|(pk, pc)| *pk != pubkey || *pc != parsed_command);
+
+        // Handle the request
+        if let Some(server) = GLOBALS.storage.read_nip46server(pubkey)? {
+            if matches!(approval, Approval::None) {
+                return server.decline(&parsed_command);
+            }
+
+            // Remember standing approvals. "Always" is remembered as a rule for the
+            // specific event kind or counterparty that was asked about.
+            let method = parsed_command.method.as_str();
+            let first_param = parsed_command.params.first();
+            let kind = first_param.and_then(|p| crate::nip46::requested_kind(p));
+            let counterparty =
+                first_param.and_then(|p| PublicKey::try_from_hex_string(p, true).ok());
+            GLOBALS.storage.modify_nip46server(
+                pubkey,
+                |stored| match (method, approval, kind, counterparty) {
+                    (_, Approval::Once, _, _) => {}
+                    ("sign_event", Approval::Always, Some(kind), _) => {
+                        stored.set_sign_kind_rule(kind, Some(Nip46Permission::Allow));
+                    }
+                    ("sign_event", _, _, _) => stored.sign_approval = approval,
+                    (
+                        "nip04_encrypt" | "nip44_encrypt" | "nip04_decrypt" | "nip44_decrypt"
+                        | "nip44_get_key",
+                        Approval::Always,
+                        _,
+                        Some(other),
+                    ) => {
+                        stored.set_counterparty_rule(other, Some(Nip46Permission::Allow));
+                    }
+                    ("nip04_encrypt" | "nip44_encrypt", _, _, _) => {
+                        stored.encrypt_approval = approval
+                    }
+                    ("nip04_decrypt" | "nip44_decrypt", _, _, _) => {
+                        stored.decrypt_approval = approval
+                    }
+                    ("nip44_get_key", _, _, _) => {
+                        stored.encrypt_approval = approval;
+                        stored.decrypt_approval = approval;
+                    }
+                    _ => {}
+                },
+                None,
+            )?;
+
+            server.audit(&parsed_command, Nip46Decision::AllowedByUser)?;
+            server.execute(&parsed_command)?;
+        }
+
+        Ok(())
+    }
+
+    /// Trigger the relay picker to find relays for people not fully covered
+    pub async fn refresh_scores_and_pick_relays(&mut self) -> Result<(), Error> {
         // When manually doing this, we refresh person_relay scores first which
         // often change if the user just added follows.
         GLOBALS.relay_picker.refresh_person_relay_scores().await?;
@@ -1767,16 +2325,31 @@ impl Overlord {
     pub async fn post(
         &mut self,
         content: String,
-        mut tags: Vec<Tag>,
+        tags: Vec<Tag>,
         reply_to: Option<Id>,
         dm_channel: Option<DmChannel>,
     ) -> Result<(), Error> {
+        if GLOBALS.identity.public_key().is_none() {
+            tracing::warn!("No public key! Not posting");
+            return Ok(());
+        }
+
+        let pre_event = Self::build_post(content, tags, reply_to, dm_channel).await?;
+        let event = Self::sign_post(pre_event)?;
+        self.publish_post(event).await
+    }
+
+    /// Build the event for a note or DM, adding tags for what it references
+    /// and replies to. It is created now.
+    async fn build_post(
+        content: String,
+        mut tags: Vec<Tag>,
+        reply_to: Option<Id>,
+        dm_channel: Option<DmChannel>,
+    ) -> Result<PreEvent, Error> {
         let public_key = match GLOBALS.identity.public_key() {
             Some(pk) => pk,
-            None => {
-                tracing::warn!("No public key! Not posting");
-                return Ok(());
-            }
+            None => return Err(ErrorKind::NoPublicKey.into()),
         };
 
         let pre_event = match dm_channel {
@@ -1825,14 +2398,24 @@ impl Overlord {
                     match bech32 {
                         NostrBech32::EventAddr(ea) => {
                             add_addr_to_tags(&mut tags, ea, Some("mention".to_string())).await;
+                            // NIP-18: quote reposts carry a 'q' tag
+                            add_quote_to_tags(&mut tags, bech32.clone()).await;
                         }
                         NostrBech32::EventPointer(ep) => {
                             // NIP-10: "Those marked with "mention" denote a quoted or reposted event id."
-                            add_event_to_tags(&mut tags, ep.id, None, "mention").await;
+                            add_event_to_tags(
+                                &mut tags,
+                                ep.id,
+                                ep.relays.first().cloned(),
+                                "mention",
+                            )
+                            .await;
+                            add_quote_to_tags(&mut tags, bech32.clone()).await;
                         }
                         NostrBech32::Id(id) => {
                             // NIP-10: "Those marked with "mention" denote a quoted or reposted event id."
                             add_event_to_tags(&mut tags, *id, None, "mention").await;
+                            add_quote_to_tags(&mut tags, bech32.clone()).await;
                         }
                         NostrBech32::Profile(prof) => {
                             if dm_channel.is_none() {
@@ -1960,8 +2543,30 @@ impl Overlord {
             }
         };
 
+        Ok(pre_event)
+    }
+
+    // Sign a post, with proof of work if the settings ask for it
+    fn sign_post(pre_event: PreEvent) -> Result<Event, Error> {
+        let powint = GLOBALS.storage.read_setting_pow();
+        if powint > 0 {
+            let (work_sender, work_receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                work_logger(work_receiver, powint);
+            });
+            GLOBALS
+                .identity
+                .sign_event_with_pow(pre_event, powint, Some(work_sender))
+        } else {
+            GLOBALS.identity.sign_event(pre_event)
+        }
+    }
+
+    /// Process a signed post locally, then send it to our write relays and
+    /// the read relays of everybody tagged in it
+    async fn publish_post(&mut self, event: Event) -> Result<(), Error> {
         // Copy the tagged pubkeys for determine which relays to send to
-        let mut tagged_pubkeys: Vec<PublicKey> = pre_event
+        let mut tagged_pubkeys: Vec<PublicKey> = event
             .tags
             .iter()
             .filter_map(|t| {
@@ -1973,21 +2578,6 @@ impl Overlord {
             })
             .collect();
 
-        let event = {
-            let powint = GLOBALS.storage.read_setting_pow();
-            if powint > 0 {
-                let (work_sender, work_receiver) = mpsc::channel();
-                std::thread::spawn(move || {
-                    work_logger(work_receiver, powint);
-                });
-                GLOBALS
-                    .identity
-                    .sign_event_with_pow(pre_event, powint, Some(work_sender))?
-            } else {
-                GLOBALS.identity.sign_event(pre_event)?
-            }
-        };
-
         // Process this event locally
         crate::process::process_new_event(&event, None, None, false, false).await?;
 
@@ -2111,6 +2701,68 @@ impl Overlord {
         Ok(())
     }
 
+    /// Publish the user's bookmark list (empty dtag) or one of their bookmark sets
+    pub async fn push_bookmark_list(&mut self, dtag: String) -> Result<(), Error> {
+        if !GLOBALS.identity.is_unlocked() {
+            GLOBALS.status_queue.write().write(
+                "Your bookmarks were saved locally, but you must be signed in to publish them."
+                    .to_string(),
+            );
+            return Ok(());
+        }
+
+        if !Self::own_lists_loaded_or_defer(ToOverlordMessage::PushBookmarkList(dtag.clone())) {
+            return Ok(());
+        }
+
+        let event = crate::bookmarks::generate_bookmark_list_event(&dtag)?;
+
+        // process event locally
+        crate::process::process_new_event(&event, None, None, false, false).await?;
+
+        // Push to all of the relays we post to
+        let relays: Vec<Relay> = GLOBALS
+            .storage
+            .filter_relays(|r| r.has_usage_bits(Relay::WRITE) && r.rank != 0)?;
+
+        for relay in relays {
+            tracing::debug!("Pushing bookmarks to {}", &relay.url);
+
+            self.engage_minion(
+                relay.url.clone(),
+                vec![RelayJob {
+                    reason: RelayConnectionReason::PostBookmarks,
+                    payload: ToMinionPayload {
+                        job_id: rand::random::<u64>(),
+                        detail: ToMinionPayloadDetail::PostEvent(Box::new(event.clone())),
+                    },
+                }],
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    // Our lists on relays may be newer than our local copies. Until they have
+    // loaded (and been merged into ours), publishing would overwrite them, so
+    // the message is held and sent again once they have. Returns whether they
+    // have loaded.
+    fn own_lists_loaded_or_defer(message: ToOverlordMessage) -> bool {
+        {
+            let mut own_lists = GLOBALS.own_lists.write();
+            if own_lists.loaded_for(GLOBALS.identity.public_key()) {
+                return true;
+            }
+            own_lists.deferred.push(message);
+        }
+        GLOBALS.status_queue.write().write(
+            "Your lists are still loading from your relays. Your change will be published once they have."
+                .to_string(),
+        );
+        false
+    }
+
     /// Publish the user's specified PersonList
     pub async fn push_person_list(&mut self, list: PersonList) -> Result<(), Error> {
         let metadata = match GLOBALS.storage.get_person_list_metadata(list)? {
@@ -2123,6 +2775,17 @@ impl Overlord {
         // process event locally
         crate::process::process_new_event(&event, None, None, false, false).await?;
 
+        // What we pushed is now our common base
+        let sync_base: Vec<(PublicKey, bool)> = GLOBALS
+            .storage
+            .get_people_in_list(list)?
+            .drain(..)
+            .map(|(pk, public)| (pk, public && !metadata.private))
+            .collect();
+        GLOBALS
+            .storage
+            .set_person_list_sync_base(event.pubkey, list, &sync_base, None)?;
+
         // Push to all of the relays we post to
         let relays: Vec<Relay> = GLOBALS
             .storage
@@ -2259,16 +2922,10 @@ impl Overlord {
             }
         };
 
-        let relay_url = {
-            let seen_on = GLOBALS.storage.get_event_seen_on_relay(reposted_event.id)?;
-            if seen_on.is_empty() {
-                Relay::recommended_relay_for_reply(id)
-                    .await?
-                    .map(|rr| rr.to_unchecked_url())
-            } else {
-                seen_on.get(0).map(|(rurl, _)| rurl.to_unchecked_url())
-            }
-        };
+        // Hint at the relays where the event was most recently seen
+        let relay_url = Relay::recommended_relay_for_event(id)
+            .await?
+            .map(|rr| rr.to_unchecked_url());
 
         let kind: EventKind;
         let mut tags: Vec<Tag> = vec![
@@ -2282,13 +2939,20 @@ impl Overlord {
             // Add 'k' tag
             tags.push(Tag::new_kind(reposted_event.kind));
 
+            // Replaceable and addressable events (e.g. long-form 30023) are
+            // also referenced by address, so the repost follows their latest version
             if reposted_event.kind.is_replaceable() {
+                let mut relays: Vec<UncheckedUrl> = Relay::event_relay_hints(id)?
+                    .iter()
+                    .take(3)
+                    .map(|rurl| rurl.to_unchecked_url())
+                    .collect();
+                if relays.is_empty() {
+                    relays.extend(relay_url.clone());
+                }
                 let ea = EventAddr {
                     d: reposted_event.parameter().unwrap_or("".to_string()),
-                    relays: match relay_url {
-                        Some(url) => vec![url.clone()],
-                        None => vec![],
-                    },
+                    relays,
                     kind: reposted_event.kind,
                     author: reposted_event.pubkey,
                 };
@@ -2372,6 +3036,67 @@ impl Overlord {
         Ok(())
     }
 
+    /// Put a note or DM in the outbox, to be posted at `send_at`. If `sign_now`,
+    /// it is signed now with `send_at` as its creation time. Otherwise it is signed
+    /// when posted. Either way it is not posted while the key is locked.
+    pub async fn schedule_post(
+        &mut self,
+        content: String,
+        tags: Vec<Tag>,
+        in_reply_to: Option<Id>,
+        dm_channel: Option<DmChannel>,
+        send_at: Unixtime,
+        sign_now: bool,
+    ) -> Result<(), Error> {
+        let public_key = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Err(ErrorKind::NoPublicKey.into()),
+        };
+
+        let mut post = ScheduledPost {
+            id: crate::new_local_id(),
+            send_at,
+            content,
+            tags,
+            in_reply_to,
+            dm_channel: dm_channel.map(|dmc| dmc.keys().to_vec()),
+            event: None,
+            missed: false,
+            sending: false,
+        };
+        if sign_now {
+            post.event = Some(Self::sign_scheduled_post(&post, send_at).await?);
+        }
+
+        GLOBALS
+            .storage
+            .write_scheduled_post(public_key, &post, None)?;
+
+        GLOBALS
+            .status_queue
+            .write()
+            .write("Post scheduled.".to_owned());
+
+        Ok(())
+    }
+
+    // Build and sign a scheduled post, created at the given time
+    async fn sign_scheduled_post(
+        post: &ScheduledPost,
+        created_at: Unixtime,
+    ) -> Result<Event, Error> {
+        let dm_channel = post.dm_channel.as_ref().map(|keys| DmChannel::new(keys));
+        let mut pre_event = Self::build_post(
+            post.content.clone(),
+            post.tags.clone(),
+            post.in_reply_to,
+            dm_channel,
+        )
+        .await?;
+        pre_event.created_at = created_at;
+        Self::sign_post(pre_event)
+    }
+
     /// Search people and notes in the local database.
     /// Search results eventually arrive in `GLOBALS.people_search_results` and `GLOBALS.note_search_results`
     pub async fn search(mut text: String) -> Result<(), Error> {
@@ -2498,6 +3223,138 @@ impl Overlord {
         Ok(())
     }
 
+    /// Post a post from the outbox now, whether or not it is due. If it needs
+    /// signing but the key is locked, it is flagged as missed instead. If
+    /// posting fails, it stays in the outbox flagged as missed.
+    pub async fn send_scheduled_post(&mut self, id: u64) -> Result<(), Error> {
+        let public_key = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Err(ErrorKind::NoPublicKey.into()),
+        };
+
+        let mut post = match GLOBALS.storage.read_scheduled_post(public_key, id)? {
+            Some(post) if !post.sending => post,
+            _ => return Ok(()),
+        };
+
+        // Nothing is posted while the key is locked, even if it was signed
+        // ahead of time. It stays in the outbox until then.
+        if !GLOBALS.identity.is_unlocked() {
+            GLOBALS
+                .status_queue
+                .write()
+                .write("Unlock your key to post this.".to_owned());
+            return Ok(());
+        }
+
+        let now = Unixtime::now().unwrap();
+        let event = match &post.event {
+            // If posted early, sign it again so it isn't dated in the future
+            Some(event) if event.created_at <= now => event.clone(),
+            _ => Self::sign_scheduled_post(&post, now).await?,
+        };
+
+        // Flag it as sending so it cannot be posted twice
+        post.sending = true;
+        post.missed = false;
+        GLOBALS
+            .storage
+            .write_scheduled_post(public_key, &post, None)?;
+
+        match self.publish_post(event).await {
+            Ok(()) => GLOBALS.storage.delete_scheduled_post(public_key, id, None),
+            Err(e) => {
+                post.sending = false;
+                post.missed = true;
+                GLOBALS
+                    .storage
+                    .write_scheduled_post(public_key, &post, None)?;
+                GLOBALS.status_queue.write().write(
+                    "A scheduled post could not be posted. Post it again from Scheduled Posts."
+                        .to_owned(),
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// Post the posts in the outbox that are due, except those flagged as missed.
+    /// While the key is locked they are held until it is unlocked.
+    pub async fn send_scheduled_posts(&mut self) -> Result<(), Error> {
+        let now = Unixtime::now().unwrap();
+
+        if !GLOBALS.identity.is_unlocked() {
+            let held = crate::scheduled::get_scheduled_posts()
+                .iter()
+                .filter(|post| post.send_at <= now && !post.missed && !post.sending)
+                .count();
+            if held > self.held_scheduled_posts {
+                GLOBALS.status_queue.write().write(format!(
+                    "{} scheduled post(s) are due. They will be posted when you unlock your key.",
+                    held
+                ));
+            }
+            self.held_scheduled_posts = held;
+            return Ok(());
+        }
+        self.held_scheduled_posts = 0;
+
+        for post in crate::scheduled::get_scheduled_posts() {
+            if post.send_at <= now && !post.missed {
+                if let Err(e) = self.send_scheduled_post(post.id).await {
+                    tracing::error!("{}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // At startup, post what came due while we were not running, or flag it as
+    // missed, depending on the settings
+    async fn handle_missed_scheduled_posts(&mut self) -> Result<(), Error> {
+        let public_key = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Ok(()),
+        };
+
+        // A post still flagged as sending was interrupted, and may or may not
+        // have been posted, so leave it to the user
+        for mut post in crate::scheduled::get_scheduled_posts() {
+            if post.sending {
+                post.sending = false;
+                post.missed = true;
+                GLOBALS
+                    .storage
+                    .write_scheduled_post(public_key, &post, None)?;
+            }
+        }
+
+        if GLOBALS.storage.read_setting_send_missed_scheduled_posts() {
+            return self.send_scheduled_posts().await;
+        }
+
+        let now = Unixtime::now().unwrap();
+        let mut count = 0;
+        for mut post in crate::scheduled::get_scheduled_posts() {
+            if post.send_at <= now && !post.missed {
+                post.missed = true;
+                GLOBALS
+                    .storage
+                    .write_scheduled_post(public_key, &post, None)?;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            GLOBALS.status_queue.write().write(format!(
+                "{} scheduled post(s) came due while gossip was not running. See Scheduled Posts.",
+                count
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Set a particular person as active in the `People` structure. This affects the results of
     /// some functions of that structure
     pub async fn set_active_person(pubkey: PublicKey) -> Result<(), Error> {
@@ -2557,6 +3414,51 @@ impl Overlord {
             .await?;
         }
 
+        // Pinned notes go at the top of the feed
+        self.fetch_pin_list(pubkey).await?;
+
+        Ok(())
+    }
+
+    /// Pin (or unpin) one of the user's notes to their profile, and publish their pin list
+    pub async fn set_pinned(&mut self, id: Id, pinned: bool) -> Result<(), Error> {
+        if !GLOBALS.identity.is_unlocked() {
+            GLOBALS
+                .status_queue
+                .write()
+                .write("You must be signed in to pin notes.".to_string());
+            return Ok(());
+        }
+
+        // The pin list is rebuilt from our latest one, which must have loaded
+        if !Self::own_lists_loaded_or_defer(ToOverlordMessage::SetPinned(id, pinned)) {
+            return Ok(());
+        }
+
+        let event = crate::pins::generate_pin_list_event(id, pinned)?;
+
+        // process event locally
+        crate::process::process_new_event(&event, None, None, false, false).await?;
+
+        // Push to all of the relays we post to
+        let relays: Vec<Relay> = GLOBALS
+            .storage
+            .filter_relays(|r| r.has_usage_bits(Relay::WRITE) && r.rank != 0)?;
+
+        for relay in relays {
+            self.engage_minion(
+                relay.url.clone(),
+                vec![RelayJob {
+                    reason: RelayConnectionReason::PostEvent,
+                    payload: ToMinionPayload {
+                        job_id: rand::random::<u64>(),
+                        detail: ToMinionPayloadDetail::PostEvent(Box::new(event.clone())),
+                    },
+                }],
+            )
+            .await?;
+        }
+
         Ok(())
     }
 
@@ -2755,6 +3657,11 @@ impl Overlord {
                 .map(|relay| relay.url.clone())
                 .collect(),
         };
+
+        // Our lists load from these relays
+        let pubkey = GLOBALS.identity.public_key();
+        GLOBALS.own_lists.write().start(pubkey, &config_relays);
+
         for relay_url in config_relays.iter() {
             self.engage_minion(
                 relay_url.to_owned(),
@@ -2767,6 +3674,11 @@ impl Overlord {
                 }],
             )
             .await?;
+
+            // Don't wait on relays we did not connect to
+            if !GLOBALS.connected_relays.contains_key(relay_url) {
+                GLOBALS.own_lists.write().relay_done(relay_url, pubkey);
+            }
         }
 
         Ok(())
@@ -2881,6 +3793,72 @@ impl Overlord {
         Ok(())
     }
 
+    /// Switch to another account held in this profile. The active account's person
+    /// lists and relay usage are stashed and the other account's are restored. Events
+    /// are shared between accounts. Subscriptions are restarted for the new account.
+    pub async fn switch_account(&mut self, pubkey: PublicKey) -> Result<(), Error> {
+        if GLOBALS.identity.public_key() == Some(pubkey) {
+            return Ok(());
+        }
+
+        let account = match GLOBALS.storage.read_account(pubkey)? {
+            Some(account) => account,
+            None => {
+                GLOBALS
+                    .status_queue
+                    .write()
+                    .write("That account is not in this profile.".to_owned());
+                return Ok(());
+            }
+        };
+
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+        GLOBALS.storage.stash_active_account(Some(&mut txn))?;
+        GLOBALS.storage.restore_account(&account, Some(&mut txn))?;
+        txn.commit()?;
+
+        GLOBALS.identity.switch_to(
+            pubkey,
+            account.encrypted_private_key.map(EncryptedPrivateKey),
+        )?;
+        *GLOBALS.own_lists.write() = OwnListsLoad::default();
+        Self::delegation_reset().await?;
+        self.restart_for_account().await?;
+
+        let name = match GLOBALS.storage.read_person(&pubkey)? {
+            Some(person) => person.best_name(),
+            None => pubkey.as_bech32_string(),
+        };
+        GLOBALS
+            .status_queue
+            .write()
+            .write(format!("Switched to account {}", name));
+
+        Ok(())
+    }
+
+    // After the active account changes, drop everything that was subscribed on behalf
+    // of the previous account and start over.
+    async fn restart_for_account(&mut self) -> Result<(), Error> {
+        let _ = self.to_minions.send(ToMinionMessage {
+            target: "all".to_string(),
+            payload: ToMinionPayload {
+                job_id: 0,
+                detail: ToMinionPayloadDetail::Shutdown,
+            },
+        });
+
+        GLOBALS.relay_picker.init().await?;
+        GLOBALS.feed.sync_recompute();
+        GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+
+        if GLOBALS.identity.public_key().is_some() {
+            self.start_long_lived_subscriptions().await?;
+        }
+
+        Ok(())
+    }
+
     /// Subscribe, fetch, and update metadata for the person
     pub async fn update_metadata(&mut self, pubkey: PublicKey) -> Result<(), Error> {
         // Indicate that we are doing this, as the People manager wants to know
@@ -2951,6 +3929,10 @@ impl Overlord {
     }
 
     /// Update the local person list from the last event received.
+    ///
+    /// If `merge` is false, the local list is overwritten. Otherwise it is
+    /// three-way merged with the event using the last synchronized state as the
+    /// common base, so that additions and removals on both sides are kept.
     pub async fn update_person_list(&mut self, list: PersonList, merge: bool) -> Result<(), Error> {
         // we cannot do anything without an identity setup first
         let my_pubkey = match GLOBALS.storage.read_setting_public_key() {
@@ -2993,8 +3975,17 @@ impl Overlord {
 
         let mut entries: Vec<(PublicKey, bool)> = Vec::new();
 
+        // Muted words, hashtags and threads (only used for the mute list)
+        let mut mutes: Vec<(Mute, bool)> = Vec::new();
+
         // Public entries
         for tag in &event.tags {
+            if list == PersonList::Muted {
+                if let Some(mute) = Mute::from_tag(tag) {
+                    mutes.push((mute, !metadata.private));
+                }
+            }
+
             if let Ok((pubkey, rurl, petname)) = tag.parse_pubkey() {
                 // If our list is marked private, move these public entries to private ones
                 let public = !metadata.private;
@@ -3015,8 +4006,13 @@ impl Overlord {
             }
         }
 
+        // Whether the event has private entries that we cannot read
+        let locked = list != PersonList::Followed
+            && !event.content.is_empty()
+            && !GLOBALS.identity.is_unlocked();
+
         if list != PersonList::Followed && !event.content.is_empty() {
-            if GLOBALS.identity.is_unlocked() {
+            if !locked {
                 // Private entries
                 let decrypted_content =
                     GLOBALS.identity.decrypt_nip04(&my_pubkey, &event.content)?;
@@ -3024,6 +4020,11 @@ impl Overlord {
                 let tags: Vec<Tag> = serde_json::from_slice(&decrypted_content)?;
 
                 for tag in &tags {
+                    if list == PersonList::Muted {
+                        if let Some(mute) = Mute::from_tag(tag) {
+                            mutes.push((mute, false));
+                        }
+                    }
                     if let Ok((pubkey, _, _)) = tag.parse_pubkey() {
                         // Save the pubkey
                         entries.push((pubkey.to_owned(), false));
@@ -3040,25 +4041,60 @@ impl Overlord {
             }
         }
 
-        if !merge {
-            GLOBALS.storage.clear_person_list(list, Some(&mut txn))?;
-        }
+        let local = GLOBALS.storage.get_people_in_list(list)?;
+
+        // When merging, do a three-way merge against the last synchronized state.
+        // If we could not read the private entries, keep our own.
+        let result = if merge {
+            let base = GLOBALS.storage.get_person_list_sync_base(my_pubkey, list)?;
+            crate::people::merge_person_lists(&local, &entries, &base, locked)
+        } else if locked {
+            let mut result = entries.clone();
+            for (pubkey, public) in &local {
+                if !*public && !result.iter().any(|(pk, _)| pk == pubkey) {
+                    result.push((*pubkey, false));
+                }
+            }
+            result
+        } else {
+            entries.clone()
+        };
 
-        for (pubkey, public) in &entries {
+        GLOBALS.storage.clear_person_list(list, Some(&mut txn))?;
+
+        for (pubkey, public) in &result {
             GLOBALS
                 .storage
                 .add_person_to_list(pubkey, list, *public, Some(&mut txn))?;
+        }
+
+        for (pubkey, _) in local.iter().chain(result.iter()) {
             GLOBALS.ui_people_to_invalidate.write().push(*pubkey);
         }
 
+        // The relay event is now our common base, unless we could only read part of it
+        if !locked {
+            GLOBALS
+                .storage
+                .set_person_list_sync_base(my_pubkey, list, &entries, Some(&mut txn))?;
+        }
+
+        if list == PersonList::Muted {
+            for (mute, public) in GLOBALS.storage.read_mutes(my_pubkey)? {
+                if (merge || (locked && !public)) && !mutes.iter().any(|(m, _)| *m == mute) {
+                    mutes.push((mute, public));
+                }
+            }
+            GLOBALS
+                .storage
+                .write_mutes(my_pubkey, &mutes, Some(&mut txn))?;
+            GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+        }
+
         let last_edit = if merge { now } else { event.created_at };
 
         metadata.last_edit_time = last_edit;
-        metadata.len = if merge {
-            GLOBALS.storage.get_people_in_list(list)?.len()
-        } else {
-            entries.len()
-        };
+        metadata.len = result.len();
 
         GLOBALS
             .storage
@@ -3251,6 +4287,57 @@ impl Overlord {
         Ok(())
     }
 
+    /// Change the text and time of a post in the outbox. This clears its
+    /// missed flag. If it was signed ahead of time, it is signed again.
+    pub async fn update_scheduled_post(
+        &mut self,
+        id: u64,
+        content: String,
+        send_at: Unixtime,
+    ) -> Result<(), Error> {
+        let public_key = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Err(ErrorKind::NoPublicKey.into()),
+        };
+
+        let mut post = match GLOBALS.storage.read_scheduled_post(public_key, id)? {
+            Some(post) => post,
+            None => return Ok(()),
+        };
+
+        post.content = content;
+        post.send_at = send_at;
+        post.missed = false;
+        if post.event.is_some() {
+            if !GLOBALS.identity.is_unlocked() {
+                return Err((ErrorKind::NoPrivateKey, file!(), line!()).into());
+            }
+            post.event = Some(Self::sign_scheduled_post(&post, send_at).await?);
+        }
+
+        GLOBALS
+            .storage
+            .write_scheduled_post(public_key, &post, None)?;
+
+        Ok(())
+    }
+
+    /// Upload a file to the configured media server. Progress and the result are
+    /// tracked in `GLOBALS.uploads` under `upload_id`, which should come from
+    /// [new_upload](crate::upload::new_upload). Failed uploads are no longer tracked.
+    pub fn upload_file(upload_id: u64, path: PathBuf) -> Result<(), Error> {
+        std::mem::drop(tokio::spawn(async move {
+            if let Err(e) = crate::upload::upload_file(upload_id, &path).await {
+                GLOBALS.status_queue.write().write(format!(
+                    "Could not upload {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        }));
+        Ok(())
+    }
+
     /// Set which notes are currently visible to the user. This is used to modify subscriptions
     /// that query for likes, zaps, and deletions. Such subscriptions only query for that data
     /// for events currently in view, to keep them small.
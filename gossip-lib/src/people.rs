@@ -1,6 +1,7 @@
 use crate::comms::ToOverlordMessage;
 use crate::error::{Error, ErrorKind};
 use crate::globals::GLOBALS;
+use crate::media::ImageOptions;
 use dashmap::{DashMap, DashSet};
 use gossip_relay_picker::Direction;
 use image::RgbaImage;
@@ -9,6 +10,7 @@ use nostr_types::{
     UncheckedUrl, Unixtime, Url,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -23,6 +25,26 @@ pub type PersonList = crate::storage::types::PersonList1;
 /// PersonListMetadata type, aliased to the latest version
 pub type PersonListMetadata = crate::storage::types::PersonListMetadata3;
 
+/// Mute type (muted words, hashtags and threads), aliased to the latest version
+pub type Mute = crate::storage::types::Mute1;
+
+/// Changes made to a person list on relays since it was last synchronized, which
+/// would be lost if the local list were pushed without merging first
+#[derive(Debug, Clone, Default)]
+pub struct PersonListConflicts {
+    /// People added remotely who are not in the local list
+    pub added_remotely: Vec<PublicKey>,
+
+    /// People removed remotely who are still in the local list
+    pub removed_remotely: Vec<PublicKey>,
+}
+
+impl PersonListConflicts {
+    pub fn is_empty(&self) -> bool {
+        self.added_remotely.is_empty() && self.removed_remotely.is_empty()
+    }
+}
+
 /// Handles people and remembers what needs to be done for each, such as fetching
 /// metadata or avatars.
 pub struct People {
@@ -163,6 +185,18 @@ impl People {
         self.people_of_interest.insert(pubkey);
     }
 
+    /// Mark these people as people who the UI wants fresh metadata for.
+    pub fn people_of_interest(&self, pubkeys: &[PublicKey]) {
+        // Don't set if metadata if disabled
+        if !GLOBALS.storage.read_setting_automatically_fetch_metadata() {
+            return;
+        }
+
+        for pubkey in pubkeys {
+            self.people_of_interest.insert(*pubkey);
+        }
+    }
+
     /// The overlord calls this to indicate that it is fetching metadata
     /// for this person from relays
     pub fn metadata_fetch_initiated(&self, pubkeys: &[PublicKey]) {
@@ -273,6 +307,9 @@ impl People {
         person.metadata_last_received = now.0;
         GLOBALS.storage.write_person(&person, None)?;
 
+        // Keep it in their history, even if it is not the newest
+        crate::metadata_history::record_metadata_version(pubkey, &metadata, asof).await?;
+
         // Determine whether it is fresh
         let fresh = match person.metadata_created_at {
             Some(metadata_created_at) => asof.0 > metadata_created_at,
@@ -426,6 +463,18 @@ impl People {
                             .load(Ordering::Relaxed)
                         / 100;
 
+                    // Use the decoded avatar from last time, if we kept it
+                    let variant = ImageOptions {
+                        square: true,
+                        force_resize: true,
+                        round: rounded,
+                    }
+                    .thumbnail_variant(size);
+                    if let Some(image) = GLOBALS.fetcher.read_thumbnail(&url, &variant) {
+                        GLOBALS.people.avatars_temp.insert(apubkey, image);
+                        return;
+                    }
+
                     match crate::media::load_image_bytes(
                         &bytes, true, // crop square
                         size, // default size,
@@ -433,6 +482,13 @@ impl People {
                         rounded,
                     ) {
                         Ok(color_image) => {
+                            if let Err(e) =
+                                GLOBALS
+                                    .fetcher
+                                    .write_thumbnail(&url, &variant, &color_image)
+                            {
+                                tracing::warn!("{}", e);
+                            }
                             GLOBALS.people.avatars_temp.insert(apubkey, color_image);
                         }
                         Err(_) => {
@@ -472,16 +528,26 @@ impl People {
                 let mut score = 0u16;
                 let mut result_name = String::from("");
 
-                // search for users by name
-                let name = person.best_name();
-                let matchable = name.to_lowercase();
-                if matchable.starts_with(&search) {
-                    score = 300;
-                    result_name = name.to_string();
-                }
-                if matchable.contains(&search) {
-                    score = 200;
-                    result_name = name.to_string();
+                // search for users by our petname for them, then by the
+                // names they claim
+                let names = [
+                    person.petname.clone(),
+                    person.display_name().map(|n| n.to_owned()),
+                    person.name().map(|n| n.to_owned()),
+                ];
+                for name in names.iter().flatten() {
+                    let matchable = name.to_lowercase();
+                    let name_score = if matchable.starts_with(&search) {
+                        300
+                    } else if matchable.contains(&search) {
+                        200
+                    } else {
+                        0
+                    };
+                    if name_score > score {
+                        score = name_score;
+                        result_name = name.to_string();
+                    }
                 }
 
                 // search for users by nip05 id
@@ -556,9 +622,8 @@ impl People {
 
         // Load most recent existing event, if any
         let existing_event: Option<Event> = match kind {
-            EventKind::ContactList | EventKind::MuteList => {
+            EventKind::ContactList => {
                 // We fetch for ContactList to preserve the contents
-                // We fetch for MuteList to preserve 't', 'e', and "word" tags
                 GLOBALS.storage.get_replaceable_event(kind, my_pubkey, "")?
             }
             EventKind::FollowSets => {
@@ -567,25 +632,26 @@ impl People {
                     .storage
                     .get_replaceable_event(kind, my_pubkey, &metadata.dtag)?
             }
+            EventKind::MuteList => {
+                // We fetch for MuteList to preserve tags we don't manage
+                GLOBALS.storage.get_replaceable_event(kind, my_pubkey, "")?
+            }
             _ => None,
         };
 
-        // Get all tags off of the existing event
-        // (we use local data to determine public/private, we don't need to remember
-        //  where they were in the existing event)
-        let old_tags = {
+        // Get the public and private tags off of the existing event
+        let (old_public_tags, old_private_tags) = {
             if let Some(ref event) = existing_event {
                 if !event.content.is_empty() && kind != EventKind::ContactList {
                     let decrypted_content =
                         GLOBALS.identity.decrypt_nip04(&my_pubkey, &event.content)?;
-                    let mut tags: Vec<Tag> = serde_json::from_slice(&decrypted_content)?;
-                    tags.extend(event.tags.clone());
-                    tags
+                    let tags: Vec<Tag> = serde_json::from_slice(&decrypted_content)?;
+                    (event.tags.clone(), tags)
                 } else {
-                    event.tags.clone()
+                    (event.tags.clone(), vec![])
                 }
             } else {
-                vec![]
+                (vec![], vec![])
             }
         };
 
@@ -607,7 +673,9 @@ impl People {
             }
 
             // Preserve existing tags that we don't operate on yet
-            for t in &old_tags {
+            // (we use local data to determine public/private, we don't need to remember
+            //  where they were in the existing event)
+            for t in old_private_tags.iter().chain(old_public_tags.iter()) {
                 if t.tagname() == "image" || t.tagname() == "description" {
                     if metadata.private {
                         private_tags.push(t.clone());
@@ -620,26 +688,28 @@ impl People {
 
         // If MuteList
         if person_list == PersonList::Muted {
-            // Preserve existing tags that we don't operate on yet
-            for t in &old_tags {
-                match t.tagname() {
-                    "t" | "e" => {
-                        if metadata.private {
-                            private_tags.push(t.clone());
-                        } else {
-                            public_tags.push(t.clone());
-                        }
-                    }
-                    "word" => {
-                        if metadata.private {
-                            private_tags.push(t.clone());
-                        } else {
-                            public_tags.push(t.clone());
-                        }
-                    }
-                    _ => (),
+            // Add muted words, hashtags and threads
+            for (mute, public) in GLOBALS.storage.read_mutes(my_pubkey)? {
+                if public && !metadata.private {
+                    public_tags.push(mute.to_tag());
+                } else {
+                    private_tags.push(mute.to_tag());
+                }
+            }
+
+            // Preserve other tags that other clients put there, where they were.
+            // People, words, hashtags and threads come from local data.
+            let ours = |t: &&Tag| t.tagname() == "p" || Mute::from_tag(t).is_some();
+            for t in old_public_tags.iter().filter(|t| !ours(t)) {
+                if metadata.private {
+                    private_tags.push(t.clone());
+                } else {
+                    public_tags.push(t.clone());
                 }
             }
+            for t in old_private_tags.iter().filter(|t| !ours(t)) {
+                private_tags.push(t.clone());
+            }
         }
 
         // Add the people
@@ -649,8 +719,8 @@ impl People {
                 public = false;
             }
 
-            // Only include petnames in the ContactList (which is only public people)
-            let petname = if kind == EventKind::ContactList {
+            // Include petnames, except in the mute list
+            let petname = if kind != EventKind::MuteList {
                 if let Some(person) = GLOBALS.storage.read_person(pubkey)? {
                     person.petname.clone()
                 } else {
@@ -707,6 +777,38 @@ impl People {
         GLOBALS.identity.sign_event(pre_event)
     }
 
+    /// Set (or clear) our petname for a person. Lists they are in (other than
+    /// the mute list) are marked as edited, since petnames are published with them.
+    pub fn set_petname(&self, pubkey: &PublicKey, petname: Option<String>) -> Result<(), Error> {
+        let mut person = match GLOBALS.storage.read_person(pubkey)? {
+            Some(person) => person,
+            None => Person::new(*pubkey),
+        };
+        if person.petname == petname {
+            return Ok(());
+        }
+        person.petname = petname;
+        GLOBALS.storage.write_person(&person, None)?;
+
+        let now = Unixtime::now().unwrap();
+        for list in GLOBALS.storage.read_person_lists(pubkey)?.keys() {
+            if *list == PersonList::Muted {
+                continue;
+            }
+            if let Some(mut metadata) = GLOBALS.storage.get_person_list_metadata(*list)? {
+                metadata.last_edit_time = now;
+                GLOBALS
+                    .storage
+                    .set_person_list_metadata(*list, &metadata, None)?;
+            }
+        }
+
+        GLOBALS.ui_people_to_invalidate.write().push(*pubkey);
+        GLOBALS.impersonation.invalidate();
+
+        Ok(())
+    }
+
     /// Follow (or unfollow) the public key
     pub fn follow(
         &self,
@@ -734,6 +836,11 @@ impl People {
 
         GLOBALS.ui_people_to_invalidate.write().push(*pubkey);
 
+        if list == PersonList::Followed {
+            GLOBALS.wot.invalidate();
+            GLOBALS.impersonation.invalidate();
+        }
+
         let _ = GLOBALS
             .to_overlord
             .send(ToOverlordMessage::RefreshScoresAndPickRelays);
@@ -747,6 +854,40 @@ impl People {
         Ok(())
     }
 
+    /// Follow many people at once, refreshing relay picking only once at the end
+    pub fn follow_all(
+        &self,
+        pubkeys: &[PublicKey],
+        list: PersonList,
+        public: bool,
+    ) -> Result<(), Error> {
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+        for pubkey in pubkeys {
+            GLOBALS
+                .storage
+                .add_person_to_list(pubkey, list, public, Some(&mut txn))?;
+        }
+        txn.commit()?;
+
+        for pubkey in pubkeys {
+            // Add to the relay picker. If they are already there, it will be ok.
+            GLOBALS.relay_picker.add_someone(*pubkey)?;
+        }
+
+        GLOBALS.ui_people_to_invalidate.write().extend(pubkeys);
+
+        if list == PersonList::Followed {
+            GLOBALS.wot.invalidate();
+            GLOBALS.impersonation.invalidate();
+        }
+
+        let _ = GLOBALS
+            .to_overlord
+            .send(ToOverlordMessage::RefreshScoresAndPickRelays);
+
+        Ok(())
+    }
+
     /// Clear a person list
     pub(crate) fn clear_person_list(&self, list: PersonList) -> Result<(), Error> {
         GLOBALS.storage.clear_person_list(list, None)?;
@@ -754,6 +895,63 @@ impl People {
         Ok(())
     }
 
+    /// Find changes made to a person list on relays since the last time it was
+    /// synchronized that pushing the local list would discard.
+    pub fn person_list_conflicts(&self, list: PersonList) -> Result<PersonListConflicts, Error> {
+        let my_pubkey = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Ok(Default::default()),
+        };
+
+        let metadata = match GLOBALS.storage.get_person_list_metadata(list)? {
+            Some(m) => m,
+            None => return Ok(Default::default()),
+        };
+
+        let event = match GLOBALS.storage.get_replaceable_event(
+            list.event_kind(),
+            my_pubkey,
+            &metadata.dtag,
+        )? {
+            Some(event) => event,
+            None => return Ok(Default::default()),
+        };
+
+        let remote: HashMap<PublicKey, bool> = person_list_event_entries(&event, metadata.private)?
+            .drain(..)
+            .collect();
+        // If we cannot read the private entries, only compare the public ones
+        let locked = event.kind != EventKind::ContactList
+            && !event.content.is_empty()
+            && !GLOBALS.identity.is_unlocked();
+        let base: HashMap<PublicKey, bool> = GLOBALS
+            .storage
+            .get_person_list_sync_base(my_pubkey, list)?
+            .drain(..)
+            .filter(|(_, public)| *public || !locked)
+            .collect();
+        let local: HashMap<PublicKey, bool> = GLOBALS
+            .storage
+            .get_people_in_list(list)?
+            .drain(..)
+            .filter(|(_, public)| *public || !locked)
+            .collect();
+
+        let mut conflicts: PersonListConflicts = Default::default();
+        for pubkey in remote.keys() {
+            if !base.contains_key(pubkey) && !local.contains_key(pubkey) {
+                conflicts.added_remotely.push(*pubkey);
+            }
+        }
+        for pubkey in base.keys() {
+            if !remote.contains_key(pubkey) && local.contains_key(pubkey) {
+                conflicts.removed_remotely.push(*pubkey);
+            }
+        }
+
+        Ok(conflicts)
+    }
+
     /// Mute (or unmute) a public key
     pub fn mute(&self, pubkey: &PublicKey, mute: bool, public: bool) -> Result<(), Error> {
         let mut txn = GLOBALS.storage.get_write_txn()?;
@@ -796,6 +994,66 @@ impl People {
         Ok(())
     }
 
+    /// Get the words, hashtags and threads that the user has muted. The bool
+    /// is true for public entries. (returns empty on error)
+    pub fn get_mutes(&self) -> Vec<(Mute, bool)> {
+        let my_pubkey = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return vec![],
+        };
+        match GLOBALS.storage.read_mutes(my_pubkey) {
+            Ok(mutes) => mutes,
+            Err(e) => {
+                tracing::error!("{}", e);
+                vec![]
+            }
+        }
+    }
+
+    /// Mute (or unmute) a word, hashtag or thread
+    pub fn set_mute(&self, mute: Mute, muted: bool, public: bool) -> Result<(), Error> {
+        let my_pubkey = match GLOBALS.identity.public_key() {
+            Some(pk) => pk,
+            None => return Err(ErrorKind::NoPublicKey.into()),
+        };
+
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+
+        let mut mutes = GLOBALS.storage.read_mutes(my_pubkey)?;
+        mutes.retain(|(m, _)| *m != mute);
+        if muted {
+            mutes.push((mute, public));
+        }
+        GLOBALS
+            .storage
+            .write_mutes(my_pubkey, &mutes, Some(&mut txn))?;
+
+        if let Some(mut metadata) = GLOBALS
+            .storage
+            .get_person_list_metadata(PersonList::Muted)?
+        {
+            metadata.last_edit_time = Unixtime::now().unwrap();
+            GLOBALS.storage.set_person_list_metadata(
+                PersonList::Muted,
+                &metadata,
+                Some(&mut txn),
+            )?;
+        }
+
+        txn.commit()?;
+
+        GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+        GLOBALS.feed.sync_recompute();
+
+        Ok(())
+    }
+
+    /// Is the event muted by one of the user's muted words, hashtags or threads?
+    /// (This does not consider the author, see `is_person_in_list()` for that)
+    pub fn is_event_muted(&self, event: &Event) -> bool {
+        self.get_mutes().iter().any(|(m, _)| m.matches(event))
+    }
+
     // Returns true if the date passed in is newer than what we already had
     pub(crate) async fn update_relay_list_stamps(
         &self,
@@ -843,8 +1101,17 @@ impl People {
         pubkey: &PublicKey,
         nip05: Option<String>,
         nip05_valid: bool,
+        mismatch: bool,
         nip05_last_checked: u64,
     ) -> Result<(), Error> {
+        crate::nip05::record_nip05_check(
+            pubkey,
+            &nip05,
+            nip05_valid,
+            mismatch,
+            nip05_last_checked,
+        )?;
+
         // Update memory
         if let Some(mut person) = GLOBALS.storage.read_person(pubkey)? {
             if let Some(metadata) = &mut person.metadata {
@@ -893,6 +1160,101 @@ struct Nip05Patch {
     nip05: Option<String>,
 }
 
+/// Three-way merge of a person list. `local` is the local list, `remote` is the
+/// list from the latest relay event, and `base` is the list as of the last time
+/// the two were synchronized. Additions and removals made on either side since
+/// the base are kept. If both sides changed whether an entry is public, the
+/// local side wins.
+///
+/// If `locked`, the private entries of the remote list could not be read. Local
+/// private entries are then kept as they are and only public entries are merged.
+pub fn merge_person_lists(
+    local: &[(PublicKey, bool)],
+    remote: &[(PublicKey, bool)],
+    base: &[(PublicKey, bool)],
+    locked: bool,
+) -> Vec<(PublicKey, bool)> {
+    if locked {
+        let (private, public): (Vec<(PublicKey, bool)>, Vec<(PublicKey, bool)>) =
+            local.iter().copied().partition(|(_, public)| !*public);
+        let remote: Vec<(PublicKey, bool)> = remote
+            .iter()
+            .filter(|(pubkey, _)| !private.iter().any(|(pk, _)| pk == pubkey))
+            .copied()
+            .collect();
+        let base: Vec<(PublicKey, bool)> =
+            base.iter().filter(|(_, public)| *public).copied().collect();
+        let mut output = merge_person_lists(&public, &remote, &base, false);
+        output.extend(private);
+        return output;
+    }
+
+    let remote_map: HashMap<PublicKey, bool> = remote.iter().copied().collect();
+    let base_map: HashMap<PublicKey, bool> = base.iter().copied().collect();
+    let mut output: Vec<(PublicKey, bool)> = Vec::new();
+
+    for (pubkey, local_public) in local {
+        match remote_map.get(pubkey) {
+            Some(remote_public) => {
+                // On both sides. Take the public flag from whichever side changed it.
+                let public = if base_map.get(pubkey) == Some(local_public) {
+                    *remote_public
+                } else {
+                    *local_public
+                };
+                output.push((*pubkey, public));
+            }
+            None => {
+                // Only local. Keep it unless it was removed remotely.
+                if !base_map.contains_key(pubkey) {
+                    output.push((*pubkey, *local_public));
+                }
+            }
+        }
+    }
+
+    for (pubkey, remote_public) in remote {
+        // Only remote. Add it unless it was removed locally.
+        if !local.iter().any(|(pk, _)| pk == pubkey) && !base_map.contains_key(pubkey) {
+            output.push((*pubkey, *remote_public));
+        }
+    }
+
+    output
+}
+
+// Get the people in a person list event, including private entries if we can
+// decrypt them. If the list is private, all entries are considered private.
+pub(crate) fn person_list_event_entries(
+    event: &Event,
+    private: bool,
+) -> Result<Vec<(PublicKey, bool)>, Error> {
+    let mut entries: Vec<(PublicKey, bool)> = Vec::new();
+
+    for tag in &event.tags {
+        if let Ok((pubkey, _, _)) = tag.parse_pubkey() {
+            entries.push((pubkey, !private));
+        }
+    }
+
+    if event.kind != EventKind::ContactList
+        && !event.content.is_empty()
+        && GLOBALS.identity.is_unlocked()
+    {
+        let decrypted_content = GLOBALS
+            .identity
+            .decrypt_nip04(&event.pubkey, &event.content)?;
+        let tags: Vec<Tag> = serde_json::from_slice(&decrypted_content)?;
+        for tag in &tags {
+            if let Ok((pubkey, _, _)) = tag.parse_pubkey() {
+                entries.push((pubkey, false));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
 // Determine PersonList and fetches Metadata, allocating if needed.
 // This does NOT update that metadata from the event.
 // The bool indicates if the list was freshly allocated
@@ -943,3 +1305,100 @@ pub(crate) fn fetch_current_personlist_matching_event(
 
     Ok((list, metadata, new))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nostr_types::PrivateKey;
+
+    fn pubkeys(count: usize) -> Vec<PublicKey> {
+        (0..count)
+            .map(|_| PrivateKey::generate().public_key())
+            .collect()
+    }
+
+    // Entries in any order, and no duplicates
+    fn entries(list: Vec<(PublicKey, bool)>) -> HashMap<PublicKey, bool> {
+        let len = list.len();
+        let map: HashMap<PublicKey, bool> = list.into_iter().collect();
+        assert_eq!(map.len(), len);
+        map
+    }
+
+    #[test]
+    fn test_merge_person_lists_add() {
+        let pk = pubkeys(3);
+        let base = vec![(pk[0], true)];
+        let local = vec![(pk[0], true), (pk[1], true)];
+        let remote = vec![(pk[0], true), (pk[2], false)];
+
+        // Additions on both sides are kept
+        let merged = merge_person_lists(&local, &remote, &base, false);
+        assert_eq!(
+            entries(merged),
+            entries(vec![(pk[0], true), (pk[1], true), (pk[2], false)])
+        );
+    }
+
+    #[test]
+    fn test_merge_person_lists_remove() {
+        let pk = pubkeys(3);
+        let base = vec![(pk[0], true), (pk[1], true), (pk[2], true)];
+        let local = vec![(pk[0], true), (pk[2], true)];
+        let remote = vec![(pk[0], true), (pk[1], true)];
+
+        // Removals on both sides are kept
+        let merged = merge_person_lists(&local, &remote, &base, false);
+        assert_eq!(merged, vec![(pk[0], true)]);
+    }
+
+    #[test]
+    fn test_merge_person_lists_conflict() {
+        let pk = pubkeys(2);
+        let base = vec![(pk[0], true), (pk[1], true)];
+
+        // Only the remote side made pk[0] private, so that change is taken.
+        // Both sides changed pk[1], so the local side wins.
+        let local = vec![(pk[0], true), (pk[1], false)];
+        let remote = vec![(pk[0], false), (pk[1], true)];
+        let merged = merge_person_lists(&local, &remote, &base, false);
+        assert_eq!(
+            entries(merged),
+            entries(vec![(pk[0], false), (pk[1], false)])
+        );
+
+        // With no base, everything is a union and local flags win
+        let merged = merge_person_lists(&local, &remote, &[], false);
+        assert_eq!(
+            entries(merged),
+            entries(vec![(pk[0], true), (pk[1], false)])
+        );
+    }
+
+    #[test]
+    fn test_merge_person_lists_locked() {
+        let pk = pubkeys(4);
+        let base = vec![(pk[0], true), (pk[1], false), (pk[2], false)];
+        let local = vec![(pk[0], true), (pk[1], false), (pk[2], false)];
+
+        // Only the public part of the remote list could be read, and it added pk[3]
+        let remote = vec![(pk[0], true), (pk[3], true)];
+
+        // The private entries missing from the remote list must not be treated as
+        // removed remotely
+        let merged = merge_person_lists(&local, &remote, &base, true);
+        assert_eq!(
+            entries(merged),
+            entries(vec![
+                (pk[0], true),
+                (pk[1], false),
+                (pk[2], false),
+                (pk[3], true)
+            ])
+        );
+
+        // Without the locked flag they would have been dropped
+        let merged = merge_person_lists(&local, &remote, &base, false);
+        assert_eq!(entries(merged), entries(vec![(pk[0], true), (pk[3], true)]));
+    }
+}
@@ -0,0 +1,473 @@
+use crate::error::{Error, ErrorKind};
+use crate::globals::GLOBALS;
+use crate::people::{Person, PersonList, PersonListMetadata};
+use nostr_types::{Event, EventKind, PublicKey, Unixtime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File formats that person lists can be exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersonListFormat {
+    /// A JSON document with the title, privacy and entries of the list
+    Json,
+
+    /// CSV with a `pubkey,petname,private` header row
+    Csv,
+
+    /// One npub per line
+    Npubs,
+}
+
+impl PersonListFormat {
+    /// Guess the format from a file extension (.json, .csv, anything else is npubs)
+    pub fn from_path(path: &Path) -> PersonListFormat {
+        match path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("json") => PersonListFormat::Json,
+            Some("csv") => PersonListFormat::Csv,
+            _ => PersonListFormat::Npubs,
+        }
+    }
+
+    /// The usual file extension for this format
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            PersonListFormat::Json => "json",
+            PersonListFormat::Csv => "csv",
+            PersonListFormat::Npubs => "txt",
+        }
+    }
+}
+
+/// An entry in an exported person list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonListFileEntry {
+    /// The person's public key, as an npub
+    pub pubkey: String,
+
+    /// The person's petname, if any
+    #[serde(default)]
+    pub petname: Option<String>,
+
+    /// Whether the entry is private
+    #[serde(default)]
+    pub private: bool,
+}
+
+/// An exported person list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersonListFile {
+    /// Title of the list
+    #[serde(default)]
+    pub title: String,
+
+    /// Whether the entire list is private
+    #[serde(default)]
+    pub private: bool,
+
+    /// The people in the list
+    pub entries: Vec<PersonListFileEntry>,
+}
+
+impl PersonListFile {
+    /// Build from one of our local person lists
+    pub fn from_list(list: PersonList) -> Result<PersonListFile, Error> {
+        let metadata = match GLOBALS.storage.get_person_list_metadata(list)? {
+            Some(m) => m,
+            None => return Err(ErrorKind::ListNotFound.into()),
+        };
+
+        let mut entries: Vec<PersonListFileEntry> = Vec::new();
+        for (pubkey, public) in GLOBALS.storage.get_people_in_list(list)? {
+            let petname = GLOBALS
+                .storage
+                .read_person(&pubkey)?
+                .and_then(|p| p.petname);
+            entries.push(PersonListFileEntry {
+                pubkey: pubkey.as_bech32_string(),
+                petname,
+                private: !public,
+            });
+        }
+
+        Ok(PersonListFile {
+            title: metadata.title,
+            private: metadata.private,
+            entries,
+        })
+    }
+
+    /// Build from somebody's kind-3 contact list or kind-30000 follow set. Only
+    /// public entries are available.
+    pub fn from_event(event: &Event) -> Result<PersonListFile, Error> {
+        let title = match event.kind {
+            EventKind::ContactList => {
+                let name = match GLOBALS.storage.read_person(&event.pubkey)? {
+                    Some(person) => person.best_name(),
+                    None => crate::names::pubkey_short(&event.pubkey),
+                };
+                format!("Followed by {}", name)
+            }
+            EventKind::FollowSets => match event.title() {
+                Some(title) => title.to_owned(),
+                None => event.parameter().unwrap_or_default(),
+            },
+            _ => return Err(ErrorKind::NotAPersonListEvent.into()),
+        };
+
+        let mut entries: Vec<PersonListFileEntry> = Vec::new();
+        for tag in &event.tags {
+            if let Ok((pubkey, _, petname)) = tag.parse_pubkey() {
+                entries.push(PersonListFileEntry {
+                    pubkey: pubkey.as_bech32_string(),
+                    petname,
+                    private: false,
+                });
+            }
+        }
+
+        Ok(PersonListFile {
+            title,
+            private: false,
+            entries,
+        })
+    }
+
+    /// Serialize in the given format
+    pub fn write_to_string(&self, format: PersonListFormat) -> Result<String, Error> {
+        Ok(match format {
+            PersonListFormat::Json => serde_json::to_string_pretty(self)?,
+            PersonListFormat::Csv => {
+                let mut output = "pubkey,petname,private\n".to_owned();
+                for entry in &self.entries {
+                    output.push_str(&format!(
+                        "{},{},{}\n",
+                        entry.pubkey,
+                        csv_quote(entry.petname.as_deref().unwrap_or("")),
+                        entry.private
+                    ));
+                }
+                output
+            }
+            PersonListFormat::Npubs => {
+                let mut output = String::new();
+                for entry in &self.entries {
+                    output.push_str(&entry.pubkey);
+                    output.push('\n');
+                }
+                output
+            }
+        })
+    }
+
+    /// Parse from the given format
+    pub fn read_from_str(
+        contents: &str,
+        format: PersonListFormat,
+    ) -> Result<PersonListFile, Error> {
+        let mut file = match format {
+            PersonListFormat::Json => serde_json::from_str(contents)?,
+            PersonListFormat::Csv => {
+                let mut file = PersonListFile::default();
+                for fields in csv_records(contents) {
+                    let pubkey = fields.first().map(|s| s.trim()).unwrap_or("");
+                    if pubkey.is_empty() || pubkey == "pubkey" {
+                        continue;
+                    }
+                    let petname = fields
+                        .get(1)
+                        .map(|s| s.trim().to_owned())
+                        .filter(|s| !s.is_empty());
+                    let private = fields.get(2).map(|s| s.trim() == "true").unwrap_or(false);
+                    file.entries.push(PersonListFileEntry {
+                        pubkey: pubkey.to_owned(),
+                        petname,
+                        private,
+                    });
+                }
+                file
+            }
+            PersonListFormat::Npubs => {
+                let mut file = PersonListFile::default();
+                for line in contents.lines() {
+                    let pubkey = line.trim();
+                    if pubkey.is_empty() || pubkey.starts_with('#') {
+                        continue;
+                    }
+                    file.entries.push(PersonListFileEntry {
+                        pubkey: pubkey.to_owned(),
+                        petname: None,
+                        private: false,
+                    });
+                }
+                file
+            }
+        };
+
+        // Validate the keys up front so we don't import half a list
+        for entry in &mut file.entries {
+            entry.pubkey = parse_pubkey(&entry.pubkey)?.as_bech32_string();
+        }
+
+        Ok(file)
+    }
+
+    /// Import into local storage. If `into` is None, a new list is created using
+    /// the title (or `default_title` if there is none). Petnames are only set for
+    /// people who do not already have one. Returns the list imported into.
+    pub fn import(
+        &self,
+        into: Option<PersonList>,
+        default_title: &str,
+    ) -> Result<PersonList, Error> {
+        let mut txn = GLOBALS.storage.get_write_txn()?;
+
+        let list = match into {
+            Some(list) => list,
+            None => {
+                let title = if self.title.trim().is_empty() {
+                    default_title.to_owned()
+                } else {
+                    self.title.trim().to_owned()
+                };
+                let metadata = PersonListMetadata {
+                    dtag: new_list_dtag()?,
+                    title,
+                    private: self.private,
+                    ..Default::default()
+                };
+                GLOBALS
+                    .storage
+                    .allocate_person_list(&metadata, Some(&mut txn))?
+            }
+        };
+
+        for entry in &self.entries {
+            let pubkey = parse_pubkey(&entry.pubkey)?;
+
+            let mut person = match GLOBALS.storage.read_person(&pubkey)? {
+                Some(person) => person,
+                None => Person::new(pubkey),
+            };
+            if person.petname.is_none() && entry.petname.is_some() {
+                person.petname = entry.petname.clone();
+            }
+            GLOBALS.storage.write_person(&person, Some(&mut txn))?;
+
+            // The followed list (kind 3) is always public
+            let public = list == PersonList::Followed || !entry.private;
+            GLOBALS
+                .storage
+                .add_person_to_list(&pubkey, list, public, Some(&mut txn))?;
+            GLOBALS.ui_people_to_invalidate.write().push(pubkey);
+        }
+
+        txn.commit()?;
+
+        if let Some(mut metadata) = GLOBALS.storage.get_person_list_metadata(list)? {
+            metadata.last_edit_time = Unixtime::now().unwrap();
+            metadata.len = GLOBALS.storage.get_people_in_list(list)?.len();
+            GLOBALS
+                .storage
+                .set_person_list_metadata(list, &metadata, None)?;
+        }
+
+        Ok(list)
+    }
+}
+
+/// Export a person list to a file. The format is chosen by the file extension.
+pub fn export_person_list_to_file(list: PersonList, path: &Path) -> Result<(), Error> {
+    let format = PersonListFormat::from_path(path);
+    let contents = PersonListFile::from_list(list)?.write_to_string(format)?;
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Import a person list from a file. The format is chosen by the file extension.
+/// If `into` is None, a new list is created.
+pub fn import_person_list_from_file(
+    path: &Path,
+    into: Option<PersonList>,
+) -> Result<PersonList, Error> {
+    let format = PersonListFormat::from_path(path);
+    let contents = std::fs::read_to_string(path)?;
+    let file = PersonListFile::read_from_str(&contents, format)?;
+    let default_title = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or("Imported".to_owned());
+    file.import(into, &default_title)
+}
+
+/// Copy somebody's kind-3 contact list (dtag None) or kind-30000 follow set
+/// into a new local list, if we have the event. Returns None if we don't.
+pub fn copy_person_list_from_user(
+    pubkey: PublicKey,
+    dtag: Option<&str>,
+) -> Result<Option<PersonList>, Error> {
+    let event = match dtag {
+        None => GLOBALS
+            .storage
+            .get_replaceable_event(EventKind::ContactList, pubkey, "")?,
+        Some(d) => GLOBALS
+            .storage
+            .get_replaceable_event(EventKind::FollowSets, pubkey, d)?,
+    };
+
+    match event {
+        Some(event) => {
+            let file = PersonListFile::from_event(&event)?;
+            Ok(Some(file.import(None, "Copied list")?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_pubkey(s: &str) -> Result<PublicKey, Error> {
+    let s = s.trim();
+    let s = s.strip_prefix("nostr:").unwrap_or(s);
+    match PublicKey::try_from_bech32_string(s, true) {
+        Ok(pk) => Ok(pk),
+        Err(_) => Ok(PublicKey::try_from_hex_string(s, true)?),
+    }
+}
+
+fn csv_quote(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+// Split CSV into records of fields. Quoted fields may contain commas, doubled
+// quotes and line breaks. Records end with \n or \r\n.
+fn csv_records(contents: &str) -> Vec<Vec<String>> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut fields));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(fields);
+    }
+    records
+}
+
+// A d-tag for a new list, a timestamp like the ones lists created in the UI get.
+// Another list may already have it (e.g. from an import in the same second), in
+// which case a suffix is added.
+fn new_list_dtag() -> Result<String, Error> {
+    let base = format!("pl{}", Unixtime::now().unwrap().0);
+    let mut dtag = base.clone();
+    let mut n = 1;
+    while GLOBALS.storage.find_person_list_by_dtag(&dtag)?.is_some() {
+        n += 1;
+        dtag = format!("{}-{}", base, n);
+    }
+    Ok(dtag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nostr_types::PrivateKey;
+
+    fn sample() -> PersonListFile {
+        let npub = || PrivateKey::generate().public_key().as_bech32_string();
+        PersonListFile {
+            title: String::new(),
+            private: false,
+            entries: vec![
+                PersonListFileEntry {
+                    pubkey: npub(),
+                    petname: None,
+                    private: false,
+                },
+                PersonListFileEntry {
+                    pubkey: npub(),
+                    petname: Some("Smith, \"Jo\"".to_owned()),
+                    private: true,
+                },
+                PersonListFileEntry {
+                    pubkey: npub(),
+                    petname: Some("two\nlines".to_owned()),
+                    private: false,
+                },
+            ],
+        }
+    }
+
+    fn assert_same_entries(a: &PersonListFile, b: &PersonListFile, petnames: bool) {
+        assert_eq!(a.entries.len(), b.entries.len());
+        for (x, y) in a.entries.iter().zip(b.entries.iter()) {
+            assert_eq!(x.pubkey, y.pubkey);
+            if petnames {
+                assert_eq!(x.petname, y.petname);
+                assert_eq!(x.private, y.private);
+            }
+        }
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let file = sample();
+        let csv = file.write_to_string(PersonListFormat::Csv).unwrap();
+        let back = PersonListFile::read_from_str(&csv, PersonListFormat::Csv).unwrap();
+        assert_same_entries(&file, &back, true);
+
+        // Windows line endings
+        let csv = csv
+            .replace('\n', "\r\n")
+            .replace("two\r\nlines", "two\nlines");
+        let back = PersonListFile::read_from_str(&csv, PersonListFormat::Csv).unwrap();
+        assert_same_entries(&file, &back, true);
+    }
+
+    #[test]
+    fn test_json_and_npubs_round_trip() {
+        let file = sample();
+        let json = file.write_to_string(PersonListFormat::Json).unwrap();
+        let back = PersonListFile::read_from_str(&json, PersonListFormat::Json).unwrap();
+        assert_same_entries(&file, &back, true);
+
+        let npubs = file.write_to_string(PersonListFormat::Npubs).unwrap();
+        let back = PersonListFile::read_from_str(&npubs, PersonListFormat::Npubs).unwrap();
+        assert_same_entries(&file, &back, false);
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let records = csv_records("a,\"b,\"\"c\"\"\",d\r\n\"e\nf\",,\n\ng");
+        assert_eq!(
+            records,
+            vec![
+                vec!["a".to_owned(), "b,\"c\"".to_owned(), "d".to_owned()],
+                vec!["e\nf".to_owned(), String::new(), String::new()],
+                vec![String::new()],
+                vec!["g".to_owned()],
+            ]
+        );
+    }
+}
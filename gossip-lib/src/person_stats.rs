@@ -0,0 +1,175 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use crate::people::PersonList;
+use crate::wot::GraphEdge;
+use linkify::{LinkFinder, LinkKind};
+use nostr_types::{Event, EventKind, Id, MilliSatoshi, PublicKey, PublicKeyHex, Unixtime};
+use std::collections::{HashMap, HashSet};
+
+// How far back posting frequency is measured
+const FREQUENCY_WINDOW_DAYS: i64 = 30;
+
+// How many top hashtags we report
+const MAX_TOP_HASHTAGS: usize = 10;
+
+/// Statistics about a person, computed from locally stored events and the
+/// follow graph. Followers are only those whose contact lists we have seen.
+#[derive(Debug, Clone)]
+pub struct PersonStats {
+    /// Who they follow
+    pub following: Vec<PublicKey>,
+
+    /// Who follows them (locally known)
+    pub followers: Vec<PublicKey>,
+
+    /// People that both we and they follow
+    pub mutual_follows: Vec<PublicKey>,
+
+    /// Whether they follow us
+    pub follows_you: bool,
+
+    /// How many of their root notes we have
+    pub notes: usize,
+
+    /// How many of their replies we have
+    pub replies: usize,
+
+    /// Notes and replies per day, over the last 30 days
+    pub posts_per_day: f64,
+
+    /// Their most used hashtags with counts, most used first
+    pub top_hashtags: Vec<(String, usize)>,
+
+    /// How many zap receipts we have for zaps to them
+    pub zaps_received: usize,
+
+    /// The total of those zaps
+    pub zapped_total: MilliSatoshi,
+}
+
+impl PersonStats {
+    /// Compute the statistics for a person
+    pub fn compute(pubkey: PublicKey) -> Result<PersonStats, Error> {
+        let mut stats = PersonStats {
+            following: GLOBALS
+                .storage
+                .get_graph_edges_out(GraphEdge::Follows, pubkey)?,
+            followers: GLOBALS
+                .storage
+                .get_graph_edges_in(GraphEdge::Follows, pubkey)?,
+            mutual_follows: Vec::new(),
+            follows_you: false,
+            notes: 0,
+            replies: 0,
+            posts_per_day: 0.0,
+            top_hashtags: Vec::new(),
+            zaps_received: 0,
+            zapped_total: MilliSatoshi(0),
+        };
+
+        let my_follows: HashSet<PublicKey> = GLOBALS
+            .storage
+            .get_people_in_list(PersonList::Followed)?
+            .drain(..)
+            .map(|(pk, _)| pk)
+            .collect();
+        stats.mutual_follows = stats
+            .following
+            .iter()
+            .filter(|pk| my_follows.contains(pk))
+            .copied()
+            .collect();
+        if let Some(my_pubkey) = GLOBALS.identity.public_key() {
+            stats.follows_you = stats.following.contains(&my_pubkey);
+        }
+
+        let window_start = Unixtime(Unixtime::now().unwrap().0 - FREQUENCY_WINDOW_DAYS * 86400);
+        let mut recent: usize = 0;
+        let mut hashtags: HashMap<String, usize> = HashMap::new();
+        for event in
+            GLOBALS
+                .storage
+                .find_events(&[EventKind::TextNote], &[pubkey], None, |_| true, false)?
+        {
+            if event.replies_to().is_some() {
+                stats.replies += 1;
+            } else {
+                stats.notes += 1;
+            }
+            if event.created_at >= window_start {
+                recent += 1;
+            }
+            for hashtag in event.hashtags() {
+                *hashtags.entry(hashtag.to_lowercase()).or_default() += 1;
+            }
+        }
+        stats.posts_per_day = recent as f64 / FREQUENCY_WINDOW_DAYS as f64;
+
+        let mut hashtags: Vec<(String, usize)> = hashtags.drain().collect();
+        hashtags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hashtags.truncate(MAX_TOP_HASHTAGS);
+        stats.top_hashtags = hashtags;
+
+        for zap in zaps_to(pubkey)? {
+            if let Ok(Some(zapdata)) = zap.zaps() {
+                stats.zaps_received += 1;
+                stats.zapped_total = stats.zapped_total + zapdata.amount;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Their notes (or replies, if `replies` is true), newest first
+pub fn person_notes(pubkey: PublicKey, replies: bool) -> Result<Vec<Id>, Error> {
+    Ok(GLOBALS
+        .storage
+        .find_events(
+            &[EventKind::TextNote],
+            &[pubkey],
+            None,
+            |e| e.replies_to().is_some() == replies,
+            true,
+        )?
+        .iter()
+        .map(|e| e.id)
+        .collect())
+}
+
+/// Their notes and replies that link to images or videos, newest first
+pub fn person_media_notes(pubkey: PublicKey) -> Result<Vec<Id>, Error> {
+    Ok(GLOBALS
+        .storage
+        .find_events(&[EventKind::TextNote], &[pubkey], None, has_media, true)?
+        .iter()
+        .map(|e| e.id)
+        .collect())
+}
+
+/// Zap receipts for zaps to them, newest first
+pub fn person_zaps(pubkey: PublicKey) -> Result<Vec<Id>, Error> {
+    Ok(zaps_to(pubkey)?.iter().map(|e| e.id).collect())
+}
+
+fn zaps_to(pubkey: PublicKey) -> Result<Vec<Event>, Error> {
+    let pkh: PublicKeyHex = pubkey.into();
+    GLOBALS
+        .storage
+        .find_tagged_events("p", Some(pkh.as_str()), |e| e.kind == EventKind::Zap, true)
+}
+
+fn has_media(event: &Event) -> bool {
+    LinkFinder::new()
+        .kinds(&[LinkKind::Url])
+        .links(&event.content)
+        .any(|link| {
+            let lower = link.as_str().to_lowercase();
+            let path = lower.split(['?', '#']).next().unwrap_or("");
+            [
+                ".jpg", ".jpeg", ".png", ".gif", ".webp", ".mov", ".mp4", ".mkv", ".webm",
+            ]
+            .iter()
+            .any(|ext| path.ends_with(ext))
+        })
+}
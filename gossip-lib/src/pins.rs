@@ -0,0 +1,74 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use nostr_types::{Event, EventKind, Id, PreEvent, PublicKey, Tag, Unixtime};
+
+/// The notes a person has pinned to their profile, most recently pinned first,
+/// from their latest pin list (kind 10001) that we have
+pub fn get_pinned(pubkey: PublicKey) -> Result<Vec<Id>, Error> {
+    let event = match GLOBALS
+        .storage
+        .get_replaceable_event(EventKind::PinList, pubkey, "")?
+    {
+        Some(event) => event,
+        None => return Ok(vec![]),
+    };
+
+    let mut ids: Vec<Id> = event
+        .tags
+        .iter()
+        .filter_map(|tag| match tag.parse_event() {
+            Ok((id, _, _)) => Some(id),
+            Err(_) => None,
+        })
+        .collect();
+    ids.reverse();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Has the author of this event pinned it to their profile?
+pub fn is_pinned(event: &Event) -> bool {
+    match get_pinned(event.pubkey) {
+        Ok(ids) => ids.contains(&event.id),
+        Err(e) => {
+            tracing::error!("{}", e);
+            false
+        }
+    }
+}
+
+/// Generate a new pin list for the user with the note pinned or unpinned.
+/// Tags we don't operate on are preserved.
+pub(crate) fn generate_pin_list_event(id: Id, pinned: bool) -> Result<Event, Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(crate::error::ErrorKind::NoPublicKey.into()),
+    };
+
+    let mut tags: Vec<Tag> =
+        match GLOBALS
+            .storage
+            .get_replaceable_event(EventKind::PinList, my_pubkey, "")?
+        {
+            Some(event) => event.tags,
+            None => vec![],
+        };
+
+    tags.retain(|tag| match tag.parse_event() {
+        Ok((tagged, _, _)) => tagged != id,
+        Err(_) => true,
+    });
+    if pinned {
+        tags.push(Tag::new_event(id, None, None));
+    }
+
+    let pre_event = PreEvent {
+        pubkey: my_pubkey,
+        created_at: Unixtime::now().unwrap(),
+        kind: EventKind::PinList,
+        tags,
+        content: "".to_owned(),
+    };
+
+    GLOBALS.identity.sign_event(pre_event)
+}
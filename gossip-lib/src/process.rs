@@ -1,5 +1,6 @@
 use crate::comms::ToOverlordMessage;
 use crate::error::Error;
+use crate::feed::FeedKind;
 use crate::filter::EventFilterAction;
 use crate::globals::GLOBALS;
 use crate::people::{PersonList, PersonListMetadata};
@@ -249,6 +250,16 @@ pub async fn process_new_event(
             .await?;
     }
 
+    // Keep the follow graph up to date
+    if event.kind == EventKind::ContactList || event.kind == EventKind::MuteList {
+        GLOBALS.wot.process_list_event(event)?;
+    }
+
+    // Wake up anybody waiting for a person list to arrive
+    if event.kind == EventKind::ContactList || event.kind == EventKind::FollowSets {
+        GLOBALS.person_list_event_notify.notify_waiters();
+    }
+
     if event.kind == EventKind::ContactList {
         if let Some(pubkey) = GLOBALS.identity.public_key() {
             if event.pubkey == pubkey {
@@ -270,6 +281,41 @@ pub async fn process_new_event(
                     update_or_allocate_person_list_from_event(event, pubkey)?;
             }
         }
+    } else if event.kind == EventKind::BookmarkList || event.kind == EventKind::BookmarkSets {
+        // Only our own
+        if let Some(pubkey) = GLOBALS.identity.public_key() {
+            if event.pubkey == pubkey {
+                crate::bookmarks::process_bookmark_list_event(event, pubkey)?;
+            }
+        }
+    } else if event.kind == EventKind::PinList {
+        // Seek the pinned notes we don't have
+        if let Some(url) = &seen_on {
+            for tag in &event.tags {
+                if let Ok((id, _, _)) = tag.parse_event() {
+                    if !GLOBALS.storage.has_event(id)? {
+                        let _ = GLOBALS
+                            .to_overlord
+                            .send(ToOverlordMessage::FetchEvent(id, vec![url.to_owned()]));
+                    }
+                }
+            }
+        }
+
+        // Notes may have been pinned or unpinned. For our own pins, and those of
+        // the person whose feed is shown, everything is redrawn. Elsewhere only
+        // the pinned notes are.
+        let viewing = GLOBALS.feed.get_feed_kind() == FeedKind::Person(event.pubkey);
+        if GLOBALS.identity.public_key() == Some(event.pubkey) || viewing {
+            GLOBALS.ui_invalidate_all.store(true, Ordering::Relaxed);
+        } else {
+            let mut ids: Vec<Id> = event
+                .tags
+                .iter()
+                .filter_map(|tag| tag.parse_event().ok().map(|(id, _, _)| id))
+                .collect();
+            GLOBALS.ui_notes_to_invalidate.write().append(&mut ids);
+        }
     } else if event.kind == EventKind::RelayList {
         GLOBALS.storage.process_relay_list(event)?;
 
@@ -396,8 +442,8 @@ pub async fn process_new_event(
 }
 
 async fn process_somebody_elses_contact_list(event: &Event) -> Result<(), Error> {
-    // We don't keep their contacts or show to the user yet.
-    // We only process the contents for (non-standard) relay list information.
+    // Their contacts were already recorded in the follow graph.
+    // Here we only process the contents for (non-standard) relay list information.
 
     // Try to parse the contents as a SimpleRelayList (ignore if it is not)
     if let Ok(srl) = serde_json::from_str::<SimpleRelayList>(&event.content) {
@@ -0,0 +1,57 @@
+use crate::comms::ToOverlordMessage;
+use crate::error::{Error, ErrorKind};
+use crate::globals::GLOBALS;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::task;
+
+// How often the outbox is checked for posts that are due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// ScheduledPost type, aliased to the latest version
+pub type ScheduledPost = crate::storage::types::ScheduledPost1;
+
+/// The posts in the current account's outbox, soonest first
+/// (returns empty on error or without an identity)
+pub fn get_scheduled_posts() -> Vec<ScheduledPost> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return vec![],
+    };
+    let mut posts = match GLOBALS.storage.read_scheduled_posts(my_pubkey) {
+        Ok(posts) => posts,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return vec![];
+        }
+    };
+    posts.sort_by(|a, b| a.send_at.cmp(&b.send_at));
+    posts
+}
+
+/// Remove a post from the current account's outbox without posting it
+pub fn cancel_scheduled_post(id: u64) -> Result<(), Error> {
+    let my_pubkey = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err(ErrorKind::NoPublicKey.into()),
+    };
+
+    GLOBALS.storage.delete_scheduled_post(my_pubkey, id, None)
+}
+
+/// Periodically have the overlord post what is due
+pub(crate) fn start() {
+    task::spawn(async {
+        loop {
+            tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+
+            if GLOBALS.shutting_down.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let _ = GLOBALS
+                .to_overlord
+                .send(ToOverlordMessage::SendScheduledPosts);
+        }
+    });
+}
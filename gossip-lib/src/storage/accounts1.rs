@@ -0,0 +1,115 @@
+use super::types::Account1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// PublicKey -> Account1
+//   key: pubkey.as_bytes()
+//   val: account.write_to_vec() | Account1::read_from_buffer(val)
+
+static ACCOUNTS1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut ACCOUNTS1_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_accounts1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = ACCOUNTS1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = ACCOUNTS1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = ACCOUNTS1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("accounts1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                ACCOUNTS1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_account1<'a>(
+        &'a self,
+        account: &Account1,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = account.pubkey.as_bytes();
+        let bytes = account.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_accounts1()?.put(txn, key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_account1(&self, pubkey: PublicKey) -> Result<Option<Account1>, Error> {
+        let key = pubkey.as_bytes();
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_accounts1()?.get(&txn, key)? {
+            Some(bytes) => Some(Account1::read_from_buffer(bytes)?),
+            None => None,
+        })
+    }
+
+    pub(crate) fn read_all_accounts1(&self) -> Result<Vec<Account1>, Error> {
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<Account1> = Vec::new();
+        for result in self.db_accounts1()?.iter(&txn)? {
+            let (_key, val) = result?;
+            output.push(Account1::read_from_buffer(val)?);
+        }
+        Ok(output)
+    }
+
+    pub(crate) fn delete_account1<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = pubkey.as_bytes();
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_accounts1()?.delete(txn, key);
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use super::types::BookmarkList1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// (PublicKey, dtag) -> BookmarkList1
+//   key: pubkey.as_bytes() + dtag.as_bytes()
+//   val: list.write_to_vec() | BookmarkList1::read_from_buffer(val)
+//
+// The bookmark list (kind 10003) has an empty dtag, bookmark sets (kind 30003)
+// have their own.
+
+static BOOKMARKS1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut BOOKMARKS1_DB: Option<RawDatabase> = None;
+
+fn key(owner: PublicKey, dtag: &str) -> Vec<u8> {
+    let mut key: Vec<u8> = owner.as_bytes().to_owned();
+    key.extend(dtag.as_bytes());
+    key
+}
+
+impl Storage {
+    pub(super) fn db_bookmarks1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = BOOKMARKS1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = BOOKMARKS1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = BOOKMARKS1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("bookmarks1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                BOOKMARKS1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_bookmark_list1<'a>(
+        &'a self,
+        owner: PublicKey,
+        list: &BookmarkList1,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(owner, &list.dtag);
+        let bytes = list.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_bookmarks1()?.put(txn, &key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_bookmark_list1(
+        &self,
+        owner: PublicKey,
+        dtag: &str,
+    ) -> Result<Option<BookmarkList1>, Error> {
+        let key = key(owner, dtag);
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_bookmarks1()?.get(&txn, &key)? {
+            Some(bytes) => Some(BookmarkList1::read_from_buffer(bytes)?),
+            None => None,
+        })
+    }
+
+    pub(crate) fn read_bookmark_lists1(
+        &self,
+        owner: PublicKey,
+    ) -> Result<Vec<BookmarkList1>, Error> {
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<BookmarkList1> = Vec::new();
+        for result in self.db_bookmarks1()?.prefix_iter(&txn, owner.as_bytes())? {
+            let (_key, val) = result?;
+            output.push(BookmarkList1::read_from_buffer(val)?);
+        }
+        Ok(output)
+    }
+
+    pub(crate) fn delete_bookmark_list1<'a>(
+        &'a self,
+        owner: PublicKey,
+        dtag: &str,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(owner, dtag);
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_bookmarks1()?.delete(txn, &key)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use super::types::GraphEdge1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::{types::UnalignedSlice, DatabaseFlags, RwTxn};
+use nostr_types::PublicKey;
+use std::sync::Mutex;
+
+// (GraphEdge1, PublicKey) -> PublicKey
+// (dup keys, so multiple targets per source)
+//
+// The forward table maps the author of a list to the people in it:
+//   key: [edge.as_u8()] ++ from.as_bytes()
+//   val: to.as_bytes() | PublicKey::from_bytes(val, false)
+//
+// The reverse table maps a person to the authors of lists that contain them:
+//   key: [edge.as_u8()] ++ to.as_bytes()
+//   val: from.as_bytes() | PublicKey::from_bytes(val, false)
+
+static FOLLOW_GRAPH1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut FOLLOW_GRAPH1_DB: Option<RawDatabase> = None;
+
+static FOLLOW_GRAPH1_REVERSE_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut FOLLOW_GRAPH1_REVERSE_DB: Option<RawDatabase> = None;
+
+fn graph_key(edge: GraphEdge1, pubkey: &PublicKey) -> Vec<u8> {
+    let mut key: Vec<u8> = Vec::with_capacity(33);
+    key.push(edge.as_u8());
+    key.extend_from_slice(pubkey.as_bytes());
+    key
+}
+
+impl Storage {
+    pub(super) fn db_follow_graph1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = FOLLOW_GRAPH1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = FOLLOW_GRAPH1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = FOLLOW_GRAPH1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    .flags(DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED)
+                    .name("follow_graph1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                FOLLOW_GRAPH1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(super) fn db_follow_graph1_reverse(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = FOLLOW_GRAPH1_REVERSE_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = FOLLOW_GRAPH1_REVERSE_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = FOLLOW_GRAPH1_REVERSE_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    .flags(DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED)
+                    .name("follow_graph1_reverse")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                FOLLOW_GRAPH1_REVERSE_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    /// Replace all of the edges of the given kind going out of `from`.
+    /// Returns the edges that were there before.
+    pub(crate) fn set_graph_edges1<'a>(
+        &'a self,
+        edge: GraphEdge1,
+        from: PublicKey,
+        to: &[PublicKey],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<Vec<PublicKey>, Error> {
+        let from_key = graph_key(edge, &from);
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<Vec<PublicKey>, Error> {
+            // Collect the old edges
+            let mut old: Vec<PublicKey> = Vec::new();
+            if let Some(iter) = self.db_follow_graph1()?.get_duplicates(txn, &from_key)? {
+                for result in iter {
+                    let (_key, val) = result?;
+                    old.push(PublicKey::from_bytes(val, false)?);
+                }
+            }
+
+            // Remove them in both directions
+            for target in &old {
+                self.db_follow_graph1_reverse()?.delete_one_duplicate(
+                    txn,
+                    &graph_key(edge, target),
+                    from.as_bytes(),
+                )?;
+            }
+            self.db_follow_graph1()?.delete(txn, &from_key)?;
+
+            // Write the new edges in both directions
+            for target in to {
+                self.db_follow_graph1()?
+                    .put(txn, &from_key, target.as_bytes())?;
+                self.db_follow_graph1_reverse()?.put(
+                    txn,
+                    &graph_key(edge, target),
+                    from.as_bytes(),
+                )?;
+            }
+
+            Ok(old)
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn),
+            None => {
+                let mut txn = self.env.write_txn()?;
+                let old = f(&mut txn)?;
+                txn.commit()?;
+                Ok(old)
+            }
+        }
+    }
+
+    /// The people that `from` has an edge of the given kind to
+    pub(crate) fn get_graph_edges_out1(
+        &self,
+        edge: GraphEdge1,
+        from: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        self.get_graph_edges1(self.db_follow_graph1()?, edge, from)
+    }
+
+    /// The people that have an edge of the given kind to `to`
+    pub(crate) fn get_graph_edges_in1(
+        &self,
+        edge: GraphEdge1,
+        to: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        self.get_graph_edges1(self.db_follow_graph1_reverse()?, edge, to)
+    }
+
+    fn get_graph_edges1(
+        &self,
+        db: RawDatabase,
+        edge: GraphEdge1,
+        pubkey: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        let key = graph_key(edge, &pubkey);
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<PublicKey> = Vec::new();
+        let iter = match db.get_duplicates(&txn, &key)? {
+            Some(i) => i,
+            None => return Ok(vec![]),
+        };
+        for result in iter {
+            let (_key, val) = result?;
+            output.push(PublicKey::from_bytes(val, false)?);
+        }
+        Ok(output)
+    }
+}
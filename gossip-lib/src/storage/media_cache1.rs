@@ -0,0 +1,124 @@
+use super::types::MediaCacheEntry1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use sha2::Digest;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// Url -> MediaCacheEntry1
+//   key: sha256(url)
+//   val: entry.write_to_vec() | MediaCacheEntry1::read_from_buffer(val)
+//
+// URLs can be longer than LMDB keys may be, so they are hashed.
+
+static MEDIA_CACHE1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut MEDIA_CACHE1_DB: Option<RawDatabase> = None;
+
+fn key(url: &str) -> Vec<u8> {
+    sha2::Sha256::digest(url.as_bytes()).to_vec()
+}
+
+impl Storage {
+    pub(super) fn db_media_cache1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = MEDIA_CACHE1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = MEDIA_CACHE1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = MEDIA_CACHE1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("media_cache1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                MEDIA_CACHE1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_media_cache_entry1<'a>(
+        &'a self,
+        entry: &MediaCacheEntry1,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(&entry.url);
+        let bytes = entry.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_media_cache1()?.put(txn, &key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_media_cache_entry1(
+        &self,
+        url: &str,
+    ) -> Result<Option<MediaCacheEntry1>, Error> {
+        let key = key(url);
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_media_cache1()?.get(&txn, &key)? {
+            Some(bytes) => Some(MediaCacheEntry1::read_from_buffer(bytes)?),
+            None => None,
+        })
+    }
+
+    pub(crate) fn delete_media_cache_entry1<'a>(
+        &'a self,
+        url: &str,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(url);
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_media_cache1()?.delete(txn, &key)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_all_media_cache_entries1(&self) -> Result<Vec<MediaCacheEntry1>, Error> {
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<MediaCacheEntry1> = Vec::new();
+        for result in self.db_media_cache1()?.iter(&txn)? {
+            let (_key, val) = result?;
+            output.push(MediaCacheEntry1::read_from_buffer(val)?);
+        }
+        Ok(output)
+    }
+}
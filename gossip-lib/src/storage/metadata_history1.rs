@@ -0,0 +1,103 @@
+use super::types::MetadataVersion1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// PublicKey -> Vec<MetadataVersion1>
+//   key: pubkey.as_bytes()
+//   val: versions.write_to_vec() | Vec::<MetadataVersion1>::read_from_buffer(val)
+//
+// Oldest first, at most one version per created_at.
+
+static METADATA_HISTORY1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut METADATA_HISTORY1_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_metadata_history1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = METADATA_HISTORY1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = METADATA_HISTORY1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = METADATA_HISTORY1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("metadata_history1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                METADATA_HISTORY1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    /// Add a metadata version to a person's history, keeping the newest `max`.
+    /// Returns false if we already had this version.
+    pub(crate) fn add_metadata_version1<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        version: MetadataVersion1,
+        max: usize,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<bool, Error> {
+        let key = pubkey.as_bytes();
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<bool, Error> {
+            let mut history: Vec<MetadataVersion1> =
+                match self.db_metadata_history1()?.get(txn, key)? {
+                    Some(bytes) => Vec::<MetadataVersion1>::read_from_buffer(bytes)?,
+                    None => vec![],
+                };
+            let index = match history.binary_search_by_key(&version.created_at, |v| v.created_at) {
+                Ok(_) => return Ok(false),
+                Err(index) => index,
+            };
+            history.insert(index, version);
+            if history.len() > max {
+                let excess = history.len() - max;
+                history.drain(..excess);
+            }
+            let bytes = history.write_to_vec()?;
+            self.db_metadata_history1()?.put(txn, key, &bytes)?;
+            Ok(true)
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn),
+            None => {
+                let mut txn = self.env.write_txn()?;
+                let added = f(&mut txn)?;
+                txn.commit()?;
+                Ok(added)
+            }
+        }
+    }
+
+    pub(crate) fn read_metadata_history1(
+        &self,
+        pubkey: PublicKey,
+    ) -> Result<Vec<MetadataVersion1>, Error> {
+        let key = pubkey.as_bytes();
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_metadata_history1()?.get(&txn, key)? {
+            Some(bytes) => Vec::<MetadataVersion1>::read_from_buffer(bytes)?,
+            None => vec![],
+        })
+    }
+}
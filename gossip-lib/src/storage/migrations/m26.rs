@@ -1,5 +1,7 @@
+use super::m27::Nip46Server2;
+use super::m30::Nip46ClientMetadata1;
 use crate::error::Error;
-use crate::nip46::{Approval, Nip46ClientMetadata, Nip46Server};
+use crate::nip46::Approval;
 use crate::storage::Storage;
 use heed::RwTxn;
 use nostr_types::{PublicKey, RelayUrl};
@@ -9,7 +11,7 @@ use speedy::{Readable, Writable};
 pub struct Nip46Server1 {
     pub peer_pubkey: PublicKey,
     pub relays: Vec<RelayUrl>,
-    pub metadata: Option<Nip46ClientMetadata>,
+    pub metadata: Option<Nip46ClientMetadata1>,
 }
 
 impl Storage {
@@ -38,7 +40,7 @@ impl Storage {
         for result in self.db_nip46servers1()?.iter(&loop_txn)? {
             let (key, val) = result?;
             let server1 = Nip46Server1::read_from_buffer(val)?;
-            let server2 = Nip46Server {
+            let server2 = Nip46Server2 {
                 peer_pubkey: server1.peer_pubkey,
                 relays: server1.relays,
                 metadata: server1.metadata,
@@ -0,0 +1,65 @@
+use super::m30::{Nip46ClientMetadata1, Nip46Server3};
+use crate::error::Error;
+use crate::nip46::Approval;
+use crate::storage::Storage;
+use heed::RwTxn;
+use nostr_types::{PublicKey, RelayUrl};
+use speedy::{Readable, Writable};
+
+#[derive(Debug, Clone, Readable, Writable)]
+pub struct Nip46Server2 {
+    pub peer_pubkey: PublicKey,
+    pub relays: Vec<RelayUrl>,
+    pub metadata: Option<Nip46ClientMetadata1>,
+    pub sign_approval: Approval,
+    pub encrypt_approval: Approval,
+    pub decrypt_approval: Approval,
+}
+
+impl Storage {
+    pub(super) fn m27_trigger(&self) -> Result<(), Error> {
+        let _ = self.db_nip46servers2()?;
+        let _ = self.db_nip46servers3()?;
+        Ok(())
+    }
+
+    pub(super) fn m27_migrate<'a>(
+        &'a self,
+        prefix: &str,
+        txn: &mut RwTxn<'a>,
+    ) -> Result<(), Error> {
+        // Info message
+        tracing::info!("{prefix}: migrating nostr connect services...");
+
+        // Migrate
+        self.m27_migrate_nostr_connect_services(txn)?;
+
+        Ok(())
+    }
+
+    fn m27_migrate_nostr_connect_services<'a>(&'a self, txn: &mut RwTxn<'a>) -> Result<(), Error> {
+        let loop_txn = self.env.read_txn()?;
+        for result in self.db_nip46servers2()?.iter(&loop_txn)? {
+            let (key, val) = result?;
+            let server2 = Nip46Server2::read_from_buffer(val)?;
+            let server3 = Nip46Server3 {
+                peer_pubkey: server2.peer_pubkey,
+                relays: server2.relays,
+                metadata: server2.metadata,
+                sign_approval: server2.sign_approval,
+                encrypt_approval: server2.encrypt_approval,
+                decrypt_approval: server2.decrypt_approval,
+                sign_kind_rules: Vec::new(),
+                counterparty_rules: Vec::new(),
+                rate_limit: None,
+            };
+            let bytes = server3.write_to_vec()?;
+            self.db_nip46servers3()?.put(txn, key, &bytes)?;
+        }
+
+        // clear old database (we don't have an interface to delete it)
+        self.db_nip46servers2()?.clear(txn)?;
+
+        Ok(())
+    }
+}
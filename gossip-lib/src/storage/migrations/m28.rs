@@ -0,0 +1,50 @@
+use crate::error::Error;
+use crate::storage::Storage;
+use heed::RwTxn;
+use nostr_types::{EventKind, PublicKey};
+use std::collections::HashSet;
+
+impl Storage {
+    pub(super) fn m28_trigger(&self) -> Result<(), Error> {
+        let _ = self.db_events3()?;
+        let _ = self.db_follow_graph1()?;
+        let _ = self.db_follow_graph1_reverse()?;
+        Ok(())
+    }
+
+    pub(super) fn m28_migrate<'a>(
+        &'a self,
+        prefix: &str,
+        txn: &mut RwTxn<'a>,
+    ) -> Result<(), Error> {
+        // Info message
+        tracing::info!("{prefix}: building the follow graph...");
+
+        // Migrate
+        self.m28_build_follow_graph(txn)?;
+
+        Ok(())
+    }
+
+    fn m28_build_follow_graph<'a>(&'a self, txn: &mut RwTxn<'a>) -> Result<(), Error> {
+        let mut count: usize = 0;
+        for kind in [EventKind::ContactList, EventKind::MuteList] {
+            // Newest first, so we only use the latest list of each author
+            let events = self.find_events(&[kind], &[], None, |_| true, true)?;
+            let mut done: HashSet<PublicKey> = HashSet::new();
+            for event in &events {
+                if !done.insert(event.pubkey) {
+                    continue;
+                }
+                if let Some((edge, targets)) = crate::wot::graph_edges_of_event(event) {
+                    self.set_graph_edges1(edge, event.pubkey, &targets, Some(txn))?;
+                    count += 1;
+                }
+            }
+        }
+
+        tracing::info!("Added the lists of {} people to the follow graph", count);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use crate::error::Error;
+use crate::storage::types::{MetadataVersion1, PersonList1};
+use crate::storage::Storage;
+use heed::RwTxn;
+
+impl Storage {
+    pub(super) fn m29_trigger(&self) -> Result<(), Error> {
+        let _ = self.db_people2()?;
+        let _ = self.db_person_lists2()?;
+        let _ = self.db_metadata_history1()?;
+        Ok(())
+    }
+
+    pub(super) fn m29_migrate<'a>(
+        &'a self,
+        prefix: &str,
+        txn: &mut RwTxn<'a>,
+    ) -> Result<(), Error> {
+        // Info message
+        tracing::info!("{prefix}: starting metadata history...");
+
+        // Migrate
+        self.m29_seed_metadata_history(txn)?;
+
+        Ok(())
+    }
+
+    fn m29_seed_metadata_history<'a>(&'a self, txn: &mut RwTxn<'a>) -> Result<(), Error> {
+        let max = self.read_setting_max_metadata_versions() as usize;
+        let mut count: usize = 0;
+        for person in self.filter_people(|p| p.metadata.is_some())? {
+            // We only keep the history of people we follow
+            if !self
+                .read_person_lists2(&person.pubkey)?
+                .contains_key(&PersonList1::Followed)
+            {
+                continue;
+            }
+            if let (Some(metadata), Some(created_at)) =
+                (person.metadata, person.metadata_created_at)
+            {
+                let version = MetadataVersion1::new(created_at, &metadata);
+                self.add_metadata_version1(person.pubkey, version, max.max(1), Some(txn))?;
+                count += 1;
+            }
+        }
+
+        tracing::info!("Started the metadata history of {} people", count);
+
+        Ok(())
+    }
+}
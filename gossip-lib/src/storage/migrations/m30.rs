@@ -0,0 +1,78 @@
+use crate::error::Error;
+use crate::nip46::{Approval, Nip46ClientMetadata, Nip46Permission, Nip46RateLimit, Nip46Server};
+use crate::storage::Storage;
+use heed::RwTxn;
+use nostr_types::{PublicKey, RelayUrl};
+use speedy::{Readable, Writable};
+
+#[derive(Debug, Clone, Readable, Writable)]
+pub struct Nip46ClientMetadata1 {
+    pub name: String,
+    pub url: RelayUrl,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Readable, Writable)]
+pub struct Nip46Server3 {
+    pub peer_pubkey: PublicKey,
+    pub relays: Vec<RelayUrl>,
+    pub metadata: Option<Nip46ClientMetadata1>,
+    pub sign_approval: Approval,
+    pub encrypt_approval: Approval,
+    pub decrypt_approval: Approval,
+    pub sign_kind_rules: Vec<(u32, Nip46Permission)>,
+    pub counterparty_rules: Vec<(PublicKey, Nip46Permission)>,
+    pub rate_limit: Option<Nip46RateLimit>,
+}
+
+impl Storage {
+    pub(super) fn m30_trigger(&self) -> Result<(), Error> {
+        let _ = self.db_nip46servers3()?;
+        let _ = self.db_nip46servers4()?;
+        Ok(())
+    }
+
+    pub(super) fn m30_migrate<'a>(
+        &'a self,
+        prefix: &str,
+        txn: &mut RwTxn<'a>,
+    ) -> Result<(), Error> {
+        // Info message
+        tracing::info!("{prefix}: migrating nostr connect services...");
+
+        // Migrate
+        self.m30_migrate_nostr_connect_services(txn)?;
+
+        Ok(())
+    }
+
+    fn m30_migrate_nostr_connect_services<'a>(&'a self, txn: &mut RwTxn<'a>) -> Result<(), Error> {
+        let loop_txn = self.env.read_txn()?;
+        for result in self.db_nip46servers3()?.iter(&loop_txn)? {
+            let (key, val) = result?;
+            let server3 = Nip46Server3::read_from_buffer(val)?;
+            let server4 = Nip46Server {
+                peer_pubkey: server3.peer_pubkey,
+                relays: server3.relays,
+                metadata: server3.metadata.map(|md| Nip46ClientMetadata {
+                    name: md.name,
+                    url: md.url.as_str().to_owned(),
+                    description: md.description,
+                }),
+                sign_approval: server3.sign_approval,
+                encrypt_approval: server3.encrypt_approval,
+                decrypt_approval: server3.decrypt_approval,
+                sign_kind_rules: server3.sign_kind_rules,
+                counterparty_rules: server3.counterparty_rules,
+                rate_limit: server3.rate_limit,
+            };
+            let bytes = server4.write_to_vec()?;
+            self.db_nip46servers4()?.put(txn, key, &bytes)?;
+        }
+
+        // clear old database (we don't have an interface to delete it)
+        self.db_nip46servers3()?.clear(txn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::Error;
+use crate::storage::types::Mute1;
+use crate::storage::Storage;
+use heed::RwTxn;
+use nostr_types::EventKind;
+
+impl Storage {
+    pub(super) fn m31_trigger(&self) -> Result<(), Error> {
+        let _ = self.db_mutes1()?;
+        Ok(())
+    }
+
+    pub(super) fn m31_migrate<'a>(
+        &'a self,
+        prefix: &str,
+        txn: &mut RwTxn<'a>,
+    ) -> Result<(), Error> {
+        // Info message
+        tracing::info!("{prefix}: seeding muted words, hashtags and threads...");
+
+        // Migrate
+        self.m31_seed_mutes(txn)?;
+
+        Ok(())
+    }
+
+    fn m31_seed_mutes<'a>(&'a self, txn: &mut RwTxn<'a>) -> Result<(), Error> {
+        let pubkey = match self.read_setting_public_key() {
+            Some(pk) => pk,
+            None => return Ok(()),
+        };
+
+        // Don't clobber mutes that were already recorded
+        if !self.read_mutes1(pubkey)?.is_empty() {
+            return Ok(());
+        }
+
+        let event = match self.get_replaceable_event(EventKind::MuteList, pubkey, "")? {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+
+        // Only the public entries can be read here, and they stay public. The private
+        // ones are encrypted, and are picked up the next time the mute list is updated
+        // from this event.
+        let mut mutes: Vec<(Mute1, bool)> = Vec::new();
+        for tag in &event.tags {
+            if let Some(mute) = Mute1::from_tag(tag) {
+                if !mutes.iter().any(|(m, _)| *m == mute) {
+                    mutes.push((mute, true));
+                }
+            }
+        }
+
+        if !mutes.is_empty() {
+            self.write_mutes1(pubkey, &mutes, Some(txn))?;
+        }
+
+        tracing::info!("Seeded {} muted words, hashtags and threads", mutes.len());
+
+        Ok(())
+    }
+}
@@ -19,7 +19,12 @@ mod m23;
 mod m24;
 mod m25;
 mod m26;
+mod m27;
+mod m28;
+mod m29;
 mod m3;
+mod m30;
+mod m31;
 mod m4;
 mod m5;
 mod m6;
@@ -32,7 +37,7 @@ use crate::error::{Error, ErrorKind};
 use heed::RwTxn;
 
 impl Storage {
-    const MAX_MIGRATION_LEVEL: u32 = 26;
+    const MAX_MIGRATION_LEVEL: u32 = 31;
 
     /// Initialize the database from empty
     pub(super) fn init_from_empty(&self) -> Result<(), Error> {
@@ -113,6 +118,11 @@ impl Storage {
             24 => self.m24_trigger()?,
             25 => self.m25_trigger()?,
             26 => self.m26_trigger()?,
+            27 => self.m27_trigger()?,
+            28 => self.m28_trigger()?,
+            29 => self.m29_trigger()?,
+            30 => self.m30_trigger()?,
+            31 => self.m31_trigger()?,
             _ => panic!("Unreachable migration level"),
         }
 
@@ -148,6 +158,11 @@ impl Storage {
             24 => self.m24_migrate(&prefix, txn)?,
             25 => self.m25_migrate(&prefix, txn)?,
             26 => self.m26_migrate(&prefix, txn)?,
+            27 => self.m27_migrate(&prefix, txn)?,
+            28 => self.m28_migrate(&prefix, txn)?,
+            29 => self.m29_migrate(&prefix, txn)?,
+            30 => self.m30_migrate(&prefix, txn)?,
+            31 => self.m31_migrate(&prefix, txn)?,
             _ => panic!("Unreachable migration level"),
         };
 
@@ -16,6 +16,9 @@ mod migrations;
 pub mod types;
 
 // database implementations
+mod accounts1;
+mod bookmarks1;
+mod drafts1;
 mod event_ek_c_index1;
 mod event_ek_pk_index1;
 mod event_seen_on_relay1;
@@ -24,9 +27,17 @@ mod event_viewed1;
 mod events1;
 mod events2;
 mod events3;
+mod follow_graph1;
 mod hashtags1;
+mod media_cache1;
+mod metadata_history1;
+mod mutes1;
+mod nip05_history1;
+mod nip46_audit1;
 mod nip46servers1;
 mod nip46servers2;
+mod nip46servers3;
+mod nip46servers4;
 mod people1;
 mod people2;
 mod person_lists1;
@@ -34,6 +45,7 @@ mod person_lists2;
 mod person_lists_metadata1;
 mod person_lists_metadata2;
 mod person_lists_metadata3;
+mod person_lists_sync_base1;
 mod person_relays1;
 mod relationships1;
 mod relationships_by_addr1;
@@ -41,18 +53,27 @@ mod relationships_by_id1;
 mod relays1;
 mod relays2;
 mod reprel1;
+mod scheduled_posts1;
 mod unindexed_giftwraps1;
 mod versioned;
 
+use crate::bookmarks::BookmarkList;
 use crate::dm_channel::{DmChannel, DmChannelData};
+use crate::drafts::Draft;
 use crate::error::{Error, ErrorKind};
+use crate::fetcher::MediaCacheEntry;
 use crate::globals::GLOBALS;
-use crate::nip46::{Nip46Server, Nip46UnconnectedServer};
-use crate::people::{Person, PersonList, PersonListMetadata};
+use crate::gossip_identity::Account;
+use crate::metadata_history::MetadataVersion;
+use crate::nip05::Nip05HistoryEntry;
+use crate::nip46::{Nip46AuditEntry, Nip46Server, Nip46UnconnectedServer};
+use crate::people::{Mute, Person, PersonList, PersonListMetadata};
 use crate::person_relay::PersonRelay;
 use crate::profile::Profile;
 use crate::relationship::{RelationshipByAddr, RelationshipById};
 use crate::relay::Relay;
+use crate::scheduled::ScheduledPost;
+use crate::wot::GraphEdge;
 use gossip_relay_picker::Direction;
 use heed::types::UnalignedSlice;
 use heed::{Database, Env, EnvFlags, EnvOpenOptions, RwTxn};
@@ -192,7 +213,7 @@ impl Storage {
             builder.flags(EnvFlags::NO_TLS);
         }
         // builder.max_readers(126); // this is the default
-        builder.max_dbs(32);
+        builder.max_dbs(48);
 
         // This has to be big enough for all the data.
         // Note that it is the size of the map in VIRTUAL address space,
@@ -233,14 +254,25 @@ impl Storage {
         //
         // old-version databases will be handled by their migration code and only
         // triggered into existence if their migration is necessary.
+        let _ = self.db_accounts()?;
         let _ = self.db_event_ek_c_index()?;
         let _ = self.db_event_ek_pk_index()?;
         let _ = self.db_event_tag_index()?;
+        let _ = self.db_bookmarks()?;
+        let _ = self.db_drafts()?;
+        let _ = self.db_scheduled_posts()?;
         let _ = self.db_events()?;
         let _ = self.db_event_seen_on_relay()?;
+        let _ = self.db_follow_graph()?;
+        let _ = self.db_follow_graph_reverse()?;
         let _ = self.db_event_viewed()?;
         let _ = self.db_hashtags()?;
+        let _ = self.db_media_cache()?;
+        let _ = self.db_metadata_history()?;
+        let _ = self.db_mutes()?;
+        let _ = self.db_nip05_history()?;
         let _ = self.db_nip46servers()?;
+        let _ = self.db_nip46_audit()?;
         let _ = self.db_people()?;
         let _ = self.db_person_relays()?;
         let _ = self.db_relationships_by_id()?;
@@ -249,6 +281,7 @@ impl Storage {
         let _ = self.db_unindexed_giftwraps()?;
         let _ = self.db_person_lists()?;
         let _ = self.db_person_lists_metadata()?;
+        let _ = self.db_person_lists_sync_base()?;
 
         // Do migrations
         match self.read_migration_level()? {
@@ -274,6 +307,11 @@ impl Storage {
 
     // Database getters ---------------------------------
 
+    #[inline]
+    pub(crate) fn db_accounts(&self) -> Result<RawDatabase, Error> {
+        self.db_accounts1()
+    }
+
     #[inline]
     pub(crate) fn db_event_ek_c_index(&self) -> Result<RawDatabase, Error> {
         self.db_event_ek_c_index1()
@@ -304,14 +342,64 @@ impl Storage {
         self.db_event_viewed1()
     }
 
+    #[inline]
+    pub(crate) fn db_follow_graph(&self) -> Result<RawDatabase, Error> {
+        self.db_follow_graph1()
+    }
+
+    #[inline]
+    pub(crate) fn db_follow_graph_reverse(&self) -> Result<RawDatabase, Error> {
+        self.db_follow_graph1_reverse()
+    }
+
     #[inline]
     pub(crate) fn db_hashtags(&self) -> Result<RawDatabase, Error> {
         self.db_hashtags1()
     }
 
+    #[inline]
+    pub(crate) fn db_bookmarks(&self) -> Result<RawDatabase, Error> {
+        self.db_bookmarks1()
+    }
+
+    #[inline]
+    pub(crate) fn db_drafts(&self) -> Result<RawDatabase, Error> {
+        self.db_drafts1()
+    }
+
+    #[inline]
+    pub(crate) fn db_scheduled_posts(&self) -> Result<RawDatabase, Error> {
+        self.db_scheduled_posts1()
+    }
+
+    #[inline]
+    pub(crate) fn db_mutes(&self) -> Result<RawDatabase, Error> {
+        self.db_mutes1()
+    }
+
+    #[inline]
+    pub(crate) fn db_media_cache(&self) -> Result<RawDatabase, Error> {
+        self.db_media_cache1()
+    }
+
+    #[inline]
+    pub(crate) fn db_metadata_history(&self) -> Result<RawDatabase, Error> {
+        self.db_metadata_history1()
+    }
+
+    #[inline]
+    pub(crate) fn db_nip05_history(&self) -> Result<RawDatabase, Error> {
+        self.db_nip05_history1()
+    }
+
     #[inline]
     pub(crate) fn db_nip46servers(&self) -> Result<RawDatabase, Error> {
-        self.db_nip46servers2()
+        self.db_nip46servers4()
+    }
+
+    #[inline]
+    pub(crate) fn db_nip46_audit(&self) -> Result<RawDatabase, Error> {
+        self.db_nip46_audit1()
     }
 
     #[inline]
@@ -354,6 +442,11 @@ impl Storage {
         self.db_person_lists_metadata3()
     }
 
+    #[inline]
+    pub(crate) fn db_person_lists_sync_base(&self) -> Result<RawDatabase, Error> {
+        self.db_person_lists_sync_base1()
+    }
+
     // Database length functions ---------------------------------
 
     /// The number of records in the general table
@@ -621,6 +714,47 @@ impl Storage {
         }
     }
 
+    /// Record that we migrated from one key to another
+    pub fn add_key_migration<'a>(
+        &'a self,
+        old: PublicKey,
+        new: PublicKey,
+        when: Unixtime,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let mut migrations = self.read_key_migrations()?;
+        migrations.push((old, new, when));
+        let bytes = migrations.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.general.put(txn, b"key_migrations", &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Read the key migrations we have done, as (old, new, when)
+    pub fn read_key_migrations(&self) -> Result<Vec<(PublicKey, PublicKey, Unixtime)>, Error> {
+        let txn = self.env.read_txn()?;
+
+        match self.general.get(&txn, b"key_migrations")? {
+            None => Ok(vec![]),
+            Some(bytes) => Ok(Vec::<(PublicKey, PublicKey, Unixtime)>::read_from_buffer(
+                bytes,
+            )?),
+        }
+    }
+
     /// Write NIP-46 unconnected server
     #[allow(dead_code)]
     pub fn write_nip46_unconnected_server<'a>(
@@ -744,9 +878,23 @@ impl Storage {
         60 * 15
     );
     def_setting!(hide_mutes_entirely, b"hide_mutes_entirely", bool, true);
+    def_setting!(inbox_hide_untrusted, b"inbox_hide_untrusted", bool, false);
     def_setting!(reactions, b"reactions", bool, true);
     def_setting!(enable_zap_receipts, b"enable_zap_receipts", bool, true);
     def_setting!(show_media, b"show_media", bool, true);
+    def_setting!(link_previews, b"link_previews", bool, false);
+    def_setting!(
+        link_preview_allowed_domains,
+        b"link_preview_allowed_domains",
+        String,
+        "".to_owned()
+    );
+    def_setting!(
+        link_preview_denied_domains,
+        b"link_preview_denied_domains",
+        String,
+        "".to_owned()
+    );
     def_setting!(
         approve_content_warning,
         b"approve_content_warning",
@@ -756,7 +904,21 @@ impl Storage {
     def_setting!(show_deleted_events, b"show_deleted_events", bool, false);
     def_setting!(pow, b"pow", u8, 0);
     def_setting!(set_client_tag, b"set_client_tag", bool, false);
+    def_setting!(
+        send_missed_scheduled_posts,
+        b"send_missed_scheduled_posts",
+        bool,
+        false
+    );
     def_setting!(set_user_agent, b"set_user_agent", bool, false);
+    def_setting!(upload_server, b"upload_server", String, "".to_owned());
+    def_setting!(
+        upload_protocol,
+        b"upload_protocol",
+        String,
+        "NIP-96".to_owned()
+    );
+    def_setting!(upload_strip_metadata, b"upload_strip_metadata", bool, true);
     def_setting!(delegatee_tag, b"delegatee_tag", String, String::new());
     def_setting!(max_fps, b"max_fps", u32, 12);
     def_setting!(
@@ -787,6 +949,7 @@ impl Storage {
         true
     );
     def_setting!(posting_area_at_top, b"posting_area_at_top", bool, true);
+    def_setting!(petnames_first, b"petnames_first", bool, false);
     def_setting!(status_bar, b"status_bar", bool, false);
     def_setting!(
         image_resize_algorithm,
@@ -832,6 +995,12 @@ impl Storage {
         u64,
         8
     );
+    def_setting!(
+        link_preview_becomes_stale_hours,
+        b"link_preview_becomes_stale_hours",
+        u64,
+        72
+    );
     def_setting!(
         max_websocket_message_size_kb,
         b"max_websocket_message_size_kb",
@@ -908,6 +1077,8 @@ impl Storage {
     );
     def_setting!(prune_period_days, b"prune_period_days", u64, 90);
     def_setting!(cache_prune_period_days, b"cache_prune_period_days", u64, 90);
+    def_setting!(media_cache_max_mb, b"media_cache_max_mb", u64, 2048);
+    def_setting!(max_metadata_versions, b"max_metadata_versions", u8, 10);
     def_setting!(
         avoid_spam_on_unsafe_relays,
         b"avoid_spam_on_unsafe_relays",
@@ -965,13 +1136,56 @@ impl Storage {
     }
 
     /// Deallocate an empty person list
-    #[inline]
     pub fn deallocate_person_list<'a>(
         &'a self,
         list: PersonList,
         rw_txn: Option<&mut RwTxn<'a>>,
     ) -> Result<(), Error> {
-        self.deallocate_person_list3(list, rw_txn)
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.deallocate_person_list3(list, Some(txn))?;
+
+            // A list allocated here later must not inherit this one's sync base
+            if let Some(pubkey) = self.read_setting_public_key() {
+                self.delete_person_list_sync_base1(pubkey, list, Some(txn))?;
+            }
+
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get the people in a person list of the given account as of the last time
+    /// it was synchronized with relays. The bool is true if the entry is public.
+    #[inline]
+    pub fn get_person_list_sync_base(
+        &self,
+        owner: PublicKey,
+        list: PersonList,
+    ) -> Result<Vec<(PublicKey, bool)>, Error> {
+        self.read_person_list_sync_base1(owner, list)
+    }
+
+    /// Set the people in a person list of the given account as of now, when it has
+    /// been synchronized with relays
+    #[inline]
+    pub fn set_person_list_sync_base<'a>(
+        &'a self,
+        owner: PublicKey,
+        list: PersonList,
+        entries: &[(PublicKey, bool)],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_person_list_sync_base1(owner, list, entries, rw_txn)
     }
 
     pub fn rename_person_list<'a>(
@@ -2394,6 +2608,13 @@ impl Storage {
         self.read_person_lists2(pubkey)
     }
 
+    /// Read the person lists of everybody in any of our lists
+    pub fn read_all_person_lists(
+        &self,
+    ) -> Result<Vec<(PublicKey, HashMap<PersonList, bool>)>, Error> {
+        self.read_all_person_lists2()
+    }
+
     /// Write person lists
     pub fn write_person_lists<'a>(
         &'a self,
@@ -2599,22 +2820,431 @@ impl Storage {
         server: &Nip46Server,
         rw_txn: Option<&mut RwTxn<'a>>,
     ) -> Result<(), Error> {
-        self.write_nip46server2(server, rw_txn)
+        self.write_nip46server4(server, rw_txn)
+    }
+
+    /// Modify a NIP-46 server record in place, if it exists
+    pub fn modify_nip46server<'a, M>(
+        &'a self,
+        pubkey: PublicKey,
+        modify: M,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error>
+    where
+        M: FnMut(&mut Nip46Server),
+    {
+        self.modify_nip46server4(pubkey, modify, rw_txn)
     }
 
     pub fn read_nip46server(&self, pubkey: PublicKey) -> Result<Option<Nip46Server>, Error> {
-        self.read_nip46server2(pubkey)
+        self.read_nip46server4(pubkey)
     }
 
     pub fn read_all_nip46servers(&self) -> Result<Vec<Nip46Server>, Error> {
-        self.read_all_nip46servers2()
+        self.read_all_nip46servers4()
     }
 
+    /// Delete a NIP-46 server along with its audit log
     pub fn delete_nip46server<'a>(
         &'a self,
         pubkey: PublicKey,
         rw_txn: Option<&mut RwTxn<'a>>,
     ) -> Result<(), Error> {
-        self.delete_nip46server2(pubkey, rw_txn)
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.delete_nip46server4(pubkey, Some(txn))?;
+            self.delete_nip46_audit_log1(pubkey, Some(txn))?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Add an entry to a NIP-46 client's audit log
+    #[inline]
+    pub fn add_nip46_audit_entry<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        entry: Nip46AuditEntry,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.add_nip46_audit_entry1(pubkey, entry, rw_txn)
+    }
+
+    /// Read a NIP-46 client's audit log, oldest first
+    #[inline]
+    pub fn read_nip46_audit_log(&self, pubkey: PublicKey) -> Result<Vec<Nip46AuditEntry>, Error> {
+        self.read_nip46_audit_log1(pubkey)
+    }
+
+    /// Write the words, hashtags and threads muted by the given account
+    #[inline]
+    pub fn write_mutes<'a>(
+        &'a self,
+        owner: PublicKey,
+        mutes: &[(Mute, bool)],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_mutes1(owner, mutes, rw_txn)
+    }
+
+    /// Read the words, hashtags and threads muted by the given account. The bool
+    /// is true for public entries.
+    #[inline]
+    pub fn read_mutes(&self, owner: PublicKey) -> Result<Vec<(Mute, bool)>, Error> {
+        self.read_mutes1(owner)
+    }
+
+    /// Write a bookmark list or bookmark set of the given account
+    #[inline]
+    pub fn write_bookmark_list<'a>(
+        &'a self,
+        owner: PublicKey,
+        list: &BookmarkList,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_bookmark_list1(owner, list, rw_txn)
+    }
+
+    /// Read a bookmark list (empty dtag) or bookmark set of the given account
+    #[inline]
+    pub fn read_bookmark_list(
+        &self,
+        owner: PublicKey,
+        dtag: &str,
+    ) -> Result<Option<BookmarkList>, Error> {
+        self.read_bookmark_list1(owner, dtag)
+    }
+
+    /// Read the bookmark list and all bookmark sets of the given account
+    #[inline]
+    pub fn read_bookmark_lists(&self, owner: PublicKey) -> Result<Vec<BookmarkList>, Error> {
+        self.read_bookmark_lists1(owner)
+    }
+
+    /// Delete a bookmark list or bookmark set of the given account
+    #[inline]
+    pub fn delete_bookmark_list<'a>(
+        &'a self,
+        owner: PublicKey,
+        dtag: &str,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.delete_bookmark_list1(owner, dtag, rw_txn)
+    }
+
+    /// Write a draft of the given account
+    #[inline]
+    pub fn write_draft<'a>(
+        &'a self,
+        owner: PublicKey,
+        draft: &Draft,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_draft1(owner, draft, rw_txn)
+    }
+
+    /// Read a draft of the given account
+    #[inline]
+    pub fn read_draft(&self, owner: PublicKey, id: u64) -> Result<Option<Draft>, Error> {
+        self.read_draft1(owner, id)
+    }
+
+    /// Read all drafts of the given account
+    #[inline]
+    pub fn read_drafts(&self, owner: PublicKey) -> Result<Vec<Draft>, Error> {
+        self.read_drafts1(owner)
+    }
+
+    /// Delete a draft of the given account
+    #[inline]
+    pub fn delete_draft<'a>(
+        &'a self,
+        owner: PublicKey,
+        id: u64,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.delete_draft1(owner, id, rw_txn)
+    }
+
+    /// Write a post to the outbox of the given account
+    #[inline]
+    pub fn write_scheduled_post<'a>(
+        &'a self,
+        owner: PublicKey,
+        post: &ScheduledPost,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_scheduled_post1(owner, post, rw_txn)
+    }
+
+    /// Read a post in the outbox of the given account
+    #[inline]
+    pub fn read_scheduled_post(
+        &self,
+        owner: PublicKey,
+        id: u64,
+    ) -> Result<Option<ScheduledPost>, Error> {
+        self.read_scheduled_post1(owner, id)
+    }
+
+    /// Read all posts in the outbox of the given account
+    #[inline]
+    pub fn read_scheduled_posts(&self, owner: PublicKey) -> Result<Vec<ScheduledPost>, Error> {
+        self.read_scheduled_posts1(owner)
+    }
+
+    /// Remove a post from the outbox of the given account
+    #[inline]
+    pub fn delete_scheduled_post<'a>(
+        &'a self,
+        owner: PublicKey,
+        id: u64,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.delete_scheduled_post1(owner, id, rw_txn)
+    }
+
+    /// Replace all of the follow graph edges of the given kind going out of
+    /// `from`. Returns the edges that were there before.
+    #[inline]
+    pub fn set_graph_edges<'a>(
+        &'a self,
+        edge: GraphEdge,
+        from: PublicKey,
+        to: &[PublicKey],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<Vec<PublicKey>, Error> {
+        self.set_graph_edges1(edge, from, to, rw_txn)
+    }
+
+    /// The people that `from` follows (or mutes), as far as we know
+    #[inline]
+    pub fn get_graph_edges_out(
+        &self,
+        edge: GraphEdge,
+        from: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        self.get_graph_edges_out1(edge, from)
+    }
+
+    /// The people that follow (or mute) `to`, as far as we know
+    #[inline]
+    pub fn get_graph_edges_in(
+        &self,
+        edge: GraphEdge,
+        to: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        self.get_graph_edges_in1(edge, to)
+    }
+
+    /// Append to a person's NIP-05 history, keeping at most `max` entries
+    #[inline]
+    pub fn add_nip05_history<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        entry: Nip05HistoryEntry,
+        max: usize,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.add_nip05_history1(pubkey, entry, max, rw_txn)
+    }
+
+    /// Read a person's NIP-05 history, oldest first
+    #[inline]
+    pub fn read_nip05_history(&self, pubkey: PublicKey) -> Result<Vec<Nip05HistoryEntry>, Error> {
+        self.read_nip05_history1(pubkey)
+    }
+
+    /// Add a version to a person's metadata history, keeping the newest `max`.
+    /// Returns false if we already had it.
+    #[inline]
+    pub fn add_metadata_version<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        version: MetadataVersion,
+        max: usize,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<bool, Error> {
+        self.add_metadata_version1(pubkey, version, max, rw_txn)
+    }
+
+    /// Read a person's metadata history, oldest first
+    #[inline]
+    pub fn read_metadata_history(&self, pubkey: PublicKey) -> Result<Vec<MetadataVersion>, Error> {
+        self.read_metadata_history1(pubkey)
+    }
+
+    /// Write a media cache entry, replacing any for the same URL
+    #[inline]
+    pub fn write_media_cache_entry<'a>(
+        &'a self,
+        entry: &MediaCacheEntry,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_media_cache_entry1(entry, rw_txn)
+    }
+
+    /// Read the media cache entry for a URL
+    #[inline]
+    pub fn read_media_cache_entry(&self, url: &str) -> Result<Option<MediaCacheEntry>, Error> {
+        self.read_media_cache_entry1(url)
+    }
+
+    /// Delete the media cache entry for a URL
+    #[inline]
+    pub fn delete_media_cache_entry<'a>(
+        &'a self,
+        url: &str,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.delete_media_cache_entry1(url, rw_txn)
+    }
+
+    /// Read every media cache entry
+    #[inline]
+    pub fn read_all_media_cache_entries(&self) -> Result<Vec<MediaCacheEntry>, Error> {
+        self.read_all_media_cache_entries1()
+    }
+
+    /// Write an inactive account
+    #[inline]
+    pub fn write_account<'a>(
+        &'a self,
+        account: &Account,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.write_account1(account, rw_txn)
+    }
+
+    /// Read an inactive account
+    #[inline]
+    pub fn read_account(&self, pubkey: PublicKey) -> Result<Option<Account>, Error> {
+        self.read_account1(pubkey)
+    }
+
+    /// Read all inactive accounts
+    #[inline]
+    pub fn read_all_accounts(&self) -> Result<Vec<Account>, Error> {
+        self.read_all_accounts1()
+    }
+
+    /// Delete an inactive account
+    #[inline]
+    pub fn delete_account<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        self.delete_account1(pubkey, rw_txn)
+    }
+
+    /// Stash the active account's state (keys, person lists and relay usage) into
+    /// the accounts table. Returns the stashed account, or None if there is no
+    /// active account.
+    pub(crate) fn stash_active_account<'a>(
+        &'a self,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<Option<Account>, Error> {
+        let pubkey = match self.read_setting_public_key() {
+            Some(pk) => pk,
+            None => return Ok(None),
+        };
+
+        let account = Account {
+            pubkey,
+            encrypted_private_key: self.read_encrypted_private_key()?.map(|epk| epk.0),
+            person_lists: self.read_all_person_lists()?,
+            person_lists_metadata: self.get_all_person_list_metadata()?,
+            relay_usage_bits: self
+                .filter_relays(|r| r.usage_bits != 0)?
+                .drain(..)
+                .map(|r| (r.url, r.usage_bits))
+                .collect(),
+        };
+
+        self.write_account(&account, rw_txn)?;
+
+        Ok(Some(account))
+    }
+
+    /// Reset the active account's person lists (and optionally its relay usage)
+    /// to that of a brand new account. This does not touch the keys.
+    pub(crate) fn reset_active_account<'a>(
+        &'a self,
+        keep_relays: bool,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_person_lists()?.clear(txn)?;
+            self.db_person_lists_metadata()?.clear(txn)?;
+
+            // The well-known lists always exist
+            for list in [PersonList::Followed, PersonList::Muted] {
+                self.set_person_list_metadata(list, &Default::default(), Some(txn))?;
+            }
+
+            if !keep_relays {
+                self.modify_all_relays(|r| r.usage_bits = 0, Some(txn))?;
+            }
+
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Make the given (stashed) account's person lists and relay usage the active
+    /// ones, and remove it from the accounts table. This does not touch the keys.
+    pub(crate) fn restore_account<'a>(
+        &'a self,
+        account: &Account,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.reset_active_account(false, Some(txn))?;
+
+            for (pubkey, lists) in &account.person_lists {
+                self.write_person_lists(pubkey, lists.clone(), Some(txn))?;
+            }
+            for (list, metadata) in &account.person_lists_metadata {
+                self.set_person_list_metadata(*list, metadata, Some(txn))?;
+            }
+            for (url, bits) in &account.relay_usage_bits {
+                self.modify_relay(url, |r| r.usage_bits = *bits, Some(txn))?;
+            }
+
+            self.delete_account(account.pubkey, Some(txn))?;
+
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
     }
 }
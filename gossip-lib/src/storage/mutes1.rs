@@ -0,0 +1,84 @@
+use super::types::Mute1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// PublicKey (the owner of the mute list) -> Vec<(Mute1, bool)>
+//   key: pubkey.as_bytes()
+//   val: mutes.write_to_vec() | Vec::<(Mute1, bool)>::read_from_buffer(val)
+//
+// The bool is true if the entry is public, false if private.
+
+static MUTES1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut MUTES1_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_mutes1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = MUTES1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = MUTES1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = MUTES1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("mutes1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                MUTES1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_mutes1<'a>(
+        &'a self,
+        owner: PublicKey,
+        mutes: &[(Mute1, bool)],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = owner.as_bytes();
+        let bytes = mutes.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_mutes1()?.put(txn, key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_mutes1(&self, owner: PublicKey) -> Result<Vec<(Mute1, bool)>, Error> {
+        let key = owner.as_bytes();
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_mutes1()?.get(&txn, key)? {
+            Some(bytes) => Vec::<(Mute1, bool)>::read_from_buffer(bytes)?,
+            None => vec![],
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use super::types::Nip05HistoryEntry1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// PublicKey -> Vec<Nip05HistoryEntry1>
+//   key: pubkey.as_bytes()
+//   val: history.write_to_vec() | Vec::<Nip05HistoryEntry1>::read_from_buffer(val)
+//
+// Oldest first, and only entries where something changed are kept.
+
+static NIP05_HISTORY1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut NIP05_HISTORY1_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_nip05_history1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = NIP05_HISTORY1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = NIP05_HISTORY1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = NIP05_HISTORY1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("nip05_history1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                NIP05_HISTORY1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    /// Append to a person's NIP-05 history, keeping at most `max` entries
+    pub(crate) fn add_nip05_history1<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        entry: Nip05HistoryEntry1,
+        max: usize,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = pubkey.as_bytes();
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let mut history = match self.db_nip05_history1()?.get(txn, key)? {
+                Some(bytes) => Vec::<Nip05HistoryEntry1>::read_from_buffer(bytes)?,
+                None => vec![],
+            };
+            history.push(entry);
+            if history.len() > max {
+                let excess = history.len() - max;
+                history.drain(..excess);
+            }
+            let bytes = history.write_to_vec()?;
+            self.db_nip05_history1()?.put(txn, key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_nip05_history1(
+        &self,
+        pubkey: PublicKey,
+    ) -> Result<Vec<Nip05HistoryEntry1>, Error> {
+        let key = pubkey.as_bytes();
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_nip05_history1()?.get(&txn, key)? {
+            Some(bytes) => Vec::<Nip05HistoryEntry1>::read_from_buffer(bytes)?,
+            None => vec![],
+        })
+    }
+}
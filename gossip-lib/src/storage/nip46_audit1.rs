@@ -0,0 +1,121 @@
+use crate::error::Error;
+use crate::nip46::Nip46AuditEntry;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// PublicKey -> Vec<Nip46AuditEntry>
+//   key: pubkey.as_bytes()
+//   val: entries.write_to_vec() | Vec::<Nip46AuditEntry>::read_from_buffer(val)
+
+// How many entries we keep per client
+const MAX_AUDIT_ENTRIES: usize = 500;
+
+static NIP46_AUDIT1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut NIP46_AUDIT1_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_nip46_audit1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = NIP46_AUDIT1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = NIP46_AUDIT1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = NIP46_AUDIT1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("nip46_audit1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                NIP46_AUDIT1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn add_nip46_audit_entry1<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        entry: Nip46AuditEntry,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = pubkey.as_bytes();
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let mut entries = match self.db_nip46_audit1()?.get(txn, key)? {
+                Some(bytes) => Vec::<Nip46AuditEntry>::read_from_buffer(bytes)?,
+                None => Vec::new(),
+            };
+            entries.push(entry);
+            if entries.len() > MAX_AUDIT_ENTRIES {
+                let excess = entries.len() - MAX_AUDIT_ENTRIES;
+                entries.drain(..excess);
+            }
+            let bytes = entries.write_to_vec()?;
+            self.db_nip46_audit1()?.put(txn, key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_nip46_audit_log1(
+        &self,
+        pubkey: PublicKey,
+    ) -> Result<Vec<Nip46AuditEntry>, Error> {
+        let key = pubkey.as_bytes();
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_nip46_audit1()?.get(&txn, key)? {
+            Some(bytes) => Vec::<Nip46AuditEntry>::read_from_buffer(bytes)?,
+            None => Vec::new(),
+        })
+    }
+
+    pub(crate) fn delete_nip46_audit_log1<'a>(
+        &'a self,
+        pubkey: PublicKey,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = pubkey.as_bytes();
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_nip46_audit1()?.delete(txn, key);
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+}
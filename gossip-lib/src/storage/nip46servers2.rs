@@ -1,15 +1,11 @@
 use crate::error::Error;
-use crate::nip46::Nip46Server;
 use crate::storage::{RawDatabase, Storage};
 use heed::types::UnalignedSlice;
-use heed::RwTxn;
-use nostr_types::PublicKey;
-use speedy::{Readable, Writable};
 use std::sync::Mutex;
 
-// PublicKey -> Nip46Server
+// PublicKey -> Nip46Server2 (see migration 27)
 //   key: pubkey.as_bytes()
-//   val: nip46server.write_to_vec() | Nip46Server::read_from_buffer(val)
+//   val: nip46server.write_to_vec() | Nip46Server2::read_from_buffer(val)
 
 static NIP46SERVER2_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
 static mut NIP46SERVER2_DB: Option<RawDatabase> = None;
@@ -44,77 +40,4 @@ impl Storage {
             }
         }
     }
-
-    #[allow(dead_code)]
-    pub(crate) fn write_nip46server2<'a>(
-        &'a self,
-        server: &Nip46Server,
-        rw_txn: Option<&mut RwTxn<'a>>,
-    ) -> Result<(), Error> {
-        let key = server.peer_pubkey.as_bytes();
-        let bytes = server.write_to_vec()?;
-
-        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
-            self.db_nip46servers2()?.put(txn, key, &bytes)?;
-            Ok(())
-        };
-
-        match rw_txn {
-            Some(txn) => f(txn)?,
-            None => {
-                let mut txn = self.env.write_txn()?;
-                f(&mut txn)?;
-                txn.commit()?;
-            }
-        };
-
-        Ok(())
-    }
-
-    pub(crate) fn read_nip46server2(
-        &self,
-        pubkey: PublicKey,
-    ) -> Result<Option<Nip46Server>, Error> {
-        let key = pubkey.as_bytes();
-        let txn = self.env.read_txn()?;
-        Ok(match self.db_nip46servers2()?.get(&txn, key)? {
-            Some(bytes) => Some(Nip46Server::read_from_buffer(bytes)?),
-            None => None,
-        })
-    }
-
-    pub(crate) fn read_all_nip46servers2(&self) -> Result<Vec<Nip46Server>, Error> {
-        let txn = self.env.read_txn()?;
-        let mut output: Vec<Nip46Server> = Vec::new();
-        for result in self.db_nip46servers2()?.iter(&txn)? {
-            let (_key, val) = result?;
-            let server = Nip46Server::read_from_buffer(val)?;
-            output.push(server);
-        }
-        Ok(output)
-    }
-
-    pub(crate) fn delete_nip46server2<'a>(
-        &'a self,
-        pubkey: PublicKey,
-        rw_txn: Option<&mut RwTxn<'a>>,
-    ) -> Result<(), Error> {
-        let key = pubkey.as_bytes();
-
-        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
-            let _ = self.db_nip46servers2()?.delete(txn, key);
-            Ok(())
-        };
-
-        match rw_txn {
-            Some(txn) => f(txn)?,
-            None => {
-                let mut txn = self.env.write_txn()?;
-                f(&mut txn)?;
-                txn.commit()?;
-            }
-        };
-
-        Ok(())
-    }
 }
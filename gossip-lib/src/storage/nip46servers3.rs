@@ -0,0 +1,43 @@
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use std::sync::Mutex;
+
+// PublicKey -> Nip46Server3 (see migration 30)
+//   key: pubkey.as_bytes()
+//   val: nip46server.write_to_vec() | Nip46Server3::read_from_buffer(val)
+
+static NIP46SERVER3_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut NIP46SERVER3_DB: Option<RawDatabase> = None;
+
+impl Storage {
+    pub(super) fn db_nip46servers3(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = NIP46SERVER3_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = NIP46SERVER3_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = NIP46SERVER3_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("nip46server3")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                NIP46SERVER3_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+}
@@ -83,6 +83,20 @@ impl Storage {
         Ok(())
     }
 
+    pub(crate) fn read_all_person_lists2(
+        &self,
+    ) -> Result<Vec<(PublicKey, HashMap<PersonList1, bool>)>, Error> {
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<(PublicKey, HashMap<PersonList1, bool>)> = Vec::new();
+        for result in self.db_person_lists2()?.iter(&txn)? {
+            let (key, val) = result?;
+            let pubkey = PublicKey::from_bytes(key, true)?;
+            let map = HashMap::<PersonList1, bool>::read_from_buffer(val)?;
+            output.push((pubkey, map));
+        }
+        Ok(output)
+    }
+
     pub(crate) fn get_people_in_all_followed_lists2(&self) -> Result<Vec<PublicKey>, Error> {
         let txn = self.env.read_txn()?;
         let mut pubkeys: Vec<PublicKey> = Vec::new();
@@ -0,0 +1,121 @@
+use super::types::PersonList1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// (PublicKey, PersonList1) -> Vec<(PublicKey, bool)>
+//   key: owner.as_bytes() + u8::from(list)
+//   val: entries.write_to_vec() | Vec::<(PublicKey, bool)>::read_from_buffer(val)
+//
+// The people in the owner's list as of the last time it was synchronized with
+// relays. The bool is true if the entry is public, false if private.
+
+static PERSON_LISTS_SYNC_BASE1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut PERSON_LISTS_SYNC_BASE1_DB: Option<RawDatabase> = None;
+
+fn sync_base_key(owner: PublicKey, list: PersonList1) -> Vec<u8> {
+    let mut key = owner.as_bytes().to_vec();
+    key.push(list.into());
+    key
+}
+
+impl Storage {
+    pub(super) fn db_person_lists_sync_base1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = PERSON_LISTS_SYNC_BASE1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = PERSON_LISTS_SYNC_BASE1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = PERSON_LISTS_SYNC_BASE1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("person_lists_sync_base1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                PERSON_LISTS_SYNC_BASE1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_person_list_sync_base1<'a>(
+        &'a self,
+        owner: PublicKey,
+        list: PersonList1,
+        entries: &[(PublicKey, bool)],
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = sync_base_key(owner, list);
+        let bytes = entries.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_person_lists_sync_base1()?.put(txn, &key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_person_list_sync_base1(
+        &self,
+        owner: PublicKey,
+        list: PersonList1,
+    ) -> Result<Vec<(PublicKey, bool)>, Error> {
+        let key = sync_base_key(owner, list);
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_person_lists_sync_base1()?.get(&txn, &key)? {
+            Some(bytes) => Vec::<(PublicKey, bool)>::read_from_buffer(bytes)?,
+            None => vec![],
+        })
+    }
+
+    pub(crate) fn delete_person_list_sync_base1<'a>(
+        &'a self,
+        owner: PublicKey,
+        list: PersonList1,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = sync_base_key(owner, list);
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_person_lists_sync_base1()?.delete(txn, &key);
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use super::types::ScheduledPost1;
+use crate::error::Error;
+use crate::storage::{RawDatabase, Storage};
+use heed::types::UnalignedSlice;
+use heed::RwTxn;
+use nostr_types::PublicKey;
+use speedy::{Readable, Writable};
+use std::sync::Mutex;
+
+// (PublicKey, id) -> ScheduledPost1
+//   key: pubkey.as_bytes() + id.to_be_bytes()
+//   val: post.write_to_vec() | ScheduledPost1::read_from_buffer(val)
+
+static SCHEDULED_POSTS1_DB_CREATE_LOCK: Mutex<()> = Mutex::new(());
+static mut SCHEDULED_POSTS1_DB: Option<RawDatabase> = None;
+
+fn key(owner: PublicKey, id: u64) -> Vec<u8> {
+    let mut key: Vec<u8> = owner.as_bytes().to_owned();
+    key.extend(id.to_be_bytes());
+    key
+}
+
+impl Storage {
+    pub(super) fn db_scheduled_posts1(&self) -> Result<RawDatabase, Error> {
+        unsafe {
+            if let Some(db) = SCHEDULED_POSTS1_DB {
+                Ok(db)
+            } else {
+                // Lock.  This drops when anything returns.
+                let _lock = SCHEDULED_POSTS1_DB_CREATE_LOCK.lock();
+
+                // In case of a race, check again
+                if let Some(db) = SCHEDULED_POSTS1_DB {
+                    return Ok(db);
+                }
+
+                // Create it. We know that nobody else is doing this and that
+                // it cannot happen twice.
+                let mut txn = self.env.write_txn()?;
+                let db = self
+                    .env
+                    .database_options()
+                    .types::<UnalignedSlice<u8>, UnalignedSlice<u8>>()
+                    // no .flags needed
+                    .name("scheduled_posts1")
+                    .create(&mut txn)?;
+                txn.commit()?;
+                SCHEDULED_POSTS1_DB = Some(db);
+                Ok(db)
+            }
+        }
+    }
+
+    pub(crate) fn write_scheduled_post1<'a>(
+        &'a self,
+        owner: PublicKey,
+        post: &ScheduledPost1,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(owner, post.id);
+        let bytes = post.write_to_vec()?;
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            self.db_scheduled_posts1()?.put(txn, &key, &bytes)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+
+    pub(crate) fn read_scheduled_post1(
+        &self,
+        owner: PublicKey,
+        id: u64,
+    ) -> Result<Option<ScheduledPost1>, Error> {
+        let key = key(owner, id);
+        let txn = self.env.read_txn()?;
+        Ok(match self.db_scheduled_posts1()?.get(&txn, &key)? {
+            Some(bytes) => Some(ScheduledPost1::read_from_buffer(bytes)?),
+            None => None,
+        })
+    }
+
+    pub(crate) fn read_scheduled_posts1(
+        &self,
+        owner: PublicKey,
+    ) -> Result<Vec<ScheduledPost1>, Error> {
+        let txn = self.env.read_txn()?;
+        let mut output: Vec<ScheduledPost1> = Vec::new();
+        for result in self
+            .db_scheduled_posts1()?
+            .prefix_iter(&txn, owner.as_bytes())?
+        {
+            let (_key, val) = result?;
+            output.push(ScheduledPost1::read_from_buffer(val)?);
+        }
+        Ok(output)
+    }
+
+    pub(crate) fn delete_scheduled_post1<'a>(
+        &'a self,
+        owner: PublicKey,
+        id: u64,
+        rw_txn: Option<&mut RwTxn<'a>>,
+    ) -> Result<(), Error> {
+        let key = key(owner, id);
+
+        let f = |txn: &mut RwTxn<'a>| -> Result<(), Error> {
+            let _ = self.db_scheduled_posts1()?.delete(txn, &key)?;
+            Ok(())
+        };
+
+        match rw_txn {
+            Some(txn) => f(txn)?,
+            None => {
+                let mut txn = self.env.write_txn()?;
+                f(&mut txn)?;
+                txn.commit()?;
+            }
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use super::{PersonList1, PersonListMetadata3};
+use nostr_types::{PublicKey, RelayUrl};
+use speedy::{Readable, Writable};
+use std::collections::HashMap;
+
+/// The per-identity state of an account which is not currently active.
+///
+/// The active account lives in the regular tables (person lists, relay usage
+/// bits and the key settings). When switching accounts that state is stashed
+/// into one of these, and the target account's state is restored from its own.
+#[derive(Debug, Clone, Readable, Writable)]
+pub struct Account1 {
+    /// Public key of the account
+    pub pubkey: PublicKey,
+
+    /// Encrypted private key (ncryptsec), if we have one
+    pub encrypted_private_key: Option<String>,
+
+    /// Membership of people in this account's person lists
+    pub person_lists: Vec<(PublicKey, HashMap<PersonList1, bool>)>,
+
+    /// This account's person list metadata
+    pub person_lists_metadata: Vec<(PersonList1, PersonListMetadata3)>,
+
+    /// This account's relay usage bits (read, write, inbox, outbox, ...)
+    pub relay_usage_bits: Vec<(RelayUrl, u64)>,
+}
+
+impl Account1 {
+    pub fn new(pubkey: PublicKey) -> Account1 {
+        Account1 {
+            pubkey,
+            encrypted_private_key: None,
+            person_lists: Vec::new(),
+            person_lists_metadata: Vec::new(),
+            relay_usage_bits: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use nostr_types::{Event, EventAddr, EventKind, Id, PublicKey, Tag};
+use speedy::{Readable, Writable};
+
+/// Something bookmarked in a NIP-51 bookmark list or bookmark set
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Readable, Writable)]
+pub enum Bookmark1 {
+    /// A regular event, by its id
+    Event(Id),
+
+    /// A replaceable event, by its kind, author and d-tag
+    Addr {
+        kind: u32,
+        author: PublicKey,
+        d: String,
+    },
+}
+
+impl Bookmark1 {
+    /// Create a Bookmark1 from a bookmark list tag, if it is an "e" or "a" tag
+    pub fn from_tag(tag: &Tag) -> Option<Bookmark1> {
+        match tag.tagname() {
+            "e" => Id::try_from_hex_string(tag.value())
+                .ok()
+                .map(Bookmark1::Event),
+            "a" => tag.parse_address().ok().map(|(ea, _)| Bookmark1::Addr {
+                kind: ea.kind.into(),
+                author: ea.author,
+                d: ea.d,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Create a Bookmark1 for an event. Replaceable events are bookmarked by
+    /// address so that the bookmark follows their latest version.
+    pub fn of_event(event: &Event) -> Bookmark1 {
+        if event.kind.is_replaceable() {
+            Bookmark1::Addr {
+                kind: event.kind.into(),
+                author: event.pubkey,
+                d: event.parameter().unwrap_or_default(),
+            }
+        } else {
+            Bookmark1::Event(event.id)
+        }
+    }
+
+    /// The address of a bookmarked replaceable event
+    pub fn event_addr(&self) -> Option<EventAddr> {
+        match self {
+            Bookmark1::Event(_) => None,
+            Bookmark1::Addr { kind, author, d } => Some(EventAddr {
+                d: d.clone(),
+                relays: vec![],
+                kind: EventKind::from(*kind),
+                author: *author,
+            }),
+        }
+    }
+
+    /// Create the bookmark list tag for this Bookmark1
+    pub fn to_tag(&self) -> Tag {
+        match self {
+            Bookmark1::Event(id) => Tag::new(&["e", &id.as_hex_string()]),
+            Bookmark1::Addr { .. } => Tag::new_address(&self.event_addr().unwrap(), None),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use super::Bookmark1;
+use nostr_types::Unixtime;
+use speedy::{Readable, Writable};
+
+/// A NIP-51 bookmark list (kind 10003, with an empty dtag) or a named
+/// bookmark set (kind 30003)
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct BookmarkList1 {
+    /// The d-tag of a bookmark set, or empty for the bookmark list
+    pub dtag: String,
+
+    /// The title of a bookmark set
+    pub title: String,
+
+    /// The bookmarks, oldest first. The bool is true if the entry is public,
+    /// false if private.
+    pub entries: Vec<(Bookmark1, bool)>,
+
+    /// When the list was last changed locally
+    pub last_edit_time: Unixtime,
+
+    /// The created_at of the event the list was last synchronized with
+    pub event_created_at: Unixtime,
+}
+
+impl Default for BookmarkList1 {
+    fn default() -> BookmarkList1 {
+        BookmarkList1 {
+            dtag: "".to_owned(),
+            title: "".to_owned(),
+            entries: Vec::new(),
+            last_edit_time: Unixtime::now().unwrap(),
+            event_created_at: Unixtime(0),
+        }
+    }
+}
+
+impl BookmarkList1 {
+    /// Is this the main bookmark list (kind 10003) rather than a set?
+    pub fn is_main_list(&self) -> bool {
+        self.dtag.is_empty()
+    }
+}
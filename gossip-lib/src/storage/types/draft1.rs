@@ -0,0 +1,42 @@
+use nostr_types::{Id, PublicKey, Tag, Unixtime};
+use speedy::{Readable, Writable};
+
+/// A saved draft of a note or direct message
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct Draft1 {
+    /// An identifier for the draft, unique within the account
+    pub id: u64,
+
+    /// A name the user gave the draft (may be empty)
+    pub name: String,
+
+    /// The text
+    pub content: String,
+
+    /// Tags to post with it, such as a subject, content warning or
+    /// attachment metadata
+    pub tags: Vec<Tag>,
+
+    /// The note it replies to
+    pub replying_to: Option<Id>,
+
+    /// The members of the DM channel, if it is a direct message
+    pub dm_channel: Option<Vec<PublicKey>>,
+
+    /// When the draft was last saved
+    pub last_edit_time: Unixtime,
+}
+
+impl Default for Draft1 {
+    fn default() -> Draft1 {
+        Draft1 {
+            id: 0,
+            name: "".to_owned(),
+            content: "".to_owned(),
+            tags: vec![],
+            replying_to: None,
+            dm_channel: None,
+            last_edit_time: Unixtime(0),
+        }
+    }
+}
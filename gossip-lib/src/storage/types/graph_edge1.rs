@@ -0,0 +1,17 @@
+/// The kind of relationship recorded in the follow graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum GraphEdge1 {
+    /// A public 'p' tag in somebody's kind-3 contact list
+    Follows = 0,
+
+    /// A public 'p' tag in somebody's kind-10000 mute list
+    Mutes = 1,
+}
+
+impl GraphEdge1 {
+    /// The key prefix byte for this kind of edge
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,23 @@
+use speedy::{Readable, Writable};
+
+/// A file in the media cache, as fetched from one URL
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct MediaCacheEntry1 {
+    /// The URL it was fetched from
+    pub url: String,
+
+    /// The SHA-256 of the content (hex), which names the cached file
+    pub hash: String,
+
+    /// Size of the content in bytes
+    pub size: u64,
+
+    /// When it was last fetched or revalidated (seconds since the epoch)
+    pub fetched: i64,
+
+    /// When it was last used (seconds since the epoch)
+    pub last_access: i64,
+
+    /// The ETag the server gave, for revalidation
+    pub etag: Option<Vec<u8>>,
+}
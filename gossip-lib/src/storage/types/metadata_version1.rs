@@ -0,0 +1,43 @@
+use nostr_types::Metadata;
+use speedy::{Readable, Writable};
+
+/// One version of a person's metadata (kind 0). Only the fields whose changes we
+/// track are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct MetadataVersion1 {
+    /// When the metadata event was created
+    pub created_at: i64,
+
+    /// name
+    pub name: Option<String>,
+
+    /// display_name
+    pub display_name: Option<String>,
+
+    /// picture
+    pub picture: Option<String>,
+
+    /// nip05
+    pub nip05: Option<String>,
+
+    /// lud16
+    pub lud16: Option<String>,
+}
+
+impl MetadataVersion1 {
+    /// Keep the tracked fields of some metadata
+    pub fn new(created_at: i64, metadata: &Metadata) -> MetadataVersion1 {
+        let other = |key: &str| match metadata.other.get(key) {
+            Some(serde_json::Value::String(s)) => Some(s.to_owned()),
+            _ => None,
+        };
+        MetadataVersion1 {
+            created_at,
+            name: metadata.name.clone(),
+            display_name: other("display_name"),
+            picture: metadata.picture.clone(),
+            nip05: metadata.nip05.clone(),
+            lud16: other("lud16"),
+        }
+    }
+}
@@ -1,3 +1,30 @@
+mod account1;
+pub use account1::Account1;
+
+mod bookmark1;
+pub use bookmark1::Bookmark1;
+
+mod bookmark_list1;
+pub use bookmark_list1::BookmarkList1;
+
+mod draft1;
+pub use draft1::Draft1;
+
+mod graph_edge1;
+pub use graph_edge1::GraphEdge1;
+
+mod media_cache_entry1;
+pub use media_cache_entry1::MediaCacheEntry1;
+
+mod metadata_version1;
+pub use metadata_version1::MetadataVersion1;
+
+mod mute1;
+pub use mute1::Mute1;
+
+mod nip05_history_entry1;
+pub use nip05_history_entry1::Nip05HistoryEntry1;
+
 mod person1;
 pub(crate) use person1::Person1;
 
@@ -34,6 +61,9 @@ pub use relay1::Relay1;
 mod relay2;
 pub use relay2::Relay2;
 
+mod scheduled_post1;
+pub use scheduled_post1::ScheduledPost1;
+
 mod settings1;
 pub(crate) use settings1::Settings1;
 
@@ -0,0 +1,73 @@
+use nostr_types::{Event, EventReference, Id, Tag};
+use speedy::{Readable, Writable};
+
+/// Something other than a person that can be muted in a NIP-51 mute list
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Readable, Writable)]
+pub enum Mute1 {
+    /// A word, matched case-insensitively against event content
+    Word(String),
+
+    /// A hashtag (lowercase, without the leading '#')
+    Hashtag(String),
+
+    /// A thread, identified by the id of its root event
+    Thread(Id),
+}
+
+impl Mute1 {
+    /// Create a Mute1 from a mute list tag, if it is a word, hashtag or thread tag
+    pub fn from_tag(tag: &Tag) -> Option<Mute1> {
+        match tag.tagname() {
+            "word" if !tag.value().is_empty() => Some(Mute1::Word(tag.value().to_lowercase())),
+            "t" if !tag.value().is_empty() => Some(Mute1::Hashtag(
+                tag.value().trim_start_matches('#').to_lowercase(),
+            )),
+            "e" => Id::try_from_hex_string(tag.value()).ok().map(Mute1::Thread),
+            _ => None,
+        }
+    }
+
+    /// Create a Mute1 for the thread that this event is part of
+    pub fn thread_of(event: &Event) -> Mute1 {
+        match event.replies_to_root() {
+            Some(EventReference::Id(root, _, _)) => Mute1::Thread(root),
+            _ => match event.replies_to() {
+                Some(EventReference::Id(parent, _, _)) => Mute1::Thread(parent),
+                _ => Mute1::Thread(event.id),
+            },
+        }
+    }
+
+    /// Create the mute list tag for this Mute1
+    pub fn to_tag(&self) -> Tag {
+        match self {
+            Mute1::Word(w) => Tag::new(&["word", w]),
+            Mute1::Hashtag(h) => Tag::new(&["t", h]),
+            Mute1::Thread(id) => Tag::new(&["e", &id.as_hex_string()]),
+        }
+    }
+
+    /// Does this Mute1 apply to the event?
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Mute1::Word(w) => event.content.to_lowercase().contains(w.as_str()),
+            Mute1::Hashtag(h) => event.hashtags().iter().any(|t| t.to_lowercase() == *h),
+            Mute1::Thread(id) => {
+                event.id == *id
+                    || event.tags.iter().any(|tag| match tag.parse_event() {
+                        Ok((tagged, _, _)) => tagged == *id,
+                        Err(_) => false,
+                    })
+            }
+        }
+    }
+
+    /// A short human readable description
+    pub fn describe(&self) -> String {
+        match self {
+            Mute1::Word(w) => format!("word \"{}\"", w),
+            Mute1::Hashtag(h) => format!("#{}", h),
+            Mute1::Thread(id) => format!("thread {}", id.as_hex_string()),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use speedy::{Readable, Writable};
+
+/// A record of a person's NIP-05 identifier at the time it was checked
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct Nip05HistoryEntry1 {
+    /// When this was checked (seconds since the epoch)
+    pub when: u64,
+
+    /// The NIP-05 identifier they claimed, if any
+    pub nip05: Option<String>,
+
+    /// Whether it validated
+    pub valid: bool,
+}
@@ -54,23 +54,9 @@ impl Person2 {
         }
     }
 
+    /// The name to show for this person. See [display_names](crate::names::display_names).
     pub fn best_name(&self) -> String {
-        if let Some(pn) = &self.petname {
-            return pn.to_owned();
-        }
-        if let Some(md) = &self.metadata {
-            if let Some(n) = &md.name {
-                if !n.is_empty() {
-                    return n.to_owned();
-                }
-            }
-            if let Some(serde_json::Value::String(s)) = md.other.get("display_name") {
-                if !s.is_empty() {
-                    return s.to_owned();
-                }
-            }
-        }
-        crate::names::pubkey_short(&self.pubkey)
+        crate::names::display_names(self).0
     }
 
     pub fn name(&self) -> Option<&str> {
@@ -152,6 +152,33 @@ impl Relay2 {
         self.rank > 0 && self.success_rate() > 0.35 && self.success_count > 10
     }
 
+    /// The relays an event was seen on, most recently seen first. Relays
+    /// the user has ranked at zero are left out.
+    pub fn event_relay_hints(id: Id) -> Result<Vec<RelayUrl>, Error> {
+        let mut seen_on_relays: Vec<(RelayUrl, Unixtime)> =
+            GLOBALS.storage.get_event_seen_on_relay(id)?;
+        seen_on_relays.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut hints: Vec<RelayUrl> = Vec::new();
+        for (url, _) in seen_on_relays {
+            if let Some(relay) = GLOBALS.storage.read_relay(&url)? {
+                if relay.rank == 0 {
+                    continue;
+                }
+            }
+            hints.push(url);
+        }
+        Ok(hints)
+    }
+
+    /// This generates a "recommended_relay_url" for an 'e' or 'q' tag referencing
+    /// an event (rather than replying to it): where it was most recently seen.
+    pub async fn recommended_relay_for_event(id: Id) -> Result<Option<RelayUrl>, Error> {
+        if let Some(url) = Self::event_relay_hints(id)?.into_iter().next() {
+            return Ok(Some(url));
+        }
+        Self::recommended_relay_for_reply(id).await
+    }
+
     /// This generates a "recommended_relay_url" for an 'e' tag.
     pub async fn recommended_relay_for_reply(reply_to: Id) -> Result<Option<RelayUrl>, Error> {
         let seen_on_relays: Vec<(RelayUrl, Unixtime)> =
@@ -0,0 +1,35 @@
+use nostr_types::{Event, Id, PublicKey, Tag, Unixtime};
+use speedy::{Readable, Writable};
+
+/// A note or direct message waiting in the outbox to be posted later
+#[derive(Debug, Clone, PartialEq, Eq, Readable, Writable)]
+pub struct ScheduledPost1 {
+    /// An identifier for the post, unique within the account
+    pub id: u64,
+
+    /// When it is due to be posted
+    pub send_at: Unixtime,
+
+    /// The text
+    pub content: String,
+
+    /// Tags to post with it, besides those derived from the content
+    pub tags: Vec<Tag>,
+
+    /// The note it replies to
+    pub in_reply_to: Option<Id>,
+
+    /// The members of the DM channel, if it is a direct message
+    pub dm_channel: Option<Vec<PublicKey>>,
+
+    /// The event, if it was signed ahead of time (created at `send_at`).
+    /// Otherwise it is signed when it is posted.
+    pub event: Option<Event>,
+
+    /// Set if it could not be posted when due (e.g. gossip was not running),
+    /// and waits for the user to post or cancel it
+    pub missed: bool,
+
+    /// Set while it is being posted. It leaves the outbox once posted.
+    pub sending: bool,
+}
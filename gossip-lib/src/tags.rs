@@ -1,5 +1,5 @@
 use crate::relay::Relay;
-use nostr_types::{EventAddr, Id, PublicKey, Tag, UncheckedUrl};
+use nostr_types::{EventAddr, Id, NostrBech32, PublicKey, Tag, UncheckedUrl};
 
 pub async fn add_pubkey_to_tags(existing_tags: &mut Vec<Tag>, added: PublicKey) -> usize {
     let newtag = Tag::new_pubkey(added, None, None);
@@ -72,6 +72,53 @@ pub async fn add_addr_to_tags(
     }
 }
 
+/// Add a NIP-18 'q' tag for a quoted event, by id or by address
+pub async fn add_quote_to_tags(existing_tags: &mut Vec<Tag>, quoted: NostrBech32) -> usize {
+    let (value, relay_url) = match quoted {
+        NostrBech32::EventAddr(ea) => (
+            format!(
+                "{}:{}:{}",
+                u32::from(ea.kind),
+                ea.author.as_hex_string(),
+                ea.d
+            ),
+            ea.relays.first().cloned(),
+        ),
+        NostrBech32::EventPointer(ep) => {
+            let relay_url = match ep.relays.first() {
+                Some(url) => Some(url.clone()),
+                None => recommended_relay_for_quote(ep.id).await,
+            };
+            (ep.id.as_hex_string(), relay_url)
+        }
+        NostrBech32::Id(id) => (id.as_hex_string(), recommended_relay_for_quote(id).await),
+        _ => return existing_tags.len(),
+    };
+
+    match existing_tags
+        .iter()
+        .position(|existing_tag| existing_tag.tagname() == "q" && existing_tag.value() == value)
+    {
+        Some(idx) => idx,
+        None => {
+            let newtag = match relay_url {
+                Some(url) => Tag::new(&["q", &value, url.as_str()]),
+                None => Tag::new(&["q", &value]),
+            };
+            existing_tags.push(newtag);
+            existing_tags.len() - 1
+        }
+    }
+}
+
+async fn recommended_relay_for_quote(id: Id) -> Option<UncheckedUrl> {
+    Relay::recommended_relay_for_event(id)
+        .await
+        .ok()
+        .flatten()
+        .map(|rr| rr.to_unchecked_url())
+}
+
 pub fn add_subject_to_tags_if_missing(existing_tags: &mut Vec<Tag>, subject: String) {
     if !existing_tags.iter().any(|t| t.tagname() == "subject") {
         existing_tags.push(Tag::new_subject(subject));
@@ -0,0 +1,1020 @@
+use crate::error::{Error, ErrorKind};
+use crate::file_metadata::parse_dim;
+use crate::globals::GLOBALS;
+use crate::USER_AGENT;
+use base64::Engine;
+use futures::StreamExt;
+use image::ImageFormat;
+use nostr_types::{Event, EventKind, PreEvent, Tag, Unixtime};
+use serde::Deserialize;
+use sha2::Digest;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Uploads are sent in chunks of this size, so we can report progress
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Blossom authorization events are this kind (BUD-01)
+const BLOSSOM_AUTH_KIND: u32 = 24242;
+
+// How long we wait for a NIP-96 server that processes uploads asynchronously
+const PROCESSING_POLLS: usize = 30;
+const PROCESSING_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The protocols we can upload files with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadProtocol {
+    /// NIP-96 HTTP file storage
+    Nip96,
+
+    /// Blossom (BUD-01/02)
+    Blossom,
+}
+
+impl UploadProtocol {
+    /// All of the protocols
+    pub const ALL: [UploadProtocol; 2] = [UploadProtocol::Nip96, UploadProtocol::Blossom];
+
+    /// The name, as stored in the `upload_protocol` setting
+    pub fn name(&self) -> &'static str {
+        match *self {
+            UploadProtocol::Nip96 => "NIP-96",
+            UploadProtocol::Blossom => "Blossom",
+        }
+    }
+
+    /// From the name stored in the `upload_protocol` setting
+    pub fn from_name(name: &str) -> UploadProtocol {
+        match name {
+            "Blossom" => UploadProtocol::Blossom,
+            _ => UploadProtocol::Nip96,
+        }
+    }
+}
+
+/// A file that was uploaded
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    /// Where it can be downloaded from
+    pub url: String,
+
+    /// The SHA-256 of the file as served, hex encoded
+    pub sha256: String,
+
+    /// The SHA-256 of the file as we uploaded it, if the server transformed it
+    pub original_sha256: Option<String>,
+
+    /// The MIME type
+    pub mime: String,
+
+    /// The size in bytes, if known
+    pub size: Option<usize>,
+
+    /// Width and height in pixels, for images
+    pub dim: Option<(u32, u32)>,
+
+    /// The blurhash, if the server computed one
+    pub blurhash: Option<String>,
+}
+
+impl UploadedFile {
+    /// A NIP-92 `imeta` tag describing the file
+    pub fn imeta_tag(&self) -> Tag {
+        let mut fields: Vec<String> = vec![
+            "imeta".to_owned(),
+            format!("url {}", self.url),
+            format!("m {}", self.mime),
+            format!("x {}", self.sha256),
+        ];
+        if let Some(ox) = &self.original_sha256 {
+            fields.push(format!("ox {}", ox));
+        }
+        if let Some(size) = self.size {
+            fields.push(format!("size {}", size));
+        }
+        if let Some((w, h)) = self.dim {
+            fields.push(format!("dim {}x{}", w, h));
+        }
+        if let Some(blurhash) = &self.blurhash {
+            fields.push(format!("blurhash {}", blurhash));
+        }
+        let fields: Vec<&str> = fields.iter().map(|s| s.as_str()).collect();
+        Tag::new(&fields)
+    }
+}
+
+/// The state of an upload
+#[derive(Debug, Clone)]
+pub struct Upload {
+    /// The name of the file being uploaded
+    pub file_name: String,
+
+    /// Bytes sent so far
+    pub sent: usize,
+
+    /// Total bytes to send (0 until the file is prepared)
+    pub total: usize,
+
+    /// Set when the upload has finished successfully
+    pub result: Option<UploadedFile>,
+}
+
+static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Start tracking an upload of `file_name` in `GLOBALS.uploads`, returning the
+/// id to pass to [upload_file]. Take the result with [take_finished_upload],
+/// or [abandon_upload] it.
+pub fn new_upload(file_name: String) -> u64 {
+    let upload_id = NEXT_UPLOAD_ID.fetch_add(1, Ordering::Relaxed);
+    GLOBALS.uploads.insert(
+        upload_id,
+        Upload {
+            file_name,
+            sent: 0,
+            total: 0,
+            result: None,
+        },
+    );
+    upload_id
+}
+
+/// If the upload has finished successfully, stop tracking it and return the
+/// uploaded file. Failed uploads are no longer tracked at all.
+pub fn take_finished_upload(upload_id: u64) -> Option<UploadedFile> {
+    match GLOBALS
+        .uploads
+        .remove_if(&upload_id, |_, upload| upload.result.is_some())
+    {
+        Some((_, upload)) => upload.result,
+        None => None,
+    }
+}
+
+/// Stop tracking an upload. If it has not finished yet, its result is dropped.
+pub fn abandon_upload(upload_id: u64) {
+    GLOBALS.uploads.remove(&upload_id);
+}
+
+/// Upload a file to the configured server, tracking it in `GLOBALS.uploads`
+/// under `upload_id` (from [new_upload]). On failure it is no longer tracked.
+/// If the upload was abandoned before it started, nothing is uploaded.
+pub async fn upload_file(upload_id: u64, path: &Path) -> Result<(), Error> {
+    if !GLOBALS.uploads.contains_key(&upload_id) {
+        return Ok(());
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or("file".to_owned());
+
+    match upload_file_inner(upload_id, path, &file_name).await {
+        Ok(uploaded) => {
+            // If it was abandoned meanwhile, there is nobody to take it up
+            if let Some(mut upload) = GLOBALS.uploads.get_mut(&upload_id) {
+                upload.result = Some(uploaded);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            GLOBALS.uploads.remove(&upload_id);
+            Err(e)
+        }
+    }
+}
+
+async fn upload_file_inner(
+    upload_id: u64,
+    path: &Path,
+    file_name: &str,
+) -> Result<UploadedFile, Error> {
+    let server = GLOBALS.storage.read_setting_upload_server();
+    let server = server.trim().trim_end_matches('/');
+    if server.is_empty() {
+        return Err(ErrorKind::NoUploadServer.into());
+    }
+    let protocol = UploadProtocol::from_name(&GLOBALS.storage.read_setting_upload_protocol());
+
+    let mut bytes = tokio::fs::read(path).await?;
+    let mime = mime_from_path(path);
+    if GLOBALS.storage.read_setting_upload_strip_metadata() {
+        bytes = strip_image_metadata(bytes, &mime)?;
+    }
+    let dim = image_dimensions(&bytes);
+    let sha256 = hex::encode(sha2::Sha256::digest(&bytes));
+
+    if let Some(mut upload) = GLOBALS.uploads.get_mut(&upload_id) {
+        upload.total = bytes.len();
+    }
+
+    let mut uploaded = match protocol {
+        UploadProtocol::Nip96 => {
+            upload_nip96(upload_id, server, file_name, &mime, bytes, &sha256).await?
+        }
+        UploadProtocol::Blossom => {
+            upload_blossom(upload_id, server, file_name, &mime, bytes, &sha256).await?
+        }
+    };
+    if uploaded.dim.is_none() {
+        uploaded.dim = dim;
+    }
+
+    Ok(uploaded)
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder().timeout(timeout).gzip(true);
+    if GLOBALS.storage.read_setting_set_user_agent() {
+        builder = builder.user_agent(USER_AGENT);
+    }
+    Ok(builder.build()?)
+}
+
+// The file as a body that records how much of it has been sent
+fn progress_body(upload_id: u64, bytes: Vec<u8>) -> reqwest::Body {
+    let chunks: Vec<Vec<u8>> = bytes.chunks(CHUNK_SIZE).map(|c| c.to_vec()).collect();
+    let stream = futures::stream::iter(chunks).map(move |chunk| {
+        if let Some(mut upload) = GLOBALS.uploads.get_mut(&upload_id) {
+            upload.sent += chunk.len();
+        }
+        Ok::<Vec<u8>, std::io::Error>(chunk)
+    });
+    reqwest::Body::wrap_stream(stream)
+}
+
+// The value of an `Authorization` header carrying a signed event
+fn authorization(event: &Event) -> Result<String, Error> {
+    let json = serde_json::to_string(event)?;
+    Ok(format!(
+        "Nostr {}",
+        base64::engine::general_purpose::STANDARD.encode(json)
+    ))
+}
+
+// A NIP-98 HTTP auth event
+fn nip98_auth(url: &str, method: &str, payload_sha256: Option<&str>) -> Result<String, Error> {
+    let public_key = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err((ErrorKind::NoPrivateKey, file!(), line!()).into()),
+    };
+    let mut tags = vec![Tag::new(&["u", url]), Tag::new(&["method", method])];
+    if let Some(payload) = payload_sha256 {
+        tags.push(Tag::new(&["payload", payload]));
+    }
+    let event = GLOBALS.identity.sign_event(PreEvent {
+        pubkey: public_key,
+        created_at: Unixtime::now().unwrap(),
+        kind: EventKind::HttpAuth,
+        tags,
+        content: "".to_owned(),
+    })?;
+    authorization(&event)
+}
+
+#[derive(Debug, Deserialize)]
+struct Nip96Info {
+    #[serde(default)]
+    api_url: String,
+    #[serde(default)]
+    delegated_to_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nip96Nip94Event {
+    #[serde(default)]
+    tags: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nip96Response {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    processing_url: Option<String>,
+    #[serde(default)]
+    nip94_event: Option<Nip96Nip94Event>,
+}
+
+async fn nip96_api_url(client: &reqwest::Client, server: &str) -> Result<String, Error> {
+    let mut base = server.to_owned();
+    // Follow at most one delegation
+    for _ in 0..2 {
+        let info: Nip96Info = client
+            .get(format!("{}/.well-known/nostr/nip96.json", base))
+            .send()
+            .await?
+            .json()
+            .await?;
+        match info.delegated_to_url {
+            Some(delegated) if info.api_url.is_empty() => {
+                base = delegated.trim_end_matches('/').to_owned()
+            }
+            _ => {
+                if info.api_url.is_empty() {
+                    break;
+                }
+                return Ok(info.api_url);
+            }
+        }
+    }
+    Err(ErrorKind::UploadFailed(format!("{} does not support NIP-96", server)).into())
+}
+
+async fn upload_nip96(
+    upload_id: u64,
+    server: &str,
+    file_name: &str,
+    mime: &str,
+    bytes: Vec<u8>,
+    sha256: &str,
+) -> Result<UploadedFile, Error> {
+    let api_url = nip96_api_url(&client(Duration::from_secs(30))?, server).await?;
+    let size = bytes.len();
+
+    let part =
+        reqwest::multipart::Part::stream_with_length(progress_body(upload_id, bytes), size as u64)
+            .file_name(file_name.to_owned())
+            .mime_str(mime)?;
+    let form = reqwest::multipart::Form::new()
+        .part("file", part)
+        .text("size", size.to_string())
+        .text("content_type", mime.to_owned());
+
+    let response = client(Duration::from_secs(300))?
+        .post(&api_url)
+        .header("Authorization", nip98_auth(&api_url, "POST", Some(sha256))?)
+        .multipart(form)
+        .send()
+        .await?;
+    let status = response.status();
+    let mut reply: Nip96Response = match response.json().await {
+        Ok(reply) => reply,
+        Err(_) => {
+            return Err(ErrorKind::UploadFailed(format!("server responded with {}", status)).into())
+        }
+    };
+
+    // Some servers process the file before it is available
+    if reply.nip94_event.is_none() {
+        if let Some(processing_url) = reply.processing_url.clone() {
+            let client = client(Duration::from_secs(30))?;
+            for _ in 0..PROCESSING_POLLS {
+                tokio::time::sleep(PROCESSING_POLL_INTERVAL).await;
+                reply = client
+                    .get(&processing_url)
+                    .header("Authorization", nip98_auth(&processing_url, "GET", None)?)
+                    .send()
+                    .await?
+                    .json()
+                    .await?;
+                if reply.nip94_event.is_some() || reply.status == "error" {
+                    break;
+                }
+            }
+        }
+    }
+
+    nip96_uploaded_file(reply, status, mime, sha256)
+}
+
+// The file described by the NIP-94 event in a NIP-96 server's reply
+fn nip96_uploaded_file(
+    reply: Nip96Response,
+    status: reqwest::StatusCode,
+    mime: &str,
+    sha256: &str,
+) -> Result<UploadedFile, Error> {
+    let tags = match reply.nip94_event {
+        Some(event) if reply.status != "error" => event.tags,
+        _ => {
+            return Err(ErrorKind::UploadFailed(
+                reply
+                    .message
+                    .unwrap_or(format!("server responded with {}", status)),
+            )
+            .into())
+        }
+    };
+
+    let get = |name: &str| -> Option<String> {
+        tags.iter()
+            .find(|t| t.first().map(|s| s.as_str()) == Some(name))
+            .and_then(|t| t.get(1).cloned())
+    };
+
+    let url = match get("url") {
+        Some(url) => url,
+        None => return Err(ErrorKind::UploadFailed("server returned no URL".to_owned()).into()),
+    };
+    let served_sha256 = get("x").unwrap_or(sha256.to_owned());
+    let original_sha256 = if served_sha256 != sha256 {
+        Some(get("ox").unwrap_or(sha256.to_owned()))
+    } else {
+        None
+    };
+
+    Ok(UploadedFile {
+        url,
+        sha256: served_sha256,
+        original_sha256,
+        mime: get("m").unwrap_or(mime.to_owned()),
+        size: get("size").and_then(|s| s.parse().ok()),
+        dim: get("dim").and_then(|d| parse_dim(&d)),
+        blurhash: get("blurhash"),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct BlobDescriptor {
+    url: String,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    size: Option<usize>,
+    #[serde(default, rename = "type")]
+    mime: Option<String>,
+}
+
+async fn upload_blossom(
+    upload_id: u64,
+    server: &str,
+    file_name: &str,
+    mime: &str,
+    bytes: Vec<u8>,
+    sha256: &str,
+) -> Result<UploadedFile, Error> {
+    let public_key = match GLOBALS.identity.public_key() {
+        Some(pk) => pk,
+        None => return Err((ErrorKind::NoPrivateKey, file!(), line!()).into()),
+    };
+    let now = Unixtime::now().unwrap();
+    let expiration = (now.0 + 300).to_string();
+    let event = GLOBALS.identity.sign_event(PreEvent {
+        pubkey: public_key,
+        created_at: now,
+        kind: EventKind::from(BLOSSOM_AUTH_KIND),
+        tags: vec![
+            Tag::new(&["t", "upload"]),
+            Tag::new(&["x", sha256]),
+            Tag::new(&["expiration", &expiration]),
+        ],
+        content: format!("Upload {}", file_name),
+    })?;
+
+    let size = bytes.len();
+    blossom_put(
+        &client(Duration::from_secs(300))?,
+        server,
+        &authorization(&event)?,
+        mime,
+        size,
+        progress_body(upload_id, bytes),
+        sha256,
+    )
+    .await
+}
+
+// PUT a blob to a Blossom server
+async fn blossom_put(
+    client: &reqwest::Client,
+    server: &str,
+    authorization: &str,
+    mime: &str,
+    size: usize,
+    body: reqwest::Body,
+    sha256: &str,
+) -> Result<UploadedFile, Error> {
+    let response = client
+        .put(format!("{}/upload", server))
+        .header("Authorization", authorization)
+        .header("Content-Type", mime)
+        .header("Content-Length", size)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let reason = response
+            .headers()
+            .get("X-Reason")
+            .and_then(|r| r.to_str().ok())
+            .map(|r| r.to_owned())
+            .unwrap_or(format!("server responded with {}", status));
+        return Err(ErrorKind::UploadFailed(reason).into());
+    }
+
+    let blob: BlobDescriptor = response.json().await?;
+    Ok(UploadedFile {
+        url: blob.url,
+        sha256: blob.sha256.unwrap_or(sha256.to_owned()),
+        original_sha256: None,
+        mime: blob.mime.unwrap_or(mime.to_owned()),
+        size: blob.size.or(Some(size)),
+        dim: None,
+        blurhash: None,
+    })
+}
+
+/// Guess the MIME type of a file from its extension
+pub fn mime_from_path(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+    .to_owned()
+}
+
+fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    image::io::Reader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Remove EXIF (and similar) metadata from JPEG, PNG and WebP images, which
+/// may reveal where and with what a photo was taken. If the EXIF data rotates a
+/// JPEG or PNG image, the image is rotated and re-encoded instead. HEIC and
+/// TIFF images, and rotated WebP images, cannot be stripped and are refused.
+/// Other files are returned unchanged.
+pub fn strip_image_metadata(bytes: Vec<u8>, mime: &str) -> Result<Vec<u8>, Error> {
+    let cannot_strip = |what: &str| -> Error {
+        ErrorKind::UploadFailed(format!(
+            "cannot remove metadata from {}; turn off metadata stripping to upload it anyway",
+            what
+        ))
+        .into()
+    };
+
+    let format = match mime {
+        "image/jpeg" => ImageFormat::Jpeg,
+        "image/png" => ImageFormat::Png,
+        "image/webp" => {
+            if crate::media::get_orientation(&bytes) != 1 {
+                return Err(cannot_strip("rotated WebP images"));
+            }
+            return strip_webp_metadata(&bytes);
+        }
+        "image/heic" | "image/heif" => return Err(cannot_strip("HEIC images")),
+        "image/tiff" => return Err(cannot_strip("TIFF images")),
+        _ => return Ok(bytes),
+    };
+
+    if crate::media::get_orientation(&bytes) != 1 {
+        // Re-encoding writes no metadata
+        let image = image::load_from_memory_with_format(&bytes, format)?;
+        let image = crate::media::adjust_orientation(&bytes, image);
+        let mut output = std::io::Cursor::new(Vec::new());
+        match format {
+            // JPEG has no alpha channel
+            ImageFormat::Jpeg => image.to_rgb8().write_to(&mut output, format)?,
+            _ => image.write_to(&mut output, format)?,
+        }
+        return Ok(output.into_inner());
+    }
+
+    match format {
+        ImageFormat::Jpeg => strip_jpeg_metadata(&bytes),
+        _ => strip_png_metadata(&bytes),
+    }
+}
+
+// Drop APP1 (EXIF, XMP), APP13 (IPTC) and comment segments
+fn strip_jpeg_metadata(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let malformed = || -> Error { ErrorKind::General("Malformed JPEG".to_owned()).into() };
+
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+        return Err(malformed());
+    }
+
+    let mut output: Vec<u8> = Vec::with_capacity(bytes.len());
+    output.extend_from_slice(&bytes[0..2]);
+    let mut pos = 2;
+    while pos + 2 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            return Err(malformed());
+        }
+        // Any marker may be preceded by fill bytes
+        if bytes[pos + 1] == 0xFF {
+            pos += 1;
+            continue;
+        }
+        let marker = bytes[pos + 1];
+        if marker == 0xDA {
+            // Start of scan, the rest is image data
+            output.extend_from_slice(&bytes[pos..]);
+            return Ok(output);
+        }
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            // Standalone markers have no length
+            output.extend_from_slice(&bytes[pos..pos + 2]);
+            pos += 2;
+            continue;
+        }
+        if pos + 4 > bytes.len() {
+            return Err(malformed());
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let end = pos + 2 + len;
+        if len < 2 || end > bytes.len() {
+            return Err(malformed());
+        }
+        if !matches!(marker, 0xE1 | 0xED | 0xFE) {
+            output.extend_from_slice(&bytes[pos..end]);
+        }
+        pos = end;
+    }
+
+    Err(malformed())
+}
+
+// Drop eXIf, text and timestamp chunks
+fn strip_png_metadata(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    let malformed = || -> Error { ErrorKind::General("Malformed PNG".to_owned()).into() };
+
+    if bytes.len() < 8 || bytes[0..8] != SIGNATURE {
+        return Err(malformed());
+    }
+
+    let mut output: Vec<u8> = Vec::with_capacity(bytes.len());
+    output.extend_from_slice(&SIGNATURE);
+    let mut pos = 8;
+    while pos + 12 <= bytes.len() {
+        let len = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+            as usize;
+        let end = pos + 12 + len;
+        if end > bytes.len() {
+            return Err(malformed());
+        }
+        let chunk_type = &bytes[pos + 4..pos + 8];
+        if !matches!(chunk_type, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            output.extend_from_slice(&bytes[pos..end]);
+        }
+        pos = end;
+    }
+
+    Ok(output)
+}
+
+// Drop EXIF and XMP chunks, and clear the VP8X flags announcing them
+fn strip_webp_metadata(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let malformed = || -> Error { ErrorKind::General("Malformed WebP".to_owned()).into() };
+
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WEBP" {
+        return Err(malformed());
+    }
+
+    let mut output: Vec<u8> = Vec::with_capacity(bytes.len());
+    output.extend_from_slice(&bytes[0..12]);
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_le_bytes([
+            bytes[pos + 4],
+            bytes[pos + 5],
+            bytes[pos + 6],
+            bytes[pos + 7],
+        ]) as usize;
+        // Chunks are padded to an even length
+        let end = (pos + 8 + len + (len & 1)).min(bytes.len());
+        if pos + 8 + len > bytes.len() {
+            return Err(malformed());
+        }
+        match &bytes[pos..pos + 4] {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if len > 0 => {
+                let flags = output.len() + 8;
+                output.extend_from_slice(&bytes[pos..end]);
+                output[flags] &= !(0x08 | 0x04);
+            }
+            _ => output.extend_from_slice(&bytes[pos..end]),
+        }
+        pos = end;
+    }
+
+    let riff_len = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // A minimal HTTP server answering each request with `respond(method, path)`,
+    // which gives the status line, extra headers and body. Returns its base URL.
+    async fn mock_server<F>(respond: F) -> String
+    where
+        F: Fn(&str, &str) -> (&'static str, Vec<(&'static str, &'static str)>, String)
+            + Send
+            + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::mem::drop(tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                // Read the head, then the body so the client is not cut off
+                let mut request: Vec<u8> = Vec::new();
+                let mut buf = [0u8; 4096];
+                let head_end = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break None;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break Some(i + 4);
+                    }
+                };
+                let head_end = match head_end {
+                    Some(i) => i,
+                    None => continue,
+                };
+                let head = String::from_utf8_lossy(&request[..head_end]).to_string();
+                let content_length: usize = head
+                    .lines()
+                    .find_map(|l| {
+                        let (name, value) = l.split_once(':')?;
+                        if name.eq_ignore_ascii_case("content-length") {
+                            value.trim().parse().ok()
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or(0);
+                while request.len() < head_end + content_length {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let mut words = head.split_whitespace();
+                let method = words.next().unwrap_or("");
+                let path = words.next().unwrap_or("");
+                let (status, headers, body) = respond(method, path);
+                let mut response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    status,
+                    body.len()
+                );
+                for (name, value) in headers {
+                    response.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                response.push_str("\r\n");
+                response.push_str(&body);
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            }
+        }));
+        base
+    }
+
+    #[test]
+    fn test_strip_jpeg_metadata() {
+        let mut jpeg: Vec<u8> = vec![0xFF, 0xD8];
+        // APP0 (JFIF), kept
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46]);
+        // APP1 (EXIF), dropped
+        jpeg.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x05, b'E', b'x', b'i']);
+        // Fill bytes, then a comment, dropped
+        jpeg.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFE, 0x00, 0x03, b'x']);
+        // A standalone marker, kept
+        jpeg.extend_from_slice(&[0xFF, 0xD0]);
+        // DQT, kept
+        jpeg.extend_from_slice(&[0xFF, 0xDB, 0x00, 0x03, 0x01]);
+        // Start of scan and image data, kept
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+
+        let stripped = strip_jpeg_metadata(&jpeg).unwrap();
+        let mut expected: Vec<u8> = vec![0xFF, 0xD8];
+        expected.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46]);
+        expected.extend_from_slice(&[0xFF, 0xD0]);
+        expected.extend_from_slice(&[0xFF, 0xDB, 0x00, 0x03, 0x01]);
+        expected.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        assert_eq!(stripped, expected);
+
+        assert!(strip_jpeg_metadata(&[0x89, b'P', b'N', b'G']).is_err());
+        // A segment running past the end
+        assert!(strip_jpeg_metadata(&[0xFF, 0xD8, 0xFF, 0xE1, 0x10, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_strip_png_metadata() {
+        fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+            let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+            chunk.extend_from_slice(kind);
+            chunk.extend_from_slice(data);
+            chunk.extend_from_slice(&[0, 0, 0, 0]); // CRC, not checked
+            chunk
+        }
+
+        let signature = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        let ihdr = chunk(b"IHDR", &[0; 13]);
+        let idat = chunk(b"IDAT", &[1, 2, 3]);
+        let iend = chunk(b"IEND", &[]);
+
+        let mut png = signature.clone();
+        png.extend_from_slice(&ihdr);
+        png.extend_from_slice(&chunk(b"eXIf", b"exif"));
+        png.extend_from_slice(&chunk(b"tEXt", b"Author\0me"));
+        png.extend_from_slice(&idat);
+        png.extend_from_slice(&chunk(b"tIME", &[0; 7]));
+        png.extend_from_slice(&iend);
+
+        let mut expected = signature;
+        expected.extend_from_slice(&ihdr);
+        expected.extend_from_slice(&idat);
+        expected.extend_from_slice(&iend);
+        assert_eq!(strip_png_metadata(&png).unwrap(), expected);
+
+        assert!(strip_png_metadata(&[0xFF, 0xD8, 0xFF, 0xE0]).is_err());
+    }
+
+    #[test]
+    fn test_strip_webp_metadata() {
+        fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+            let mut chunk = kind.to_vec();
+            chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            chunk.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                chunk.push(0);
+            }
+            chunk
+        }
+        fn riff(chunks: &[Vec<u8>]) -> Vec<u8> {
+            let body: Vec<u8> = chunks.concat();
+            let mut riff = b"RIFF".to_vec();
+            riff.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+            riff.extend_from_slice(b"WEBP");
+            riff.extend_from_slice(&body);
+            riff
+        }
+
+        let vp8 = chunk(b"VP8 ", &[1, 2, 3]);
+        let webp = riff(&[
+            chunk(b"VP8X", &[0x08 | 0x04 | 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            vp8.clone(),
+            chunk(b"EXIF", b"exif"),
+            chunk(b"XMP ", b"<xmp/>x"),
+        ]);
+        let expected = riff(&[chunk(b"VP8X", &[0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0]), vp8]);
+        assert_eq!(strip_webp_metadata(&webp).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_strip_image_metadata_refuses() {
+        assert!(strip_image_metadata(vec![0; 16], "image/heic").is_err());
+        assert!(strip_image_metadata(vec![0; 16], "image/tiff").is_err());
+        assert_eq!(
+            strip_image_metadata(vec![1, 2, 3], "video/mp4").unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_nip96_uploaded_file() {
+        let reply: Nip96Response = serde_json::from_str(
+            r#"{"status":"success","nip94_event":{"tags":[
+                ["url","https://example.com/abc.jpg"],
+                ["x","served"],
+                ["m","image/jpeg"],
+                ["size","1234"],
+                ["dim","640x480"],
+                ["blurhash","LKO2?U%2Tw=w"]]}}"#,
+        )
+        .unwrap();
+        let file =
+            nip96_uploaded_file(reply, reqwest::StatusCode::OK, "image/png", "original").unwrap();
+        assert_eq!(file.url, "https://example.com/abc.jpg");
+        assert_eq!(file.sha256, "served");
+        assert_eq!(file.original_sha256.as_deref(), Some("original"));
+        assert_eq!(file.mime, "image/jpeg");
+        assert_eq!(file.size, Some(1234));
+        assert_eq!(file.dim, Some((640, 480)));
+        assert_eq!(file.blurhash.as_deref(), Some("LKO2?U%2Tw=w"));
+
+        let reply: Nip96Response =
+            serde_json::from_str(r#"{"status":"error","message":"too big"}"#).unwrap();
+        let err =
+            nip96_uploaded_file(reply, reqwest::StatusCode::PAYLOAD_TOO_LARGE, "", "").unwrap_err();
+        assert!(err.to_string().contains("too big"));
+    }
+
+    #[tokio::test]
+    async fn test_nip96_api_url() {
+        let server = mock_server(|_, path| match path {
+            "/.well-known/nostr/nip96.json" => (
+                "200 OK",
+                vec![("Content-Type", "application/json")],
+                r#"{"api_url":"https://files.example.com/api"}"#.to_owned(),
+            ),
+            _ => ("404 Not Found", vec![], "".to_owned()),
+        })
+        .await;
+        let delegated_to = server.clone();
+        let delegating = mock_server(move |_, _| {
+            (
+                "200 OK",
+                vec![("Content-Type", "application/json")],
+                format!(r#"{{"api_url":"","delegated_to_url":"{}/"}}"#, delegated_to),
+            )
+        })
+        .await;
+        let unsupported = mock_server(|_, _| {
+            (
+                "200 OK",
+                vec![("Content-Type", "application/json")],
+                "{}".to_owned(),
+            )
+        })
+        .await;
+
+        let client = reqwest::Client::new();
+        assert_eq!(
+            nip96_api_url(&client, &server).await.unwrap(),
+            "https://files.example.com/api"
+        );
+        assert_eq!(
+            nip96_api_url(&client, &delegating).await.unwrap(),
+            "https://files.example.com/api"
+        );
+        assert!(nip96_api_url(&client, &unsupported).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blossom_put() {
+        let server = mock_server(|method, path| match (method, path) {
+            ("PUT", "/upload") => (
+                "200 OK",
+                vec![("Content-Type", "application/json")],
+                r#"{"url":"https://cdn.example.com/abcd.png","sha256":"abcd","size":3,"type":"image/png"}"#
+                    .to_owned(),
+            ),
+            _ => ("404 Not Found", vec![], "".to_owned()),
+        })
+        .await;
+        let refusing = mock_server(|_, _| {
+            (
+                "413 Payload Too Large",
+                vec![("X-Reason", "File too large")],
+                "".to_owned(),
+            )
+        })
+        .await;
+
+        let client = reqwest::Client::new();
+        let file = blossom_put(
+            &client,
+            &server,
+            "Nostr x",
+            "image/png",
+            3,
+            reqwest::Body::from(vec![1, 2, 3]),
+            "abcd",
+        )
+        .await
+        .unwrap();
+        assert_eq!(file.url, "https://cdn.example.com/abcd.png");
+        assert_eq!(file.sha256, "abcd");
+        assert_eq!(file.size, Some(3));
+        assert_eq!(file.mime, "image/png");
+
+        let err = blossom_put(
+            &client,
+            &refusing,
+            "Nostr x",
+            "image/png",
+            3,
+            reqwest::Body::from(vec![1, 2, 3]),
+            "abcd",
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("File too large"));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::error::Error;
+use crate::globals::GLOBALS;
+use crate::people::PersonList;
+use crate::storage::types::GraphEdge1;
+use dashmap::DashMap;
+use nostr_types::{Event, EventKind, PublicKey};
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+pub type GraphEdge = GraphEdge1;
+
+// How long we trust our cached copy of the Followed list. Lists can be edited
+// through storage directly, so we don't rely on being told about every change.
+const MY_FOLLOWS_TTL: Duration = Duration::from_secs(30);
+
+/// How the people we follow regard somebody
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrustScore {
+    /// How many of the people we follow also follow them
+    pub followed_by: usize,
+
+    /// How many of the people we follow publicly mute them
+    pub muted_by: usize,
+}
+
+impl TrustScore {
+    /// Whether more of the people we follow follow them than mute them
+    pub fn is_trusted(&self) -> bool {
+        self.followed_by > self.muted_by
+    }
+}
+
+/// The web of trust built from the contact lists and mute lists of everybody
+/// we have seen. Scores are relative to the people in our Followed list.
+pub struct WebOfTrust {
+    my_follows: RwLock<Option<(Instant, HashSet<PublicKey>)>>,
+    scores: DashMap<PublicKey, TrustScore>,
+}
+
+impl WebOfTrust {
+    pub(crate) fn new() -> WebOfTrust {
+        WebOfTrust {
+            my_follows: RwLock::new(None),
+            scores: DashMap::new(),
+        }
+    }
+
+    /// The people in our Followed list
+    pub fn my_follows(&self) -> HashSet<PublicKey> {
+        self.refresh_my_follows();
+        match &*self.my_follows.read() {
+            Some((_, follows)) => follows.clone(),
+            None => HashSet::new(),
+        }
+    }
+
+    fn refresh_my_follows(&self) {
+        if let Some((when, _)) = &*self.my_follows.read() {
+            if when.elapsed() < MY_FOLLOWS_TTL {
+                return;
+            }
+        }
+
+        let follows: HashSet<PublicKey> =
+            match GLOBALS.storage.get_people_in_list(PersonList::Followed) {
+                Ok(people) => people.iter().map(|(pk, _)| *pk).collect(),
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    HashSet::new()
+                }
+            };
+
+        // If who we follow changed, every cached score may be wrong
+        let changed = match &*self.my_follows.read() {
+            Some((_, old)) => *old != follows,
+            None => true,
+        };
+        if changed {
+            self.scores.clear();
+        }
+
+        *self.my_follows.write() = Some((Instant::now(), follows));
+    }
+
+    /// Forget everything cached. Call this when our Followed list changes.
+    pub fn invalidate(&self) {
+        *self.my_follows.write() = None;
+        self.scores.clear();
+    }
+
+    /// The trust score of a person
+    pub fn score(&self, pubkey: PublicKey) -> TrustScore {
+        self.refresh_my_follows();
+
+        if let Some(score) = self.scores.get(&pubkey) {
+            return *score;
+        }
+
+        match self.compute_score(pubkey) {
+            Ok(score) => {
+                self.scores.insert(pubkey, score);
+                score
+            }
+            Err(e) => {
+                tracing::error!("{}", e);
+                TrustScore::default()
+            }
+        }
+    }
+
+    fn compute_score(&self, pubkey: PublicKey) -> Result<TrustScore, Error> {
+        Ok(TrustScore {
+            followed_by: self
+                .edges_from_my_follows(GraphEdge::Follows, pubkey)?
+                .len(),
+            muted_by: self.edges_from_my_follows(GraphEdge::Mutes, pubkey)?.len(),
+        })
+    }
+
+    /// The people we follow who follow this person
+    pub fn followed_by(&self, pubkey: PublicKey) -> Result<Vec<PublicKey>, Error> {
+        self.refresh_my_follows();
+        self.edges_from_my_follows(GraphEdge::Follows, pubkey)
+    }
+
+    /// The people we follow who publicly mute this person
+    pub fn muted_by(&self, pubkey: PublicKey) -> Result<Vec<PublicKey>, Error> {
+        self.refresh_my_follows();
+        self.edges_from_my_follows(GraphEdge::Mutes, pubkey)
+    }
+
+    fn edges_from_my_follows(
+        &self,
+        edge: GraphEdge,
+        pubkey: PublicKey,
+    ) -> Result<Vec<PublicKey>, Error> {
+        let mut sources = GLOBALS.storage.get_graph_edges_in(edge, pubkey)?;
+        Ok(match &*self.my_follows.read() {
+            Some((_, follows)) => sources
+                .drain(..)
+                .filter(|pk| follows.contains(pk))
+                .collect(),
+            None => vec![],
+        })
+    }
+
+    /// Record the edges of a (newer) contact list or mute list, and update the
+    /// cached scores of the people who were added or removed.
+    pub(crate) fn process_list_event(&self, event: &Event) -> Result<(), Error> {
+        let (edge, targets) = match graph_edges_of_event(event) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let old = GLOBALS
+            .storage
+            .set_graph_edges(edge, event.pubkey, &targets, None)?;
+
+        if Some(event.pubkey) == GLOBALS.identity.public_key() {
+            // Our own list; who we follow is handled by the Followed list
+            return Ok(());
+        }
+
+        let author_counts = match &*self.my_follows.read() {
+            Some((_, follows)) => follows.contains(&event.pubkey),
+            None => return Ok(()), // nothing is cached yet
+        };
+        if !author_counts {
+            return Ok(());
+        }
+
+        let old: HashSet<PublicKey> = old.into_iter().collect();
+        let new: HashSet<PublicKey> = targets.into_iter().collect();
+        for added in new.difference(&old) {
+            if let Some(mut score) = self.scores.get_mut(added) {
+                match edge {
+                    GraphEdge::Follows => score.followed_by += 1,
+                    GraphEdge::Mutes => score.muted_by += 1,
+                }
+            }
+        }
+        for removed in old.difference(&new) {
+            if let Some(mut score) = self.scores.get_mut(removed) {
+                match edge {
+                    GraphEdge::Follows => score.followed_by = score.followed_by.saturating_sub(1),
+                    GraphEdge::Mutes => score.muted_by = score.muted_by.saturating_sub(1),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The follow graph edges that a contact list or mute list event declares. Only
+/// public entries are used.
+pub(crate) fn graph_edges_of_event(event: &Event) -> Option<(GraphEdge, Vec<PublicKey>)> {
+    let edge = match event.kind {
+        EventKind::ContactList => GraphEdge::Follows,
+        EventKind::MuteList => GraphEdge::Mutes,
+        _ => return None,
+    };
+
+    let mut seen: HashSet<PublicKey> = HashSet::new();
+    let mut targets: Vec<PublicKey> = Vec::new();
+    for tag in &event.tags {
+        if let Ok((pubkey, _, _)) = tag.parse_pubkey() {
+            if pubkey != event.pubkey && seen.insert(pubkey) {
+                targets.push(pubkey);
+            }
+        }
+    }
+
+    Some((edge, targets))
+}